@@ -0,0 +1,98 @@
+//! Compares how `create_reply` fills a new reply account today (decode the zeroed buffer with
+//! `try_from_slice_unchecked`, set fields, `serialize` back) against writing the same Borsh
+//! layout at fixed byte offsets. Run with `cargo bench --bench reply_packing`.
+//!
+//! These are host timings; they rank the two approaches but are not compute units.
+
+use borsh::BorshSerialize;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use solana_program::{borsh::try_from_slice_unchecked, pubkey::Pubkey};
+use student_intro_sol::state::StudentReplyState;
+
+fn borsh_path(data: &mut [u8], intro: Pubkey, replier: Pubkey, name: &str, message: &str) {
+    let mut reply_data = try_from_slice_unchecked::<StudentReplyState>(data).unwrap();
+    assert!(!reply_data.is_initialized);
+
+    reply_data.discriminator = StudentReplyState::DISCRIMINATOR.to_string();
+    reply_data.intro = intro;
+    reply_data.replier = replier;
+    reply_data.name = name.to_string();
+    reply_data.message = message.to_string();
+    reply_data.is_initialized = true;
+
+    reply_data.serialize(&mut &mut data[..]).unwrap();
+}
+
+fn manual_path(data: &mut [u8], intro: Pubkey, replier: Pubkey, name: &str, message: &str) {
+    let discriminator = StudentReplyState::DISCRIMINATOR.as_bytes();
+    // `is_initialized` sits right after the discriminator string.
+    assert_eq!(data[4 + discriminator.len()], 0);
+
+    let mut offset = 0;
+    let mut put = |bytes: &[u8]| {
+        data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        offset += bytes.len();
+    };
+    put(&(discriminator.len() as u32).to_le_bytes());
+    put(discriminator);
+    put(&[1]);
+    put(intro.as_ref());
+    put(replier.as_ref());
+    put(&(name.len() as u32).to_le_bytes());
+    put(name.as_bytes());
+    put(&(message.len() as u32).to_le_bytes());
+    put(message.as_bytes());
+    put(&[0]);
+    put(&[0]);
+    put(&[0, 0]);
+    put(&[0]);
+    put(&0u32.to_le_bytes());
+    put(&[0]);
+}
+
+fn reply_packing(c: &mut Criterion) {
+    let intro = Pubkey::new_unique();
+    let replier = Pubkey::new_unique();
+    let name = "Grace Hopper";
+    let mut group = c.benchmark_group("create_reply");
+
+    for message_len in [16, 256, 1024] {
+        let message = "m".repeat(message_len);
+        let len = StudentReplyState::get_account_size(name, &message);
+
+        // Both writers must produce identical account data for the numbers to mean anything.
+        let mut expected = vec![0; len];
+        let mut actual = vec![0; len];
+        borsh_path(&mut expected, intro, replier, name, &message);
+        manual_path(&mut actual, intro, replier, name, &message);
+        assert_eq!(expected, actual);
+
+        group.bench_with_input(
+            BenchmarkId::new("borsh", message_len),
+            &message,
+            |b, message| {
+                b.iter(|| {
+                    let mut data = vec![0; len];
+                    borsh_path(&mut data, intro, replier, name, message);
+                    black_box(data)
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("manual", message_len),
+            &message,
+            |b, message| {
+                b.iter(|| {
+                    let mut data = vec![0; len];
+                    manual_path(&mut data, intro, replier, name, message);
+                    black_box(data)
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, reply_packing);
+criterion_main!(benches);
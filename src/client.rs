@@ -0,0 +1,400 @@
+//! Off-chain helpers for building instructions against this program. Kept free of RPC
+//! dependencies: callers fetch account data however they like and hand the bytes in.
+
+use std::collections::HashSet;
+
+use borsh::BorshDeserialize;
+use solana_program::{
+    instruction::Instruction, program_error::ProgramError, pubkey, pubkey::Pubkey,
+};
+
+use crate::{
+    entrypoint::HEAP_LENGTH,
+    error::{AccountCheck, ACCOUNT_CHECK_LOG},
+    pda,
+    state::{ReplyCount, StudentIntroState, StudentReplyState, UserNonce},
+};
+
+/// `getMultipleAccounts` accepts at most this many keys per request.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// The RPC access the fetch helpers need, so this module stays independent of any particular
+/// RPC client; implement it over `RpcClient::get_multiple_accounts`.
+pub trait AccountFetcher {
+    type Error: From<ProgramError>;
+
+    /// Account data for each key, in order, `None` where the account does not exist.
+    fn get_multiple_accounts(&self, keys: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>, Self::Error>;
+}
+
+/// An intro and its replies in posting order.
+pub struct Thread {
+    pub intro: AnyIntro,
+    pub replies: Vec<(Pubkey, StudentReplyState)>,
+}
+
+/// Intro layout written before visibility, course scoping, expiry and timestamps were added.
+#[derive(BorshDeserialize)]
+pub struct StudentIntroV1 {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub writer: Pubkey,
+    pub name: String,
+    pub message: String,
+}
+
+/// An intro account decoded with whichever layout wrote it.
+pub enum AnyIntro {
+    V1(StudentIntroV1),
+    V2(StudentIntroState),
+}
+
+impl AnyIntro {
+    /// Intro accounts are allocated at a fixed size, so a legacy intro is one whose bytes after
+    /// the message are all zero; every current intro has at least `created_at` set.
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut rest = data;
+        let v1 =
+            StudentIntroV1::deserialize(&mut rest).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if v1.discriminator != StudentIntroState::DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if rest.iter().all(|&byte| byte == 0) {
+            return Ok(Self::V1(v1));
+        }
+
+        let v2 = StudentIntroState::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(Self::V2(v2))
+    }
+
+    pub fn writer(&self) -> &Pubkey {
+        match self {
+            Self::V1(intro) => &intro.writer,
+            Self::V2(intro) => &intro.writer,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::V1(intro) => &intro.name,
+            Self::V2(intro) => &intro.name,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::V1(intro) => &intro.message,
+            Self::V2(intro) => &intro.message,
+        }
+    }
+}
+
+/// Address of `user`'s replay-protection nonce account.
+pub fn nonce_address(program_id: &Pubkey, user: &Pubkey) -> Pubkey {
+    pda::nonce(program_id, user)
+}
+
+/// Nonce the next payload signed by the account's owner must carry. Pass `None` when the
+/// account does not exist yet.
+pub fn next_nonce(nonce_account_data: Option<&[u8]>) -> Result<u64, ProgramError> {
+    let data = match nonce_account_data {
+        Some(data) => data,
+        None => return Ok(0),
+    };
+
+    let nonce_data =
+        UserNonce::deserialize(&mut &data[..]).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if !nonce_data.is_initialized {
+        return Ok(0);
+    }
+
+    Ok(nonce_data.next_nonce)
+}
+
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+
+// ComputeBudgetInstruction discriminants.
+const REQUEST_HEAP_FRAME: u8 = 1;
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Compute unit limits for `StudentIntro` and `ReplyIntro` under the default config (no
+/// gateway, oracle or credential accounts). Asking for less than the 200k default makes priority
+/// fees cheaper. These are still estimates, not yet measured against a deployed build. To derive
+/// them, simulate each instruction with a maximum-length name and message, pass the logs to
+/// `consumed_compute_units`, and set the constant to `compute_unit_limit` of the result.
+pub const STUDENT_INTRO_COMPUTE_UNITS: u32 = 100_000;
+pub const REPLY_INTRO_COMPUTE_UNITS: u32 = 80_000;
+
+/// Headroom `compute_unit_limit` adds on top of a measured count, in basis points.
+pub const COMPUTE_UNIT_HEADROOM_BPS: u64 = 2_000;
+/// The most compute units a transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Compute units `program_id` used in a simulated transaction that calls it once, read from the
+/// runtime's `Program <id> consumed <n> of <m> compute units` log line. A CPI back into the
+/// program logs its own line first; the last line is the outer call, which includes it.
+pub fn consumed_compute_units<S: AsRef<str>>(logs: &[S], program_id: &Pubkey) -> Option<u64> {
+    let prefix = format!("Program {program_id} consumed ");
+    logs.iter().rev().find_map(|line| {
+        let rest = line.as_ref().strip_prefix(&prefix)?;
+        rest.split_once(" of ")?.0.parse().ok()
+    })
+}
+
+/// A limit for an instruction measured at `consumed` units: `COMPUTE_UNIT_HEADROOM_BPS` more,
+/// rounded up to a multiple of 1000.
+pub fn compute_unit_limit(consumed: u64) -> u32 {
+    let padded = consumed + consumed * COMPUTE_UNIT_HEADROOM_BPS / 10_000;
+    let rounded = padded.div_ceil(1_000) * 1_000;
+    rounded.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+/// Supplies a compute unit price, e.g. from `getRecentPrioritizationFees` for the accounts the
+/// transaction writes.
+pub trait PriorityFeeEstimator {
+    fn micro_lamports_per_compute_unit(&self, writable_accounts: &[Pubkey]) -> u64;
+}
+
+/// Prepends ComputeBudget instructions setting `compute_unit_limit` and, with an estimator, a
+/// compute unit price, so the transaction competes for block space during congestion.
+pub fn with_priority_fee(
+    instructions: Vec<Instruction>,
+    compute_unit_limit: u32,
+    estimator: Option<&dyn PriorityFeeEstimator>,
+) -> Vec<Instruction> {
+    let mut prefixed = vec![compute_budget_instruction(
+        SET_COMPUTE_UNIT_LIMIT,
+        &compute_unit_limit.to_le_bytes(),
+    )];
+
+    if let Some(estimator) = estimator {
+        let writable_accounts: Vec<Pubkey> = instructions
+            .iter()
+            .flat_map(|instruction| instruction.accounts.iter())
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        let price = estimator.micro_lamports_per_compute_unit(&writable_accounts);
+        prefixed.push(compute_budget_instruction(
+            SET_COMPUTE_UNIT_PRICE,
+            &price.to_le_bytes(),
+        ));
+    }
+
+    prefixed.extend(instructions);
+    prefixed
+}
+
+/// Prepends the RequestHeapFrame instruction a program built with `custom-heap` needs; without
+/// it the larger allocator runs past the 32KB region the runtime maps.
+pub fn with_heap_frame(instructions: Vec<Instruction>) -> Vec<Instruction> {
+    let mut prefixed = vec![compute_budget_instruction(
+        REQUEST_HEAP_FRAME,
+        &(HEAP_LENGTH as u32).to_le_bytes(),
+    )];
+    prefixed.extend(instructions);
+    prefixed
+}
+
+fn compute_budget_instruction(discriminant: u8, value: &[u8]) -> Instruction {
+    let mut data = vec![discriminant];
+    data.extend_from_slice(value);
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Loads an intro and all its replies, deriving the reply addresses from the counter and
+/// fetching them in `getMultipleAccounts` batches instead of one request per reply.
+pub fn fetch_thread<F: AccountFetcher>(
+    fetcher: &F,
+    program_id: &Pubkey,
+    intro: &Pubkey,
+) -> Result<Thread, F::Error> {
+    let counter_key = pda::reply_counter(program_id, intro);
+
+    let mut accounts = fetcher.get_multiple_accounts(&[*intro, counter_key])?;
+    let counter_data = accounts.pop().flatten();
+    let intro_data = accounts
+        .pop()
+        .flatten()
+        .ok_or(ProgramError::UninitializedAccount)?;
+
+    let intro_state = AnyIntro::decode(&intro_data)?;
+    let reply_count = match counter_data {
+        Some(data) => {
+            ReplyCount::deserialize(&mut &data[..])
+                .map_err(|_| ProgramError::InvalidAccountData)?
+                .counter
+        }
+        None => 0,
+    };
+
+    let replies = fetch_reply_range(fetcher, program_id, intro, 0..reply_count)?;
+
+    Ok(Thread {
+        intro: intro_state,
+        replies,
+    })
+}
+
+/// One page of an intro's replies; pass `next_cursor` back to get the following page.
+pub struct ReplyPage {
+    pub replies: Vec<(Pubkey, StudentReplyState)>,
+    /// Reply index the next page starts at, `None` once the thread is exhausted.
+    pub next_cursor: Option<u64>,
+}
+
+/// Up to `limit` replies starting at reply index `cursor` (0 for the first page). The reply
+/// counter is re-read on every call, so replies posted while paging show up on later pages.
+pub fn fetch_replies_page<F: AccountFetcher>(
+    fetcher: &F,
+    program_id: &Pubkey,
+    intro: &Pubkey,
+    cursor: u64,
+    limit: u64,
+) -> Result<ReplyPage, F::Error> {
+    let reply_count = fetch_reply_count(fetcher, program_id, intro)?;
+    let end = cursor.saturating_add(limit).min(reply_count);
+
+    let replies = if cursor < end {
+        fetch_reply_range(fetcher, program_id, intro, cursor..end)?
+    } else {
+        Vec::new()
+    };
+
+    Ok(ReplyPage {
+        replies,
+        next_cursor: (end < reply_count).then_some(end),
+    })
+}
+
+fn fetch_reply_count<F: AccountFetcher>(
+    fetcher: &F,
+    program_id: &Pubkey,
+    intro: &Pubkey,
+) -> Result<u64, F::Error> {
+    let counter_key = pda::reply_counter(program_id, intro);
+
+    match fetcher
+        .get_multiple_accounts(&[counter_key])?
+        .pop()
+        .flatten()
+    {
+        Some(data) => Ok(ReplyCount::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::InvalidAccountData)?
+            .counter),
+        None => Ok(0),
+    }
+}
+
+/// Replies with indexes in `range`, skipping any that no longer exist.
+fn fetch_reply_range<F: AccountFetcher>(
+    fetcher: &F,
+    program_id: &Pubkey,
+    intro: &Pubkey,
+    range: std::ops::Range<u64>,
+) -> Result<Vec<(Pubkey, StudentReplyState)>, F::Error> {
+    let keys: Vec<Pubkey> = range
+        .map(|index| pda::reply(program_id, intro, index))
+        .collect();
+
+    let mut replies = Vec::with_capacity(keys.len());
+
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        for (key, data) in chunk.iter().zip(fetcher.get_multiple_accounts(chunk)?) {
+            if let Some(data) = data {
+                let reply = StudentReplyState::deserialize(&mut &data[..])
+                    .map_err(|_| ProgramError::InvalidAccountData)?;
+                replies.push((*key, reply));
+            }
+        }
+    }
+
+    Ok(replies)
+}
+
+/// A reply account seen on a program subscription.
+pub struct ReplyCreated {
+    pub reply: Pubkey,
+    pub state: StudentReplyState,
+}
+
+/// Bytes a `programSubscribe` memcmp filter at offset 0 must match to receive only reply
+/// accounts: the Borsh-encoded reply discriminator.
+pub fn reply_discriminator_filter() -> Vec<u8> {
+    let discriminator = StudentReplyState::DISCRIMINATOR.as_bytes();
+    let mut bytes = (discriminator.len() as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(discriminator);
+    bytes
+}
+
+/// Turns raw `(pubkey, account data)` notifications from a `programSubscribe` stream, e.g.
+/// `PubsubClient::program_subscribe` filtered with `reply_discriminator_filter`, into typed
+/// replies. Notifications that are not replies are skipped, and so is every notification for
+/// a reply already yielded: the stream also fires when a reply is edited or scored, and only
+/// the first sighting is its creation.
+pub fn reply_updates<S>(notifications: S) -> impl Iterator<Item = ReplyCreated>
+where
+    S: IntoIterator<Item = (Pubkey, Vec<u8>)>,
+{
+    let mut seen = HashSet::new();
+    notifications.into_iter().filter_map(move |(reply, data)| {
+        let state = StudentReplyState::deserialize(&mut &data[..]).ok()?;
+        (state.is_initialized
+            && state.discriminator == StudentReplyState::DISCRIMINATOR
+            && seen.insert(reply))
+        .then_some(ReplyCreated { reply, state })
+    })
+}
+
+/// An account check the program logged as failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountCheckFailure {
+    /// The account's name in the instruction, e.g. `writer` or `intro_pda`.
+    pub role: String,
+    pub key: Pubkey,
+    pub expected: AccountCheck,
+}
+
+/// Reads a line written by `error::log_account_check`, as found in a transaction's log
+/// messages with or without the `Program log: ` prefix. Keys it does not know are skipped, so
+/// fields added later do not break older clients.
+pub fn parse_account_check(line: &str) -> Option<AccountCheckFailure> {
+    let line = line.strip_prefix("Program log: ").unwrap_or(line);
+    let mut fields = line.split_whitespace();
+
+    if fields.next()? != ACCOUNT_CHECK_LOG {
+        return None;
+    }
+
+    let (mut role, mut key, mut expected) = (None, None, None);
+    for field in fields {
+        match field.split_once('=')? {
+            ("role", value) => role = Some(value.to_string()),
+            ("key", value) => key = value.parse().ok(),
+            ("expected", value) => expected = AccountCheck::parse(value),
+            _ => {}
+        }
+    }
+
+    Some(AccountCheckFailure {
+        role: role?,
+        key: key?,
+        expected: expected?,
+    })
+}
+
+/// The first failed account check in a transaction's log messages.
+pub fn first_account_check_failure<S: AsRef<str>>(logs: &[S]) -> Option<AccountCheckFailure> {
+    logs.iter()
+        .find_map(|line| parse_account_check(line.as_ref()))
+}
@@ -0,0 +1,206 @@
+//! Typed CPI into this program for companion programs, e.g. a quiz or grading program that
+//! replies from its own PDA. Each function takes the handler's fixed accounts in a struct,
+//! builds the instruction and calls `invoke_signed`; pass empty `signer_seeds` when the
+//! signers signed the outer transaction. Accounts the config or intro make a handler read
+//! (gateway token, price feed, enrollment, the replier's reputation and so on) go in
+//! `remaining_accounts`, in the handler's order, and keep their own signer and writable flags.
+//! The config PDA every instruction ends with is appended after them.
+
+use crate::instruction::StudentInstruction;
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+};
+
+/// Accounts of `ReplyIntro`. `reply` is the intro's next reply address, and `replier` pays
+/// for it, so a PDA replier must hold lamports and be owned by the system program.
+pub struct ReplyAccounts<'a, 'info> {
+    /// This program.
+    pub program: &'a AccountInfo<'info>,
+    pub replier: &'a AccountInfo<'info>,
+    pub intro: &'a AccountInfo<'info>,
+    pub reply_counter: &'a AccountInfo<'info>,
+    pub reply: &'a AccountInfo<'info>,
+    #[cfg(not(feature = "no-rewards"))]
+    pub token_mint: &'a AccountInfo<'info>,
+    #[cfg(not(feature = "no-rewards"))]
+    pub mint_auth: &'a AccountInfo<'info>,
+    /// The replier's associated token account for the reward mint.
+    #[cfg(not(feature = "no-rewards"))]
+    pub replier_ata: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    #[cfg(not(feature = "no-rewards"))]
+    pub token_program: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+    #[cfg(not(feature = "no-rewards"))]
+    pub reply_tally: &'a AccountInfo<'info>,
+}
+
+/// Posts a plain-text, untagged reply as `accounts.replier`.
+pub fn reply<'info>(
+    accounts: ReplyAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    name: &str,
+    message: &str,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut infos = vec![
+        accounts.replier.clone(),
+        accounts.intro.clone(),
+        accounts.reply_counter.clone(),
+        accounts.reply.clone(),
+    ];
+    let mut metas = vec![
+        AccountMeta::new(*accounts.replier.key, true),
+        AccountMeta::new_readonly(*accounts.intro.key, false),
+        AccountMeta::new(*accounts.reply_counter.key, false),
+        AccountMeta::new(*accounts.reply.key, false),
+    ];
+    #[cfg(not(feature = "no-rewards"))]
+    {
+        infos.extend([
+            accounts.token_mint.clone(),
+            accounts.mint_auth.clone(),
+            accounts.replier_ata.clone(),
+        ]);
+        metas.extend([
+            AccountMeta::new(*accounts.token_mint.key, false),
+            AccountMeta::new_readonly(*accounts.mint_auth.key, false),
+            AccountMeta::new(*accounts.replier_ata.key, false),
+        ]);
+    }
+    infos.push(accounts.system_program.clone());
+    metas.push(AccountMeta::new_readonly(
+        *accounts.system_program.key,
+        false,
+    ));
+    #[cfg(not(feature = "no-rewards"))]
+    {
+        infos.push(accounts.token_program.clone());
+        metas.push(AccountMeta::new_readonly(
+            *accounts.token_program.key,
+            false,
+        ));
+    }
+    #[cfg(not(feature = "no-rewards"))]
+    {
+        infos.push(accounts.reply_tally.clone());
+        metas.push(AccountMeta::new(*accounts.reply_tally.key, false));
+    }
+
+    call(
+        accounts.program,
+        accounts.config,
+        metas,
+        infos,
+        remaining_accounts,
+        instruction_data(2, &(name, message)),
+        signer_seeds,
+    )
+}
+
+/// Accounts of `VoteReply`; `vote` is the `[reply, voter, "vote"]` address.
+pub struct VoteReplyAccounts<'a, 'info> {
+    /// This program.
+    pub program: &'a AccountInfo<'info>,
+    pub voter: &'a AccountInfo<'info>,
+    pub intro: &'a AccountInfo<'info>,
+    pub reply: &'a AccountInfo<'info>,
+    pub vote: &'a AccountInfo<'info>,
+    pub score: &'a AccountInfo<'info>,
+    pub top_replies: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    /// The voter's `["link"]` address, linked or not.
+    pub voter_link: &'a AccountInfo<'info>,
+    /// The replier's `["link"]` address, linked or not.
+    pub replier_link: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+}
+
+/// Votes on a reply as `accounts.voter`, who pays for the vote and score accounts.
+pub fn vote_reply<'info>(
+    accounts: VoteReplyAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    upvote: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let metas = vec![
+        AccountMeta::new(*accounts.voter.key, true),
+        AccountMeta::new_readonly(*accounts.intro.key, false),
+        AccountMeta::new_readonly(*accounts.reply.key, false),
+        AccountMeta::new(*accounts.vote.key, false),
+        AccountMeta::new(*accounts.score.key, false),
+        AccountMeta::new(*accounts.top_replies.key, false),
+        AccountMeta::new_readonly(*accounts.system_program.key, false),
+        AccountMeta::new_readonly(*accounts.voter_link.key, false),
+        AccountMeta::new_readonly(*accounts.replier_link.key, false),
+    ];
+    let infos = vec![
+        accounts.voter.clone(),
+        accounts.intro.clone(),
+        accounts.reply.clone(),
+        accounts.vote.clone(),
+        accounts.score.clone(),
+        accounts.top_replies.clone(),
+        accounts.system_program.clone(),
+        accounts.voter_link.clone(),
+        accounts.replier_link.clone(),
+    ];
+
+    call(
+        accounts.program,
+        accounts.config,
+        metas,
+        infos,
+        remaining_accounts,
+        instruction_data(11, &upvote),
+        signer_seeds,
+    )
+}
+
+/// Appends `remaining_accounts`, the config and the program to the fixed accounts and invokes
+/// it.
+fn call<'info>(
+    program: &AccountInfo<'info>,
+    config: &AccountInfo<'info>,
+    mut metas: Vec<AccountMeta>,
+    mut infos: Vec<AccountInfo<'info>>,
+    remaining_accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    for account in remaining_accounts {
+        metas.push(if account.is_writable {
+            AccountMeta::new(*account.key, account.is_signer)
+        } else {
+            AccountMeta::new_readonly(*account.key, account.is_signer)
+        });
+        infos.push(account.clone());
+    }
+    metas.push(AccountMeta::new_readonly(*config.key, false));
+    infos.extend([config.clone(), program.clone()]);
+
+    invoke_signed(
+        &Instruction {
+            program_id: *program.key,
+            accounts: metas,
+            data,
+        },
+        &infos,
+        signer_seeds,
+    )
+}
+
+/// `payload` in the versioned envelope.
+fn instruction_data<T: BorshSerialize>(variant: u8, payload: &T) -> Vec<u8> {
+    let mut data = vec![
+        StudentInstruction::VERSIONED_PREFIX,
+        StudentInstruction::CURRENT_VERSION,
+        variant,
+    ];
+    data.extend(payload.try_to_vec().unwrap());
+    data
+}
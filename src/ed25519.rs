@@ -0,0 +1,72 @@
+use solana_program::{
+    account_info::AccountInfo, ed25519_program, msg, program_error::ProgramError, pubkey::Pubkey,
+    sysvar::instructions::get_instruction_relative,
+};
+
+use crate::error::IntroError;
+
+// Layout of the Ed25519 native program's instruction data: a signature count and padding byte,
+// then one offsets record per signature.
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+/// Instruction index meaning "the Ed25519 instruction itself" in an offsets record.
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Checks that the instruction immediately before this one is an Ed25519 program instruction
+/// verifying exactly one signature by `signer` over `message`. The native program has already
+/// checked the signature itself; a failed check would have aborted the transaction.
+pub fn verify_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<(), ProgramError> {
+    let instruction = get_instruction_relative(-1, instructions_sysvar)?;
+
+    if instruction.program_id != ed25519_program::id() {
+        msg!("Previous instruction is not an Ed25519 signature check");
+        return Err(IntroError::InvalidAttestation.into());
+    }
+
+    let data = &instruction.data;
+
+    if data.len() < SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN || data[0] != 1 {
+        msg!("Expected exactly one Ed25519 signature");
+        return Err(IntroError::InvalidAttestation.into());
+    }
+
+    let read_u16 = |index: usize| {
+        let at = SIGNATURE_OFFSETS_START + index * 2;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+
+    let signature_instruction = read_u16(1);
+    let pubkey_offset = read_u16(2) as usize;
+    let pubkey_instruction = read_u16(3);
+    let message_offset = read_u16(4) as usize;
+    let message_len = read_u16(5) as usize;
+    let message_instruction = read_u16(6);
+
+    // Offsets pointing into other instructions could verify bytes we never look at.
+    if signature_instruction != CURRENT_INSTRUCTION
+        || pubkey_instruction != CURRENT_INSTRUCTION
+        || message_instruction != CURRENT_INSTRUCTION
+    {
+        msg!("Ed25519 signature data must live in its own instruction");
+        return Err(IntroError::InvalidAttestation.into());
+    }
+
+    let signed_pubkey = data
+        .get(pubkey_offset..pubkey_offset + PUBKEY_LEN)
+        .ok_or(IntroError::InvalidAttestation)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_len)
+        .ok_or(IntroError::InvalidAttestation)?;
+
+    if signed_pubkey != signer.as_ref() || signed_message != message {
+        msg!("Ed25519 signature is for another signer or message");
+        return Err(IntroError::InvalidAttestation.into());
+    }
+
+    Ok(())
+}
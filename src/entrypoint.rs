@@ -1,11 +1,32 @@
+#[cfg(not(feature = "no-entrypoint"))]
 use solana_program::{
     account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
 };
 
+#[cfg(not(feature = "no-entrypoint"))]
 use crate::processor;
 
+/// Heap size under the `custom-heap` feature. Must be a multiple of 1KB between 32KB and
+/// 256KB, and match what clients request.
+pub const HEAP_LENGTH: usize = 256 * 1024;
+
+#[cfg(all(
+    feature = "custom-heap",
+    target_os = "solana",
+    not(feature = "no-entrypoint")
+))]
+#[global_allocator]
+static A: solana_program::entrypoint::BumpAllocator = solana_program::entrypoint::BumpAllocator {
+    start: solana_program::entrypoint::HEAP_START_ADDRESS as usize,
+    len: HEAP_LENGTH,
+};
+
+// Programs that depend on this crate for `cpi` build it with `no-entrypoint`, keeping their
+// own entrypoint and allocator.
+#[cfg(not(feature = "no-entrypoint"))]
 entrypoint!(process_instruction);
 
+#[cfg(not(feature = "no-entrypoint"))]
 fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
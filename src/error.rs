@@ -1,4 +1,4 @@
-use solana_program::program_error::ProgramError;
+use solana_program::{msg, program_error::ProgramError, pubkey::Pubkey};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -11,6 +11,126 @@ pub enum IntroError {
     InvalidDataLength,
     #[error("Accounts are not same")]
     IncorrectAccountError,
+    #[error("Signer is not the config admin")]
+    Unauthorized,
+    #[error("Gateway token is missing, inactive or issued for another wallet")]
+    InvalidGatewayToken,
+    #[error("Reveal delay has not elapsed yet")]
+    RevealTooEarly,
+    #[error("Revealed reply does not match the commitment")]
+    CommitmentMismatch,
+    #[error("Intro does not accept replies from this wallet")]
+    RepliesNotAllowed,
+    #[error("Student is not enrolled in the course")]
+    NotEnrolled,
+    #[error("Intro has expired")]
+    IntroExpired,
+    #[error("Intro has not expired yet")]
+    IntroNotExpired,
+    #[error("NFT is not a verified member of the credential collection")]
+    InvalidCredentialNft,
+    #[error("Oracle account is not a usable price feed")]
+    InvalidOracleAccount,
+    #[error("Oracle price is stale or not trading")]
+    StaleOraclePrice,
+    #[error("Wormhole account is invalid or cross-chain mode is disabled")]
+    InvalidWormholeAccount,
+    #[error("VAA was not emitted by the registered foreign emitter")]
+    UnknownEmitter,
+    #[error("Config changes must go through the timelock")]
+    TimelockRequired,
+    #[error("Timelock delay has not elapsed yet")]
+    TimelockNotElapsed,
+    #[error("Admin action has not reached the approval threshold")]
+    ThresholdNotMet,
+    #[error("Invalid admin signer set or threshold")]
+    InvalidMultisig,
+    #[error("Intro has not reached the milestone")]
+    MilestoneNotReached,
+    #[error("Nonce does not match the user's next nonce")]
+    InvalidNonce,
+    #[error("Invalid signed attestation")]
+    InvalidAttestation,
+    #[error("Account has been closed")]
+    AccountClosed,
+    #[error("Account must be passed as writable")]
+    AccountNotWritable,
+    #[error("Slug must be 3-32 lowercase letters, digits or inner hyphens")]
+    InvalidSlug,
+    #[error("Slug belongs to another intro")]
+    SlugTaken,
+    #[error("Quoted reply is not on this intro or does not contain the excerpt")]
+    InvalidQuote,
+    #[error("Language tag is not an ISO 639-1 code")]
+    InvalidLanguage,
+    #[error("Message format is unknown or the message breaks its rules")]
+    InvalidFormat,
+    #[error("Profile field is too long or malformed")]
+    InvalidProfile,
+    #[error("Course requires a student ID hash")]
+    StudentIdRequired,
+    #[error("Student ID is already registered to another wallet in this course")]
+    StudentIdTaken,
+    #[error("Email hash is already registered to another wallet")]
+    EmailHashTaken,
+    #[error("Intro is not graduated or does not meet the course certificate requirements")]
+    CertificateNotEarned,
+    #[error("Office-hours queue is full")]
+    QueueFull,
+    #[error("Student is already in the office-hours queue")]
+    AlreadyQueued,
+    #[error("Student is not in the office-hours queue")]
+    NotQueued,
+    #[error("Office-hours queue is empty")]
+    QueueEmpty,
+    #[error("Session window or reward is invalid")]
+    InvalidSession,
+    #[error("Session is not open for check-in")]
+    SessionClosed,
+    #[error("Quiz window or reward is invalid")]
+    InvalidQuiz,
+    #[error("Quiz is closed to submissions")]
+    QuizClosed,
+    #[error("Quiz answers cannot be revealed before the quiz closes")]
+    QuizStillOpen,
+    #[error("Revealed answer does not match the quiz commitment")]
+    AnswerMismatch,
+    #[error("Team is full")]
+    TeamFull,
+    #[error("Student is already on a team")]
+    AlreadyOnTeam,
+    #[error("Reply was posted before read receipts and has no room for one")]
+    ReadReceiptUnsupported,
+    #[error("Views were already recorded for this intro this epoch")]
+    ViewsAlreadyRecorded,
+    #[error("Instruction is disabled by the operator")]
+    InstructionDisabled,
+    #[error("Account is already in this index")]
+    AlreadyIndexed,
+    #[error("Reward split is invalid")]
+    InvalidRewardSplit,
+    #[error("Reply was posted before spam scores and has no room for one")]
+    SpamScoreUnsupported,
+    #[error("Program was upgraded and its accounts must be migrated first")]
+    UpgradeRequiresMigration,
+    #[error("Replies on this course are not due for anonymization")]
+    AnonymizationNotDue,
+    #[error("Account export does not fit in return data")]
+    ExportTooLarge,
+    #[error("Account still has a live parent")]
+    NotOrphaned,
+    #[error("Text breaks the course's character policy")]
+    CharacterPolicyViolation,
+    #[error("Intro is not a question")]
+    NotAQuestion,
+    #[error("Question was answered and no longer takes replies")]
+    QuestionClosed,
+    #[error("Course already pins the most intros it can")]
+    TooManyPins,
+    #[error("Featured draw slot has not passed yet")]
+    FeaturedDrawPending,
+    #[error("No eligible intro among the drawn candidates")]
+    NoEligibleIntro,
 }
 
 impl From<IntroError> for ProgramError {
@@ -18,3 +138,69 @@ impl From<IntroError> for ProgramError {
         ProgramError::Custom(err as u32)
     }
 }
+
+/// What an account passed to an instruction failed to be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountCheck {
+    /// Signed the transaction.
+    Signer,
+    /// Marked writable.
+    Writable,
+    /// The address derived from the account's seeds.
+    Pda,
+    /// A fixed or configured address, such as a program, the reward mint or a token account.
+    Address,
+    /// Owned by the expected program.
+    Owner,
+    /// Already initialized.
+    Initialized,
+    /// Not initialized yet.
+    Uninitialized,
+}
+
+impl AccountCheck {
+    pub const ALL: [AccountCheck; 7] = [
+        AccountCheck::Signer,
+        AccountCheck::Writable,
+        AccountCheck::Pda,
+        AccountCheck::Address,
+        AccountCheck::Owner,
+        AccountCheck::Initialized,
+        AccountCheck::Uninitialized,
+    ];
+
+    /// The `expected=` value logged for this check; stable across releases.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccountCheck::Signer => "signer",
+            AccountCheck::Writable => "writable",
+            AccountCheck::Pda => "pda",
+            AccountCheck::Address => "address",
+            AccountCheck::Owner => "owner",
+            AccountCheck::Initialized => "initialized",
+            AccountCheck::Uninitialized => "uninitialized",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<AccountCheck> {
+        AccountCheck::ALL
+            .into_iter()
+            .find(|check| check.as_str() == value)
+    }
+}
+
+/// First word of the line `log_account_check` writes.
+pub const ACCOUNT_CHECK_LOG: &str = "account_check_failed";
+
+/// Logs a failed account check as `account_check_failed role=<role> key=<key> expected=<check>`,
+/// where `role` names the account in the instruction, e.g. `writer` or `intro_pda`.
+/// `client::parse_account_check` reads the line back.
+pub fn log_account_check(role: &str, key: &Pubkey, expected: AccountCheck) {
+    msg!(
+        "{} role={} key={} expected={}",
+        ACCOUNT_CHECK_LOG,
+        role,
+        key,
+        expected.as_str()
+    );
+}
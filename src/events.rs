@@ -0,0 +1,261 @@
+// `derive(BorshSchema)` on an enum declares a struct per variant whose fields are never read.
+#![allow(dead_code)]
+
+use borsh::{BorshSchema, BorshSerialize};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+/// Structured events emitted through `sol_log_data` as Borsh-encoded bytes.
+/// New variants must be appended so existing indexers keep decoding old ones.
+#[derive(BorshSchema, BorshSerialize)]
+pub enum ProgramEvent {
+    ConfigChangeProposed {
+        proposer: Pubkey,
+        params_hash: [u8; 32],
+        eta_slot: u64,
+    },
+    ConfigChangeExecuted {
+        executor: Pubkey,
+        params_hash: [u8; 32],
+        slot: u64,
+    },
+    AdminNominated {
+        admin: Pubkey,
+        nominee: Pubkey,
+    },
+    AdminTransferred {
+        previous_admin: Pubkey,
+        new_admin: Pubkey,
+    },
+    Burned {
+        authority: Pubkey,
+        amount: u64,
+    },
+    BadgeMinted {
+        intro: Pubkey,
+        // Borsh 0.9's derive names its output parameter `writer`, so the field can't be.
+        author: Pubkey,
+        reply_count: u64,
+    },
+    /// A reply in `course` earned nothing because its teacher has rewards switched off.
+    RewardsDisabled {
+        course: Pubkey,
+        recipient: Pubkey,
+    },
+    MintMigrated {
+        version: u8,
+        mint: Pubkey,
+    },
+    /// `owner` burned `amount` of the `from_version` mint for the same amount of the current one.
+    OldTokensSwapped {
+        owner: Pubkey,
+        from_version: u8,
+        amount: u64,
+    },
+    TreasuryWithdrawalProposed {
+        proposer: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+        eta_slot: u64,
+    },
+    TreasuryWithdrawn {
+        executor: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+    },
+    /// `intro` took `slug`; `previous`, if any, now redirects to it.
+    SlugSet {
+        intro: Pubkey,
+        slug: String,
+        previous: Option<String>,
+    },
+    /// A new intro, tagged with its ISO 639-1 language (`[0, 0]` when untagged).
+    IntroPosted {
+        intro: Pubkey,
+        author: Pubkey,
+        lang: [u8; 2],
+    },
+    ReplyPosted {
+        intro: Pubkey,
+        reply: Pubkey,
+        replier: Pubkey,
+        lang: [u8; 2],
+    },
+    /// The https links found in `reply`'s message, as stored on the reply. Not emitted for
+    /// replies without any.
+    ReplyLinks {
+        reply: Pubkey,
+        links: Vec<String>,
+    },
+    /// `intro`'s profile changed. Fields the writer keeps private are blanked (empty, or
+    /// `[0, 0]` for the country), whatever the account holds.
+    ProfileUpdated {
+        intro: Pubkey,
+        pronouns: String,
+        timezone: String,
+        country: [u8; 2],
+    },
+    /// The admin freed `entry`, which `wallet` had claimed.
+    EmailHashReleased {
+        entry: Pubkey,
+        wallet: Pubkey,
+        admin: Pubkey,
+    },
+    /// `course`'s teacher moved `intro` from `previous` to `status` (`StudentIntroState::STATUS_*`).
+    StudentStatusChanged {
+        intro: Pubkey,
+        student: Pubkey,
+        course: Pubkey,
+        previous: u8,
+        status: u8,
+    },
+    CertificateClaimed {
+        course: Pubkey,
+        intro: Pubkey,
+        student: Pubkey,
+        mint: Pubkey,
+        completion_epoch: u64,
+    },
+    /// `student` joined `course`'s office-hours queue with `position` students ahead.
+    QueueJoined {
+        course: Pubkey,
+        student: Pubkey,
+        position: u8,
+    },
+    QueueLeft {
+        course: Pubkey,
+        student: Pubkey,
+    },
+    /// The teacher took `student` from the front of `course`'s queue.
+    QueuePopped {
+        course: Pubkey,
+        student: Pubkey,
+    },
+    /// `student` checked in to `session`, earning `reward` (0 when rewards are off).
+    CheckedIn {
+        session: Pubkey,
+        student: Pubkey,
+        reward: u64,
+    },
+    /// `student`'s submission to `quiz` was graded against the revealed answer.
+    QuizAnswerGraded {
+        quiz: Pubkey,
+        student: Pubkey,
+        correct: bool,
+        reward: u64,
+    },
+    /// `student` joined `team`, as its creator when it is the first member.
+    TeamJoined {
+        team: Pubkey,
+        student: Pubkey,
+    },
+    TeamLeft {
+        team: Pubkey,
+        student: Pubkey,
+    },
+    /// `intro`'s writer marked `reply` as read.
+    ReplyRead {
+        intro: Pubkey,
+        reply: Pubkey,
+    },
+    /// `reply` reached `milestones` vote milestones, crediting `points` to `replier` and
+    /// accruing `claimable` reward tokens.
+    ReputationCredited {
+        replier: Pubkey,
+        reply: Pubkey,
+        milestones: u8,
+        points: u64,
+        claimable: u64,
+    },
+    ReputationClaimed {
+        user: Pubkey,
+        amount: u64,
+    },
+    /// The view indexer added `count` views to `intro` in `epoch`, for `total` so far.
+    IntroViewsRecorded {
+        intro: Pubkey,
+        count: u64,
+        total: u64,
+        epoch: u64,
+    },
+    /// `admin` switched the instruction with variant byte `variant` on or off.
+    InstructionToggled {
+        admin: Pubkey,
+        variant: u8,
+        enabled: bool,
+    },
+    /// The instruction with variant byte `instruction` was rejected with `IntroError` `code`.
+    /// `offender` is its first signer, or the default key when nothing signed. Logged by the
+    /// failing transaction, so it shows up in log streams even though the state is rolled back.
+    Error {
+        code: u32,
+        instruction: u8,
+        offender: Pubkey,
+    },
+    /// `verifier` attested that `author`, who wrote `intro`, holds identity `identity_hash`.
+    IdentityVerified {
+        intro: Pubkey,
+        author: Pubkey,
+        verifier: Pubkey,
+        identity_hash: [u8; 32],
+    },
+    /// The spam classifier scored `target`, by `author`; `held` when the score put the
+    /// author's rewards on hold.
+    SpamScoreSet {
+        target: Pubkey,
+        author: Pubkey,
+        score: u8,
+        held: bool,
+    },
+    /// `admin` reviewed `author` and released their reward hold.
+    RewardHoldReleased {
+        author: Pubkey,
+        admin: Pubkey,
+    },
+    /// `admin` recorded that the accounts were migrated from layout `from_version` to
+    /// `to_version`.
+    MigrationCompleted {
+        admin: Pubkey,
+        from_version: u32,
+        to_version: u32,
+    },
+    /// `count` replies to `intro` had their replier and name removed after `course` ended.
+    RepliesAnonymized {
+        course: Pubkey,
+        intro: Pubkey,
+        count: u64,
+    },
+    /// `GcOrphans` closed `account`, left behind by `intro`, and paid its `lamports` to
+    /// `destination`.
+    OrphanCollected {
+        account: Pubkey,
+        intro: Pubkey,
+        destination: Pubkey,
+        lamports: u64,
+    },
+    /// The writer of question `intro` selected `reply`, by `replier`, as its best answer.
+    BestAnswerSelected {
+        intro: Pubkey,
+        reply: Pubkey,
+        replier: Pubkey,
+    },
+    /// A reply prepared in `prepared_slot` was posted as `reply`; indexers order it by the
+    /// preparation rather than by when it landed.
+    ReplyFinalized {
+        intro: Pubkey,
+        reply: Pubkey,
+        prepared_slot: u64,
+    },
+    /// `tipper` sent `replier` `amount` reward tokens for `reply`, of which `fee` went to the
+    /// treasury.
+    ReplyTipped {
+        reply: Pubkey,
+        tipper: Pubkey,
+        replier: Pubkey,
+        amount: u64,
+        fee: u64,
+    },
+}
+
+pub fn emit(event: ProgramEvent) {
+    sol_log_data(&[&event.try_to_vec().unwrap()]);
+}
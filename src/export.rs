@@ -0,0 +1,84 @@
+//! Canonical encoding of program accounts for `ExportAccount`. Each account is decoded with its
+//! type's reader and Borsh-encoded back, so padding, stale bytes past the end and fields missing
+//! from older accounts (read as their defaults) never reach the export. A change to any of
+//! these encodings bumps `ExportReturn::VERSION`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{borsh::try_from_slice_unchecked, msg, program_error::ProgramError};
+
+use crate::state::*;
+
+/// `data`'s discriminator and canonical encoding, or `InvalidAccountData` when it is not an
+/// account this program writes.
+pub fn canonical(data: &[u8]) -> Result<(String, Vec<u8>), ProgramError> {
+    let kind = String::deserialize(&mut &data[..]).map_err(|_| ProgramError::InvalidAccountData)?;
+
+    let encoded = match kind.as_str() {
+        StudentIntroState::DISCRIMINATOR => reencode::<StudentIntroState>(data)?,
+        StudentReplyState::DISCRIMINATOR => reencode::<StudentReplyState>(data)?,
+        ReplyCount::DISCRIMINATOR => reencode::<ReplyCount>(data)?,
+        ProgramConfig::DISCRIMINATOR => reencode::<ProgramConfig>(data)?,
+        WalletLink::DISCRIMINATOR => reencode::<WalletLink>(data)?,
+        WalletLinkCount::DISCRIMINATOR => reencode::<WalletLinkCount>(data)?,
+        ReplyCommitment::DISCRIMINATOR => reencode::<ReplyCommitment>(data)?,
+        ReplyPreparation::DISCRIMINATOR => reencode::<ReplyPreparation>(data)?,
+        DirectMessage::DISCRIMINATOR => reencode::<DirectMessage>(data)?,
+        DirectMessageCount::DISCRIMINATOR => reencode::<DirectMessageCount>(data)?,
+        ReplyVote::DISCRIMINATOR => reencode::<ReplyVote>(data)?,
+        ReplyScore::DISCRIMINATOR => reencode::<ReplyScore>(data)?,
+        Reputation::DISCRIMINATOR => reencode::<Reputation>(data)?,
+        TopReplies::DISCRIMINATOR => reencode::<TopReplies>(data)?,
+        Course::DISCRIMINATOR => reencode::<Course>(data)?,
+        IntroEndorsement::DISCRIMINATOR => reencode::<IntroEndorsement>(data)?,
+        Certificate::DISCRIMINATOR => reencode::<Certificate>(data)?,
+        Session::DISCRIMINATOR => reencode::<Session>(data)?,
+        Attendance::DISCRIMINATOR => reencode::<Attendance>(data)?,
+        Quiz::DISCRIMINATOR => reencode::<Quiz>(data)?,
+        QuizSubmission::DISCRIMINATOR => reencode::<QuizSubmission>(data)?,
+        TeamState::DISCRIMINATOR => reencode::<TeamState>(data)?,
+        TeamMembership::DISCRIMINATOR => reencode::<TeamMembership>(data)?,
+        ActivityFeed::DISCRIMINATOR => reencode::<ActivityFeed>(data)?,
+        OfficeHoursQueue::DISCRIMINATOR => reencode::<OfficeHoursQueue>(data)?,
+        EmailHashEntry::DISCRIMINATOR => reencode::<EmailHashEntry>(data)?,
+        StudentIdEntry::DISCRIMINATOR => reencode::<StudentIdEntry>(data)?,
+        Enrollment::DISCRIMINATOR => reencode::<Enrollment>(data)?,
+        CourseStats::DISCRIMINATOR => reencode::<CourseStats>(data)?,
+        UserNonce::DISCRIMINATOR => reencode::<UserNonce>(data)?,
+        StudentActivity::DISCRIMINATOR => reencode::<StudentActivity>(data)?,
+        ReplyTally::DISCRIMINATOR => reencode::<ReplyTally>(data)?,
+        IntroTally::DISCRIMINATOR => reencode::<IntroTally>(data)?,
+        EditHistory::DISCRIMINATOR => reencode::<EditHistory>(data)?,
+        IntroRegistry::DISCRIMINATOR => reencode::<IntroRegistry>(data)?,
+        RegistryEntry::DISCRIMINATOR => reencode::<RegistryEntry>(data)?,
+        IntroFeedPage::DISCRIMINATOR => reencode::<IntroFeedPage>(data)?,
+        QuestionFeed::DISCRIMINATOR => reencode::<QuestionFeed>(data)?,
+        RewardHold::DISCRIMINATOR => reencode::<RewardHold>(data)?,
+        ProfileCard::DISCRIMINATOR => reencode::<ProfileCard>(data)?,
+        Featured::DISCRIMINATOR => reencode::<Featured>(data)?,
+        ReceivedMessage::DISCRIMINATOR => reencode::<ReceivedMessage>(data)?,
+        PendingConfigChange::DISCRIMINATOR => reencode::<PendingConfigChange>(data)?,
+        PendingWithdrawal::DISCRIMINATOR => reencode::<PendingWithdrawal>(data)?,
+        IntroTranslation::DISCRIMINATOR => reencode::<IntroTranslation>(data)?,
+        IntroProfile::DISCRIMINATOR => reencode::<IntroProfile>(data)?,
+        CategoryRegistry::DISCRIMINATOR => reencode::<CategoryRegistry>(data)?,
+        Category::DISCRIMINATOR => reencode::<Category>(data)?,
+        IntroSlug::DISCRIMINATOR => reencode::<IntroSlug>(data)?,
+        PendingAction::DISCRIMINATOR => reencode::<PendingAction>(data)?,
+        VestingSchedule::DISCRIMINATOR => reencode::<VestingSchedule>(data)?,
+        BadgeAward::DISCRIMINATOR => reencode::<BadgeAward>(data)?,
+        _ => {
+            msg!("Account kind {:?} cannot be exported", kind);
+            return Err(ProgramError::InvalidAccountData);
+        }
+    };
+
+    Ok((kind, encoded))
+}
+
+fn decode<T: BorshDeserialize>(data: &[u8]) -> Result<T, ProgramError> {
+    try_from_slice_unchecked(data).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+fn reencode<T: BorshDeserialize + BorshSerialize>(data: &[u8]) -> Result<Vec<u8>, ProgramError> {
+    Ok(decode::<T>(data)?.try_to_vec()?)
+}
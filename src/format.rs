@@ -0,0 +1,109 @@
+//! How clients render intro and reply messages, and the rules markdown messages are held to.
+
+use solana_program::{msg, program_error::ProgramError};
+
+use crate::{error::IntroError, state::CharacterPolicy};
+
+/// Rendered as-is; every older intro and reply reads as this.
+pub const PLAIN: u8 = 0;
+pub const MARKDOWN: u8 = 1;
+
+/// Most inline links (`[text](url)`) a markdown message may contain.
+pub const MAX_MARKDOWN_LINKS: usize = 3;
+
+/// Scheme a link needs to be extracted from a message.
+const LINK_SCHEME: &str = "https://";
+
+/// The first `max` https URLs in `message`, whatever its format. A URL runs until whitespace or
+/// a bracket or quote, so markdown link targets come out whole; trailing sentence punctuation
+/// is dropped. Other schemes are skipped rather than rejected.
+pub fn extract_links(message: &str, max: usize) -> Vec<String> {
+    message
+        .split(|c: char| c.is_whitespace() || "()[]<>\"'".contains(c))
+        .map(|word| word.trim_end_matches(|c: char| ".,;:!?".contains(c)))
+        .filter(|word| word.len() > LINK_SCHEME.len() && word.starts_with(LINK_SCHEME))
+        .take(max)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Accepts a `name` and `message` that follow a course's character policy.
+pub fn verify_character_policy(
+    policy: &CharacterPolicy,
+    name: &str,
+    message: &str,
+) -> Result<(), ProgramError> {
+    let texts = [name, message];
+
+    if policy.ascii_only && !texts.iter().all(|text| text.is_ascii()) {
+        msg!("The course allows only ASCII text");
+        return Err(IntroError::CharacterPolicyViolation.into());
+    }
+
+    if policy.disallow_urls && texts.iter().any(|text| contains_url(text)) {
+        msg!("The course does not allow URLs");
+        return Err(IntroError::CharacterPolicyViolation.into());
+    }
+
+    if let Some(max_emoji) = policy.max_emoji {
+        let emoji = texts.iter().map(|text| emoji_count(text)).sum::<usize>();
+        if emoji > usize::from(max_emoji) {
+            msg!("The course allows at most {} emoji", max_emoji);
+            return Err(IntroError::CharacterPolicyViolation.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Pictographs, symbols and dingbats in `text`. Modifiers, variation selectors and joiners are
+/// not counted, so a composed emoji counts once per base character.
+pub fn emoji_count(text: &str) -> usize {
+    text.chars()
+        .filter(|c| {
+            matches!(
+                u32::from(*c),
+                0x1F000..=0x1F3FA | 0x1F400..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF
+            )
+        })
+        .count()
+}
+
+fn contains_url(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    lower.contains("://") || lower.contains("www.")
+}
+
+/// Accepts a known format, and for markdown a `message` within the link limit and free of raw
+/// HTML: any `<` opening a tag, closing tag, comment or declaration. Autolinks (`<https://..>`)
+/// read as tags too, so links have to use the inline form.
+pub fn verify_format(format: u8, message: &str) -> Result<(), ProgramError> {
+    match format {
+        PLAIN => Ok(()),
+        MARKDOWN => {
+            let bytes = message.as_bytes();
+
+            if bytes.windows(2).any(|pair| {
+                pair[0] == b'<' && (pair[1].is_ascii_alphabetic() || b"/!?".contains(&pair[1]))
+            }) {
+                msg!("Markdown messages may not contain raw HTML");
+                return Err(IntroError::InvalidFormat.into());
+            }
+
+            let links = bytes.windows(2).filter(|pair| pair == b"](").count();
+            if links > MAX_MARKDOWN_LINKS {
+                msg!(
+                    "Markdown messages may contain at most {} links",
+                    MAX_MARKDOWN_LINKS
+                );
+                return Err(IntroError::InvalidFormat.into());
+            }
+
+            Ok(())
+        }
+        _ => {
+            msg!("Unknown message format {}", format);
+            Err(IntroError::InvalidFormat.into())
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo, borsh::try_from_slice_unchecked, clock::Clock, msg,
+    program_error::ProgramError, pubkey, pubkey::Pubkey, sysvar::Sysvar,
+};
+
+use crate::error::{log_account_check, AccountCheck, IntroError};
+
+pub const CIVIC_GATEWAY_PROGRAM_ID: Pubkey = pubkey!("gatem74V238djXdzWnJf94Wo1DcnuGkfijbf3AuBhfs");
+
+const GATEWAY_TOKEN_ACTIVE: u8 = 0;
+
+// Mirrors the account layout written by the Civic gateway program.
+#[derive(BorshDeserialize)]
+struct GatewayToken {
+    _features: u8,
+    _parent_gateway_token: Option<Pubkey>,
+    owner_wallet: Pubkey,
+    _owner_identity: Option<Pubkey>,
+    gatekeeper_network: Pubkey,
+    _issuing_gatekeeper: Pubkey,
+    state: u8,
+    expire_time: Option<i64>,
+}
+
+pub fn verify_gateway_token(
+    gateway_token: &AccountInfo,
+    wallet: &Pubkey,
+    gatekeeper_network: &Pubkey,
+) -> Result<(), ProgramError> {
+    if *gateway_token.owner != CIVIC_GATEWAY_PROGRAM_ID {
+        log_account_check("gateway_token", gateway_token.key, AccountCheck::Owner);
+        return Err(IntroError::InvalidGatewayToken.into());
+    }
+
+    let token = try_from_slice_unchecked::<GatewayToken>(&gateway_token.data.borrow())
+        .map_err(|_| IntroError::InvalidGatewayToken)?;
+
+    if token.owner_wallet != *wallet || token.gatekeeper_network != *gatekeeper_network {
+        msg!("Gateway token was issued for another wallet or network");
+        return Err(IntroError::InvalidGatewayToken.into());
+    }
+
+    if token.state != GATEWAY_TOKEN_ACTIVE {
+        msg!("Gateway token is not active");
+        return Err(IntroError::InvalidGatewayToken.into());
+    }
+
+    if let Some(expire_time) = token.expire_time {
+        if Clock::get()?.unix_timestamp >= expire_time {
+            msg!("Gateway token expired");
+            return Err(IntroError::InvalidGatewayToken.into());
+        }
+    }
+
+    Ok(())
+}
@@ -1,11 +1,417 @@
-use borsh::BorshDeserialize;
-use solana_program::program_error::ProgramError;
+// `derive(BorshSchema)` on an enum declares a struct per variant whose fields are never read.
+#![allow(dead_code)]
 
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{instruction::AccountMeta, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    format, lang, pda,
+    state::{
+        ActionAccount, CertificateRequirements, CharacterPolicy, ReplyRewardTier, StudentIntroState,
+    },
+};
+
+/// The config PDA, read-only: the last account of every instruction.
+pub fn config_account(program_id: &Pubkey) -> AccountMeta {
+    AccountMeta::new_readonly(pda::config(program_id), false)
+}
+
+/// The config PDA, writable, for the instructions that change it.
+pub fn config_account_mut(program_id: &Pubkey) -> AccountMeta {
+    AccountMeta::new(pda::config(program_id), false)
+}
+
+/// The schema describes the v1 payloads with every optional trailing field present.
+///
+/// Every instruction ends with `config_account`, after the accounts its handler reads, so the
+/// kill switch and data-version checks run before dispatch. Handlers read the config from
+/// there and take no copy of their own; `InitializeConfig`, `UpdateConfig`,
+/// `ExecuteConfigChange`, `SetInstructionEnabled`, `CompleteMigration`, `NominateAdmin`,
+/// `AcceptAdmin` and `MigrateMint` change it, so they end with `config_account_mut` instead.
+#[derive(BorshSchema)]
 pub enum StudentInstruction {
-    StudentIntro { name: String, message: String },
-    UpdateIntro { name: String, message: String },
-    ReplyIntro { name: String, message: String },
-    InitializeMint,
+    /// A question intro passes the `["questions"]` feed last, just before the config, after every
+    /// optional account before it (the program id stands in for ones not provided).
+    StudentIntro {
+        name: String,
+        message: String,
+        expires_at: Option<i64>,
+        lang: [u8; 2],
+        format: u8,
+        kind: u8,
+    },
+    /// An intro scoped to a course takes the course after the system program.
+    UpdateIntro {
+        name: String,
+        message: String,
+    },
+    /// A quoting reply passes the quoted reply as its last account, after every optional
+    /// account before it (the program id stands in for ones not provided). Each entry of
+    /// `reward_split_bps` routes that share of the reward to a co-recipient's reward token
+    /// account, passed in the same order after everything else; the replier keeps the rest.
+    ReplyIntro {
+        name: String,
+        message: String,
+        quote: Option<QuoteParams>,
+        lang: [u8; 2],
+        format: u8,
+        reward_split_bps: Vec<u16>,
+    },
+    InitializeMint {
+        enable_freeze: bool,
+    },
+    InitializeConfig {
+        params: ConfigParams,
+    },
+    UpdateConfig {
+        params: ConfigParams,
+    },
+    /// Takes the primary's and the linked wallet's `["link_count"]` accounts after the system
+    /// program. A wallet that is already linked, or that other wallets link to, can't be
+    /// linked.
+    LinkWallet,
+    /// Takes the primary's `["link_count"]` account after the link.
+    UnlinkWallet,
+    /// Commits to a reply at `[intro, committer, hash]`, where `hash` is
+    /// `ReplyCommitment::hash_reply` of its name, message and salt.
+    CommitReply {
+        hash: [u8; 32],
+    },
+    /// Takes the committer, writable, between the reply counter and the commitment. A
+    /// course-only intro then takes the committer's enrollment, the course stats, the
+    /// committer's activity account and the course after the system program, as `ReplyIntro`
+    /// does.
+    RevealReply {
+        name: String,
+        message: String,
+        salt: [u8; 32],
+    },
+    SendDirectMessage {
+        ciphertext: Vec<u8>,
+        nonce: [u8; 24],
+    },
+    /// The voter's and the replier's `["link"]` addresses follow the system program, whether
+    /// or not a link lives there; a vote between wallets of the same primary is rejected.
+    /// Passing the replier's (or their primary's) `[wallet, "reputation"]` account after them
+    /// credits it for any vote milestones the reply has reached; with a spam classifier
+    /// configured, the replier's `["reward_hold"]` account follows it.
+    VoteReply {
+        upvote: bool,
+    },
+    CreateCourse {
+        name: String,
+        requires_student_id: bool,
+        certificate: CertificateRequirements,
+    },
+    EnrollStudent,
+    /// Scoping to a course that requires student IDs takes `student_id_hash`, and the course
+    /// and `["sid", course, hash]` accounts after the system program.
+    SetVisibility {
+        visibility: u8,
+        student_id_hash: Option<[u8; 32]>,
+    },
+    CloseExpiredIntro,
+    /// Takes the cranker, `["featured"]`, `["registry"]`, `["featured_draw"]`, SlotHashes and
+    /// the system program. The first call of an epoch only commits the draw to a slot; the
+    /// call that draws appends a (registry entry, intro) pair per candidate index up to the
+    /// first eligible intro, then the reward accounts. With a spam classifier configured, the
+    /// writer's `["reward_hold"]` account follows the token program, ahead of any vesting
+    /// accounts.
+    SelectFeatured,
+    EmitCrossChainIntro {
+        nonce: u32,
+    },
+    /// A course-only intro takes the sender's enrollment, the course stats, the sender's
+    /// activity account and the course after the system program, as `ReplyIntro` does.
+    ReceiveCrossChainReply,
+    ProposeConfigChange {
+        params: ConfigParams,
+    },
+    ExecuteConfigChange,
+    ProposeAdminAction {
+        action_id: u64,
+        accounts: Vec<ActionAccount>,
+        instruction_data: Vec<u8>,
+    },
+    ApproveAdminAction,
+    ExecuteAdminAction,
+    NominateAdmin {
+        new_admin: Pubkey,
+    },
+    AcceptAdmin,
+    FreezeRewardAccount,
+    ThawRewardAccount,
+    BurnTreasuryTokens {
+        amount: u64,
+    },
+    ClaimVested,
+    MintMilestoneBadge,
+    Batch {
+        ops: Vec<BatchOp>,
+    },
+    /// Same as `StudentIntro`, but succeeds without changes when the intro already exists.
+    CreateIntroIfMissing {
+        name: String,
+        message: String,
+        expires_at: Option<i64>,
+        lang: [u8; 2],
+        format: u8,
+        kind: u8,
+    },
+    /// Read-only; results come back as return data, see `return_data`.
+    GetConfig,
+    GetCourseStats,
+    GetVersion,
+    /// Skips the signer's current nonce, invalidating any payload they signed but nobody relayed.
+    AdvanceNonce,
+    ImportIntro {
+        attestation: IntroAttestation,
+    },
+    SetCourseRewards {
+        enabled: bool,
+    },
+    /// Admin: creates the next versioned reward mint and pays all rewards from it from now on.
+    MigrateMint {
+        enable_freeze: bool,
+    },
+    /// Burns `amount` of an earlier reward mint and mints the same amount of the current one.
+    SwapOldTokens {
+        from_version: u8,
+        amount: u64,
+    },
+    /// Admin: queues a transfer of `amount` from the treasury, executable after the timelock.
+    ProposeTreasuryWithdrawal {
+        amount: u64,
+    },
+    ExecuteTreasuryWithdrawal,
+    /// Gives the signer's intro a unique URL handle; its previous one redirects for an epoch.
+    /// Claiming a slug whose entry names another intro takes that intro after the system
+    /// program, ahead of the previous slug's entry: the slug is free once that intro is
+    /// closed or no longer holds it.
+    SetSlug {
+        slug: String,
+    },
+    /// Admin: creates the next category.
+    CreateCategory {
+        name: String,
+    },
+    /// Files the signer's intro under a category, or clears it when the program id is passed.
+    SetCategory,
+    /// Writes the signer's intro in another language; an empty message removes the translation.
+    AddTranslation {
+        lang: [u8; 2],
+        message: String,
+    },
+    SetProfile {
+        params: ProfileParams,
+    },
+    RegisterEmailHash {
+        hash: [u8; 32],
+    },
+    /// Admin override freeing an email hash, e.g. for a student who moved to a new wallet.
+    ReleaseEmailHash,
+    SetStudentStatus {
+        status: u8,
+    },
+    EndorseIntro,
+    ClaimCertificate,
+    /// Office-hours help queue for a course; only its teacher pops.
+    JoinQueue,
+    LeaveQueue,
+    PopQueue,
+    /// Teacher-only: opens attendance session `session_id` between `opens_at` and `closes_at`
+    /// (unix seconds), paying `reward` base units to each student who checks in.
+    OpenSession {
+        session_id: u64,
+        opens_at: i64,
+        closes_at: i64,
+        reward: u64,
+    },
+    /// A paying session takes the course writable: its rewards count against the config's
+    /// `course_reward_cap`. With a spam classifier configured, the student's `["reward_hold"]`
+    /// account follows the token program.
+    CheckIn,
+    /// Teacher-only: opens quiz `quiz_id` for submissions until `closes_at`, committing to the
+    /// answer with `Quiz::commit_answer(answer, salt)`. Correct submitters earn `reward`.
+    CreateQuiz {
+        quiz_id: u64,
+        closes_at: i64,
+        answer_hash: [u8; 32],
+        reward: u64,
+    },
+    /// `answer_hash` is `QuizSubmission::hash_answer(quiz, student, answer)`, so it cannot be
+    /// copied from another student's submission.
+    SubmitQuizAnswer {
+        quiz_id: u64,
+        answer_hash: [u8; 32],
+    },
+    /// Teacher-only, once the quiz closes: reveals the committed answer and grades the
+    /// submissions passed in. Can be repeated to grade more submissions. A paying quiz takes
+    /// the course writable, as `CheckIn` does, and each submission is followed by its
+    /// student's token account and, with a spam classifier configured, their reward hold.
+    RevealAnswer {
+        answer: String,
+        salt: [u8; 32],
+    },
+    /// Project teams of students with intros; the creator is the first member.
+    CreateTeam {
+        name: String,
+    },
+    JoinTeam,
+    LeaveTeam,
+    /// Creates the signer's `[user, "feed"]` activity feed. Any later instruction signed by
+    /// the user records itself there when the feed is passed writable after its other accounts.
+    InitActivityFeed,
+    /// Intro writer only: marks a reply to their intro as read.
+    MarkReplyRead,
+    /// Mints the reward tokens the signer's reputation has accrued.
+    ClaimReputationRewards,
+    /// Config `view_indexer` only: adds `count` off-chain views to an intro, at most once per
+    /// intro per epoch.
+    RecordViews {
+        count: u64,
+    },
+    /// Admin kill switch: turns the instruction with variant byte `variant` off or back on,
+    /// without going through the config timelock. Any instruction can be switched off except
+    /// those in `ALWAYS_ENABLED`.
+    SetInstructionEnabled {
+        variant: u8,
+        enabled: bool,
+    },
+    /// Dry run of `StudentIntro` with the same payload and accounts: runs its checks, writes
+    /// nothing and reports the outcome as `IntroValidationReturn` return data.
+    ValidateIntroPayload {
+        name: String,
+        message: String,
+        expires_at: Option<i64>,
+        lang: [u8; 2],
+        format: u8,
+        kind: u8,
+    },
+    /// Permissionless backfill: adds an intro created before index `target` existed, one of
+    /// the `StudentIntroState::INDEX_*` values, to it exactly once.
+    IndexExisting {
+        target: u8,
+    },
+    /// Sets the intro's identity checkmark. The config's `identity_verifier` signs the
+    /// attestation in the preceding Ed25519 instruction; anyone can relay it.
+    VerifyIdentity {
+        attestation: IdentityAttestation,
+    },
+    /// Config `spam_classifier` only: scores the intro or reply `target`. A score above the
+    /// config's `spam_threshold` also holds its author's rewards until `ReleaseRewardHold`.
+    SetSpamScore {
+        target: Pubkey,
+        score: u8,
+    },
+    /// Admin, after reviewing the author's flagged posts: lets their intros and replies earn
+    /// rewards again.
+    ReleaseRewardHold,
+    /// Permissionless: creates or rebuilds the intro writer's `ProfileCard` from their intro,
+    /// reputation and badge accounts.
+    SyncProfileCard,
+    /// Admin, once an upgrade's migration cranks have run: records the config's data version
+    /// as the binary's `ProgramConfig::DATA_VERSION`, letting the other instructions run again.
+    CompleteMigration,
+    /// Teacher only: sets when the course ends and whether replies to its intros are
+    /// anonymized afterwards.
+    SetCourseRetention {
+        ends_at: i64,
+        anonymize_replies: bool,
+    },
+    /// Permissionless crank, once a course with `anonymize_replies` has ended: replaces the
+    /// replier of each passed reply to an intro scoped to it with `ANONYMOUS_REPLIER` and
+    /// clears its name. Takes the course, the intro, then the replies.
+    AnonymizeReplies,
+    /// Read-only: reports the program account `target`, passed as the only account, in the
+    /// canonical encoding of `export::canonical` as `ExportReturn` return data.
+    ExportAccount {
+        target: Pubkey,
+    },
+    /// Permissionless: closes an intro's reply counter once the intro is gone, closed or was
+    /// never initialized, paying its rent to the config's `orphan_rent_destination`.
+    GcOrphans,
+    /// Teacher only: sets the character policy intros scoped to the course and replies to its
+    /// course-only intros are held to from now on.
+    SetCharacterPolicy {
+        policy: CharacterPolicy,
+    },
+    /// Question writer only: marks reply `index` as the best answer. The question stops taking
+    /// replies `StudentIntroState::ANSWER_GRACE_SECS` later.
+    SelectBestAnswer {
+        index: u64,
+    },
+    /// Records a draft reply by its `ReplyPreparation::hash_reply`, holding its place for the
+    /// first-reply bonus until `FinalizeReply` posts it.
+    PrepareReply {
+        content_hash: [u8; 32],
+    },
+    /// Posts a prepared reply: takes the accounts of `ReplyIntro`, then the replier's
+    /// preparation as its last account, after every optional account before it.
+    FinalizeReply {
+        name: String,
+        message: String,
+    },
+    /// Teacher only: pins the intro passed after the course to the top of the course board.
+    PinIntro,
+    /// Teacher only: unpins the intro passed after the course, which may since have closed.
+    UnpinIntro,
+    /// Sends `amount` reward tokens to a reply's author, less the config's `protocol_fee_bps`,
+    /// which goes to the treasury.
+    TipReply {
+        amount: u64,
+    },
+}
+
+/// Personal details for `SetProfile`; empty strings and `[0, 0]` leave a field unset.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone, Default)]
+pub struct ProfileParams {
+    pub pronouns: String,
+    pub timezone: String,
+    pub country: [u8; 2],
+    /// `IntroProfile::*_PUBLIC` bits for the fields that may appear in events.
+    pub public_fields: u8,
+}
+
+/// What an intro's writer signs (Borsh-encoded, via the Ed25519 program) to have the intro
+/// recreated by `ImportIntro` on another deployment.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct IntroAttestation {
+    /// The deployment the intro is imported into, so the signature can't be replayed elsewhere.
+    pub program_id: Pubkey,
+    pub writer: Pubkey,
+    pub name: String,
+    pub message: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    /// The writer's next nonce on the importing deployment.
+    pub nonce: u64,
+}
+
+/// What the config's `identity_verifier` signs (Borsh-encoded, via the Ed25519 program) to
+/// vouch that `writer` belongs to the off-chain identity `identity_hash` stands for.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct IdentityAttestation {
+    /// The deployment the checkmark is set on, so the signature can't be replayed elsewhere.
+    pub program_id: Pubkey,
+    pub writer: Pubkey,
+    /// Hash of the identity as the verifier records it, e.g. of a university ID.
+    pub identity_hash: [u8; 32],
+}
+
+/// The reply a `ReplyIntro` quotes and the exact text quoted from its message.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct QuoteParams {
+    pub reply: Pubkey,
+    pub excerpt: String,
+}
+
+/// One sub-instruction of a `Batch`: its own instruction data, run against the next
+/// `account_count` accounts of the batch.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct BatchOp {
+    pub account_count: u8,
+    pub data: Vec<u8>,
 }
 
 #[derive(BorshDeserialize)]
@@ -14,29 +420,701 @@ struct StudentIntroPayload {
     message: String,
 }
 
+#[derive(BorshDeserialize)]
+struct CommitReplyPayload {
+    hash: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct RevealReplyPayload {
+    name: String,
+    message: String,
+    salt: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct DirectMessagePayload {
+    ciphertext: Vec<u8>,
+    nonce: [u8; 24],
+}
+
+#[derive(BorshDeserialize)]
+struct VoteReplyPayload {
+    upvote: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct CreateCoursePayload {
+    name: String,
+}
+
+#[derive(BorshDeserialize)]
+struct SetVisibilityPayload {
+    visibility: u8,
+}
+
+#[derive(BorshDeserialize)]
+struct SetCourseRewardsPayload {
+    enabled: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct TreasuryWithdrawalPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct AddTranslationPayload {
+    lang: [u8; 2],
+    message: String,
+}
+
+#[derive(BorshDeserialize)]
+struct CreateCategoryPayload {
+    name: String,
+}
+
+#[derive(BorshDeserialize)]
+struct SetSlugPayload {
+    slug: String,
+}
+
+#[derive(BorshDeserialize)]
+struct MigrateMintPayload {
+    enable_freeze: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SwapOldTokensPayload {
+    from_version: u8,
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct EmitCrossChainIntroPayload {
+    nonce: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct ProposeAdminActionPayload {
+    action_id: u64,
+    accounts: Vec<ActionAccount>,
+    instruction_data: Vec<u8>,
+}
+
+#[derive(BorshDeserialize)]
+struct NominateAdminPayload {
+    new_admin: Pubkey,
+}
+
+#[derive(BorshDeserialize)]
+struct BurnTreasuryTokensPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct TipReplyPayload {
+    amount: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct CreateQuizPayload {
+    quiz_id: u64,
+    closes_at: i64,
+    answer_hash: [u8; 32],
+    reward: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SubmitQuizAnswerPayload {
+    quiz_id: u64,
+    answer_hash: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct RevealAnswerPayload {
+    answer: String,
+    salt: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct CreateTeamPayload {
+    name: String,
+}
+
+#[derive(BorshDeserialize)]
+struct SetSpamScorePayload {
+    target: Pubkey,
+    score: u8,
+}
+
+#[derive(BorshDeserialize)]
+struct SetCourseRetentionPayload {
+    ends_at: i64,
+    anonymize_replies: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetInstructionEnabledPayload {
+    variant: u8,
+    enabled: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct OpenSessionPayload {
+    session_id: u64,
+    opens_at: i64,
+    closes_at: i64,
+    reward: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct ConfigParams {
+    pub gatekeeper_network: Option<Pubkey>,
+    pub reveal_delay_slots: u64,
+    pub credential_collection: Option<Pubkey>,
+    pub credential_multiplier_bps: u16,
+    pub price_feed: Option<Pubkey>,
+    pub reference_price_cents: u64,
+    pub max_price_staleness_slots: u64,
+    pub max_price_confidence_bps: u16,
+    pub wormhole_program: Option<Pubkey>,
+    pub foreign_emitter_chain: u16,
+    pub foreign_emitter_address: [u8; 32],
+    pub governance_program: Option<Pubkey>,
+    pub governance: Pubkey,
+    pub config_timelock_slots: u64,
+    pub admin_signers: Vec<Pubkey>,
+    pub admin_threshold: u8,
+    pub vesting_threshold: u64,
+    pub vesting_duration_secs: i64,
+    pub badge_reply_threshold: u64,
+    pub reply_reward_tiers: Vec<ReplyRewardTier>,
+    pub first_reply_bonus: u64,
+    pub first_reply_window_secs: i64,
+    pub welcome_bots: Vec<Pubkey>,
+    pub view_indexer: Option<Pubkey>,
+    pub identity_verifier: Option<Pubkey>,
+    pub spam_classifier: Option<Pubkey>,
+    pub spam_threshold: u8,
+    pub orphan_rent_destination: Option<Pubkey>,
+    pub protocol_fee_bps: u16,
+    pub course_reward_cap: u64,
+}
+
 impl StudentInstruction {
+    /// Variant bytes from here up are reserved; this one introduces a versioned envelope.
+    pub const VERSIONED_PREFIX: u8 = 0xff;
+    /// Payload layout produced by current clients.
+    pub const CURRENT_VERSION: u8 = 1;
+    pub const MAX_BATCH_OPS: usize = 4;
+    /// Variant byte of `SetInstructionEnabled`, which can never switch itself off.
+    pub const SET_INSTRUCTION_ENABLED: u8 = 68;
+    /// Variants the kill switch refuses to turn off: itself, and the admin path (the multisig's
+    /// `ProposeAdminAction`, `ApproveAdminAction` and `ExecuteAdminAction`, `AcceptAdmin` and
+    /// `CompleteMigration`). Under a multisig, turning one of these off could leave no way to
+    /// turn it back on.
+    pub const ALWAYS_ENABLED: [u8; 6] = [
+        21,
+        22,
+        23,
+        25,
+        Self::SET_INSTRUCTION_ENABLED,
+        Self::COMPLETE_MIGRATION,
+    ];
+    /// Variant byte of `CompleteMigration`, the one instruction that runs while a migration is
+    /// outstanding, along with the multisig steps proposing, approving and executing it.
+    pub const COMPLETE_MIGRATION: u8 = 75;
+
+    /// The variant byte of `input`, looking through the versioned envelope.
+    pub fn variant(input: &[u8]) -> Option<u8> {
+        match input {
+            [Self::VERSIONED_PREFIX, _version, variant, ..] => Some(*variant),
+            [variant, ..] => Some(*variant),
+            [] => None,
+        }
+    }
+
+    /// Instruction data is either `[variant, payload..]`, the unversioned form read as
+    /// version 1, or `[VERSIONED_PREFIX, version, variant, payload..]`. A payload change gets a
+    /// new version so clients built against an older layout keep working.
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let (&variant, rest) = input
+        let (&first, rest) = input
             .split_first()
             .ok_or(ProgramError::InvalidInstructionData)?;
 
-        let payload = StudentIntroPayload::try_from_slice(rest).unwrap();
+        if first != Self::VERSIONED_PREFIX {
+            return Self::unpack_v1(first, rest);
+        }
+
+        let (&version, rest) = rest
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let (&variant, rest) = rest
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
 
+        match version {
+            1 => Self::unpack_v1(variant, rest),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+
+    fn unpack_v1(variant: u8, rest: &[u8]) -> Result<Self, ProgramError> {
         Ok(match variant {
-            0 => Self::StudentIntro {
-                name: payload.name,
-                message: payload.message,
+            0 => {
+                let payload = unpack_intro_payload(rest)?;
+                Self::StudentIntro {
+                    name: payload.name,
+                    message: payload.message,
+                    expires_at: payload.expires_at,
+                    lang: payload.lang,
+                    format: payload.format,
+                    kind: payload.kind,
+                }
+            }
+            1 => {
+                let payload = StudentIntroPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::UpdateIntro {
+                    name: payload.name,
+                    message: payload.message,
+                }
+            }
+            2 => {
+                let mut data = rest;
+                let payload = StudentIntroPayload::deserialize(&mut data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                // Older clients stop after the message, so the quote, language, format and
+                // reward split are optional tails.
+                let quote = if data.is_empty() {
+                    None
+                } else {
+                    Option::<QuoteParams>::deserialize(&mut data)
+                        .map_err(|_| ProgramError::InvalidInstructionData)?
+                };
+                let (lang, format) = take_lang_and_format(&mut data)?;
+                let reward_split_bps = if data.is_empty() {
+                    Vec::new()
+                } else {
+                    Vec::<u16>::try_from_slice(data)
+                        .map_err(|_| ProgramError::InvalidInstructionData)?
+                };
+                Self::ReplyIntro {
+                    name: payload.name,
+                    message: payload.message,
+                    quote,
+                    lang,
+                    format,
+                    reward_split_bps,
+                }
+            }
+            // Older clients send no payload, which keeps the mint without a freeze authority.
+            3 => Self::InitializeMint {
+                enable_freeze: if rest.is_empty() {
+                    false
+                } else {
+                    bool::try_from_slice(rest).map_err(|_| ProgramError::InvalidInstructionData)?
+                },
+            },
+            4 => Self::InitializeConfig {
+                params: ConfigParams::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            5 => Self::UpdateConfig {
+                params: ConfigParams::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            6 => Self::LinkWallet,
+            7 => Self::UnlinkWallet,
+            8 => {
+                let payload = CommitReplyPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CommitReply { hash: payload.hash }
+            }
+            9 => {
+                let payload = RevealReplyPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RevealReply {
+                    name: payload.name,
+                    message: payload.message,
+                    salt: payload.salt,
+                }
+            }
+            10 => {
+                let payload = DirectMessagePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SendDirectMessage {
+                    ciphertext: payload.ciphertext,
+                    nonce: payload.nonce,
+                }
+            }
+            11 => {
+                let payload = VoteReplyPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::VoteReply {
+                    upvote: payload.upvote,
+                }
+            }
+            12 => {
+                let mut data = rest;
+                let payload = CreateCoursePayload::deserialize(&mut data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                // Older clients send only the name and get a course without student IDs or
+                // certificate requirements.
+                let requires_student_id = !data.is_empty()
+                    && bool::deserialize(&mut data)
+                        .map_err(|_| ProgramError::InvalidInstructionData)?;
+                let certificate = if data.is_empty() {
+                    CertificateRequirements::default()
+                } else {
+                    CertificateRequirements::try_from_slice(data)
+                        .map_err(|_| ProgramError::InvalidInstructionData)?
+                };
+                Self::CreateCourse {
+                    name: payload.name,
+                    requires_student_id,
+                    certificate,
+                }
+            }
+            13 => Self::EnrollStudent,
+            14 => {
+                let mut data = rest;
+                let payload = SetVisibilityPayload::deserialize(&mut data)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetVisibility {
+                    visibility: payload.visibility,
+                    student_id_hash: if data.is_empty() {
+                        None
+                    } else {
+                        Option::<[u8; 32]>::try_from_slice(data)
+                            .map_err(|_| ProgramError::InvalidInstructionData)?
+                    },
+                }
+            }
+            15 => Self::CloseExpiredIntro,
+            16 => Self::SelectFeatured,
+            17 => {
+                let payload = EmitCrossChainIntroPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::EmitCrossChainIntro {
+                    nonce: payload.nonce,
+                }
+            }
+            18 => Self::ReceiveCrossChainReply,
+            19 => Self::ProposeConfigChange {
+                params: ConfigParams::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            20 => Self::ExecuteConfigChange,
+            21 => {
+                let payload = ProposeAdminActionPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::ProposeAdminAction {
+                    action_id: payload.action_id,
+                    accounts: payload.accounts,
+                    instruction_data: payload.instruction_data,
+                }
+            }
+            22 => Self::ApproveAdminAction,
+            23 => Self::ExecuteAdminAction,
+            24 => {
+                let payload = NominateAdminPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::NominateAdmin {
+                    new_admin: payload.new_admin,
+                }
+            }
+            25 => Self::AcceptAdmin,
+            26 => Self::FreezeRewardAccount,
+            27 => Self::ThawRewardAccount,
+            28 => {
+                let payload = BurnTreasuryTokensPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::BurnTreasuryTokens {
+                    amount: payload.amount,
+                }
+            }
+            29 => Self::ClaimVested,
+            30 => Self::MintMilestoneBadge,
+            31 => {
+                let ops = Vec::<BatchOp>::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                if ops.len() > Self::MAX_BATCH_OPS {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Self::Batch { ops }
+            }
+            32 => {
+                let payload = unpack_intro_payload(rest)?;
+                Self::CreateIntroIfMissing {
+                    name: payload.name,
+                    message: payload.message,
+                    expires_at: payload.expires_at,
+                    lang: payload.lang,
+                    format: payload.format,
+                    kind: payload.kind,
+                }
+            }
+            33 => Self::GetConfig,
+            34 => Self::GetCourseStats,
+            35 => Self::GetVersion,
+            36 => Self::AdvanceNonce,
+            37 => Self::ImportIntro {
+                attestation: IntroAttestation::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            38 => {
+                let payload = SetCourseRewardsPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetCourseRewards {
+                    enabled: payload.enabled,
+                }
+            }
+            39 => {
+                let payload = MigrateMintPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::MigrateMint {
+                    enable_freeze: payload.enable_freeze,
+                }
+            }
+            40 => {
+                let payload = SwapOldTokensPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SwapOldTokens {
+                    from_version: payload.from_version,
+                    amount: payload.amount,
+                }
+            }
+            41 => {
+                let payload = TreasuryWithdrawalPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::ProposeTreasuryWithdrawal {
+                    amount: payload.amount,
+                }
+            }
+            42 => Self::ExecuteTreasuryWithdrawal,
+            43 => {
+                let payload = SetSlugPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetSlug { slug: payload.slug }
+            }
+            44 => {
+                let payload = CreateCategoryPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateCategory { name: payload.name }
+            }
+            45 => Self::SetCategory,
+            46 => {
+                let payload = AddTranslationPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddTranslation {
+                    lang: payload.lang,
+                    message: payload.message,
+                }
+            }
+            47 => Self::SetProfile {
+                params: ProfileParams::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            48 => Self::RegisterEmailHash {
+                hash: <[u8; 32]>::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
             },
-            1 => Self::UpdateIntro {
-                name: payload.name,
-                message: payload.message,
+            49 => Self::ReleaseEmailHash,
+            50 => Self::SetStudentStatus {
+                status: u8::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
             },
-            2 => Self::ReplyIntro {
-                name: payload.name,
-                message: payload.message,
+            51 => Self::EndorseIntro,
+            52 => Self::ClaimCertificate,
+            53 => Self::JoinQueue,
+            54 => Self::LeaveQueue,
+            55 => Self::PopQueue,
+            56 => {
+                let payload = OpenSessionPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::OpenSession {
+                    session_id: payload.session_id,
+                    opens_at: payload.opens_at,
+                    closes_at: payload.closes_at,
+                    reward: payload.reward,
+                }
+            }
+            57 => Self::CheckIn,
+            58 => {
+                let payload = CreateQuizPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateQuiz {
+                    quiz_id: payload.quiz_id,
+                    closes_at: payload.closes_at,
+                    answer_hash: payload.answer_hash,
+                    reward: payload.reward,
+                }
+            }
+            59 => {
+                let payload = SubmitQuizAnswerPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SubmitQuizAnswer {
+                    quiz_id: payload.quiz_id,
+                    answer_hash: payload.answer_hash,
+                }
+            }
+            60 => {
+                let payload = RevealAnswerPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RevealAnswer {
+                    answer: payload.answer,
+                    salt: payload.salt,
+                }
+            }
+            61 => {
+                let payload = CreateTeamPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateTeam { name: payload.name }
+            }
+            62 => Self::JoinTeam,
+            63 => Self::LeaveTeam,
+            64 => Self::InitActivityFeed,
+            65 => Self::MarkReplyRead,
+            66 => Self::ClaimReputationRewards,
+            67 => Self::RecordViews {
+                count: u64::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
             },
-            3 => Self::InitializeMint,
+            Self::SET_INSTRUCTION_ENABLED => {
+                let payload = SetInstructionEnabledPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetInstructionEnabled {
+                    variant: payload.variant,
+                    enabled: payload.enabled,
+                }
+            }
+            69 => {
+                let payload = unpack_intro_payload(rest)?;
+                Self::ValidateIntroPayload {
+                    name: payload.name,
+                    message: payload.message,
+                    expires_at: payload.expires_at,
+                    lang: payload.lang,
+                    format: payload.format,
+                    kind: payload.kind,
+                }
+            }
+            70 => Self::IndexExisting {
+                target: u8::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            71 => Self::VerifyIdentity {
+                attestation: IdentityAttestation::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            72 => {
+                let payload = SetSpamScorePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetSpamScore {
+                    target: payload.target,
+                    score: payload.score,
+                }
+            }
+            73 => Self::ReleaseRewardHold,
+            74 => Self::SyncProfileCard,
+            Self::COMPLETE_MIGRATION => Self::CompleteMigration,
+            76 => {
+                let payload = SetCourseRetentionPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetCourseRetention {
+                    ends_at: payload.ends_at,
+                    anonymize_replies: payload.anonymize_replies,
+                }
+            }
+            77 => Self::AnonymizeReplies,
+            78 => Self::ExportAccount {
+                target: Pubkey::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            79 => Self::GcOrphans,
+            80 => Self::SetCharacterPolicy {
+                policy: CharacterPolicy::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            81 => Self::SelectBestAnswer {
+                index: u64::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            82 => Self::PrepareReply {
+                content_hash: <[u8; 32]>::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?,
+            },
+            83 => {
+                let payload = StudentIntroPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::FinalizeReply {
+                    name: payload.name,
+                    message: payload.message,
+                }
+            }
+            84 => Self::PinIntro,
+            85 => Self::UnpinIntro,
+            86 => {
+                let payload = TipReplyPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::TipReply {
+                    amount: payload.amount,
+                }
+            }
             _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
 }
+
+/// A `StudentIntro` payload with its optional tails filled in.
+struct IntroPayload {
+    name: String,
+    message: String,
+    expires_at: Option<i64>,
+    lang: [u8; 2],
+    format: u8,
+    kind: u8,
+}
+
+fn unpack_intro_payload(rest: &[u8]) -> Result<IntroPayload, ProgramError> {
+    let mut data = rest;
+    let payload = StudentIntroPayload::deserialize(&mut data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    // Older clients stop after the message, so the expiry, language, format and kind are
+    // optional tails.
+    let expires_at = if data.is_empty() {
+        None
+    } else {
+        Option::<i64>::deserialize(&mut data).map_err(|_| ProgramError::InvalidInstructionData)?
+    };
+    let (lang, format) = take_lang_and_format(&mut data)?;
+    let kind = if data.is_empty() {
+        StudentIntroState::KIND_INTRODUCTION
+    } else {
+        u8::try_from_slice(data).map_err(|_| ProgramError::InvalidInstructionData)?
+    };
+    Ok(IntroPayload {
+        name: payload.name,
+        message: payload.message,
+        expires_at,
+        lang,
+        format,
+        kind,
+    })
+}
+
+/// The optional language tag and message format of a payload. Clients that send neither post
+/// untagged plain text; a format can only follow a tag, which may be `lang::UNSPECIFIED`.
+fn take_lang_and_format(data: &mut &[u8]) -> Result<([u8; 2], u8), ProgramError> {
+    if data.is_empty() {
+        return Ok((lang::UNSPECIFIED, format::PLAIN));
+    }
+    let lang = <[u8; 2]>::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    if data.is_empty() {
+        return Ok((lang, format::PLAIN));
+    }
+    let format = u8::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    Ok((lang, format))
+}
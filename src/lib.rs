@@ -1,5 +1,24 @@
+#[cfg(not(target_os = "solana"))]
+pub mod client;
+#[cfg(feature = "no-entrypoint")]
+pub mod cpi;
+pub mod ed25519;
 pub mod entrypoint;
 pub mod error;
+pub mod events;
+pub mod export;
+pub mod format;
+pub mod gateway;
 pub mod instruction;
+pub mod lang;
+pub mod nft;
+pub mod oracle;
+pub mod pda;
 pub mod processor;
+pub mod return_data;
+#[cfg(not(target_os = "solana"))]
+pub mod schema;
 pub mod state;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+pub mod wormhole;
@@ -0,0 +1,188 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_token::{state::Account as TokenAccount, ID as TOKEN_PROGRAM_ID};
+
+use crate::error::{log_account_check, AccountCheck, IntroError};
+
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Longest `name` and `uri` Token Metadata accepts.
+pub const MAX_METADATA_NAME_LEN: usize = 32;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+
+// `CreateMetadataAccountArgsV3` with no creators, collection, uses or collection details.
+#[derive(BorshSerialize)]
+struct CreateMetadataArgs {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<()>,
+    collection: Option<()>,
+    uses: Option<()>,
+    is_mutable: bool,
+    collection_details: Option<()>,
+}
+
+/// Token Metadata's address for `mint`'s metadata account.
+pub fn find_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// `CreateMetadataAccountV3` for an immutable, royalty-free token whose mint and update
+/// authority are both `authority`.
+pub fn create_metadata_account(
+    metadata: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let args = CreateMetadataArgs {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+        is_mutable: false,
+        collection_details: None,
+    };
+    let mut data = vec![CREATE_METADATA_ACCOUNT_V3];
+    data.extend(args.try_to_vec().unwrap());
+
+    Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// A `data:` URI holding the certificate's JSON metadata, so the course and completion epoch
+/// are readable without any off-chain host. Course names are at most 32 bytes, which keeps the
+/// URI within `MAX_METADATA_URI_LEN`.
+pub fn certificate_uri(course_name: &str, completion_epoch: u64) -> String {
+    let name: String = course_name
+        .chars()
+        .filter(|c| !c.is_control())
+        .flat_map(|c| match c {
+            '"' | '\\' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect();
+    format!(
+        "data:application/json,{{\"name\":\"{}\",\"attributes\":[{{\"trait_type\":\"completion_epoch\",\"value\":{}}}]}}",
+        name, completion_epoch
+    )
+}
+
+#[derive(BorshDeserialize)]
+struct Creator {
+    _address: Pubkey,
+    _verified: bool,
+    _share: u8,
+}
+
+#[derive(BorshDeserialize)]
+struct Collection {
+    verified: bool,
+    key: Pubkey,
+}
+
+// Prefix of the Metaplex `Metadata` account up to the collection field.
+#[derive(BorshDeserialize)]
+struct Metadata {
+    _key: u8,
+    _update_authority: Pubkey,
+    mint: Pubkey,
+    _name: String,
+    _symbol: String,
+    _uri: String,
+    _seller_fee_basis_points: u16,
+    _creators: Option<Vec<Creator>>,
+    _primary_sale_happened: bool,
+    _is_mutable: bool,
+    _edition_nonce: Option<u8>,
+    _token_standard: Option<u8>,
+    collection: Option<Collection>,
+}
+
+/// Checks that `wallet` holds an NFT whose metadata is a verified member of `collection`.
+pub fn verify_collection_nft(
+    token_account: &AccountInfo,
+    metadata: &AccountInfo,
+    wallet: &Pubkey,
+    collection: &Pubkey,
+) -> Result<(), ProgramError> {
+    if *token_account.owner != TOKEN_PROGRAM_ID {
+        log_account_check("nft_token_account", token_account.key, AccountCheck::Owner);
+        return Err(IntroError::InvalidCredentialNft.into());
+    }
+
+    if *metadata.owner != TOKEN_METADATA_PROGRAM_ID {
+        log_account_check("nft_metadata", metadata.key, AccountCheck::Owner);
+        return Err(IntroError::InvalidCredentialNft.into());
+    }
+
+    let token = TokenAccount::unpack(&token_account.data.borrow())?;
+
+    if token.owner != *wallet || token.amount == 0 {
+        msg!("NFT is not held by the signer");
+        return Err(IntroError::InvalidCredentialNft.into());
+    }
+
+    let (metadata_pda, _metadata_bump) = find_metadata_address(&token.mint);
+
+    if metadata_pda != *metadata.key {
+        log_account_check("nft_metadata", metadata.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidCredentialNft.into());
+    }
+
+    let metadata_data = try_from_slice_unchecked::<Metadata>(&metadata.data.borrow())
+        .map_err(|_| IntroError::InvalidCredentialNft)?;
+
+    if metadata_data.mint != token.mint {
+        msg!("Metadata account does not belong to the NFT mint");
+        return Err(IntroError::InvalidCredentialNft.into());
+    }
+
+    match metadata_data.collection {
+        Some(nft_collection) if nft_collection.verified && nft_collection.key == *collection => {
+            Ok(())
+        }
+        _ => {
+            msg!("NFT is not a verified member of the credential collection");
+            Err(IntroError::InvalidCredentialNft.into())
+        }
+    }
+}
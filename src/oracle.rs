@@ -0,0 +1,102 @@
+use solana_program::{account_info::AccountInfo, clock::Clock, msg, sysvar::Sysvar};
+
+use crate::error::IntroError;
+
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+const PYTH_STATUS_TRADING: u32 = 1;
+
+// Offsets into a Pyth v2 price account.
+const EXPO_OFFSET: usize = 20;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUB_SLOT_OFFSET: usize = 232;
+
+/// Oracle-scaled rewards never move further than this factor away from the base reward.
+pub const MAX_PRICE_SCALE: u64 = 4;
+
+pub struct OraclePrice {
+    pub price: i64,
+    pub expo: i32,
+}
+
+/// Reads the aggregate price from a Pyth price account, rejecting stale or low-confidence data.
+pub fn read_pyth_price(
+    price_account: &AccountInfo,
+    max_staleness_slots: u64,
+    max_confidence_bps: u16,
+) -> Result<OraclePrice, IntroError> {
+    let data = price_account.data.borrow();
+
+    if data.len() < AGG_PUB_SLOT_OFFSET + 8 {
+        msg!("Price account is too small");
+        return Err(IntroError::InvalidOracleAccount);
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    let read_u64 = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+    if read_u32(0) != PYTH_MAGIC || read_u32(8) != PYTH_PRICE_ACCOUNT_TYPE {
+        msg!("Account is not a Pyth price account");
+        return Err(IntroError::InvalidOracleAccount);
+    }
+
+    if read_u32(AGG_STATUS_OFFSET) != PYTH_STATUS_TRADING {
+        msg!("Oracle price is not trading");
+        return Err(IntroError::StaleOraclePrice);
+    }
+
+    let current_slot = Clock::get().map_err(|_| IntroError::StaleOraclePrice)?.slot;
+    let pub_slot = read_u64(AGG_PUB_SLOT_OFFSET);
+
+    if current_slot.saturating_sub(pub_slot) > max_staleness_slots {
+        msg!("Oracle price was published at slot {}", pub_slot);
+        return Err(IntroError::StaleOraclePrice);
+    }
+
+    let price = read_u64(AGG_PRICE_OFFSET) as i64;
+    let conf = read_u64(AGG_CONF_OFFSET);
+
+    if price <= 0 || conf as u128 * 10_000 > price as u128 * max_confidence_bps as u128 {
+        msg!("Oracle price {} has confidence {}", price, conf);
+        return Err(IntroError::InvalidOracleAccount);
+    }
+
+    Ok(OraclePrice {
+        price,
+        expo: i32::from_le_bytes(data[EXPO_OFFSET..EXPO_OFFSET + 4].try_into().unwrap()),
+    })
+}
+
+/// Scales `base_amount` so it is worth what it would be at `reference_price_cents` per token.
+/// Rejects prices whose exponent puts the arithmetic out of range.
+pub fn scale_reward(
+    base_amount: u64,
+    reference_price_cents: u64,
+    oracle: &OraclePrice,
+) -> Result<u64, IntroError> {
+    // price in cents = price * 10^(expo + 2)
+    let exponent = oracle.expo.saturating_add(2);
+    let numerator = base_amount as u128 * reference_price_cents as u128;
+    let scaled = if exponent >= 0 {
+        10u128
+            .checked_pow(exponent as u32)
+            .and_then(|scale| scale.checked_mul(oracle.price as u128))
+            .map(|denominator| numerator / denominator)
+    } else {
+        10u128
+            .checked_pow(exponent.unsigned_abs())
+            .and_then(|scale| numerator.checked_mul(scale))
+            .map(|scaled| scaled / oracle.price as u128)
+    };
+    let scaled = scaled.ok_or_else(|| {
+        msg!("Oracle exponent {} is out of range", oracle.expo);
+        IntroError::InvalidOracleAccount
+    })?;
+
+    Ok((scaled.min(u64::MAX as u128) as u64).clamp(
+        base_amount / MAX_PRICE_SCALE,
+        base_amount.saturating_mul(MAX_PRICE_SCALE),
+    ))
+}
@@ -0,0 +1,354 @@
+//! Address derivations for the program's PDAs, mirroring the seeds the processor checks.
+
+use solana_program::pubkey::Pubkey;
+
+pub fn token_mint(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"token_mint"], program_id).0
+}
+
+/// Reward mint created by `MigrateMint` as `version`; version 0 is [`token_mint`].
+pub fn token_mint_version(program_id: &Pubkey, version: u8) -> Pubkey {
+    if version == 0 {
+        return token_mint(program_id);
+    }
+    Pubkey::find_program_address(&[b"token_mint", &[version]], program_id).0
+}
+
+pub fn mint_auth(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"token_auth"], program_id).0
+}
+
+pub fn config(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], program_id).0
+}
+
+pub fn intro(program_id: &Pubkey, writer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[writer.as_ref(), "intro".as_ref()], program_id).0
+}
+
+pub fn reply_counter(program_id: &Pubkey, intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[intro.as_ref(), "counter".as_ref()], program_id).0
+}
+
+/// Reply number `index` (0-based) on `intro`.
+pub fn reply(program_id: &Pubkey, intro: &Pubkey, index: u64) -> Pubkey {
+    Pubkey::find_program_address(&[intro.as_ref(), index.to_be_bytes().as_ref()], program_id).0
+}
+
+/// `replier`'s reply tally on `intro`. A linked wallet shares its primary's tally, so pass the
+/// primary for those.
+pub fn reply_tally(program_id: &Pubkey, intro: &Pubkey, replier: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[intro.as_ref(), replier.as_ref(), "tally".as_ref()],
+        program_id,
+    )
+    .0
+}
+
+/// `committer`'s commitment to the reply on `intro` hashing to `hash`.
+pub fn reply_commitment(
+    program_id: &Pubkey,
+    intro: &Pubkey,
+    committer: &Pubkey,
+    hash: &[u8; 32],
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[intro.as_ref(), committer.as_ref(), hash.as_ref()],
+        program_id,
+    )
+    .0
+}
+
+/// The link making `wallet` a secondary wallet of some primary.
+pub fn wallet_link(program_id: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[wallet.as_ref(), "link".as_ref()], program_id).0
+}
+
+/// How many wallets are linked to `primary`.
+pub fn wallet_link_count(program_id: &Pubkey, primary: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[primary.as_ref(), "link_count".as_ref()], program_id).0
+}
+
+/// `writer`'s intro tally, which records that their intro reward was paid.
+pub fn intro_tally(program_id: &Pubkey, writer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[writer.as_ref(), "intro_tally".as_ref()], program_id).0
+}
+
+/// `replier`'s pending `PrepareReply` on `intro`.
+pub fn reply_preparation(program_id: &Pubkey, intro: &Pubkey, replier: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[intro.as_ref(), replier.as_ref(), "prepared".as_ref()],
+        program_id,
+    )
+    .0
+}
+
+/// Recent past versions of `intro`, recorded on each update.
+pub fn edit_history(program_id: &Pubkey, intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[intro.as_ref(), "history".as_ref()], program_id).0
+}
+
+/// Authority of the treasury, whose associated token account holds the program's reward tokens.
+pub fn treasury(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury"], program_id).0
+}
+
+pub fn pending_withdrawal(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"pending_withdrawal"], program_id).0
+}
+
+pub fn course(program_id: &Pubkey, teacher: &Pubkey, name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[teacher.as_ref(), b"course", name.as_bytes()], program_id).0
+}
+
+pub fn enrollment(program_id: &Pubkey, course: &Pubkey, student: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[course.as_ref(), student.as_ref(), b"enrollment"],
+        program_id,
+    )
+    .0
+}
+
+pub fn course_stats(program_id: &Pubkey, course: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[course.as_ref(), b"stats"], program_id).0
+}
+
+pub fn student_activity(program_id: &Pubkey, enrollment: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[enrollment.as_ref(), b"activity"], program_id).0
+}
+
+/// The course teacher's endorsement of `intro`.
+pub fn endorsement(program_id: &Pubkey, course: &Pubkey, intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[course.as_ref(), intro.as_ref(), b"endorsement"],
+        program_id,
+    )
+    .0
+}
+
+/// Record of the completion certificate claimed for `intro` in `course`.
+pub fn certificate(program_id: &Pubkey, course: &Pubkey, intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[course.as_ref(), intro.as_ref(), b"certificate"],
+        program_id,
+    )
+    .0
+}
+
+/// NFT mint of the certificate recorded at `certificate`.
+pub fn certificate_mint(program_id: &Pubkey, certificate: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[certificate.as_ref(), b"mint"], program_id).0
+}
+
+/// Attendance session `session_id` of `course`.
+pub fn session(program_id: &Pubkey, course: &Pubkey, session_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[course.as_ref(), b"session", &session_id.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+/// `student`'s check-in to `session`.
+pub fn attendance(program_id: &Pubkey, session: &Pubkey, student: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[session.as_ref(), student.as_ref(), b"attendance"],
+        program_id,
+    )
+    .0
+}
+
+/// Quiz `quiz_id` of `course`.
+pub fn quiz(program_id: &Pubkey, course: &Pubkey, quiz_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[course.as_ref(), b"quiz", &quiz_id.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+/// `student`'s submission to `quiz`.
+pub fn quiz_submission(program_id: &Pubkey, quiz: &Pubkey, student: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[quiz.as_ref(), student.as_ref(), b"quiz_answer"],
+        program_id,
+    )
+    .0
+}
+
+/// Project team named `name`.
+pub fn team(program_id: &Pubkey, name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"team", name.as_bytes()], program_id).0
+}
+
+/// Record of the team `student` is on.
+pub fn team_membership(program_id: &Pubkey, student: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[student.as_ref(), b"team_member"], program_id).0
+}
+
+/// Score of `reply`.
+pub fn reply_score(program_id: &Pubkey, reply: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[reply.as_ref(), b"score"], program_id).0
+}
+
+/// `voter`'s vote on `reply`.
+pub fn reply_vote(program_id: &Pubkey, reply: &Pubkey, voter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[reply.as_ref(), voter.as_ref(), b"vote"], program_id).0
+}
+
+/// Top-ranked replies of `intro`.
+pub fn top_replies(program_id: &Pubkey, intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[intro.as_ref(), b"top_replies"], program_id).0
+}
+
+/// `user`'s reputation.
+pub fn reputation(program_id: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[user.as_ref(), b"reputation"], program_id).0
+}
+
+/// `user`'s activity feed.
+pub fn activity_feed(program_id: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[user.as_ref(), b"feed"], program_id).0
+}
+
+/// Office-hours queue of `course`.
+pub fn office_hours_queue(program_id: &Pubkey, course: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[course.as_ref(), b"queue"], program_id).0
+}
+
+/// Entry claiming the salted email hash `hash`.
+pub fn email_hash(program_id: &Pubkey, hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"email", hash], program_id).0
+}
+
+/// Entry claiming the student ID hashed to `hash` within `course`.
+pub fn student_id(program_id: &Pubkey, course: &Pubkey, hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"sid", course.as_ref(), hash], program_id).0
+}
+
+/// Personal details attached to `intro`.
+pub fn profile(program_id: &Pubkey, intro: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[intro.as_ref(), b"profile"], program_id).0
+}
+
+/// `intro`'s translation into `lang`.
+pub fn translation(program_id: &Pubkey, intro: &Pubkey, lang: &[u8; 2]) -> Pubkey {
+    Pubkey::find_program_address(&[intro.as_ref(), lang.as_ref()], program_id).0
+}
+
+pub fn category_registry(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"categories"], program_id).0
+}
+
+/// Category number `index` (0-based), in creation order.
+pub fn category(program_id: &Pubkey, index: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"category", &index.to_be_bytes()], program_id).0
+}
+
+/// Slug registry entry for `slug`, current or redirecting.
+pub fn slug(program_id: &Pubkey, slug: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"slug", slug.as_bytes()], program_id).0
+}
+
+/// The current epoch's featured intro; see `Featured`.
+pub fn featured(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"featured"], program_id).0
+}
+
+/// The draw that picks the featured intro; see `FeaturedDraw`.
+pub fn featured_draw(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"featured_draw"], program_id).0
+}
+
+pub fn registry(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"registry"], program_id).0
+}
+
+pub fn registry_entry(program_id: &Pubkey, index: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"registry", &index.to_be_bytes()], program_id).0
+}
+
+/// Page `page` of the global intro feed; see `IntroFeedPage`.
+pub fn intro_feed_page(program_id: &Pubkey, page: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"intro_feed", &page.to_be_bytes()], program_id).0
+}
+
+/// The feed of question intros; see `QuestionFeed`.
+pub fn questions(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"questions"], program_id).0
+}
+
+/// `author`'s reward hold; see `RewardHold`.
+pub fn reward_hold(program_id: &Pubkey, author: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[author.as_ref(), b"reward_hold"], program_id).0
+}
+
+/// `student`'s profile card; see `ProfileCard`.
+pub fn profile_card(program_id: &Pubkey, student: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[student.as_ref(), b"card"], program_id).0
+}
+
+/// Admin action `action_id` awaiting multisig approvals; see `PendingAction`.
+pub fn admin_action(program_id: &Pubkey, action_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"action", &action_id.to_be_bytes()], program_id).0
+}
+
+/// The authority that signs admin actions once a multisig threshold is set.
+pub fn multisig(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"multisig"], program_id).0
+}
+
+pub fn nonce(program_id: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[user.as_ref(), "nonce".as_ref()], program_id).0
+}
+
+/// Known derivations for checking client implementations in other languages against this
+/// crate. Every address is derived under [`PROGRAM_ID`](test_vectors::PROGRAM_ID), and the
+/// writers are the keys with all 32 bytes set to 1, 2 and 3.
+pub mod test_vectors {
+    use solana_program::{pubkey, pubkey::Pubkey};
+
+    /// `[7; 32]`, the program id the integration tests run under.
+    pub const PROGRAM_ID: Pubkey = pubkey!("US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx");
+
+    /// `["token_mint"]`.
+    pub const TOKEN_MINT: Pubkey = pubkey!("BLbn9fXHvbFNZu7xPGmwoXDcPihqqvmTUaQbsWjt2vRh");
+    pub const TOKEN_MINT_BUMP: u8 = 254;
+
+    /// `["token_auth"]`.
+    pub const MINT_AUTH: Pubkey = pubkey!("HRDHZvDw8NqpsDnGm6fz38oNvKj1p3wuR5Rsjnb3uVHN");
+    pub const MINT_AUTH_BUMP: u8 = 255;
+
+    /// A writer's `[writer, "intro"]` intro and its `[intro, "counter"]` reply counter.
+    pub struct IntroVector {
+        pub writer: Pubkey,
+        pub intro: Pubkey,
+        pub intro_bump: u8,
+        pub counter: Pubkey,
+        pub counter_bump: u8,
+    }
+
+    pub const INTROS: [IntroVector; 3] = [
+        IntroVector {
+            writer: pubkey!("4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi"),
+            intro: pubkey!("GB9xoQhL9zuhAYbgJLaJtup4XShduwA4Btx9oJeBRK3E"),
+            intro_bump: 255,
+            counter: pubkey!("57NzdLHfS4o8npKkAjuye2YVx8k9cRX2ujmzhdu3JBGH"),
+            counter_bump: 254,
+        },
+        IntroVector {
+            writer: pubkey!("8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR"),
+            intro: pubkey!("EN9Vuq14tAp3nou7h3zRmLiWX3TC3guqjegzFbNkSK3e"),
+            intro_bump: 254,
+            counter: pubkey!("BdjGrYnRsVrJ5m6cHGuHd8btMHXZzVQ4rxap79eyjxut"),
+            counter_bump: 254,
+        },
+        IntroVector {
+            writer: pubkey!("CktRuQ2mttgRGkXJtyksdKHjUdc2C4TgDzyB98oEzy8"),
+            intro: pubkey!("EdzarrhBNrpT7UBC6VvSPWCQzZKyYdsPfr3LoMNAiydJ"),
+            intro_bump: 254,
+            counter: pubkey!("8ZFqvbpgX4qoRQYdKwd11E8JUJyhbrfBxUCZkKwGtRKD"),
+            counter_bump: 255,
+        },
+    ];
+}
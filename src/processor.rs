@@ -1,445 +1,9840 @@
-use borsh::BorshSerialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     borsh::try_from_slice_unchecked,
+    clock::Clock,
     entrypoint::ProgramResult,
+    hash::{hash, hashv},
+    instruction::{AccountMeta, Instruction},
     msg,
-    native_token::LAMPORTS_PER_SOL,
-    program::invoke_signed,
+    program::{invoke, invoke_signed, set_return_data, MAX_RETURN_DATA},
     program_error::ProgramError,
     program_pack::IsInitialized,
     pubkey::Pubkey,
     system_instruction,
     system_program::ID as SYSTEM_PROGRAM_ID,
-    sysvar::{rent::Rent, rent::ID as RENT_PROGRAM_ID, Sysvar},
+    sysvar::{rent::Rent, slot_hashes::ID as SLOT_HASHES_ID, Sysvar},
+};
+#[cfg(not(feature = "no-rewards"))]
+use solana_program::{
+    native_token::LAMPORTS_PER_SOL, program_pack::Pack, sysvar::rent::ID as RENT_PROGRAM_ID,
+};
+#[cfg(not(feature = "no-rewards"))]
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+#[cfg(not(feature = "no-rewards"))]
+use spl_token::{
+    instruction::{
+        burn, freeze_account, initialize_mint2, set_authority, thaw_account, AuthorityType,
+    },
+    state::Account as TokenAccount,
+    ID as TOKEN_PROGRAM_ID,
 };
-use spl_associated_token_account::get_associated_token_address;
-use spl_token::{instruction::initialize_mint, ID as TOKEN_PROGRAM_ID};
 
 use crate::{
-    error::IntroError,
-    instruction::StudentInstruction,
-    state::{ReplyCount, StudentIntroState, StudentReplyState},
+    ed25519::verify_ed25519_signature,
+    error::{log_account_check, AccountCheck, IntroError},
+    events::{self, ProgramEvent},
+    export,
+    format::{self, verify_character_policy, verify_format},
+    gateway::verify_gateway_token,
+    instruction::{
+        BatchOp, ConfigParams, IdentityAttestation, IntroAttestation, ProfileParams, QuoteParams,
+        StudentInstruction,
+    },
+    lang::{self, verify_lang},
+    return_data::{
+        self, ConfigReturn, CourseStatsReturn, ExportReturn, IntroValidationReturn, VersionReturn,
+    },
+    state::{
+        ActionAccount, ActivityEntry, ActivityFeed, Attendance, Category, CategoryRegistry,
+        CertificateRequirements, CharacterPolicy, Course, CourseStats, DirectMessage,
+        DirectMessageCount, EditHistory, EditRecord, EmailHashEntry, Enrollment, Featured,
+        FeaturedDraw, FeedEntry, IntroEndorsement, IntroFeedPage, IntroProfile, IntroRegistry,
+        IntroSlug, IntroTranslation, OfficeHoursQueue, PendingAction, PendingConfigChange,
+        ProfileCard, ProgramConfig, QuestionFeed, Quiz, QuizSubmission, ReceivedMessage,
+        RegistryEntry, ReplyCommitment, ReplyCount, ReplyPreparation, ReplyQuote, ReplyScore,
+        ReplyVote, Reputation, RewardHold, Session, StudentActivity, StudentIdEntry,
+        StudentIntroState, StudentReplyState, TeamMembership, TeamState, TopReplies, UserNonce,
+        WalletLink, WalletLinkCount, ANONYMOUS_REPLIER, CLOSED_ACCOUNT_DISCRIMINATOR,
+        QUEUE_CAPACITY,
+    },
+    wormhole::{self, CrossChainIntroPayload, CrossChainReplyPayload},
 };
+#[cfg(not(feature = "no-rewards"))]
+use crate::{
+    nft::{self, verify_collection_nft},
+    oracle::{read_pyth_price, scale_reward},
+    state::{BadgeAward, Certificate, IntroTally, PendingWithdrawal, ReplyTally, VestingSchedule},
+};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Every instruction ends with the config PDA, so the guards below cover all of them.
+    // Handlers get it from here rather than taking a copy of their own.
+    let (config_pda, accounts) = accounts
+        .split_last()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let config_data = load_config(program_id, config_pda)?;
+
+    process_with_config(
+        program_id,
+        accounts,
+        instruction_data,
+        config_pda,
+        &config_data,
+    )
+}
+
+/// Decodes and runs one instruction, or one op of a batch, after the config's guards.
+/// `config_data` is what `config_pda` held when the instruction started, the defaults before
+/// `InitializeConfig`.
+fn process_with_config<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    instruction_data: &[u8],
+    config_pda: &AccountInfo<'a>,
+    config_data: &ProgramConfig,
+) -> ProgramResult {
+    let config = config_data.is_initialized.then_some(config_data);
+    let instruction = StudentInstruction::unpack(instruction_data)?;
+    // `unpack` rejects empty data, so the variant byte is always there.
+    let variant = StudentInstruction::variant(instruction_data).unwrap_or_default();
+    // Each op of a batch records itself and reports its own rejection; dry runs write nothing.
+    let is_batch = matches!(instruction, StudentInstruction::Batch { .. });
+    let records_activity =
+        !is_batch && !matches!(instruction, StudentInstruction::ValidateIntroPayload { .. });
+
+    let result = ensure_migrated(program_id, accounts, &instruction, config)
+        .and_then(|_| ensure_instruction_enabled(config, variant))
+        .and_then(|_| dispatch(program_id, accounts, instruction, config_pda, config_data));
+
+    if let Err(err) = &result {
+        if !is_batch {
+            report_rejection(accounts, variant, err);
+        }
+    }
+    result?;
+
+    if records_activity {
+        record_activity(program_id, accounts, variant)?;
+    }
+
+    Ok(())
+}
+
+/// Emits `ProgramEvent::Error` when `err` is one of this program's errors, so operators can
+/// track rejections without parsing `msg!` text. Runtime errors such as a missing signature
+/// are left to the transaction status.
+fn report_rejection(accounts: &[AccountInfo], instruction: u8, err: &ProgramError) {
+    if let ProgramError::Custom(code) = err {
+        events::emit(ProgramEvent::Error {
+            code: *code,
+            instruction,
+            offender: first_signer(accounts),
+        });
+    }
+}
+
+fn dispatch<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    instruction: StudentInstruction,
+    config_pda: &AccountInfo<'a>,
+    config_data: &ProgramConfig,
+) -> ProgramResult {
+    #[cfg_attr(feature = "no-rewards", allow(unused_variables))]
+    let config = config_data.is_initialized.then_some(config_data);
+
+    match instruction {
+        StudentInstruction::StudentIntro {
+            name,
+            message,
+            expires_at,
+            lang,
+            format,
+            kind,
+        } => student_intro(
+            program_id,
+            accounts,
+            config_data,
+            name,
+            message,
+            expires_at,
+            lang,
+            format,
+            kind,
+        ),
+
+        StudentInstruction::UpdateIntro { name, message } => {
+            update_intro(program_id, accounts, name, message)
+        }
+
+        StudentInstruction::ReplyIntro {
+            name,
+            message,
+            quote,
+            lang,
+            format,
+            reward_split_bps,
+        } => reply_intro(
+            program_id,
+            accounts,
+            config_data,
+            name,
+            message,
+            quote,
+            lang,
+            format,
+            reward_split_bps,
+        ),
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::InitializeMint { enable_freeze } => {
+            initialize_token_mint(program_id, accounts, enable_freeze)
+        }
+
+        StudentInstruction::InitializeConfig { params } => {
+            initialize_config(program_id, accounts, config_pda, params)
+        }
+
+        StudentInstruction::UpdateConfig { params } => {
+            update_config(program_id, accounts, config_pda, params)
+        }
+
+        StudentInstruction::LinkWallet => link_wallet(program_id, accounts),
+
+        StudentInstruction::UnlinkWallet => unlink_wallet(program_id, accounts),
+
+        StudentInstruction::CommitReply { hash } => commit_reply(program_id, accounts, hash),
+
+        StudentInstruction::RevealReply {
+            name,
+            message,
+            salt,
+        } => reveal_reply(program_id, accounts, config_data, name, message, salt),
+
+        StudentInstruction::SendDirectMessage { ciphertext, nonce } => {
+            send_direct_message(program_id, accounts, ciphertext, nonce)
+        }
+
+        StudentInstruction::VoteReply { upvote } => {
+            vote_reply(program_id, accounts, config_data, upvote)
+        }
+
+        StudentInstruction::CreateCourse {
+            name,
+            requires_student_id,
+            certificate,
+        } => create_course(program_id, accounts, name, requires_student_id, certificate),
+
+        StudentInstruction::EnrollStudent => enroll_student(program_id, accounts),
+
+        StudentInstruction::SetVisibility {
+            visibility,
+            student_id_hash,
+        } => set_visibility(program_id, accounts, visibility, student_id_hash),
+
+        StudentInstruction::CloseExpiredIntro => close_expired_intro(program_id, accounts),
+
+        StudentInstruction::SelectFeatured => select_featured(program_id, accounts, config_data),
+
+        StudentInstruction::EmitCrossChainIntro { nonce } => {
+            emit_cross_chain_intro(program_id, accounts, config_data, nonce)
+        }
+
+        StudentInstruction::ReceiveCrossChainReply => {
+            receive_cross_chain_reply(program_id, accounts, config_data)
+        }
+
+        StudentInstruction::ProposeConfigChange { params } => {
+            propose_config_change(program_id, accounts, config_data, params)
+        }
+
+        StudentInstruction::ExecuteConfigChange => {
+            execute_config_change(program_id, accounts, config_pda)
+        }
+
+        StudentInstruction::ProposeAdminAction {
+            action_id,
+            accounts: action_accounts,
+            instruction_data,
+        } => propose_admin_action(
+            program_id,
+            accounts,
+            config_data,
+            action_id,
+            action_accounts,
+            instruction_data,
+        ),
+
+        StudentInstruction::ApproveAdminAction => {
+            approve_admin_action(program_id, accounts, config_data)
+        }
+
+        StudentInstruction::ExecuteAdminAction => {
+            execute_admin_action(program_id, accounts, config_data)
+        }
+
+        StudentInstruction::NominateAdmin { new_admin } => {
+            nominate_admin(program_id, accounts, config_pda, new_admin)
+        }
+
+        StudentInstruction::AcceptAdmin => accept_admin(program_id, accounts, config_pda),
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::FreezeRewardAccount => {
+            set_reward_account_frozen(program_id, accounts, config_data, true)
+        }
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::ThawRewardAccount => {
+            set_reward_account_frozen(program_id, accounts, config_data, false)
+        }
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::BurnTreasuryTokens { amount } => {
+            burn_treasury_tokens(program_id, accounts, config_data, amount)
+        }
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::ClaimVested => claim_vested(program_id, accounts, config_data),
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::MintMilestoneBadge => {
+            mint_milestone_badge(program_id, accounts, config_data)
+        }
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::MigrateMint { enable_freeze } => {
+            migrate_mint(program_id, accounts, config_pda, enable_freeze)
+        }
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::SwapOldTokens {
+            from_version,
+            amount,
+        } => swap_old_tokens(program_id, accounts, config_data, from_version, amount),
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::ProposeTreasuryWithdrawal { amount } => {
+            propose_treasury_withdrawal(program_id, accounts, config_data, amount)
+        }
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::ExecuteTreasuryWithdrawal => {
+            execute_treasury_withdrawal(program_id, accounts, config_data)
+        }
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::TipReply { amount } => tip_reply(program_id, accounts, amount, config),
+
+        #[cfg(feature = "no-rewards")]
+        StudentInstruction::InitializeMint { .. }
+        | StudentInstruction::FreezeRewardAccount
+        | StudentInstruction::ThawRewardAccount
+        | StudentInstruction::BurnTreasuryTokens { .. }
+        | StudentInstruction::ClaimVested
+        | StudentInstruction::MintMilestoneBadge
+        | StudentInstruction::MigrateMint { .. }
+        | StudentInstruction::SwapOldTokens { .. }
+        | StudentInstruction::ProposeTreasuryWithdrawal { .. }
+        | StudentInstruction::ExecuteTreasuryWithdrawal
+        | StudentInstruction::TipReply { .. }
+        | StudentInstruction::ClaimCertificate
+        | StudentInstruction::ClaimReputationRewards => {
+            msg!("Rewards are not part of this build");
+            Err(ProgramError::InvalidInstructionData)
+        }
+
+        StudentInstruction::Batch { ops } => {
+            process_batch(program_id, accounts, ops, config_pda, config_data)
+        }
+
+        StudentInstruction::CreateIntroIfMissing {
+            name,
+            message,
+            expires_at,
+            lang,
+            format,
+            kind,
+        } => create_intro_if_missing(
+            program_id,
+            accounts,
+            config_data,
+            name,
+            message,
+            expires_at,
+            lang,
+            format,
+            kind,
+        ),
+
+        StudentInstruction::GetConfig => get_config(config_data),
+
+        StudentInstruction::GetCourseStats => get_course_stats(program_id, accounts),
+
+        StudentInstruction::GetVersion => return_data::set(&VersionReturn {
+            program_version: env!("CARGO_PKG_VERSION").to_string(),
+            instruction_version: StudentInstruction::CURRENT_VERSION,
+            data_version: ProgramConfig::DATA_VERSION,
+        }),
+
+        StudentInstruction::AdvanceNonce => advance_nonce(program_id, accounts),
+
+        StudentInstruction::ImportIntro { attestation } => {
+            import_intro(program_id, accounts, config_data, attestation)
+        }
+
+        StudentInstruction::SetCourseRewards { enabled } => {
+            set_course_rewards(program_id, accounts, enabled)
+        }
+
+        StudentInstruction::SetSlug { slug } => set_slug(program_id, accounts, slug),
+
+        StudentInstruction::CreateCategory { name } => {
+            create_category(program_id, accounts, config_data, name)
+        }
+
+        StudentInstruction::SetCategory => set_category(program_id, accounts),
+
+        StudentInstruction::AddTranslation { lang, message } => {
+            add_translation(program_id, accounts, lang, message)
+        }
+
+        StudentInstruction::SetProfile { params } => set_profile(program_id, accounts, params),
+
+        StudentInstruction::RegisterEmailHash { hash } => {
+            register_email_hash(program_id, accounts, hash)
+        }
+
+        StudentInstruction::ReleaseEmailHash => {
+            release_email_hash(program_id, accounts, config_data)
+        }
+
+        StudentInstruction::SetStudentStatus { status } => {
+            set_student_status(program_id, accounts, status)
+        }
+
+        StudentInstruction::EndorseIntro => endorse_intro(program_id, accounts),
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::ClaimCertificate => claim_certificate(program_id, accounts),
+
+        StudentInstruction::JoinQueue => join_queue(program_id, accounts),
+
+        StudentInstruction::LeaveQueue => leave_queue(program_id, accounts),
+
+        StudentInstruction::PopQueue => pop_queue(program_id, accounts),
+
+        StudentInstruction::OpenSession {
+            session_id,
+            opens_at,
+            closes_at,
+            reward,
+        } => open_session(
+            program_id, accounts, session_id, opens_at, closes_at, reward,
+        ),
+
+        StudentInstruction::CheckIn => check_in(program_id, accounts, config_data),
+
+        StudentInstruction::CreateQuiz {
+            quiz_id,
+            closes_at,
+            answer_hash,
+            reward,
+        } => create_quiz(
+            program_id,
+            accounts,
+            quiz_id,
+            closes_at,
+            answer_hash,
+            reward,
+        ),
+
+        StudentInstruction::SubmitQuizAnswer {
+            quiz_id,
+            answer_hash,
+        } => submit_quiz_answer(program_id, accounts, quiz_id, answer_hash),
+
+        StudentInstruction::RevealAnswer { answer, salt } => {
+            reveal_answer(program_id, accounts, config_data, answer, salt)
+        }
+
+        StudentInstruction::CreateTeam { name } => create_team(program_id, accounts, name),
+
+        StudentInstruction::JoinTeam => join_team(program_id, accounts),
+
+        StudentInstruction::LeaveTeam => leave_team(program_id, accounts),
+
+        StudentInstruction::InitActivityFeed => init_activity_feed(program_id, accounts),
+
+        StudentInstruction::MarkReplyRead => mark_reply_read(program_id, accounts),
+
+        #[cfg(not(feature = "no-rewards"))]
+        StudentInstruction::ClaimReputationRewards => {
+            claim_reputation_rewards(program_id, accounts, config_data)
+        }
+
+        StudentInstruction::RecordViews { count } => {
+            record_views(program_id, accounts, config_data, count)
+        }
+
+        StudentInstruction::SetInstructionEnabled { variant, enabled } => {
+            set_instruction_enabled(program_id, accounts, config_pda, variant, enabled)
+        }
+
+        StudentInstruction::ValidateIntroPayload {
+            name,
+            message,
+            expires_at,
+            lang,
+            format,
+            kind,
+        } => validate_intro_payload(
+            program_id,
+            accounts,
+            config_data,
+            name,
+            message,
+            expires_at,
+            lang,
+            format,
+            kind,
+        ),
+
+        StudentInstruction::IndexExisting { target } => {
+            index_existing(program_id, accounts, target)
+        }
+
+        StudentInstruction::VerifyIdentity { attestation } => {
+            verify_identity(program_id, accounts, config_data, attestation)
+        }
+
+        StudentInstruction::SetSpamScore { target, score } => {
+            set_spam_score(program_id, accounts, config_data, target, score)
+        }
+
+        StudentInstruction::ReleaseRewardHold => {
+            release_reward_hold(program_id, accounts, config_data)
+        }
+
+        StudentInstruction::SyncProfileCard => sync_profile_card(program_id, accounts),
+
+        StudentInstruction::CompleteMigration => {
+            complete_migration(program_id, accounts, config_pda)
+        }
+
+        StudentInstruction::SetCourseRetention {
+            ends_at,
+            anonymize_replies,
+        } => set_course_retention(program_id, accounts, ends_at, anonymize_replies),
+
+        StudentInstruction::AnonymizeReplies => anonymize_replies(program_id, accounts),
+
+        StudentInstruction::ExportAccount { target } => {
+            export_account(program_id, accounts, target)
+        }
+
+        StudentInstruction::GcOrphans => gc_orphans(program_id, accounts, config_data),
+
+        StudentInstruction::SetCharacterPolicy { policy } => {
+            set_character_policy(program_id, accounts, policy)
+        }
+
+        StudentInstruction::SelectBestAnswer { index } => {
+            select_best_answer(program_id, accounts, index)
+        }
+
+        StudentInstruction::PrepareReply { content_hash } => {
+            prepare_reply(program_id, accounts, content_hash)
+        }
+
+        StudentInstruction::FinalizeReply { name, message } => {
+            finalize_reply(program_id, accounts, config_data, name, message)
+        }
+
+        StudentInstruction::PinIntro => pin_intro(program_id, accounts),
+
+        StudentInstruction::UnpinIntro => unpin_intro(program_id, accounts),
+    }
+}
+
+/// The first signer among `accounts`, or the default key when nothing signed.
+fn first_signer(accounts: &[AccountInfo]) -> Pubkey {
+    accounts
+        .iter()
+        .find(|account| account.is_signer)
+        .map_or_else(Pubkey::default, |account| *account.key)
+}
+
+/// The config passed as an instruction's last account, or `None` before `InitializeConfig`.
+/// Rejects `variant` when it is switched off in the config. Nothing is blocked before
+/// `InitializeConfig`.
+fn ensure_instruction_enabled(config: Option<&ProgramConfig>, variant: u8) -> ProgramResult {
+    match config {
+        Some(config_data) if !config_data.is_instruction_enabled(variant) => {
+            msg!("Instruction {} is disabled", variant);
+            Err(IntroError::InstructionDisabled.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rejects `instruction` while the config's data is older than this binary's layout, so a
+/// freshly upgraded program does not read or write accounts its migration cranks have not
+/// reached.
+fn ensure_migrated(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction: &StudentInstruction,
+    config: Option<&ProgramConfig>,
+) -> ProgramResult {
+    match config {
+        Some(config_data)
+            if config_data.data_version < ProgramConfig::DATA_VERSION
+                && !runs_during_migration(program_id, accounts, instruction) =>
+        {
+            msg!(
+                "Accounts are at data version {}, this program needs {}",
+                config_data.data_version,
+                ProgramConfig::DATA_VERSION
+            );
+            Err(IntroError::UpgradeRequiresMigration.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `CompleteMigration`, and under a multisig the steps proposing, approving and executing it,
+/// since the `["multisig"]` authority can only sign through `ExecuteAdminAction`.
+fn runs_during_migration(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction: &StudentInstruction,
+) -> bool {
+    let completes_migration = |instruction_data: &[u8]| {
+        StudentInstruction::variant(instruction_data)
+            == Some(StudentInstruction::COMPLETE_MIGRATION)
+    };
+
+    match instruction {
+        StudentInstruction::CompleteMigration => true,
+        StudentInstruction::ProposeAdminAction {
+            instruction_data, ..
+        } => completes_migration(instruction_data),
+        StudentInstruction::ApproveAdminAction | StudentInstruction::ExecuteAdminAction => accounts
+            .get(1)
+            .and_then(|action_pda| load_pending_action(program_id, action_pda).ok())
+            .is_some_and(|action_data| completes_migration(&action_data.instruction_data)),
+        _ => false,
+    }
+}
+
+/// Admin only: flips `variant`'s bit in the config's kill-switch bitmap. Takes effect at once,
+/// for use during spam waves and incidents.
+pub fn set_instruction_enabled(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_pda: &AccountInfo,
+    variant: u8,
+    enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config(program_id, config_pda)?;
+    authorize_admin(program_id, &config_data, admin)?;
+
+    if StudentInstruction::ALWAYS_ENABLED.contains(&variant) && !enabled {
+        msg!(
+            "Instruction {} is on the admin path and cannot be disabled",
+            variant
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.set_instruction_enabled(variant, enabled);
+    config_data.serialize(&mut &mut config_pda.data.borrow_mut()[..])?;
+    msg!(
+        "Instruction {} {}",
+        variant,
+        if enabled { "enabled" } else { "disabled" }
+    );
+
+    events::emit(ProgramEvent::InstructionToggled {
+        admin: *admin.key,
+        variant,
+        enabled,
+    });
+
+    Ok(())
+}
+
+/// Admin only: moves the config's data version up to this binary's `DATA_VERSION` once the
+/// upgrade's migration cranks have run.
+pub fn complete_migration(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_pda: &AccountInfo,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config(program_id, config_pda)?;
+    authorize_admin(program_id, &config_data, admin)?;
+
+    let from_version = config_data.data_version;
+    if from_version >= ProgramConfig::DATA_VERSION {
+        msg!("Accounts are already at data version {}", from_version);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.data_version = ProgramConfig::DATA_VERSION;
+    config_data.serialize(&mut &mut config_pda.data.borrow_mut()[..])?;
+    msg!(
+        "Migrated from data version {} to {}",
+        from_version,
+        ProgramConfig::DATA_VERSION
+    );
+
+    events::emit(ProgramEvent::MigrationCompleted {
+        admin: *admin.key,
+        from_version,
+        to_version: ProgramConfig::DATA_VERSION,
+    });
+
+    Ok(())
+}
+
+/// View indexer only: adds a batch of off-chain views to the intro's count. One batch per
+/// intro per epoch keeps a compromised indexer key from inflating counts quickly.
+pub fn record_views(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    count: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let indexer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+
+    if !indexer.is_signer {
+        log_account_check("indexer", indexer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_intro, "intro_pda")])?;
+
+    if config.view_indexer != Some(*indexer.key) {
+        msg!("Signer is not the view indexer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if count == 0 {
+        msg!("View count must be positive");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let mut intro_data = load_intro(program_id, pda_intro)?;
+
+    let epoch = Clock::get()?.epoch;
+
+    if intro_data.views > 0 && intro_data.views_epoch >= epoch {
+        msg!("Views already recorded in epoch {}", epoch);
+        return Err(IntroError::ViewsAlreadyRecorded.into());
+    }
+
+    let total = intro_data.views.saturating_add(count);
+    intro_data.views = total;
+    intro_data.views_epoch = epoch;
+    store_intro(pda_intro, &intro_data)?;
+
+    events::emit(ProgramEvent::IntroViewsRecorded {
+        intro: *pda_intro.key,
+        count,
+        total,
+        epoch,
+    });
+
+    Ok(())
+}
+
+/// Intro writer only: sets `read` on a reply to their intro. Marking a read reply again is a
+/// no-op.
+pub fn mark_reply_read(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_reply = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_reply, "reply_pda")])?;
+
+    if load_intro(program_id, pda_intro)?.writer != *writer.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if pda_reply.owner != program_id {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut reply_data = try_from_slice_unchecked::<StudentReplyState>(&pda_reply.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !reply_data.is_initialized() || reply_data.discriminator != StudentReplyState::DISCRIMINATOR
+    {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if reply_data.intro != *pda_intro.key {
+        msg!("Reply is not on this intro");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if reply_data.read {
+        msg!("Reply already read");
+        return Ok(());
+    }
+
+    reply_data.read = true;
+    let data = reply_data.try_to_vec()?;
+
+    // Replies from before spam scores end right after the receipt; their score reads as 0
+    // and is left off.
+    if data.len() - 1 > pda_reply.data_len() {
+        return Err(IntroError::ReadReceiptUnsupported.into());
+    }
+    let len = data.len().min(pda_reply.data_len());
+    pda_reply.data.borrow_mut()[..len].copy_from_slice(&data[..len]);
+
+    events::emit(ProgramEvent::ReplyRead {
+        intro: *pda_intro.key,
+        reply: *pda_reply.key,
+    });
+
+    Ok(())
+}
+
+/// Marks reply `index` of a question intro as its best answer, once. The question keeps taking
+/// replies for `ANSWER_GRACE_SECS` afterwards; see `ensure_question_open`.
+pub fn select_best_answer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_reply = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_intro, "intro_pda")])?;
+
+    let mut intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *writer.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if intro_data.kind != StudentIntroState::KIND_QUESTION {
+        msg!("Intro is not a question");
+        return Err(IntroError::NotAQuestion.into());
+    }
+
+    if intro_data.answered_at != 0 {
+        msg!("Best answer already selected");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (reply_key, _reply_bump) = Pubkey::find_program_address(
+        &[pda_intro.key.as_ref(), index.to_be_bytes().as_ref()],
+        program_id,
+    );
+
+    if reply_key != *pda_reply.key {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_reply.owner != program_id {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let reply_data = try_from_slice_unchecked::<StudentReplyState>(&pda_reply.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !reply_data.is_initialized() || reply_data.discriminator != StudentReplyState::DISCRIMINATOR
+    {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    intro_data.best_answer = index;
+    intro_data.answered_at = Clock::get()?.unix_timestamp;
+    store_intro(pda_intro, &intro_data)?;
+
+    events::emit(ProgramEvent::BestAnswerSelected {
+        intro: *pda_intro.key,
+        reply: *pda_reply.key,
+        replier: reply_data.replier,
+    });
+
+    Ok(())
+}
+
+/// Creates the signer's activity feed.
+pub fn init_activity_feed(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user = next_account_info(account_info_iter)?;
+    let pda_feed = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !user.is_signer {
+        log_account_check("user", user.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(user, "user"), (pda_feed, "feed_pda")])?;
+
+    let (pda, bump_seed) = Pubkey::find_program_address(&[user.key.as_ref(), b"feed"], program_id);
+
+    if pda != *pda_feed.key {
+        log_account_check("feed_pda", pda_feed.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        user,
+        pda_feed,
+        system_program,
+        ActivityFeed::SIZE,
+        &[user.key.as_ref(), b"feed", &[bump_seed]],
+    )?;
+
+    let feed_data = ActivityFeed {
+        discriminator: ActivityFeed::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        user: *user.key,
+        head: 0,
+        len: 0,
+        entries: Default::default(),
+    };
+    feed_data.serialize(&mut &mut pda_feed.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Appends `action` to the feed of the instruction's first account when it signed and its feed
+/// was passed writable. Feeds are matched by owner, size and contents rather than address, so
+/// no handler needs to know about them.
+fn record_activity(program_id: &Pubkey, accounts: &[AccountInfo], action: u8) -> ProgramResult {
+    let (user, target) = match accounts {
+        [user, target, ..] if user.is_signer => (user, target),
+        _ => return Ok(()),
+    };
+
+    for pda_feed in &accounts[1..] {
+        if pda_feed.owner != program_id
+            || !pda_feed.is_writable
+            || pda_feed.data_len() != ActivityFeed::SIZE
+        {
+            continue;
+        }
+
+        let mut feed_data = match try_from_slice_unchecked::<ActivityFeed>(&pda_feed.data.borrow())
+        {
+            Ok(feed_data) => feed_data,
+            Err(_) => continue,
+        };
+
+        if feed_data.discriminator != ActivityFeed::DISCRIMINATOR || feed_data.user != *user.key {
+            continue;
+        }
+
+        feed_data.record(ActivityEntry {
+            action,
+            target: *target.key,
+            slot: Clock::get()?.slot,
+        });
+        feed_data.serialize(&mut &mut pda_feed.data.borrow_mut()[..])?;
+        break;
+    }
+
+    Ok(())
+}
+
+/// Runs each op in order against its own slice of `accounts`; any failure aborts the whole
+/// transaction, so onboarding steps land together or not at all.
+pub fn process_batch<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    ops: Vec<BatchOp>,
+    config_pda: &AccountInfo<'a>,
+    config_data: &ProgramConfig,
+) -> ProgramResult {
+    let mut offset = 0;
+
+    for (index, op) in ops.iter().enumerate() {
+        let end = offset + op.account_count as usize;
+        let op_accounts = accounts
+            .get(offset..end)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        if let StudentInstruction::Batch { .. } = StudentInstruction::unpack(&op.data)? {
+            msg!("Batches cannot be nested");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        msg!("Batch op {}", index);
+        process_with_config(program_id, op_accounts, &op.data, config_pda, config_data)?;
+        offset = end;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn student_intro(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    name: String,
+    message: String,
+    expires_at: Option<i64>,
+    lang: [u8; 2],
+    format: u8,
+    kind: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let intro_accounts = IntroAccounts::next(account_info_iter)?;
+    let checks = check_student_intro(
+        program_id,
+        &intro_accounts,
+        config,
+        account_info_iter,
+        &name,
+        &message,
+        expires_at,
+        &lang,
+        format,
+        kind,
+    )?;
+
+    create_intro_accounts(
+        program_id,
+        intro_accounts.writer,
+        intro_accounts.intro_pda,
+        intro_accounts.counter_pda,
+        intro_accounts.system_program,
+        checks.bumps,
+        name,
+        message,
+        expires_at,
+        lang,
+        format,
+        kind,
+    )?;
+
+    append_to_registry(
+        program_id,
+        intro_accounts.writer,
+        intro_accounts.registry_pda,
+        intro_accounts.registry_entry_pda,
+        intro_accounts.feed_page_pda,
+        intro_accounts.system_program,
+        intro_accounts.intro_pda,
+    )?;
+
+    if kind == StudentIntroState::KIND_QUESTION {
+        append_to_questions(
+            program_id,
+            intro_accounts.writer,
+            accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            intro_accounts.system_program,
+            intro_accounts.intro_pda.key,
+        )?;
+    }
+
+    #[cfg(not(feature = "no-rewards"))]
+    if checks.reward > 0 && !intro_already_rewarded(program_id, &intro_accounts)? {
+        record_intro_reward(program_id, &intro_accounts)?;
+        mint_reward(
+            intro_accounts.token_program,
+            intro_accounts.token_mint,
+            intro_accounts.user_ata,
+            intro_accounts.mint_auth,
+            checks.mint_auth_bump,
+            checks.reward,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Whether the writer's intro tally shows an intro of theirs was already rewarded.
+#[cfg(not(feature = "no-rewards"))]
+fn intro_already_rewarded(
+    program_id: &Pubkey,
+    intro_accounts: &IntroAccounts,
+) -> Result<bool, ProgramError> {
+    let tally_pda = intro_accounts.intro_tally_pda;
+    let (tally_key, _tally_bump) = Pubkey::find_program_address(
+        &[intro_accounts.writer.key.as_ref(), "intro_tally".as_ref()],
+        program_id,
+    );
+
+    if tally_key != *tally_pda.key {
+        log_account_check("intro_tally_pda", tally_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if tally_pda.owner != program_id {
+        return Ok(false);
+    }
+
+    let tally_data = try_from_slice_unchecked::<IntroTally>(&tally_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    Ok(tally_data.is_initialized() && tally_data.rewarded_intros > 0)
+}
+
+/// Counts a rewarded intro in the writer's intro tally, creating it on first use.
+#[cfg(not(feature = "no-rewards"))]
+fn record_intro_reward(program_id: &Pubkey, intro_accounts: &IntroAccounts) -> ProgramResult {
+    let writer = intro_accounts.writer;
+    let tally_pda = intro_accounts.intro_tally_pda;
+
+    ensure_writable(&[(tally_pda, "intro_tally_pda")])?;
+
+    if tally_pda.owner != program_id {
+        let (_tally_key, tally_bump) = Pubkey::find_program_address(
+            &[writer.key.as_ref(), "intro_tally".as_ref()],
+            program_id,
+        );
+        create_pda_account(
+            program_id,
+            writer,
+            tally_pda,
+            intro_accounts.system_program,
+            IntroTally::SIZE,
+            &[writer.key.as_ref(), "intro_tally".as_ref(), &[tally_bump]],
+        )?;
+    }
+
+    let mut tally_data = try_from_slice_unchecked::<IntroTally>(&tally_pda.data.borrow()).unwrap();
+
+    if !tally_data.is_initialized() {
+        tally_data.discriminator = IntroTally::DISCRIMINATOR.to_string();
+        tally_data.is_initialized = true;
+    }
+
+    tally_data.rewarded_intros += 1;
+    tally_data.serialize(&mut &mut tally_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Runs every check `student_intro` makes and reports the outcome as `IntroValidationReturn`
+/// return data, adding the ones that would only fail when the accounts are created: an intro
+/// that already exists, a stale registry entry and a writer short of rent. Writes nothing.
+#[allow(clippy::too_many_arguments)]
+pub fn validate_intro_payload(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    name: String,
+    message: String,
+    expires_at: Option<i64>,
+    lang: [u8; 2],
+    format: u8,
+    kind: u8,
+) -> ProgramResult {
+    let (error, reward) = match preflight_student_intro(
+        program_id, accounts, config, &name, &message, expires_at, &lang, format, kind,
+    ) {
+        Ok(reward) => (0, reward),
+        Err(err) => {
+            msg!("Intro would be rejected: {}", err);
+            (u64::from(err), 0)
+        }
+    };
+
+    return_data::set(&IntroValidationReturn { error, reward })
+}
+
+/// The checks behind `validate_intro_payload`, returning the reward the intro would earn.
+#[allow(clippy::too_many_arguments)]
+fn preflight_student_intro(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    name: &str,
+    message: &str,
+    expires_at: Option<i64>,
+    lang: &[u8; 2],
+    format: u8,
+    kind: u8,
+) -> Result<u64, ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    let intro_accounts = IntroAccounts::next(account_info_iter)?;
+
+    if !config.is_instruction_enabled(0) {
+        msg!("Instruction 0 is disabled");
+        return Err(IntroError::InstructionDisabled.into());
+    }
+
+    #[cfg_attr(feature = "no-rewards", allow(unused_variables))]
+    let checks = check_student_intro(
+        program_id,
+        &intro_accounts,
+        config,
+        account_info_iter,
+        name,
+        message,
+        expires_at,
+        lang,
+        format,
+        kind,
+    )?;
+
+    if intro_accounts.intro_pda.lamports() > 0 || intro_accounts.counter_pda.lamports() > 0 {
+        msg!("Writer already has an intro");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    #[cfg(feature = "no-rewards")]
+    let reward = 0;
+    #[cfg(not(feature = "no-rewards"))]
+    let reward = if intro_already_rewarded(program_id, &intro_accounts)? {
+        0
+    } else {
+        checks.reward
+    };
+
+    let registry_pda = intro_accounts.registry_pda;
+    let (registry_key, _registry_bump) = Pubkey::find_program_address(&[b"registry"], program_id);
+
+    if registry_key != *registry_pda.key {
+        log_account_check("registry_pda", registry_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let registry_exists = registry_pda.owner == program_id;
+    let index = if registry_exists {
+        try_from_slice_unchecked::<IntroRegistry>(&registry_pda.data.borrow())
+            .map_err(|_| IntroError::UninitializedAccount)?
+            .count
+    } else {
+        0
+    };
+    let (entry_key, _entry_bump) =
+        Pubkey::find_program_address(&[b"registry", &index.to_be_bytes()], program_id);
+
+    if entry_key != *intro_accounts.registry_entry_pda.key {
+        log_account_check(
+            "registry_entry_pda",
+            intro_accounts.registry_entry_pda.key,
+            AccountCheck::Pda,
+        );
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let page = IntroFeedPage::page_of(index);
+    let (page_key, _page_bump) =
+        Pubkey::find_program_address(&[b"intro_feed", &page.to_be_bytes()], program_id);
+
+    if page_key != *intro_accounts.feed_page_pda.key {
+        log_account_check(
+            "feed_page_pda",
+            intro_accounts.feed_page_pda.key,
+            AccountCheck::Pda,
+        );
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let rent = Rent::get()?;
+    let mut needed = rent.minimum_balance(StudentIntroState::ACCOUNT_LEN)
+        + rent.minimum_balance(ReplyCount::SIZE)
+        + rent.minimum_balance(RegistryEntry::SIZE);
+    if !registry_exists {
+        needed += rent.minimum_balance(IntroRegistry::SIZE);
+    }
+    if intro_accounts.feed_page_pda.owner != program_id {
+        needed += rent.minimum_balance(IntroFeedPage::SIZE);
+    }
+    #[cfg(not(feature = "no-rewards"))]
+    if reward > 0 && intro_accounts.intro_tally_pda.owner != program_id {
+        needed += rent.minimum_balance(IntroTally::SIZE);
+    }
+    if kind == StudentIntroState::KIND_QUESTION {
+        let questions_pda = accounts.last().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        verify_questions_feed(program_id, questions_pda)?;
+        if questions_pda.owner != program_id {
+            needed += rent.minimum_balance(QuestionFeed::SIZE);
+        }
+    }
+
+    if intro_accounts.writer.lamports() < needed {
+        msg!("Writer needs {} lamports for rent", needed);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    Ok(reward)
+}
+
+/// The accounts `StudentIntro` takes ahead of its optional ones.
+struct IntroAccounts<'a, 'b> {
+    writer: &'a AccountInfo<'b>,
+    intro_pda: &'a AccountInfo<'b>,
+    counter_pda: &'a AccountInfo<'b>,
+    #[cfg(not(feature = "no-rewards"))]
+    token_mint: &'a AccountInfo<'b>,
+    #[cfg(not(feature = "no-rewards"))]
+    mint_auth: &'a AccountInfo<'b>,
+    #[cfg(not(feature = "no-rewards"))]
+    user_ata: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+    #[cfg(not(feature = "no-rewards"))]
+    token_program: &'a AccountInfo<'b>,
+    #[cfg(not(feature = "no-rewards"))]
+    intro_tally_pda: &'a AccountInfo<'b>,
+    registry_pda: &'a AccountInfo<'b>,
+    registry_entry_pda: &'a AccountInfo<'b>,
+    feed_page_pda: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b> IntroAccounts<'a, 'b> {
+    fn next<I: Iterator<Item = &'a AccountInfo<'b>>>(
+        account_info_iter: &mut I,
+    ) -> Result<Self, ProgramError> {
+        Ok(Self {
+            writer: next_account_info(account_info_iter)?,
+            intro_pda: next_account_info(account_info_iter)?,
+            counter_pda: next_account_info(account_info_iter)?,
+            #[cfg(not(feature = "no-rewards"))]
+            token_mint: next_account_info(account_info_iter)?,
+            #[cfg(not(feature = "no-rewards"))]
+            mint_auth: next_account_info(account_info_iter)?,
+            #[cfg(not(feature = "no-rewards"))]
+            user_ata: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            #[cfg(not(feature = "no-rewards"))]
+            token_program: next_account_info(account_info_iter)?,
+            #[cfg(not(feature = "no-rewards"))]
+            intro_tally_pda: next_account_info(account_info_iter)?,
+            registry_pda: next_account_info(account_info_iter)?,
+            registry_entry_pda: next_account_info(account_info_iter)?,
+            feed_page_pda: next_account_info(account_info_iter)?,
+        })
+    }
+}
+
+/// What `check_student_intro` worked out for the writes that follow it.
+struct IntroChecks {
+    bumps: (u8, u8),
+    #[cfg(not(feature = "no-rewards"))]
+    mint_auth_bump: u8,
+    #[cfg(not(feature = "no-rewards"))]
+    reward: u64,
+}
+
+/// Every check `student_intro` makes before writing, consuming the optional gateway, oracle,
+/// wallet link and credential accounts from `account_info_iter`.
+#[inline(never)]
+#[allow(clippy::too_many_arguments)]
+fn check_student_intro<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    program_id: &Pubkey,
+    accounts: &IntroAccounts<'a, 'b>,
+    config: &ProgramConfig,
+    account_info_iter: &mut I,
+    name: &str,
+    message: &str,
+    expires_at: Option<i64>,
+    lang: &[u8; 2],
+    format: u8,
+    kind: u8,
+) -> Result<IntroChecks, ProgramError> {
+    let writer = accounts.writer;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(solana_program::program_error::ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (writer, "writer"),
+        (accounts.intro_pda, "intro_pda"),
+        (accounts.counter_pda, "counter_pda"),
+        (accounts.registry_pda, "registry_pda"),
+        (accounts.registry_entry_pda, "registry_entry_pda"),
+        (accounts.feed_page_pda, "feed_page_pda"),
+    ])?;
+    #[cfg(not(feature = "no-rewards"))]
+    ensure_writable(&[
+        (accounts.token_mint, "token_mint"),
+        (accounts.user_ata, "user_ata"),
+    ])?;
+
+    if let Some(gatekeeper_network) = config.gatekeeper_network {
+        let gateway_token = next_account_info(account_info_iter)?;
+        verify_gateway_token(gateway_token, writer.key, &gatekeeper_network)?;
+    }
+
+    #[cfg(not(feature = "no-rewards"))]
+    let (identity, reward) = {
+        let base_reward = priced_reward(config, account_info_iter, 10 * LAMPORTS_PER_SOL)?;
+        let identity = resolve_identity(
+            program_id,
+            writer.key,
+            next_optional_account(account_info_iter, program_id),
+        )?;
+        let reward = credential_reward(
+            config,
+            writer.key,
+            account_info_iter,
+            program_id,
+            base_reward,
+        )?;
+        let reward = if rewards_held(program_id, config, writer.key, account_info_iter)? {
+            msg!("Rewards are held for review");
+            0
+        } else {
+            reward
+        };
+        (identity, reward)
+    };
+
+    let bumps =
+        verify_intro_addresses(program_id, writer, accounts.intro_pda, accounts.counter_pda)?;
+    #[cfg(not(feature = "no-rewards"))]
+    let mint_auth_bump = verify_intro_reward_accounts(
+        program_id,
+        config,
+        &identity,
+        accounts.token_mint,
+        accounts.mint_auth,
+        accounts.user_ata,
+        accounts.token_program,
+    )?;
+
+    if let Some(expires_at) = expires_at {
+        if expires_at <= Clock::get()?.unix_timestamp {
+            msg!("Expiry must be in the future");
+            return Err(IntroError::IntroExpired.into());
+        }
+    }
+
+    if StudentIntroState::get_account_size(name, message) > StudentIntroState::ACCOUNT_LEN {
+        msg!("Data length is larger than 1000 bytes");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    verify_lang(lang)?;
+    verify_format(format, message)?;
+
+    if kind != StudentIntroState::KIND_INTRODUCTION && kind != StudentIntroState::KIND_QUESTION {
+        msg!("Unknown intro kind {}", kind);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(IntroChecks {
+        bumps,
+        #[cfg(not(feature = "no-rewards"))]
+        mint_auth_bump,
+        #[cfg(not(feature = "no-rewards"))]
+        reward,
+    })
+}
+
+// The helpers below are kept out of line so `student_intro` does not inline their locals into
+// one stack frame; SBF frames are capped at 4KB.
+
+/// Checks the intro and counter addresses for `writer`, returning their bump seeds.
+#[inline(never)]
+fn verify_intro_addresses(
+    program_id: &Pubkey,
+    writer: &AccountInfo,
+    intro_pda: &AccountInfo,
+    counter_pda: &AccountInfo,
+) -> Result<(u8, u8), ProgramError> {
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[writer.key.as_ref(), "intro".as_ref()], program_id);
+
+    if pda != *intro_pda.key {
+        log_account_check("intro_pda", intro_pda.key, AccountCheck::Pda);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (pda_count, counter_bump_seed) =
+        Pubkey::find_program_address(&[pda.as_ref(), "counter".as_ref()], program_id);
+
+    if pda_count != *counter_pda.key {
+        log_account_check("counter_pda", counter_pda.key, AccountCheck::Pda);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok((bump_seed, counter_bump_seed))
+}
+
+/// Checks the mint, mint authority, reward account and token program, returning the mint
+/// authority bump.
+#[cfg(not(feature = "no-rewards"))]
+#[inline(never)]
+fn verify_intro_reward_accounts(
+    program_id: &Pubkey,
+    config: &ProgramConfig,
+    identity: &Pubkey,
+    token_mint: &AccountInfo,
+    mint_auth: &AccountInfo,
+    user_ata: &AccountInfo,
+    token_program: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (mint_pda, _mint_bump) = find_reward_mint(program_id, config.mint_version);
+    let (mint_auth_pda, mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    if mint_pda != *token_mint.key {
+        log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if mint_auth_pda != *mint_auth.key {
+        log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *user_ata.key != get_associated_token_address(identity, token_mint.key) {
+        log_account_check("user_ata", user_ata.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    Ok(mint_auth_bump)
+}
+
+#[inline(never)]
+#[allow(clippy::too_many_arguments)]
+fn create_intro_accounts<'a>(
+    program_id: &Pubkey,
+    writer: &AccountInfo<'a>,
+    intro_pda: &AccountInfo<'a>,
+    counter_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    (bump_seed, counter_bump_seed): (u8, u8),
+    name: String,
+    message: String,
+    expires_at: Option<i64>,
+    lang: [u8; 2],
+    format: u8,
+    kind: u8,
+) -> ProgramResult {
+    let account_len = StudentIntroState::ACCOUNT_LEN;
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(account_len);
+    let counter_rent_lamp = rent.minimum_balance(ReplyCount::SIZE);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            writer.key,
+            intro_pda.key,
+            rent_lamports,
+            account_len.try_into().unwrap(),
+            program_id,
+        ),
+        &[writer.clone(), intro_pda.clone(), system_program.clone()],
+        &[&[writer.key.as_ref(), "intro".as_bytes(), &[bump_seed]]],
+    )?;
+    msg!("Intro PDA Created: {}", intro_pda.key);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            writer.key,
+            counter_pda.key,
+            counter_rent_lamp,
+            ReplyCount::SIZE.try_into().unwrap(),
+            program_id,
+        ),
+        &[writer.clone(), counter_pda.clone(), system_program.clone()],
+        &[&[
+            intro_pda.key.as_ref(),
+            "counter".as_ref(),
+            &[counter_bump_seed],
+        ]],
+    )?;
+    msg!("Reply Counter Created: {}", counter_pda.key);
+
+    let mut intro_data =
+        try_from_slice_unchecked::<StudentIntroState>(&intro_pda.data.borrow()).unwrap();
+    let mut counter_data =
+        try_from_slice_unchecked::<ReplyCount>(&counter_pda.data.borrow()).unwrap();
+
+    if intro_data.is_initialized() {
+        log_account_check("intro_pda", intro_pda.key, AccountCheck::Uninitialized);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    if counter_data.is_initialized() {
+        msg!("Counter already initialized!");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    intro_data.discriminator = StudentIntroState::DISCRIMINATOR.to_string();
+    intro_data.writer = *writer.key;
+    intro_data.name = name;
+    intro_data.message = message;
+    intro_data.expires_at = expires_at;
+    intro_data.created_at = Clock::get()?.unix_timestamp;
+    intro_data.layout_version = StudentIntroState::LAYOUT_VERSION;
+    intro_data.lang = lang;
+    intro_data.format = format;
+    intro_data.kind = kind;
+    intro_data.is_initialized = true;
+
+    counter_data.discriminator = ReplyCount::DISCRIMINATOR.to_string();
+    counter_data.counter = 0;
+    counter_data.is_initialized = true;
+
+    msg!("Reply Count: {}", counter_data.counter);
+
+    intro_data.serialize(&mut &mut intro_pda.data.borrow_mut()[..])?;
+    counter_data.serialize(&mut &mut counter_pda.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::IntroPosted {
+        intro: *intro_pda.key,
+        author: *writer.key,
+        lang,
+    });
+
+    Ok(())
+}
+
+/// The part of `reward` the course may still mint this epoch under the config's
+/// `course_reward_cap`, counted against its budget. The caller writes the course back.
+#[cfg(not(feature = "no-rewards"))]
+fn take_course_reward(
+    config: &ProgramConfig,
+    course_data: &mut Course,
+    reward: u64,
+) -> Result<u64, ProgramError> {
+    let epoch = Clock::get()?.epoch;
+    if course_data.reward_epoch != epoch {
+        course_data.reward_epoch = epoch;
+        course_data.rewards_minted = 0;
+    }
+
+    let granted = reward.min(
+        config
+            .course_reward_cap
+            .saturating_sub(course_data.rewards_minted),
+    );
+    if granted < reward {
+        msg!(
+            "Course has minted {} of its {} per epoch",
+            course_data.rewards_minted,
+            config.course_reward_cap
+        );
+    }
+    course_data.rewards_minted += granted;
+
+    Ok(granted)
+}
+
+/// Mints `reward` reward tokens to `user_ata`, signed by the mint authority PDA.
+#[cfg(not(feature = "no-rewards"))]
+#[inline(never)]
+fn mint_reward<'a>(
+    token_program: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    user_ata: &AccountInfo<'a>,
+    mint_auth: &AccountInfo<'a>,
+    mint_auth_bump: u8,
+    reward: u64,
+) -> ProgramResult {
+    msg!("Minting {} tokens to user associated token account", reward);
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            token_mint.key,
+            user_ata.key,
+            mint_auth.key,
+            &[],
+            reward,
+        )?,
+        &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
+        &[&[b"token_auth", &[mint_auth_bump]]],
+    )
+}
+
+/// Retry-friendly `StudentIntro`: an existing intro for the writer is logged and left untouched.
+#[allow(clippy::too_many_arguments)]
+pub fn create_intro_if_missing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    name: String,
+    message: String,
+    expires_at: Option<i64>,
+    lang: [u8; 2],
+    format: u8,
+    kind: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let intro_pda = next_account_info(account_info_iter)?;
+
+    let (pda, _bump_seed) =
+        Pubkey::find_program_address(&[writer.key.as_ref(), "intro".as_ref()], program_id);
+
+    if pda == *intro_pda.key && intro_pda.owner == program_id {
+        let intro_data = load_intro(program_id, intro_pda)?;
+        if intro_data.writer == *writer.key {
+            msg!("Intro already exists: {}", pda);
+            return Ok(());
+        }
+    }
+
+    student_intro(
+        program_id, accounts, config, name, message, expires_at, lang, format, kind,
+    )
+}
+
+pub fn update_intro(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+    message: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_history = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (writer, "writer"),
+        (pda_intro, "intro_pda"),
+        (pda_history, "history_pda"),
+    ])?;
+
+    let mut intro_data = load_intro(program_id, pda_intro)?;
+
+    let (pda, _bump_seed) =
+        Pubkey::find_program_address(&[writer.key.as_ref(), "intro".as_ref()], program_id);
+
+    if pda != *pda_intro.key {
+        log_account_check("intro_pda", pda_intro.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if StudentIntroState::get_account_size(&name, &message) > StudentIntroState::ACCOUNT_LEN {
+        msg!("Data length is larger than 1000 bytes");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    // Edits keep the intro's format, so the new message is held to the same rules.
+    verify_format(intro_data.format, &message)?;
+
+    if let Some(course) = intro_data.course {
+        let pda_course = next_account_info(account_info_iter)?;
+        if *pda_course.key != course {
+            msg!("Course does not match the intro");
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+        verify_character_policy(
+            &load_course(program_id, pda_course)?.character_policy,
+            &intro_data.name,
+            &message,
+        )?;
+    }
+
+    let (pda_count, _counter_bump_seed) =
+        Pubkey::find_program_address(&[pda.as_ref(), "counter".as_ref()], program_id);
+
+    if pda_count != *pda_counter.key {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Pda);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_counter.owner != program_id {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Owner);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let counter_data = try_from_slice_unchecked::<ReplyCount>(&pda_counter.data.borrow()).unwrap();
+
+    let (history_pda, history_bump) =
+        Pubkey::find_program_address(&[pda.as_ref(), "history".as_ref()], program_id);
+
+    if history_pda != *pda_history.key {
+        log_account_check("history_pda", pda_history.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_history.owner != program_id {
+        create_pda_account(
+            program_id,
+            writer,
+            pda_history,
+            system_program,
+            EditHistory::SIZE,
+            &[pda.as_ref(), "history".as_ref(), &[history_bump]],
+        )?;
+    }
+
+    let mut history_data =
+        try_from_slice_unchecked::<EditHistory>(&pda_history.data.borrow()).unwrap();
+
+    if !history_data.is_initialized() {
+        history_data.discriminator = EditHistory::DISCRIMINATOR.to_string();
+        history_data.intro = pda;
+        history_data.is_initialized = true;
+    }
+
+    history_data.record(EditRecord {
+        slot: Clock::get()?.slot,
+        message_hash: hash(intro_data.message.as_bytes()).to_bytes(),
+        reply_count: counter_data.counter,
+    });
+    msg!("Intro Edits: {}", history_data.edit_count);
+
+    intro_data.message = message;
+    intro_data.serialize(&mut &mut pda_intro.data.borrow_mut()[..])?;
+    history_data.serialize(&mut &mut pda_history.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn reply_intro(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    name: String,
+    message: String,
+    quote: Option<QuoteParams>,
+    lang: [u8; 2],
+    format: u8,
+    reward_split_bps: Vec<u16>,
+) -> ProgramResult {
+    post_reply(
+        program_id,
+        accounts,
+        config,
+        name,
+        message,
+        quote,
+        lang,
+        format,
+        reward_split_bps,
+        None,
+    )?;
+
+    Ok(())
+}
+
+/// `reply_intro`, for a reply that was prepared first when `prepared` is set, returning the
+/// new reply's index.
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(feature = "no-rewards", allow(unused_variables))]
+fn post_reply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    name: String,
+    message: String,
+    quote: Option<QuoteParams>,
+    lang: [u8; 2],
+    format: u8,
+    reward_split_bps: Vec<u16>,
+    prepared: Option<&ReplyPreparation>,
+) -> Result<u64, ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+
+    let replier = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_reply = next_account_info(account_info_iter)?;
+    #[cfg(not(feature = "no-rewards"))]
+    let (token_mint, mint_auth, user_ata) = (
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+    );
+    let system_program = next_account_info(account_info_iter)?;
+    #[cfg(not(feature = "no-rewards"))]
+    let token_program = next_account_info(account_info_iter)?;
+    #[cfg(not(feature = "no-rewards"))]
+    let tally_pda = next_account_info(account_info_iter)?;
+
+    ensure_writable(&[
+        (replier, "replier"),
+        (pda_counter, "counter_pda"),
+        (pda_reply, "reply_pda"),
+    ])?;
+    #[cfg(not(feature = "no-rewards"))]
+    ensure_writable(&[
+        (token_mint, "token_mint"),
+        (user_ata, "user_ata"),
+        (tally_pda, "tally_pda"),
+    ])?;
+
+    let intro_data = load_intro(program_id, pda_intro)?;
+    ensure_not_expired(&intro_data)?;
+    ensure_question_open(&intro_data)?;
+    verify_lang(&lang)?;
+    verify_format(format, &message)?;
+
+    let is_bot = config.is_welcome_bot(replier.key);
+
+    // The intro's course, when its teacher has switched rewards off.
+    #[cfg_attr(feature = "no-rewards", allow(unused_variables))]
+    let rewards_disabled_course = verify_reply_audience(
+        program_id,
+        account_info_iter,
+        &intro_data,
+        replier.key,
+        replier,
+        system_program,
+        &name,
+        &message,
+    )?;
+
+    // Bots never earn, so none of the pricing, credential, hold or tally accounts are read for
+    // them.
+    #[cfg(not(feature = "no-rewards"))]
+    let (reward, welcome_bonus, mint_auth_bump, held) = if is_bot {
+        (0, 0, 0, false)
+    } else {
+        let base_reward = priced_reward(
+            config,
+            account_info_iter,
+            config.reply_reward(message.len(), 5 * LAMPORTS_PER_SOL),
+        )?;
+
+        let identity = resolve_identity(
+            program_id,
+            replier.key,
+            next_optional_account(account_info_iter, program_id),
+        )?;
+        let reward = credential_reward(
+            config,
+            replier.key,
+            account_info_iter,
+            program_id,
+            base_reward,
+        )?;
+        let held = rewards_held(program_id, config, replier.key, account_info_iter)?;
+        let reward = record_reply_tally(
+            program_id,
+            replier,
+            &identity,
+            pda_intro,
+            tally_pda,
+            system_program,
+            reward,
+        )?;
+
+        let (mint_pda, _mint_bump) = find_reward_mint(program_id, config.mint_version);
+        let (mint_auth_pda, mint_auth_bump) =
+            Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+        if *token_mint.key != mint_pda {
+            log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if *mint_auth.key != mint_auth_pda {
+            log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if *user_ata.key != get_associated_token_address(&identity, token_mint.key) {
+            log_account_check("user_ata", user_ata.key, AccountCheck::Address);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if *token_program.key != TOKEN_PROGRAM_ID {
+            log_account_check("token_program", token_program.key, AccountCheck::Address);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        // Only paid if this turns out to be the intro's first reply, counted from when it was
+        // prepared for prepared replies; writers can't self-serve, even through a linked
+        // wallet. Intros from before `created_at` existed read as created at 0 and never
+        // qualify.
+        let replied_at = match prepared {
+            Some(preparation) => preparation.prepared_at,
+            None => Clock::get()?.unix_timestamp,
+        };
+        let welcome_bonus = if config.first_reply_bonus > 0
+            && intro_data.writer != *replier.key
+            && intro_data.writer != identity
+            && replied_at.saturating_sub(intro_data.created_at) <= config.first_reply_window_secs
+        {
+            config.first_reply_bonus
+        } else {
+            0
+        };
+
+        (reward, welcome_bonus, mint_auth_bump, held)
+    };
+
+    let quote = match quote {
+        Some(quote) => Some(verify_quote(
+            program_id,
+            pda_intro,
+            next_account_info(account_info_iter)?,
+            quote,
+        )?),
+        None => None,
+    };
+
+    #[cfg(not(feature = "no-rewards"))]
+    let reward_split = take_reward_split(
+        account_info_iter,
+        &reward_split_bps,
+        &find_reward_mint(program_id, config.mint_version).0,
+        user_ata,
+    )?;
+    #[cfg(feature = "no-rewards")]
+    if !reward_split_bps.is_empty() {
+        msg!("Rewards are not part of this build");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let reply_index = create_reply(
+        program_id,
+        replier,
+        pda_intro,
+        pda_counter,
+        pda_reply,
+        system_program,
+        *replier.key,
+        name,
+        message,
+        is_bot,
+        quote,
+        lang,
+        format,
+    )?;
+
+    // A preparation made before any reply holds the first-reply position over whichever reply
+    // lands at index 0.
+    #[cfg(not(feature = "no-rewards"))]
+    let first_reply = match prepared {
+        Some(preparation) => preparation.first,
+        None => reply_index == 0 && !intro_data.first_reply_claimed,
+    };
+    #[cfg(not(feature = "no-rewards"))]
+    let reward = if first_reply && welcome_bonus > 0 {
+        msg!("First reply bonus: {}", welcome_bonus);
+        reward.saturating_add(welcome_bonus)
+    } else {
+        reward
+    };
+
+    #[cfg(not(feature = "no-rewards"))]
+    match rewards_disabled_course {
+        Some(course) => {
+            msg!("Rewards are disabled for course {}", course);
+            events::emit(ProgramEvent::RewardsDisabled {
+                course,
+                recipient: *replier.key,
+            });
+        }
+        None if is_bot => msg!("Welcome bot replies earn no reward"),
+        None if held => msg!("Rewards are held for review"),
+        None if intro_data.status != StudentIntroState::STATUS_ACTIVE => {
+            msg!("Replies to inactive intros earn no reward")
+        }
+        None if reward == 0 => msg!("Repeat reply earns no reward"),
+        None => mint_split_reward(
+            token_program,
+            token_mint,
+            user_ata,
+            mint_auth,
+            mint_auth_bump,
+            reward,
+            &reward_split,
+        )?,
+    }
+
+    Ok(reply_index)
+}
+
+/// The audience checks every reply makes for `author`. A course-only intro takes `author`'s
+/// enrollment, the course's stats and `author`'s activity accounts (created at `payer`'s
+/// expense) and the course, and holds the text to the course's character policy; private
+/// intros take no replies. Returns the course when its teacher has switched rewards off.
+#[allow(clippy::too_many_arguments)]
+fn verify_reply_audience<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    program_id: &Pubkey,
+    account_info_iter: &mut I,
+    intro_data: &StudentIntroState,
+    author: &Pubkey,
+    payer: &AccountInfo<'b>,
+    system_program: &AccountInfo<'b>,
+    name: &str,
+    message: &str,
+) -> Result<Option<Pubkey>, ProgramError> {
+    match intro_data.visibility {
+        StudentIntroState::VISIBILITY_PUBLIC => Ok(None),
+        StudentIntroState::VISIBILITY_COURSE_ONLY => {
+            let course = intro_data.course.ok_or(IntroError::RepliesNotAllowed)?;
+            let enrollment = next_account_info(account_info_iter)?;
+            let stats_pda = next_account_info(account_info_iter)?;
+            let activity_pda = next_account_info(account_info_iter)?;
+            verify_enrollment(program_id, enrollment, &course, author)?;
+            record_course_activity(
+                program_id,
+                payer,
+                &course,
+                enrollment,
+                stats_pda,
+                activity_pda,
+                system_program,
+                CourseActivity::Reply,
+            )?;
+
+            let pda_course = next_account_info(account_info_iter)?;
+            if *pda_course.key != course {
+                msg!("Course does not match the intro");
+                return Err(IntroError::IncorrectAccountError.into());
+            }
+            let course_data = load_course(program_id, pda_course)?;
+            verify_character_policy(&course_data.character_policy, name, message)?;
+            Ok(course_data.rewards_disabled.then_some(course))
+        }
+        _ => {
+            msg!("Intro is private");
+            Err(IntroError::RepliesNotAllowed.into())
+        }
+    }
+}
+
+/// Records `replier`'s draft reply to the intro by the hash of its text. The first preparation
+/// made while the intro has no replies claims the first-reply position for its reply.
+pub fn prepare_reply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    content_hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let replier = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_preparation = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !replier.is_signer {
+        log_account_check("replier", replier.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (replier, "replier"),
+        (pda_intro, "intro_pda"),
+        (pda_preparation, "preparation_pda"),
+    ])?;
+
+    let mut intro_data = load_intro(program_id, pda_intro)?;
+    ensure_not_expired(&intro_data)?;
+    ensure_question_open(&intro_data)?;
+
+    let (counter_key, _counter_bump) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), b"counter"], program_id);
+
+    if counter_key != *pda_counter.key {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Pda);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_counter.owner != program_id {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Owner);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let counter_data = try_from_slice_unchecked::<ReplyCount>(&pda_counter.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[pda_intro.key.as_ref(), replier.key.as_ref(), b"prepared"],
+        program_id,
+    );
+
+    if pda != *pda_preparation.key {
+        log_account_check("preparation_pda", pda_preparation.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        replier,
+        pda_preparation,
+        system_program,
+        ReplyPreparation::SIZE,
+        &[
+            pda_intro.key.as_ref(),
+            replier.key.as_ref(),
+            b"prepared",
+            &[bump_seed],
+        ],
+    )?;
+
+    // Intros filled to capacity before the claim existed have no room for it and never claim.
+    let first = counter_data.counter == 0
+        && !intro_data.first_reply_claimed
+        && intro_data.try_to_vec()?.len() <= pda_intro.data_len();
+    if first {
+        intro_data.first_reply_claimed = true;
+        store_intro(pda_intro, &intro_data)?;
+    }
+
+    let clock = Clock::get()?;
+    let preparation = ReplyPreparation {
+        discriminator: ReplyPreparation::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: *pda_intro.key,
+        replier: *replier.key,
+        content_hash,
+        prepared_slot: clock.slot,
+        prepared_at: clock.unix_timestamp,
+        first,
+    };
+    preparation.serialize(&mut &mut pda_preparation.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Posts the reply prepared in the last account once its text matches the preparation's hash,
+/// taking the same accounts and making the same checks as `ReplyIntro`, and closes the
+/// preparation to the replier.
+pub fn finalize_reply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    name: String,
+    message: String,
+) -> ProgramResult {
+    let (pda_preparation, reply_accounts) = accounts
+        .split_last()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let account_info_iter = &mut reply_accounts.iter();
+
+    let replier = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let _pda_counter = next_account_info(account_info_iter)?;
+    let pda_reply = next_account_info(account_info_iter)?;
+
+    ensure_writable(&[(pda_preparation, "preparation_pda")])?;
+
+    let (pda, _bump_seed) = Pubkey::find_program_address(
+        &[pda_intro.key.as_ref(), replier.key.as_ref(), b"prepared"],
+        program_id,
+    );
+
+    if pda != *pda_preparation.key {
+        log_account_check("preparation_pda", pda_preparation.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_preparation.owner != program_id {
+        log_account_check("preparation_pda", pda_preparation.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let preparation = try_from_slice_unchecked::<ReplyPreparation>(&pda_preparation.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !preparation.is_initialized() || preparation.discriminator != ReplyPreparation::DISCRIMINATOR
+    {
+        log_account_check(
+            "preparation_pda",
+            pda_preparation.key,
+            AccountCheck::Initialized,
+        );
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if preparation.content_hash != ReplyPreparation::hash_reply(&name, &message) {
+        msg!("Reply does not match its preparation");
+        return Err(IntroError::CommitmentMismatch.into());
+    }
+
+    post_reply(
+        program_id,
+        reply_accounts,
+        config,
+        name,
+        message,
+        None,
+        lang::UNSPECIFIED,
+        format::PLAIN,
+        Vec::new(),
+        Some(&preparation),
+    )?;
+
+    close_account(pda_preparation, replier)?;
+
+    events::emit(ProgramEvent::ReplyFinalized {
+        intro: *pda_intro.key,
+        reply: *pda_reply.key,
+        prepared_slot: preparation.prepared_slot,
+    });
+
+    Ok(())
+}
+
+/// Whether `author`'s rewards are on hold for review. Only read while a spam classifier is
+/// configured, from the `[author, "reward_hold"]` account next in `account_info_iter`; one the
+/// classifier never created holds nothing.
+#[cfg(not(feature = "no-rewards"))]
+#[inline(never)]
+fn rewards_held<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    program_id: &Pubkey,
+    config: &ProgramConfig,
+    author: &Pubkey,
+    account_info_iter: &mut I,
+) -> Result<bool, ProgramError> {
+    if config.spam_classifier.is_none() {
+        return Ok(false);
+    }
+
+    let pda_hold = next_account_info(account_info_iter)?;
+    let (hold_key, _hold_bump) =
+        Pubkey::find_program_address(&[author.as_ref(), b"reward_hold"], program_id);
+
+    if hold_key != *pda_hold.key {
+        log_account_check("hold_pda", pda_hold.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_hold.owner != program_id {
+        return Ok(false);
+    }
+
+    let hold_data = try_from_slice_unchecked::<RewardHold>(&pda_hold.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    Ok(hold_data.is_initialized() && hold_data.held)
+}
+
+/// The reward token accounts a `ReplyIntro` splits its reward with, one per share. Each must
+/// be the associated token account of its owner for the reward mint, and none may repeat or
+/// be the replier's own.
+#[cfg(not(feature = "no-rewards"))]
+#[inline(never)]
+fn take_reward_split<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    account_info_iter: &mut I,
+    shares_bps: &[u16],
+    reward_mint: &Pubkey,
+    user_ata: &AccountInfo,
+) -> Result<Vec<(&'a AccountInfo<'b>, u16)>, ProgramError> {
+    if shares_bps.len() > StudentReplyState::MAX_REWARD_SPLITS
+        || shares_bps.contains(&0)
+        || shares_bps.iter().map(|&bps| u32::from(bps)).sum::<u32>() > 10_000
+    {
+        msg!(
+            "Reward split takes at most {} non-zero shares totalling at most 10000 bps",
+            StudentReplyState::MAX_REWARD_SPLITS
+        );
+        return Err(IntroError::InvalidRewardSplit.into());
+    }
+
+    let mut recipients: Vec<(&AccountInfo, u16)> = Vec::with_capacity(shares_bps.len());
+    for &bps in shares_bps {
+        let recipient = next_account_info(account_info_iter)?;
+        ensure_writable(&[(recipient, "recipient")])?;
+
+        if *recipient.owner != TOKEN_PROGRAM_ID {
+            msg!(
+                "Reward split account {} is not a token account",
+                recipient.key
+            );
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        let token_account = TokenAccount::unpack(&recipient.data.borrow())?;
+        if token_account.mint != *reward_mint
+            || *recipient.key != get_associated_token_address(&token_account.owner, reward_mint)
+        {
+            msg!(
+                "Reward split account {} is not an associated token account of the reward mint",
+                recipient.key
+            );
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if recipient.key == user_ata.key
+            || recipients
+                .iter()
+                .any(|(other, _)| other.key == recipient.key)
+        {
+            msg!("Reward split accounts must differ from each other and the replier's");
+            return Err(IntroError::InvalidRewardSplit.into());
+        }
+
+        recipients.push((recipient, bps));
+    }
+
+    Ok(recipients)
+}
+
+/// Mints `reward`, paying each split recipient its share and the replier what is left.
+#[cfg(not(feature = "no-rewards"))]
+#[inline(never)]
+fn mint_split_reward<'a>(
+    token_program: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    user_ata: &AccountInfo<'a>,
+    mint_auth: &AccountInfo<'a>,
+    mint_auth_bump: u8,
+    reward: u64,
+    split: &[(&AccountInfo<'a>, u16)],
+) -> ProgramResult {
+    let mut remainder = reward;
+    for (recipient, bps) in split {
+        let share = (u128::from(reward) * u128::from(*bps) / 10_000) as u64;
+        if share > 0 {
+            mint_reward(
+                token_program,
+                token_mint,
+                recipient,
+                mint_auth,
+                mint_auth_bump,
+                share,
+            )?;
+            remainder -= share;
+        }
+    }
+
+    if remainder > 0 {
+        mint_reward(
+            token_program,
+            token_mint,
+            user_ata,
+            mint_auth,
+            mint_auth_bump,
+            remainder,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// With `enable_freeze`, the mint authority PDA also becomes the freeze authority so the admin
+/// can freeze reward accounts; otherwise nobody can ever freeze.
+#[cfg(not(feature = "no-rewards"))]
+pub fn initialize_token_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enable_freeze: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    // Deprecated: older clients still append the rent sysvar. It is checked but no longer used.
+    let legacy_sysvar_rent = account_info_iter.next();
+
+    let (mint_pda, mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+    let (mint_auth_pda, _mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    msg!("Token mint: {:?}", mint_pda);
+    msg!("Mint authority: {:?}", mint_auth_pda);
+
+    if mint_pda != *token_mint.key {
+        log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        log_account_check("system_program", system_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if let Some(sysvar_rent) = legacy_sysvar_rent {
+        if *sysvar_rent.key != RENT_PROGRAM_ID {
+            log_account_check("sysvar_rent", sysvar_rent.key, AccountCheck::Address);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+        msg!("Passing the rent sysvar to InitializeMint is deprecated");
+    }
+
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(82);
+
+    // create the token mint PDA.
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            token_mint.key,
+            rent_lamports,
+            82, // Size of the token mint account
+            token_program.key,
+        ),
+        // Accounts we're reading from or writing to
+        &[
+            initializer.clone(),
+            token_mint.clone(),
+            system_program.clone(),
+        ],
+        // Seeds for our token mint account
+        &[&[b"token_mint", &[mint_bump]]],
+    )?;
+    msg!("Created token mint account");
+
+    // Initialize the mint account; `initialize_mint2` reads rent itself.
+    invoke(
+        &initialize_mint2(
+            token_program.key,
+            token_mint.key,
+            mint_auth.key,
+            enable_freeze.then_some(mint_auth.key),
+            9, // Number of decimals
+        )?,
+        // Which accounts we're reading from or writing to
+        &[token_mint.clone(), token_program.clone()],
+    )?;
+    msg!("Initialized token mint");
+
+    Ok(())
+}
+
+pub fn initialize_config<'a>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'a>],
+    config_pda: &AccountInfo<'a>,
+    params: ConfigParams,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    #[cfg(not(feature = "no-rewards"))]
+    let (treasury_auth, treasury_ata, token_mint, token_program, ata_program) = (
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+    );
+
+    if !admin.is_signer {
+        log_account_check("admin", admin.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+
+    if pda != *config_pda.key {
+        log_account_check("config_pda", config_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        log_account_check("system_program", system_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(ProgramConfig::ACCOUNT_LEN);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            config_pda.key,
+            rent_lamports,
+            ProgramConfig::ACCOUNT_LEN.try_into().unwrap(),
+            program_id,
+        ),
+        &[admin.clone(), config_pda.clone(), system_program.clone()],
+        &[&[b"config", &[bump_seed]]],
+    )?;
+    msg!("Config PDA Created: {}", pda);
+
+    let mut config_data =
+        try_from_slice_unchecked::<ProgramConfig>(&config_pda.data.borrow()).unwrap();
+
+    if config_data.is_initialized() {
+        msg!("Config already initialized!");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    config_data.discriminator = ProgramConfig::DISCRIMINATOR.to_string();
+    config_data.admin = *admin.key;
+    config_data.data_version = ProgramConfig::DATA_VERSION;
+    apply_config_params(&mut config_data, params)?;
+    config_data.is_initialized = true;
+
+    config_data.serialize(&mut &mut config_pda.data.borrow_mut()[..])?;
+
+    #[cfg(not(feature = "no-rewards"))]
+    create_treasury_account(
+        program_id,
+        &config_data,
+        admin,
+        treasury_auth,
+        treasury_ata,
+        token_mint,
+        system_program,
+        token_program,
+        ata_program,
+    )?;
+
+    Ok(())
+}
+
+/// Creates the `["treasury"]` PDA's associated token account for the current reward mint.
+/// Anyone may create an associated token account, so an existing one is accepted instead of
+/// letting a front-runner block config initialization.
+#[cfg(not(feature = "no-rewards"))]
+#[allow(clippy::too_many_arguments)]
+fn create_treasury_account<'a>(
+    program_id: &Pubkey,
+    config: &ProgramConfig,
+    payer: &AccountInfo<'a>,
+    treasury_auth: &AccountInfo<'a>,
+    treasury_ata: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    ata_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (treasury_pda, _treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+    let (mint_pda, _mint_bump) = find_reward_mint(program_id, config.mint_version);
+
+    if *treasury_auth.key != treasury_pda {
+        log_account_check("treasury_auth", treasury_auth.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *token_mint.key != mint_pda {
+        log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *treasury_ata.key != get_associated_token_address(&treasury_pda, &mint_pda) {
+        log_account_check("treasury_ata", treasury_ata.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *ata_program.key != spl_associated_token_account::id() {
+        log_account_check("ata_program", ata_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *treasury_ata.owner == TOKEN_PROGRAM_ID {
+        msg!("Treasury token account already exists");
+        return Ok(());
+    }
+
+    invoke(
+        &create_associated_token_account(payer.key, treasury_auth.key, token_mint.key),
+        &[
+            payer.clone(),
+            treasury_ata.clone(),
+            treasury_auth.clone(),
+            token_mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            ata_program.clone(),
+        ],
+    )?;
+    msg!("Created treasury token account {}", treasury_ata.key);
+
+    Ok(())
+}
+
+pub fn update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_pda: &AccountInfo,
+    params: ConfigParams,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config(program_id, config_pda)?;
+    authorize_admin(program_id, &config_data, admin)?;
+
+    if config_data.config_timelock_slots > 0 {
+        msg!("Config changes must go through ProposeConfigChange");
+        return Err(IntroError::TimelockRequired.into());
+    }
+
+    apply_config_params(&mut config_data, params)?;
+
+    config_data.serialize(&mut &mut config_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn link_wallet(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let primary = next_account_info(account_info_iter)?;
+    let linked = next_account_info(account_info_iter)?;
+    let link_pda = next_account_info(account_info_iter)?;
+    let primary_link_pda = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let primary_count_pda = next_account_info(account_info_iter)?;
+    let linked_count_pda = next_account_info(account_info_iter)?;
+
+    if !primary.is_signer {
+        log_account_check("primary", primary.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !linked.is_signer {
+        log_account_check("linked", linked.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if primary.key == linked.key {
+        msg!("Cannot link a wallet to itself");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[linked.key.as_ref(), "link".as_ref()], program_id);
+
+    if pda != *link_pda.key {
+        log_account_check("link_pda", link_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let (primary_pda, _primary_bump) =
+        Pubkey::find_program_address(&[primary.key.as_ref(), "link".as_ref()], program_id);
+
+    if primary_pda != *primary_link_pda.key {
+        log_account_check("primary_link_pda", primary_link_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    // Links are one level deep, so the primary may not itself be linked elsewhere, and the
+    // linked wallet may be neither linked already nor a primary with links of its own.
+    if primary_link_pda.owner == program_id {
+        msg!("Primary wallet is already linked to another identity");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if link_pda.owner == program_id {
+        msg!("Wallet is already linked to an identity");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let (_linked_count_bump, linked_links) =
+        load_wallet_link_count(program_id, linked.key, linked_count_pda)?;
+
+    if linked_links > 0 {
+        msg!("Wallet is the primary of another identity");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let (primary_count_bump, primary_links) =
+        load_wallet_link_count(program_id, primary.key, primary_count_pda)?;
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        log_account_check("system_program", system_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(WalletLink::SIZE);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            primary.key,
+            link_pda.key,
+            rent_lamports,
+            WalletLink::SIZE.try_into().unwrap(),
+            program_id,
+        ),
+        &[primary.clone(), link_pda.clone(), system_program.clone()],
+        &[&[linked.key.as_ref(), "link".as_ref(), &[bump_seed]]],
+    )?;
+    msg!("Wallet Link Created: {}", pda);
+
+    let mut link_data = try_from_slice_unchecked::<WalletLink>(&link_pda.data.borrow()).unwrap();
+
+    if link_data.is_initialized() {
+        msg!("Wallet already linked");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    link_data.discriminator = WalletLink::DISCRIMINATOR.to_string();
+    link_data.primary = *primary.key;
+    link_data.linked = *linked.key;
+    link_data.is_initialized = true;
+
+    link_data.serialize(&mut &mut link_pda.data.borrow_mut()[..])?;
+
+    if primary_count_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            primary,
+            primary_count_pda,
+            system_program,
+            WalletLinkCount::SIZE,
+            &[
+                primary.key.as_ref(),
+                "link_count".as_ref(),
+                &[primary_count_bump],
+            ],
+        )?;
+    }
+
+    WalletLinkCount {
+        discriminator: WalletLinkCount::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        links: primary_links + 1,
+    }
+    .serialize(&mut &mut primary_count_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Checks `count_pda` is `wallet`'s `["link_count"]` PDA, returning its bump and how many
+/// wallets are linked to `wallet`; none while the account does not exist.
+fn load_wallet_link_count(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    count_pda: &AccountInfo,
+) -> Result<(u8, u64), ProgramError> {
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[wallet.as_ref(), "link_count".as_ref()], program_id);
+
+    if pda != *count_pda.key {
+        log_account_check("link_count_pda", count_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if count_pda.owner != program_id {
+        return Ok((bump_seed, 0));
+    }
+
+    let count_data = try_from_slice_unchecked::<WalletLinkCount>(&count_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    Ok((bump_seed, count_data.links))
+}
+
+pub fn unlink_wallet(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let primary = next_account_info(account_info_iter)?;
+    let linked = next_account_info(account_info_iter)?;
+    let link_pda = next_account_info(account_info_iter)?;
+    let primary_count_pda = next_account_info(account_info_iter)?;
+
+    if !primary.is_signer {
+        log_account_check("primary", primary.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !linked.is_signer {
+        log_account_check("linked", linked.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(primary, "primary"), (link_pda, "link_pda")])?;
+
+    if link_pda.owner != program_id {
+        log_account_check("link_pda", link_pda.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let (pda, _bump_seed) =
+        Pubkey::find_program_address(&[linked.key.as_ref(), "link".as_ref()], program_id);
+
+    if pda != *link_pda.key {
+        log_account_check("link_pda", link_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let link_data = try_from_slice_unchecked::<WalletLink>(&link_pda.data.borrow()).unwrap();
+
+    if !link_data.is_initialized() {
+        log_account_check("link_pda", link_pda.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if link_data.primary != *primary.key {
+        msg!("Link belongs to another primary wallet");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    // Primaries linked before link counts existed have no count to lower.
+    let (_count_bump, links) = load_wallet_link_count(program_id, primary.key, primary_count_pda)?;
+    if primary_count_pda.owner == program_id {
+        ensure_writable(&[(primary_count_pda, "link_count_pda")])?;
+        WalletLinkCount {
+            discriminator: WalletLinkCount::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            links: links.saturating_sub(1),
+        }
+        .serialize(&mut &mut primary_count_pda.data.borrow_mut()[..])?;
+    }
+
+    close_account(link_pda, primary)?;
+    msg!("Wallet Link Closed: {}", pda);
+
+    Ok(())
+}
+
+pub fn commit_reply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let committer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_commitment = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !committer.is_signer {
+        log_account_check("committer", committer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if pda_intro.owner != program_id {
+        log_account_check("intro_pda", pda_intro.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[
+            pda_intro.key.as_ref(),
+            committer.key.as_ref(),
+            hash.as_ref(),
+        ],
+        program_id,
+    );
+
+    if pda != *pda_commitment.key {
+        log_account_check("commitment_pda", pda_commitment.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(ReplyCommitment::SIZE);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            committer.key,
+            pda_commitment.key,
+            rent_lamports,
+            ReplyCommitment::SIZE.try_into().unwrap(),
+            program_id,
+        ),
+        &[
+            committer.clone(),
+            pda_commitment.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            pda_intro.key.as_ref(),
+            committer.key.as_ref(),
+            hash.as_ref(),
+            &[bump_seed],
+        ]],
+    )?;
+    msg!("Reply Commitment Created: {}", pda);
+
+    let mut commitment_data =
+        try_from_slice_unchecked::<ReplyCommitment>(&pda_commitment.data.borrow()).unwrap();
+
+    if commitment_data.is_initialized() {
+        msg!("Commitment already initialized!");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    commitment_data.discriminator = ReplyCommitment::DISCRIMINATOR.to_string();
+    commitment_data.intro = *pda_intro.key;
+    commitment_data.hash = hash;
+    commitment_data.committed_slot = Clock::get()?.slot;
+    commitment_data.is_initialized = true;
+
+    commitment_data.serialize(&mut &mut pda_commitment.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Materializes a committed reply. Anyone may submit the reveal, so the committing wallet
+/// never has to sign anything tied to the reply content; the reply is held to the intro's
+/// audience checks for the committer, and the commitment's rent goes back to them.
+pub fn reveal_reply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    name: String,
+    message: String,
+    salt: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let committer = next_account_info(account_info_iter)?;
+    let pda_commitment = next_account_info(account_info_iter)?;
+    let pda_reply = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        log_account_check("payer", payer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (payer, "payer"),
+        (pda_counter, "counter_pda"),
+        (committer, "committer"),
+        (pda_commitment, "commitment_pda"),
+        (pda_reply, "reply_pda"),
+    ])?;
+
+    if pda_commitment.owner != program_id {
+        log_account_check("commitment_pda", pda_commitment.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let hash = ReplyCommitment::hash_reply(&name, &message, &salt);
+
+    let (pda, _bump_seed) = Pubkey::find_program_address(
+        &[
+            pda_intro.key.as_ref(),
+            committer.key.as_ref(),
+            hash.as_ref(),
+        ],
+        program_id,
+    );
+
+    if pda != *pda_commitment.key {
+        msg!("Revealed reply does not match the commitment");
+        return Err(IntroError::CommitmentMismatch.into());
+    }
+
+    let commitment_data =
+        try_from_slice_unchecked::<ReplyCommitment>(&pda_commitment.data.borrow()).unwrap();
+
+    if !commitment_data.is_initialized() {
+        log_account_check(
+            "commitment_pda",
+            pda_commitment.key,
+            AccountCheck::Initialized,
+        );
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if commitment_data.hash != hash || commitment_data.intro != *pda_intro.key {
+        msg!("Revealed reply does not match the commitment");
+        return Err(IntroError::CommitmentMismatch.into());
+    }
+
+    let intro_data = load_intro(program_id, pda_intro)?;
+    ensure_not_expired(&intro_data)?;
+    ensure_question_open(&intro_data)?;
+
+    if Clock::get()?.slot
+        < commitment_data
+            .committed_slot
+            .saturating_add(config.reveal_delay_slots)
+    {
+        msg!("Reveal delay has not elapsed yet");
+        return Err(IntroError::RevealTooEarly.into());
+    }
+
+    verify_reply_audience(
+        program_id,
+        account_info_iter,
+        &intro_data,
+        committer.key,
+        payer,
+        system_program,
+        &name,
+        &message,
+    )?;
+
+    create_reply(
+        program_id,
+        payer,
+        pda_intro,
+        pda_counter,
+        pda_reply,
+        system_program,
+        ANONYMOUS_REPLIER,
+        name,
+        message,
+        false,
+        None,
+        lang::UNSPECIFIED,
+        format::PLAIN,
+    )?;
+
+    close_account(pda_commitment, committer)?;
+    msg!("Reply Commitment Closed: {}", pda);
+
+    Ok(())
+}
+
+pub fn send_direct_message(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    ciphertext: Vec<u8>,
+    nonce: [u8; 24],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let sender = next_account_info(account_info_iter)?;
+    let recipient = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_message = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !sender.is_signer {
+        log_account_check("sender", sender.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (sender, "sender"),
+        (pda_counter, "counter_pda"),
+        (pda_message, "message_pda"),
+    ])?;
+
+    if ciphertext.len() > DirectMessage::MAX_CIPHERTEXT_LEN {
+        msg!(
+            "Ciphertext is larger than {} bytes",
+            DirectMessage::MAX_CIPHERTEXT_LEN
+        );
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    let (pda_count, counter_bump_seed) = Pubkey::find_program_address(
+        &[
+            sender.key.as_ref(),
+            recipient.key.as_ref(),
+            "dm_counter".as_ref(),
+        ],
+        program_id,
+    );
+
+    if pda_count != *pda_counter.key {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let rent = Rent::get()?;
+
+    // The per-pair counter is created lazily with the first message.
+    if pda_counter.owner != program_id {
+        invoke_signed(
+            &system_instruction::create_account(
+                sender.key,
+                pda_counter.key,
+                rent.minimum_balance(DirectMessageCount::SIZE),
+                DirectMessageCount::SIZE.try_into().unwrap(),
+                program_id,
+            ),
+            &[sender.clone(), pda_counter.clone(), system_program.clone()],
+            &[&[
+                sender.key.as_ref(),
+                recipient.key.as_ref(),
+                "dm_counter".as_ref(),
+                &[counter_bump_seed],
+            ]],
+        )?;
+        msg!("Direct Message Counter Created: {}", pda_count);
+    }
+
+    let mut counter_data =
+        try_from_slice_unchecked::<DirectMessageCount>(&pda_counter.data.borrow()).unwrap();
+
+    if !counter_data.is_initialized() {
+        counter_data.discriminator = DirectMessageCount::DISCRIMINATOR.to_string();
+        counter_data.counter = 0;
+        counter_data.is_initialized = true;
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[
+            sender.key.as_ref(),
+            recipient.key.as_ref(),
+            counter_data.counter.to_be_bytes().as_ref(),
+        ],
+        program_id,
+    );
+
+    if pda != *pda_message.key {
+        log_account_check("message_pda", pda_message.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let account_len = DirectMessage::get_account_size(ciphertext.len());
+
+    invoke_signed(
+        &system_instruction::create_account(
+            sender.key,
+            pda_message.key,
+            rent.minimum_balance(account_len),
+            account_len.try_into().unwrap(),
+            program_id,
+        ),
+        &[sender.clone(), pda_message.clone(), system_program.clone()],
+        &[&[
+            sender.key.as_ref(),
+            recipient.key.as_ref(),
+            counter_data.counter.to_be_bytes().as_ref(),
+            &[bump_seed],
+        ]],
+    )?;
+    msg!("Direct Message Created: {}", pda);
+
+    let mut message_data =
+        try_from_slice_unchecked::<DirectMessage>(&pda_message.data.borrow()).unwrap();
+
+    if message_data.is_initialized() {
+        log_account_check("message_pda", pda_message.key, AccountCheck::Uninitialized);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    message_data.discriminator = DirectMessage::DISCRIMINATOR.to_string();
+    message_data.sender = *sender.key;
+    message_data.recipient = *recipient.key;
+    message_data.index = counter_data.counter;
+    message_data.nonce = nonce;
+    message_data.ciphertext = ciphertext;
+    message_data.is_initialized = true;
+
+    counter_data.counter += 1;
+
+    message_data.serialize(&mut &mut pda_message.data.borrow_mut()[..])?;
+    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+#[cfg_attr(feature = "no-rewards", allow(unused_variables))]
+pub fn vote_reply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    upvote: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let voter = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_reply = next_account_info(account_info_iter)?;
+    let pda_vote = next_account_info(account_info_iter)?;
+    let pda_score = next_account_info(account_info_iter)?;
+    let pda_top_replies = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let voter_link = next_account_info(account_info_iter)?;
+    let replier_link = next_account_info(account_info_iter)?;
+
+    if !voter.is_signer {
+        log_account_check("voter", voter.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if pda_reply.owner != program_id {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let reply_data = try_from_slice_unchecked::<StudentReplyState>(&pda_reply.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !reply_data.is_initialized() || reply_data.discriminator != StudentReplyState::DISCRIMINATOR
+    {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if reply_data.intro != *pda_intro.key {
+        msg!("Reply does not belong to this intro");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    // Both links are always passed, so a wallet cannot vote on its other wallets' replies by
+    // leaving one out.
+    let voter_identity = resolve_link_address(program_id, voter.key, voter_link)?;
+    let replier_identity = resolve_link_address(program_id, &reply_data.replier, replier_link)?;
+
+    if replier_identity == voter_identity {
+        msg!("Cannot vote on your own reply");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let (vote_pda, vote_bump) = Pubkey::find_program_address(
+        &[pda_reply.key.as_ref(), voter.key.as_ref(), "vote".as_ref()],
+        program_id,
+    );
+
+    if vote_pda != *pda_vote.key {
+        log_account_check("vote_pda", pda_vote.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let (score_pda, score_bump) =
+        Pubkey::find_program_address(&[pda_reply.key.as_ref(), "score".as_ref()], program_id);
+
+    if score_pda != *pda_score.key {
+        log_account_check("score_pda", pda_score.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let (top_pda, top_bump) = Pubkey::find_program_address(
+        &[pda_intro.key.as_ref(), "top_replies".as_ref()],
+        program_id,
+    );
+
+    if top_pda != *pda_top_replies.key {
+        log_account_check("top_replies_pda", pda_top_replies.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    // The vote PDA is created unconditionally, so a second vote by the same wallet fails here.
+    create_pda_account(
+        program_id,
+        voter,
+        pda_vote,
+        system_program,
+        ReplyVote::SIZE,
+        &[
+            pda_reply.key.as_ref(),
+            voter.key.as_ref(),
+            "vote".as_ref(),
+            &[vote_bump],
+        ],
+    )?;
+
+    let vote_data = ReplyVote {
+        discriminator: ReplyVote::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        reply: *pda_reply.key,
+        voter: *voter.key,
+        upvote,
+    };
+    vote_data.serialize(&mut &mut pda_vote.data.borrow_mut()[..])?;
+
+    if pda_score.owner != program_id {
+        create_pda_account(
+            program_id,
+            voter,
+            pda_score,
+            system_program,
+            ReplyScore::SIZE,
+            &[pda_reply.key.as_ref(), "score".as_ref(), &[score_bump]],
+        )?;
+    }
+
+    let mut score_data = try_from_slice_unchecked::<ReplyScore>(&pda_score.data.borrow()).unwrap();
+
+    if !score_data.is_initialized() {
+        score_data.discriminator = ReplyScore::DISCRIMINATOR.to_string();
+        score_data.reply = *pda_reply.key;
+        score_data.is_initialized = true;
+    }
+
+    score_data.score += if upvote { 1 } else { -1 };
+    msg!("Reply Score: {}", score_data.score);
+
+    if pda_top_replies.owner != program_id {
+        create_pda_account(
+            program_id,
+            voter,
+            pda_top_replies,
+            system_program,
+            TopReplies::SIZE,
+            &[pda_intro.key.as_ref(), "top_replies".as_ref(), &[top_bump]],
+        )?;
+    }
+
+    let mut top_data =
+        try_from_slice_unchecked::<TopReplies>(&pda_top_replies.data.borrow()).unwrap();
+
+    if !top_data.is_initialized() {
+        top_data.discriminator = TopReplies::DISCRIMINATOR.to_string();
+        top_data.intro = *pda_intro.key;
+        top_data.is_initialized = true;
+    }
+
+    // Bot replies still collect votes but never rank.
+    if !reply_data.is_bot {
+        top_data.upsert(*pda_reply.key, score_data.score);
+    }
+
+    // Bots and anonymous replies earn no reputation, and scores from before reputation have
+    // nowhere to record which milestones were credited.
+    if !reply_data.is_bot
+        && reply_data.replier != ANONYMOUS_REPLIER
+        && pda_score.data_len() >= ReplyScore::SIZE
+    {
+        // The program id stands in for the reputation when a client only appends a feed.
+        if let Some(pda_reputation) = account_info_iter
+            .next()
+            .filter(|account| account.key != program_id)
+        {
+            // Held repliers still earn points, but accrue nothing to claim.
+            #[cfg(feature = "no-rewards")]
+            let milestone_reward = 0;
+            #[cfg(not(feature = "no-rewards"))]
+            let milestone_reward =
+                if rewards_held(program_id, config, &reply_data.replier, account_info_iter)? {
+                    msg!("Rewards are held for review");
+                    0
+                } else {
+                    Reputation::MILESTONE_REWARD
+                };
+
+            // A linked replier's milestones build their primary's reputation.
+            credit_reputation(
+                program_id,
+                voter,
+                pda_reply.key,
+                &replier_identity,
+                &mut score_data,
+                pda_reputation,
+                system_program,
+                milestone_reward,
+            )?;
+        }
+    }
+
+    // Older score accounts end before `milestones`, which is always 0 for them.
+    let data = score_data.try_to_vec()?;
+    let len = data.len().min(pda_score.data_len());
+    pda_score.data.borrow_mut()[..len].copy_from_slice(&data[..len]);
+    top_data.serialize(&mut &mut pda_top_replies.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Credits `replier`'s reputation, created on first use with `payer` funding it, for every
+/// milestone `score` has reached since it was last credited, each accruing `milestone_reward`
+/// to claim. Milestones stay credited if the score later drops.
+#[allow(clippy::too_many_arguments)]
+fn credit_reputation<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    reply: &Pubkey,
+    replier: &Pubkey,
+    score: &mut ReplyScore,
+    pda_reputation: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    milestone_reward: u64,
+) -> ProgramResult {
+    let reached = score.milestones_reached();
+    if reached <= score.milestones {
+        return Ok(());
+    }
+
+    let (reputation_key, reputation_bump) =
+        Pubkey::find_program_address(&[replier.as_ref(), b"reputation"], program_id);
+
+    if reputation_key != *pda_reputation.key {
+        log_account_check("reputation_pda", pda_reputation.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    ensure_writable(&[(pda_reputation, "reputation_pda")])?;
+
+    if pda_reputation.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            pda_reputation,
+            system_program,
+            Reputation::SIZE,
+            &[replier.as_ref(), b"reputation", &[reputation_bump]],
+        )?;
+    }
+
+    let mut reputation_data =
+        try_from_slice_unchecked::<Reputation>(&pda_reputation.data.borrow()).unwrap();
+
+    if !reputation_data.is_initialized() {
+        reputation_data.discriminator = Reputation::DISCRIMINATOR.to_string();
+        reputation_data.user = *replier;
+        reputation_data.is_initialized = true;
+    }
+
+    let credited = (reached - score.milestones) as u64;
+    let points = credited * Reputation::POINTS_PER_MILESTONE;
+    let claimable = credited * milestone_reward;
+
+    reputation_data.points += points;
+    reputation_data.claimable += claimable;
+    reputation_data.serialize(&mut &mut pda_reputation.data.borrow_mut()[..])?;
+    score.milestones = reached;
+
+    events::emit(ProgramEvent::ReputationCredited {
+        replier: *replier,
+        reply: *reply,
+        milestones: reached,
+        points,
+        claimable,
+    });
+
+    Ok(())
+}
+
+/// Mints everything the signer's reputation has accrued and zeroes it.
+#[cfg(not(feature = "no-rewards"))]
+pub fn claim_reputation_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user = next_account_info(account_info_iter)?;
+    let pda_reputation = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let user_ata = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !user.is_signer {
+        log_account_check("user", user.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (pda_reputation, "reputation_pda"),
+        (token_mint, "token_mint"),
+        (user_ata, "user_ata"),
+    ])?;
+
+    let (reputation_key, _reputation_bump) =
+        Pubkey::find_program_address(&[user.key.as_ref(), b"reputation"], program_id);
+
+    if reputation_key != *pda_reputation.key {
+        log_account_check("reputation_pda", pda_reputation.key, AccountCheck::Pda);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_reputation.owner != program_id {
+        log_account_check("reputation_pda", pda_reputation.key, AccountCheck::Owner);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let mut reputation_data = try_from_slice_unchecked::<Reputation>(&pda_reputation.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !reputation_data.is_initialized() {
+        log_account_check(
+            "reputation_pda",
+            pda_reputation.key,
+            AccountCheck::Initialized,
+        );
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    let amount = reputation_data.claimable;
+    msg!("Claiming {} reputation reward tokens", amount);
+
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let mint_auth_bump = verify_intro_reward_accounts(
+        program_id,
+        config,
+        user.key,
+        token_mint,
+        mint_auth,
+        user_ata,
+        token_program,
+    )?;
+
+    reputation_data.claimable = 0;
+    reputation_data.serialize(&mut &mut pda_reputation.data.borrow_mut()[..])?;
+
+    mint_reward(
+        token_program,
+        token_mint,
+        user_ata,
+        mint_auth,
+        mint_auth_bump,
+        amount,
+    )?;
+
+    events::emit(ProgramEvent::ReputationClaimed {
+        user: *user.key,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Creates a rent-exempt account owned by this program at the PDA described by `signer_seeds`.
+fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda.key,
+            rent.minimum_balance(space),
+            space.try_into().unwrap(),
+            program_id,
+        ),
+        &[payer.clone(), pda.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+    msg!("Created Account: {}", pda.key);
+
+    Ok(())
+}
+
+pub fn create_course(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+    requires_student_id: bool,
+    certificate: CertificateRequirements,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if name.is_empty() || name.len() > Course::MAX_NAME_LEN {
+        msg!("Course name must be 1-{} bytes", Course::MAX_NAME_LEN);
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[teacher.key.as_ref(), "course".as_ref(), name.as_ref()],
+        program_id,
+    );
+
+    if pda != *pda_course.key {
+        log_account_check("course_pda", pda_course.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        teacher,
+        pda_course,
+        system_program,
+        Course::ACCOUNT_LEN,
+        &[
+            teacher.key.as_ref(),
+            "course".as_ref(),
+            name.as_ref(),
+            &[bump_seed],
+        ],
+    )?;
+
+    let mut course_data = try_from_slice_unchecked::<Course>(&pda_course.data.borrow()).unwrap();
+
+    if course_data.is_initialized() {
+        msg!("Course already initialized!");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    course_data.discriminator = Course::DISCRIMINATOR.to_string();
+    course_data.teacher = *teacher.key;
+    course_data.name = name;
+    course_data.rewards_disabled = false;
+    course_data.requires_student_id = requires_student_id;
+    course_data.certificate = certificate;
+    course_data.is_initialized = true;
+
+    course_data.serialize(&mut &mut pda_course.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Teacher-only switch for reward minting on replies scoped to the course. Replies still post
+/// while rewards are off.
+pub fn set_course_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut course_data = load_course(program_id, pda_course)?;
+
+    if course_data.teacher != *teacher.key {
+        msg!("Only the course teacher can change its rewards");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    course_data.rewards_disabled = !enabled;
+    course_data.serialize(&mut &mut pda_course.data.borrow_mut()[..])?;
+    msg!(
+        "Rewards {} for course {}",
+        if enabled { "enabled" } else { "disabled" },
+        pda_course.key
+    );
+
+    Ok(())
+}
+
+/// Teacher-only: sets the course's end date and whether replies to its intros are anonymized
+/// once it has passed, for deployments with data-retention requirements.
+pub fn set_course_retention(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    ends_at: i64,
+    anonymize_replies: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut course_data = load_course(program_id, pda_course)?;
+
+    if course_data.teacher != *teacher.key {
+        msg!("Only the course teacher can change its retention");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if anonymize_replies && ends_at <= 0 {
+        msg!("Anonymizing replies needs an end date");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    course_data.ends_at = ends_at;
+    course_data.anonymize_replies = anonymize_replies;
+    course_data.serialize(&mut &mut pda_course.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Teacher-only: sets the character policy for intros scoped to the course and replies to its
+/// course-only intros. Text already posted is left as it is.
+pub fn set_character_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    policy: CharacterPolicy,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut course_data = load_course(program_id, pda_course)?;
+
+    if course_data.teacher != *teacher.key {
+        msg!("Only the course teacher can change its character policy");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    course_data.character_policy = policy;
+    course_data.serialize(&mut &mut pda_course.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Teacher-only: pins an intro to the top of the course board. Pinning an intro that is already
+/// pinned changes nothing.
+pub fn pin_intro(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+
+    let mut course_data = authorize_teacher(program_id, teacher, pda_course)?;
+    load_intro(program_id, pda_intro)?;
+
+    if course_data.pinned_intros.contains(pda_intro.key) {
+        msg!("Intro already pinned");
+        return Ok(());
+    }
+
+    if course_data.pinned_intros.len() >= Course::MAX_PINNED_INTROS {
+        msg!("Courses pin at most {} intros", Course::MAX_PINNED_INTROS);
+        return Err(IntroError::TooManyPins.into());
+    }
+
+    course_data.pinned_intros.push(*pda_intro.key);
+    course_data.serialize(&mut &mut pda_course.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Teacher-only: removes an intro from the course board's pins. The intro is matched by
+/// address only, so pins of closed intros can be cleared.
+pub fn unpin_intro(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let intro = next_account_info(account_info_iter)?;
+
+    let mut course_data = authorize_teacher(program_id, teacher, pda_course)?;
+
+    if !course_data.pinned_intros.contains(intro.key) {
+        msg!("Intro is not pinned");
+        return Ok(());
+    }
+
+    course_data
+        .pinned_intros
+        .retain(|pinned| pinned != intro.key);
+    // Borsh writes the shorter list in place; the stale last key past it is never read.
+    course_data.serialize(&mut &mut pda_course.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Loads the course the signer teaches, for the teacher-only course settings.
+fn authorize_teacher(
+    program_id: &Pubkey,
+    teacher: &AccountInfo,
+    pda_course: &AccountInfo,
+) -> Result<Course, ProgramError> {
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_course, "course_pda")])?;
+
+    let course_data = load_course(program_id, pda_course)?;
+
+    if course_data.teacher != *teacher.key {
+        msg!("Only the course teacher can change its pins");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    Ok(course_data)
+}
+
+/// Permissionless crank for ended courses with `anonymize_replies`: rewrites each passed reply
+/// to the intro with `ANONYMOUS_REPLIER` and an empty name, keeping the message and every
+/// later field. The bytes the name took are zeroed at the end of the account, where they read
+/// as the defaults of any fields an older reply lacks. Replies already anonymized are skipped.
+pub fn anonymize_replies(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+
+    let course_data = load_course(program_id, pda_course)?;
+
+    if !course_data.anonymize_replies {
+        msg!("Course does not anonymize replies");
+        return Err(IntroError::AnonymizationNotDue.into());
+    }
+
+    if course_data.ends_at <= 0 || Clock::get()?.unix_timestamp <= course_data.ends_at {
+        msg!("Course has not ended");
+        return Err(IntroError::AnonymizationNotDue.into());
+    }
+
+    if load_intro(program_id, pda_intro)?.course != Some(*pda_course.key) {
+        msg!("Intro is not scoped to this course");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let mut count: u64 = 0;
+    for pda_reply in account_info_iter {
+        ensure_writable(&[(pda_reply, "reply_pda")])?;
+
+        if pda_reply.owner != program_id {
+            log_account_check("reply_pda", pda_reply.key, AccountCheck::Owner);
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let reply_data = try_from_slice_unchecked::<StudentReplyState>(&pda_reply.data.borrow())
+            .map_err(|_| IntroError::UninitializedAccount)?;
+
+        if !reply_data.is_initialized()
+            || reply_data.discriminator != StudentReplyState::DISCRIMINATOR
+        {
+            log_account_check("reply_pda", pda_reply.key, AccountCheck::Initialized);
+            return Err(IntroError::UninitializedAccount.into());
+        }
+
+        if reply_data.intro != *pda_intro.key {
+            msg!("Reply is not on this intro");
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if reply_data.replier == ANONYMOUS_REPLIER && reply_data.name.is_empty() {
+            continue;
+        }
+
+        let mut data = pda_reply.data.borrow_mut();
+        // Everything after the message is copied over as stored.
+        let rest = (4 + StudentReplyState::DISCRIMINATOR.len())
+            + 1
+            + 32
+            + 32
+            + (4 + reply_data.name.len())
+            + (4 + reply_data.message.len());
+        let mut anonymized = (
+            &reply_data.discriminator,
+            reply_data.is_initialized,
+            reply_data.intro,
+            ANONYMOUS_REPLIER,
+            "",
+            &reply_data.message,
+        )
+            .try_to_vec()?;
+        anonymized.extend_from_slice(&data[rest..]);
+        anonymized.resize(data.len(), 0);
+        data.copy_from_slice(&anonymized);
+        count += 1;
+    }
+
+    msg!("Anonymized {} replies", count);
+    events::emit(ProgramEvent::RepliesAnonymized {
+        course: *pda_course.key,
+        intro: *pda_intro.key,
+        count,
+    });
+
+    Ok(())
+}
+
+pub fn enroll_student(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let student = next_account_info(account_info_iter)?;
+    let pda_enrollment = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let course_data = load_course(program_id, pda_course)?;
+
+    if course_data.teacher != *teacher.key {
+        msg!("Signer is not the course teacher");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[
+            pda_course.key.as_ref(),
+            student.key.as_ref(),
+            "enrollment".as_ref(),
+        ],
+        program_id,
+    );
+
+    if pda != *pda_enrollment.key {
+        log_account_check("enrollment_pda", pda_enrollment.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        teacher,
+        pda_enrollment,
+        system_program,
+        Enrollment::SIZE,
+        &[
+            pda_course.key.as_ref(),
+            student.key.as_ref(),
+            "enrollment".as_ref(),
+            &[bump_seed],
+        ],
+    )?;
+
+    let enrollment_data = Enrollment {
+        discriminator: Enrollment::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: *pda_course.key,
+        student: *student.key,
+    };
+    enrollment_data.serialize(&mut &mut pda_enrollment.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn set_visibility(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    visibility: u8,
+    student_id_hash: Option<[u8; 32]>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *writer.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    match visibility {
+        StudentIntroState::VISIBILITY_PUBLIC | StudentIntroState::VISIBILITY_PRIVATE => {}
+        StudentIntroState::VISIBILITY_COURSE_ONLY => {
+            // The writer's own enrollment names the course the intro is scoped to.
+            let enrollment = next_account_info(account_info_iter)?;
+            let stats_pda = next_account_info(account_info_iter)?;
+            let activity_pda = next_account_info(account_info_iter)?;
+            let system_program = next_account_info(account_info_iter)?;
+            if enrollment.owner != program_id {
+                log_account_check("enrollment", enrollment.key, AccountCheck::Owner);
+                return Err(ProgramError::IllegalOwner);
+            }
+            let enrollment_data = try_from_slice_unchecked::<Enrollment>(&enrollment.data.borrow())
+                .map_err(|_| IntroError::NotEnrolled)?;
+            verify_enrollment(program_id, enrollment, &enrollment_data.course, writer.key)?;
+            let course_data = register_student_id(
+                program_id,
+                writer,
+                &enrollment_data.course,
+                system_program,
+                account_info_iter,
+                student_id_hash,
+            )?;
+            verify_character_policy(
+                &course_data.character_policy,
+                &intro_data.name,
+                &intro_data.message,
+            )?;
+            let activity = if intro_data.course == Some(enrollment_data.course) {
+                CourseActivity::Rescoped
+            } else {
+                CourseActivity::Intro
+            };
+            record_course_activity(
+                program_id,
+                writer,
+                &enrollment_data.course,
+                enrollment,
+                stats_pda,
+                activity_pda,
+                system_program,
+                activity,
+            )?;
+            intro_data.course = Some(enrollment_data.course);
+        }
+        _ => {
+            msg!("Unknown visibility level");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+
+    intro_data.visibility = visibility;
+    intro_data.serialize(&mut &mut pda_intro.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Teacher-only: records whether the writer of an intro scoped to the course is still active,
+/// has graduated or has withdrawn. Replies to intros that are no longer active post and read
+/// as before but earn no rewards.
+pub fn set_student_status(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    status: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_intro, "intro_pda")])?;
+
+    let course_data = load_course(program_id, pda_course)?;
+
+    if course_data.teacher != *teacher.key {
+        msg!("Signer is not the course teacher");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let mut intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.course != Some(*pda_course.key) {
+        msg!("Intro is not scoped to this course");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    match status {
+        StudentIntroState::STATUS_ACTIVE
+        | StudentIntroState::STATUS_GRADUATED
+        | StudentIntroState::STATUS_WITHDRAWN => {}
+        _ => {
+            msg!("Unknown student status");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+
+    let previous = intro_data.status;
+    intro_data.status = status;
+    store_intro(pda_intro, &intro_data)?;
+
+    events::emit(ProgramEvent::StudentStatusChanged {
+        intro: *pda_intro.key,
+        student: intro_data.writer,
+        course: *pda_course.key,
+        previous,
+        status,
+    });
+
+    Ok(())
+}
+
+/// Teacher-only: endorses an intro scoped to the course, for courses whose certificates need
+/// one. Endorsements are permanent.
+pub fn endorse_intro(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_endorsement = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(teacher, "teacher"), (pda_endorsement, "endorsement_pda")])?;
+
+    let course_data = load_course(program_id, pda_course)?;
+
+    if course_data.teacher != *teacher.key {
+        msg!("Signer is not the course teacher");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if load_intro(program_id, pda_intro)?.course != Some(*pda_course.key) {
+        msg!("Intro is not scoped to this course");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[
+            pda_course.key.as_ref(),
+            pda_intro.key.as_ref(),
+            b"endorsement",
+        ],
+        program_id,
+    );
+
+    if pda != *pda_endorsement.key {
+        log_account_check("endorsement_pda", pda_endorsement.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        teacher,
+        pda_endorsement,
+        system_program,
+        IntroEndorsement::SIZE,
+        &[
+            pda_course.key.as_ref(),
+            pda_intro.key.as_ref(),
+            b"endorsement",
+            &[bump_seed],
+        ],
+    )?;
+
+    let endorsement_data = IntroEndorsement {
+        discriminator: IntroEndorsement::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: *pda_course.key,
+        intro: *pda_intro.key,
+        slot: Clock::get()?.slot,
+    };
+    endorsement_data.serialize(&mut &mut pda_endorsement.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Mints the writer of a graduated intro a one-of-one certificate NFT for the course, once the
+/// course's requirements hold. The mint's authority is dropped after the single token, and
+/// its immutable metadata names the course and the completion epoch.
+#[cfg(not(feature = "no-rewards"))]
+pub fn claim_certificate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let student = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_endorsement = next_account_info(account_info_iter)?;
+    let pda_certificate = next_account_info(account_info_iter)?;
+    let certificate_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let student_ata = next_account_info(account_info_iter)?;
+    let metadata = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let ata_program = next_account_info(account_info_iter)?;
+    let metadata_program = next_account_info(account_info_iter)?;
+
+    if !student.is_signer {
+        log_account_check("student", student.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (student, "student"),
+        (pda_certificate, "certificate_pda"),
+        (certificate_mint, "certificate_mint"),
+        (student_ata, "student_ata"),
+        (metadata, "metadata"),
+    ])?;
+
+    let course_data = load_course(program_id, pda_course)?;
+    let intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *student.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if intro_data.course != Some(*pda_course.key) {
+        msg!("Intro is not scoped to this course");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if intro_data.status != StudentIntroState::STATUS_GRADUATED {
+        msg!("Intro has not graduated");
+        return Err(IntroError::CertificateNotEarned.into());
+    }
+
+    let (counter_key, _counter_bump) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), "counter".as_ref()], program_id);
+
+    if counter_key != *pda_counter.key {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let counter_data = try_from_slice_unchecked::<ReplyCount>(&pda_counter.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+    let requirements = course_data.certificate;
+
+    if counter_data.counter < requirements.min_replies {
+        msg!(
+            "Intro has {} of {} replies needed for a certificate",
+            counter_data.counter,
+            requirements.min_replies
+        );
+        return Err(IntroError::CertificateNotEarned.into());
+    }
+
+    let (endorsement_key, _endorsement_bump) = Pubkey::find_program_address(
+        &[
+            pda_course.key.as_ref(),
+            pda_intro.key.as_ref(),
+            b"endorsement",
+        ],
+        program_id,
+    );
+
+    if endorsement_key != *pda_endorsement.key {
+        log_account_check("endorsement_pda", pda_endorsement.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if requirements.requires_endorsement && pda_endorsement.owner != program_id {
+        msg!("Course requires an endorsed intro");
+        return Err(IntroError::CertificateNotEarned.into());
+    }
+
+    let (certificate_key, certificate_bump) = Pubkey::find_program_address(
+        &[
+            pda_course.key.as_ref(),
+            pda_intro.key.as_ref(),
+            b"certificate",
+        ],
+        program_id,
+    );
+    let (mint_key, mint_bump) =
+        Pubkey::find_program_address(&[certificate_key.as_ref(), b"mint"], program_id);
+    let (mint_auth_pda, mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    if certificate_key != *pda_certificate.key {
+        log_account_check("certificate_pda", pda_certificate.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if mint_key != *certificate_mint.key {
+        log_account_check("certificate_mint", certificate_mint.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if mint_auth_pda != *mint_auth.key {
+        log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *student_ata.key != get_associated_token_address(student.key, &mint_key) {
+        log_account_check("student_ata", student_ata.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *metadata.key != nft::find_metadata_address(&mint_key).0 {
+        log_account_check("metadata", metadata.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *ata_program.key != spl_associated_token_account::id() {
+        log_account_check("ata_program", ata_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *metadata_program.key != nft::TOKEN_METADATA_PROGRAM_ID {
+        log_account_check(
+            "metadata_program",
+            metadata_program.key,
+            AccountCheck::Address,
+        );
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    // Fails if the certificate was already claimed.
+    create_pda_account(
+        program_id,
+        student,
+        pda_certificate,
+        system_program,
+        Certificate::SIZE,
+        &[
+            pda_course.key.as_ref(),
+            pda_intro.key.as_ref(),
+            b"certificate",
+            &[certificate_bump],
+        ],
+    )?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            student.key,
+            certificate_mint.key,
+            Rent::get()?.minimum_balance(82),
+            82,
+            token_program.key,
+        ),
+        &[
+            student.clone(),
+            certificate_mint.clone(),
+            system_program.clone(),
+        ],
+        &[&[certificate_key.as_ref(), b"mint", &[mint_bump]]],
+    )?;
+    invoke(
+        &initialize_mint2(token_program.key, &mint_key, mint_auth.key, None, 0)?,
+        &[certificate_mint.clone(), token_program.clone()],
+    )?;
+    invoke(
+        &create_associated_token_account(student.key, student.key, &mint_key),
+        &[
+            student.clone(),
+            student_ata.clone(),
+            certificate_mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            ata_program.clone(),
+        ],
+    )?;
+
+    let mint_auth_seeds: &[&[u8]] = &[b"token_auth", &[mint_auth_bump]];
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            &mint_key,
+            student_ata.key,
+            mint_auth.key,
+            &[],
+            1,
+        )?,
+        &[
+            certificate_mint.clone(),
+            student_ata.clone(),
+            mint_auth.clone(),
+        ],
+        &[mint_auth_seeds],
+    )?;
+
+    let completion_epoch = Clock::get()?.epoch;
+    invoke_signed(
+        &nft::create_metadata_account(
+            metadata.key,
+            &mint_key,
+            mint_auth.key,
+            student.key,
+            course_data.name.clone(),
+            "CERT".to_string(),
+            nft::certificate_uri(&course_data.name, completion_epoch),
+        ),
+        &[
+            metadata.clone(),
+            certificate_mint.clone(),
+            mint_auth.clone(),
+            student.clone(),
+            system_program.clone(),
+            metadata_program.clone(),
+        ],
+        &[mint_auth_seeds],
+    )?;
+
+    // Nothing can be minted after the one certificate token.
+    invoke_signed(
+        &set_authority(
+            token_program.key,
+            &mint_key,
+            None,
+            AuthorityType::MintTokens,
+            mint_auth.key,
+            &[],
+        )?,
+        &[certificate_mint.clone(), mint_auth.clone()],
+        &[mint_auth_seeds],
+    )?;
+
+    let certificate_data = Certificate {
+        discriminator: Certificate::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: *pda_course.key,
+        intro: *pda_intro.key,
+        student: *student.key,
+        mint: mint_key,
+        completion_epoch,
+    };
+    certificate_data.serialize(&mut &mut pda_certificate.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::CertificateClaimed {
+        course: *pda_course.key,
+        intro: *pda_intro.key,
+        student: *student.key,
+        mint: mint_key,
+        completion_epoch,
+    });
+
+    Ok(())
+}
+
+/// Adds an enrolled student to the back of the course's office-hours queue, creating the queue
+/// on first use.
+pub fn join_queue(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let student = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_enrollment = next_account_info(account_info_iter)?;
+    let pda_queue = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !student.is_signer {
+        log_account_check("student", student.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(student, "student"), (pda_queue, "queue_pda")])?;
+
+    load_course(program_id, pda_course)?;
+    verify_enrollment(program_id, pda_enrollment, pda_course.key, student.key)?;
+
+    let mut queue_data = if pda_queue.owner == program_id {
+        load_queue(program_id, pda_course, pda_queue)?
+    } else {
+        let (pda, bump_seed) =
+            Pubkey::find_program_address(&[pda_course.key.as_ref(), b"queue"], program_id);
+
+        if pda != *pda_queue.key {
+            log_account_check("queue_pda", pda_queue.key, AccountCheck::Pda);
+            return Err(IntroError::InvalidPDA.into());
+        }
+
+        create_pda_account(
+            program_id,
+            student,
+            pda_queue,
+            system_program,
+            OfficeHoursQueue::SIZE,
+            &[pda_course.key.as_ref(), b"queue", &[bump_seed]],
+        )?;
+
+        OfficeHoursQueue {
+            discriminator: OfficeHoursQueue::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            course: *pda_course.key,
+            head: 0,
+            len: 0,
+            slots: Default::default(),
+        }
+    };
+
+    if queue_data.students().any(|queued| queued == student.key) {
+        msg!("{} is already queued", student.key);
+        return Err(IntroError::AlreadyQueued.into());
+    }
+
+    let position = queue_data.len;
+    if !queue_data.push(*student.key) {
+        msg!(
+            "Office-hours queue holds at most {} students",
+            QUEUE_CAPACITY
+        );
+        return Err(IntroError::QueueFull.into());
+    }
+    queue_data.serialize(&mut &mut pda_queue.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::QueueJoined {
+        course: *pda_course.key,
+        student: *student.key,
+        position,
+    });
+
+    Ok(())
+}
+
+/// Takes the signer out of the course's office-hours queue, wherever they are in it.
+pub fn leave_queue(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let student = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_queue = next_account_info(account_info_iter)?;
+
+    if !student.is_signer {
+        log_account_check("student", student.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_queue, "queue_pda")])?;
+
+    let mut queue_data = load_queue(program_id, pda_course, pda_queue)?;
+
+    if !queue_data.remove(student.key) {
+        msg!("{} is not queued", student.key);
+        return Err(IntroError::NotQueued.into());
+    }
+    queue_data.serialize(&mut &mut pda_queue.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::QueueLeft {
+        course: *pda_course.key,
+        student: *student.key,
+    });
+
+    Ok(())
+}
+
+/// Teacher-only: takes the student at the front of the course's office-hours queue.
+pub fn pop_queue(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_queue = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_queue, "queue_pda")])?;
+
+    if load_course(program_id, pda_course)?.teacher != *teacher.key {
+        msg!("Signer is not the course teacher");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let mut queue_data = load_queue(program_id, pda_course, pda_queue)?;
+    let student = queue_data.pop().ok_or(IntroError::QueueEmpty)?;
+    queue_data.serialize(&mut &mut pda_queue.data.borrow_mut()[..])?;
+    msg!("Next in office hours: {}", student);
+
+    events::emit(ProgramEvent::QueuePopped {
+        course: *pda_course.key,
+        student,
+    });
+
+    Ok(())
+}
+
+/// Teacher-only: opens an attendance session for the course with a check-in window and an
+/// optional per-student reward.
+pub fn open_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_id: u64,
+    opens_at: i64,
+    closes_at: i64,
+    reward: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_session = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(teacher, "teacher"), (pda_session, "session_pda")])?;
+
+    if load_course(program_id, pda_course)?.teacher != *teacher.key {
+        msg!("Signer is not the course teacher");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if closes_at <= opens_at || closes_at <= Clock::get()?.unix_timestamp {
+        msg!("Session must close after it opens and in the future");
+        return Err(IntroError::InvalidSession.into());
+    }
+
+    if reward > Session::MAX_REWARD {
+        msg!("Attendance reward is at most {}", Session::MAX_REWARD);
+        return Err(IntroError::InvalidSession.into());
+    }
+
+    let id_bytes = session_id.to_le_bytes();
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[pda_course.key.as_ref(), b"session", &id_bytes],
+        program_id,
+    );
+
+    if pda != *pda_session.key {
+        log_account_check("session_pda", pda_session.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        teacher,
+        pda_session,
+        system_program,
+        Session::SIZE,
+        &[pda_course.key.as_ref(), b"session", &id_bytes, &[bump_seed]],
+    )?;
+
+    let session_data = Session {
+        discriminator: Session::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: *pda_course.key,
+        session_id,
+        opens_at,
+        closes_at,
+        reward,
+        check_ins: 0,
+    };
+    session_data.serialize(&mut &mut pda_session.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Records an enrolled student's attendance while the session is open. When the session pays
+/// a reward and the course has rewards on, the config, mint, mint authority, student's reward
+/// account and token program follow.
+#[cfg_attr(feature = "no-rewards", allow(unused_variables))]
+pub fn check_in(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let student = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_enrollment = next_account_info(account_info_iter)?;
+    let pda_session = next_account_info(account_info_iter)?;
+    let pda_attendance = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !student.is_signer {
+        log_account_check("student", student.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (student, "student"),
+        (pda_session, "session_pda"),
+        (pda_attendance, "attendance_pda"),
+    ])?;
+
+    #[cfg_attr(feature = "no-rewards", allow(unused_variables, unused_mut))]
+    let mut course_data = load_course(program_id, pda_course)?;
+    verify_enrollment(program_id, pda_enrollment, pda_course.key, student.key)?;
+
+    if pda_session.owner != program_id {
+        log_account_check("session_pda", pda_session.key, AccountCheck::Owner);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    let mut session_data = try_from_slice_unchecked::<Session>(&pda_session.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !session_data.is_initialized() || session_data.discriminator != Session::DISCRIMINATOR {
+        log_account_check("session_pda", pda_session.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if session_data.course != *pda_course.key {
+        msg!("Session does not belong to the course");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if !session_data.is_open(now) {
+        msg!(
+            "Session is open from {} until {}",
+            session_data.opens_at,
+            session_data.closes_at
+        );
+        return Err(IntroError::SessionClosed.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[
+            pda_session.key.as_ref(),
+            student.key.as_ref(),
+            b"attendance",
+        ],
+        program_id,
+    );
+
+    if pda != *pda_attendance.key {
+        log_account_check("attendance_pda", pda_attendance.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    // Fails if the student already checked in.
+    create_pda_account(
+        program_id,
+        student,
+        pda_attendance,
+        system_program,
+        Attendance::SIZE,
+        &[
+            pda_session.key.as_ref(),
+            student.key.as_ref(),
+            b"attendance",
+            &[bump_seed],
+        ],
+    )?;
+
+    let attendance_data = Attendance {
+        discriminator: Attendance::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        session: *pda_session.key,
+        student: *student.key,
+        checked_in_at: now,
+    };
+    attendance_data.serialize(&mut &mut pda_attendance.data.borrow_mut()[..])?;
+
+    session_data.check_ins += 1;
+    session_data.serialize(&mut &mut pda_session.data.borrow_mut()[..])?;
+
+    #[cfg(feature = "no-rewards")]
+    let reward = 0;
+    #[cfg(not(feature = "no-rewards"))]
+    let reward = if session_data.reward == 0 {
+        0
+    } else if course_data.rewards_disabled {
+        msg!("Rewards are disabled for course {}", pda_course.key);
+        0
+    } else {
+        let token_mint = next_account_info(account_info_iter)?;
+        let mint_auth = next_account_info(account_info_iter)?;
+        let user_ata = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+
+        ensure_writable(&[
+            (pda_course, "course_pda"),
+            (token_mint, "token_mint"),
+            (user_ata, "user_ata"),
+        ])?;
+
+        let mint_auth_bump = verify_intro_reward_accounts(
+            program_id,
+            config,
+            student.key,
+            token_mint,
+            mint_auth,
+            user_ata,
+            token_program,
+        )?;
+        let reward = if rewards_held(program_id, config, student.key, account_info_iter)? {
+            msg!("Rewards are held for review");
+            0
+        } else {
+            take_course_reward(config, &mut course_data, session_data.reward)?
+        };
+        course_data.serialize(&mut &mut pda_course.data.borrow_mut()[..])?;
+        if reward > 0 {
+            mint_reward(
+                token_program,
+                token_mint,
+                user_ata,
+                mint_auth,
+                mint_auth_bump,
+                reward,
+            )?;
+        }
+        reward
+    };
+
+    events::emit(ProgramEvent::CheckedIn {
+        session: *pda_session.key,
+        student: *student.key,
+        reward,
+    });
+
+    Ok(())
+}
+
+/// Teacher-only: opens a quiz for the course, committing to its answer up front so it cannot be
+/// changed after submissions come in.
+pub fn create_quiz(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    quiz_id: u64,
+    closes_at: i64,
+    answer_hash: [u8; 32],
+    reward: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_quiz = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(teacher, "teacher"), (pda_quiz, "quiz_pda")])?;
+
+    if load_course(program_id, pda_course)?.teacher != *teacher.key {
+        msg!("Signer is not the course teacher");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if closes_at <= Clock::get()?.unix_timestamp {
+        msg!("Quiz must close in the future");
+        return Err(IntroError::InvalidQuiz.into());
+    }
+
+    if reward > Quiz::MAX_REWARD {
+        msg!("Quiz reward is at most {}", Quiz::MAX_REWARD);
+        return Err(IntroError::InvalidQuiz.into());
+    }
+
+    let id_bytes = quiz_id.to_le_bytes();
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[pda_course.key.as_ref(), b"quiz", &id_bytes], program_id);
+
+    if pda != *pda_quiz.key {
+        log_account_check("quiz_pda", pda_quiz.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        teacher,
+        pda_quiz,
+        system_program,
+        Quiz::SIZE,
+        &[pda_course.key.as_ref(), b"quiz", &id_bytes, &[bump_seed]],
+    )?;
+
+    let quiz_data = Quiz {
+        discriminator: Quiz::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: *pda_course.key,
+        quiz_id,
+        closes_at,
+        answer_hash,
+        reward,
+        revealed: false,
+        submissions: 0,
+        correct: 0,
+    };
+    quiz_data.serialize(&mut &mut pda_quiz.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Records an enrolled student's hashed answer while the quiz is open. Each student submits
+/// once.
+pub fn submit_quiz_answer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    quiz_id: u64,
+    answer_hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let student = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_enrollment = next_account_info(account_info_iter)?;
+    let pda_quiz = next_account_info(account_info_iter)?;
+    let pda_submission = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !student.is_signer {
+        log_account_check("student", student.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (student, "student"),
+        (pda_quiz, "quiz_pda"),
+        (pda_submission, "submission_pda"),
+    ])?;
+
+    load_course(program_id, pda_course)?;
+    verify_enrollment(program_id, pda_enrollment, pda_course.key, student.key)?;
+
+    let mut quiz_data = load_quiz(program_id, pda_course, pda_quiz)?;
+
+    if quiz_data.quiz_id != quiz_id {
+        msg!("Quiz account is not quiz {}", quiz_id);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if Clock::get()?.unix_timestamp >= quiz_data.closes_at {
+        msg!("Quiz closed at {}", quiz_data.closes_at);
+        return Err(IntroError::QuizClosed.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[pda_quiz.key.as_ref(), student.key.as_ref(), b"quiz_answer"],
+        program_id,
+    );
+
+    if pda != *pda_submission.key {
+        log_account_check("submission_pda", pda_submission.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    // Fails if the student already submitted.
+    create_pda_account(
+        program_id,
+        student,
+        pda_submission,
+        system_program,
+        QuizSubmission::SIZE,
+        &[
+            pda_quiz.key.as_ref(),
+            student.key.as_ref(),
+            b"quiz_answer",
+            &[bump_seed],
+        ],
+    )?;
+
+    let submission_data = QuizSubmission {
+        discriminator: QuizSubmission::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        quiz: *pda_quiz.key,
+        student: *student.key,
+        answer_hash,
+        graded: false,
+        correct: false,
+    };
+    submission_data.serialize(&mut &mut pda_submission.data.borrow_mut()[..])?;
+
+    quiz_data.submissions += 1;
+    quiz_data.serialize(&mut &mut pda_quiz.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Teacher-only, after the quiz closes: checks `answer` and `salt` against the quiz's
+/// commitment, then grades every submission account that follows. When the quiz pays a reward
+/// and the course has rewards on, the config, mint, mint authority and token program come
+/// first and each submission is followed by its student's reward account. Already graded
+/// submissions are skipped, so the teacher can grade a large class over several calls.
+#[cfg_attr(feature = "no-rewards", allow(unused_variables))]
+pub fn reveal_answer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    answer: String,
+    salt: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let teacher = next_account_info(account_info_iter)?;
+    let pda_course = next_account_info(account_info_iter)?;
+    let pda_quiz = next_account_info(account_info_iter)?;
+
+    if !teacher.is_signer {
+        log_account_check("teacher", teacher.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_quiz, "quiz_pda")])?;
+
+    #[cfg_attr(feature = "no-rewards", allow(unused_mut))]
+    let mut course_data = load_course(program_id, pda_course)?;
+
+    if course_data.teacher != *teacher.key {
+        msg!("Signer is not the course teacher");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let mut quiz_data = load_quiz(program_id, pda_course, pda_quiz)?;
+
+    if Clock::get()?.unix_timestamp < quiz_data.closes_at {
+        msg!("Quiz is open until {}", quiz_data.closes_at);
+        return Err(IntroError::QuizStillOpen.into());
+    }
+
+    if Quiz::commit_answer(&answer, &salt) != quiz_data.answer_hash {
+        return Err(IntroError::AnswerMismatch.into());
+    }
+    quiz_data.revealed = true;
+
+    #[cfg(not(feature = "no-rewards"))]
+    let reward_accounts = if quiz_data.reward > 0 && !course_data.rewards_disabled {
+        let token_mint = next_account_info(account_info_iter)?;
+        let mint_auth = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        ensure_writable(&[(pda_course, "course_pda"), (token_mint, "token_mint")])?;
+        Some((config, token_mint, mint_auth, token_program))
+    } else {
+        None
+    };
+
+    // A configured classifier adds each student's reward hold after their token account.
+    #[cfg(not(feature = "no-rewards"))]
+    let stride = match reward_accounts {
+        Some(_) if config.spam_classifier.is_some() => 3,
+        Some(_) => 2,
+        None => 1,
+    };
+    #[cfg(feature = "no-rewards")]
+    let stride = 1;
+
+    for submission_accounts in account_info_iter.as_slice().chunks(stride) {
+        let pda_submission = &submission_accounts[0];
+        #[cfg(not(feature = "no-rewards"))]
+        let user_ata = match reward_accounts {
+            Some(_) => Some(
+                submission_accounts
+                    .get(1)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?,
+            ),
+            None => None,
+        };
+
+        ensure_writable(&[(pda_submission, "submission_pda")])?;
+
+        if pda_submission.owner != program_id {
+            log_account_check("submission_pda", pda_submission.key, AccountCheck::Owner);
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let mut submission_data =
+            try_from_slice_unchecked::<QuizSubmission>(&pda_submission.data.borrow())
+                .map_err(|_| IntroError::UninitializedAccount)?;
+
+        if !submission_data.is_initialized()
+            || submission_data.discriminator != QuizSubmission::DISCRIMINATOR
+            || submission_data.quiz != *pda_quiz.key
+        {
+            msg!("{} is not a submission to this quiz", pda_submission.key);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if submission_data.graded {
+            msg!("{} is already graded", pda_submission.key);
+            continue;
+        }
+
+        let correct = QuizSubmission::hash_answer(pda_quiz.key, &submission_data.student, &answer)
+            == submission_data.answer_hash;
+        submission_data.graded = true;
+        submission_data.correct = correct;
+        submission_data.serialize(&mut &mut pda_submission.data.borrow_mut()[..])?;
+
+        #[cfg_attr(feature = "no-rewards", allow(unused_mut))]
+        let mut reward = 0;
+        if correct {
+            quiz_data.correct += 1;
+
+            #[cfg(not(feature = "no-rewards"))]
+            if let (Some((config, token_mint, mint_auth, token_program)), Some(user_ata)) =
+                (&reward_accounts, user_ata)
+            {
+                ensure_writable(&[(user_ata, "user_ata")])?;
+                let mint_auth_bump = verify_intro_reward_accounts(
+                    program_id,
+                    config,
+                    &submission_data.student,
+                    token_mint,
+                    mint_auth,
+                    user_ata,
+                    token_program,
+                )?;
+                let held = rewards_held(
+                    program_id,
+                    config,
+                    &submission_data.student,
+                    &mut submission_accounts[2..].iter(),
+                )?;
+                if held {
+                    msg!("Rewards are held for review");
+                } else {
+                    reward = take_course_reward(config, &mut course_data, quiz_data.reward)?;
+                }
+                if reward > 0 {
+                    mint_reward(
+                        token_program,
+                        token_mint,
+                        user_ata,
+                        mint_auth,
+                        mint_auth_bump,
+                        reward,
+                    )?;
+                }
+            }
+        }
+
+        events::emit(ProgramEvent::QuizAnswerGraded {
+            quiz: *pda_quiz.key,
+            student: submission_data.student,
+            correct,
+            reward,
+        });
+    }
+
+    quiz_data.serialize(&mut &mut pda_quiz.data.borrow_mut()[..])?;
+    #[cfg(not(feature = "no-rewards"))]
+    if reward_accounts.is_some() {
+        course_data.serialize(&mut &mut pda_course.data.borrow_mut()[..])?;
+    }
+
+    Ok(())
+}
+
+/// Creates a project team named `name` with the signer as its first member.
+pub fn create_team(program_id: &Pubkey, accounts: &[AccountInfo], name: String) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let creator = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_team = next_account_info(account_info_iter)?;
+    let pda_membership = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !creator.is_signer {
+        log_account_check("creator", creator.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(creator, "creator"), (pda_team, "team_pda")])?;
+
+    if name.is_empty() || name.len() > TeamState::MAX_NAME_LEN {
+        msg!("Team name must be 1-{} bytes", TeamState::MAX_NAME_LEN);
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(&[b"team", name.as_bytes()], program_id);
+
+    if pda != *pda_team.key {
+        log_account_check("team_pda", pda_team.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    // Fails if the name is taken.
+    create_pda_account(
+        program_id,
+        creator,
+        pda_team,
+        system_program,
+        TeamState::SIZE,
+        &[b"team", name.as_bytes(), &[bump_seed]],
+    )?;
+
+    let mut team_data = TeamState {
+        discriminator: TeamState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        name,
+        creator: *creator.key,
+        members: Vec::new(),
+    };
+    add_team_member(
+        program_id,
+        creator,
+        pda_intro,
+        pda_team,
+        &mut team_data,
+        pda_membership,
+        system_program,
+    )?;
+    team_data.serialize(&mut &mut pda_team.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Adds the signer to a team with room, if they are not on one already.
+pub fn join_team(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let student = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_team = next_account_info(account_info_iter)?;
+    let pda_membership = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !student.is_signer {
+        log_account_check("student", student.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(student, "student"), (pda_team, "team_pda")])?;
+
+    let mut team_data = load_team(program_id, pda_team)?;
+
+    if team_data.members.len() >= TeamState::MAX_MEMBERS {
+        msg!("Teams have at most {} members", TeamState::MAX_MEMBERS);
+        return Err(IntroError::TeamFull.into());
+    }
+
+    add_team_member(
+        program_id,
+        student,
+        pda_intro,
+        pda_team,
+        &mut team_data,
+        pda_membership,
+        system_program,
+    )?;
+    team_data.serialize(&mut &mut pda_team.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Takes the signer off their team and closes their membership record. The team keeps its
+/// name when its last member leaves.
+pub fn leave_team(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let student = next_account_info(account_info_iter)?;
+    let pda_team = next_account_info(account_info_iter)?;
+    let pda_membership = next_account_info(account_info_iter)?;
+
+    if !student.is_signer {
+        log_account_check("student", student.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (student, "student"),
+        (pda_team, "team_pda"),
+        (pda_membership, "membership_pda"),
+    ])?;
+
+    let mut team_data = load_team(program_id, pda_team)?;
+
+    let (pda, _bump_seed) =
+        Pubkey::find_program_address(&[student.key.as_ref(), b"team_member"], program_id);
+
+    if pda != *pda_membership.key {
+        log_account_check("membership_pda", pda_membership.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_membership.owner != program_id {
+        msg!("Student is not on a team");
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    let membership_data = try_from_slice_unchecked::<TeamMembership>(&pda_membership.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !membership_data.is_initialized() || membership_data.team != *pda_team.key {
+        msg!("Student is not on this team");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    team_data.members.retain(|member| member != student.key);
+    team_data.serialize(&mut &mut pda_team.data.borrow_mut()[..])?;
+    close_account(pda_membership, student)?;
+
+    events::emit(ProgramEvent::TeamLeft {
+        team: *pda_team.key,
+        student: *student.key,
+    });
+
+    Ok(())
+}
+
+/// Records `student`, who must have an intro and no team yet, as a member of `team_data`.
+fn add_team_member<'a>(
+    program_id: &Pubkey,
+    student: &AccountInfo<'a>,
+    pda_intro: &AccountInfo<'a>,
+    pda_team: &AccountInfo<'a>,
+    team_data: &mut TeamState,
+    pda_membership: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    ensure_writable(&[(pda_membership, "membership_pda")])?;
+
+    if load_intro(program_id, pda_intro)?.writer != *student.key {
+        msg!("Team members need an intro");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[student.key.as_ref(), b"team_member"], program_id);
+
+    if pda != *pda_membership.key {
+        log_account_check("membership_pda", pda_membership.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_membership.owner == program_id {
+        msg!("{} is already on a team", student.key);
+        return Err(IntroError::AlreadyOnTeam.into());
+    }
+
+    create_pda_account(
+        program_id,
+        student,
+        pda_membership,
+        system_program,
+        TeamMembership::SIZE,
+        &[student.key.as_ref(), b"team_member", &[bump_seed]],
+    )?;
+
+    let membership_data = TeamMembership {
+        discriminator: TeamMembership::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        team: *pda_team.key,
+        student: *student.key,
+    };
+    membership_data.serialize(&mut &mut pda_membership.data.borrow_mut()[..])?;
+    team_data.members.push(*student.key);
+
+    events::emit(ProgramEvent::TeamJoined {
+        team: *pda_team.key,
+        student: *student.key,
+    });
+
+    Ok(())
+}
+
+/// Points `["slug", slug]` at the signer's intro. The intro's previous slug stays registered as
+/// a redirect to the new entry through `IntroSlug::REDIRECT_EPOCHS` more epochs; once a redirect
+/// lapses, any intro may claim the slug.
+pub fn set_slug(program_id: &Pubkey, accounts: &[AccountInfo], slug: String) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_slug = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (writer, "writer"),
+        (pda_intro, "intro_pda"),
+        (pda_slug, "slug_pda"),
+    ])?;
+
+    let mut intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *writer.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if !IntroSlug::is_valid(&slug) {
+        msg!("Invalid slug: {}", slug);
+        return Err(IntroError::InvalidSlug.into());
+    }
+
+    if intro_data.slug == slug {
+        return Ok(());
+    }
+
+    // Intros written before slugs and categories existed may be too long to take one.
+    if StudentIntroState::get_account_size(&intro_data.name, &intro_data.message)
+        > StudentIntroState::ACCOUNT_LEN
+    {
+        msg!("Intro is too long to hold a slug");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    let (slug_pda, slug_bump) =
+        Pubkey::find_program_address(&[b"slug", slug.as_bytes()], program_id);
+
+    if slug_pda != *pda_slug.key {
+        log_account_check("slug_pda", pda_slug.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let epoch = Clock::get()?.epoch;
+
+    if pda_slug.owner != program_id {
+        create_pda_account(
+            program_id,
+            writer,
+            pda_slug,
+            system_program,
+            IntroSlug::SIZE,
+            &[b"slug", slug.as_bytes(), &[slug_bump]],
+        )?;
+    } else {
+        let existing = try_from_slice_unchecked::<IntroSlug>(&pda_slug.data.borrow())
+            .map_err(|_| IntroError::UninitializedAccount)?;
+
+        if existing.intro != *pda_intro.key {
+            let pda_holder = next_account_info(account_info_iter)?;
+
+            if *pda_holder.key != existing.intro {
+                log_account_check("holder_pda", pda_holder.key, AccountCheck::Address);
+                return Err(IntroError::IncorrectAccountError.into());
+            }
+
+            let holder = load_intro(program_id, pda_holder).ok();
+            if existing.is_held(epoch, holder.as_ref()) {
+                msg!("Slug {} is taken", slug);
+                return Err(IntroError::SlugTaken.into());
+            }
+        }
+    }
+
+    let previous = if intro_data.slug.is_empty() {
+        None
+    } else {
+        let pda_previous = next_account_info(account_info_iter)?;
+        ensure_writable(&[(pda_previous, "previous_pda")])?;
+
+        let (previous_pda, _previous_bump) =
+            Pubkey::find_program_address(&[b"slug", intro_data.slug.as_bytes()], program_id);
+
+        if previous_pda != *pda_previous.key {
+            log_account_check("previous_pda", pda_previous.key, AccountCheck::Pda);
+
+            return Err(IntroError::InvalidPDA.into());
+        }
+
+        if pda_previous.owner != program_id {
+            log_account_check("previous_pda", pda_previous.key, AccountCheck::Owner);
+
+            return Err(IntroError::InvalidPDA.into());
+        }
+
+        let mut previous_data = try_from_slice_unchecked::<IntroSlug>(&pda_previous.data.borrow())
+            .map_err(|_| IntroError::UninitializedAccount)?;
+
+        if !previous_data.is_initialized() || previous_data.intro != *pda_intro.key {
+            msg!("Slug {} belongs to another intro", intro_data.slug);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        previous_data.redirect_to = Some(slug_pda);
+        previous_data.redirect_until_epoch = epoch + IntroSlug::REDIRECT_EPOCHS;
+        previous_data.serialize(&mut &mut pda_previous.data.borrow_mut()[..])?;
+
+        Some(std::mem::take(&mut intro_data.slug))
+    };
+
+    let slug_data = IntroSlug {
+        discriminator: IntroSlug::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: *pda_intro.key,
+        slug: slug.clone(),
+        redirect_to: None,
+        redirect_until_epoch: 0,
+    };
+    slug_data.serialize(&mut &mut pda_slug.data.borrow_mut()[..])?;
+
+    intro_data.slug = slug.clone();
+    intro_data.serialize(&mut &mut pda_intro.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::SlugSet {
+        intro: *pda_intro.key,
+        slug,
+        previous,
+    });
+
+    Ok(())
+}
+
+pub fn add_translation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lang: [u8; 2],
+    message: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_translation = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(writer, "writer"), (pda_translation, "translation_pda")])?;
+
+    let intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *writer.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if lang == lang::UNSPECIFIED {
+        msg!("A translation needs a language");
+        return Err(IntroError::InvalidLanguage.into());
+    }
+    verify_lang(&lang)?;
+
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), lang.as_ref()], program_id);
+
+    if pda != *pda_translation.key {
+        log_account_check("translation_pda", pda_translation.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if message.is_empty() {
+        if pda_translation.owner == program_id {
+            close_account(pda_translation, writer)?;
+            msg!("Translation removed: {}", pda_translation.key);
+        }
+        return Ok(());
+    }
+
+    if IntroTranslation::get_account_size(&message) > IntroTranslation::ACCOUNT_LEN {
+        msg!("Data length is larger than 1000 bytes");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    if pda_translation.owner != program_id {
+        create_pda_account(
+            program_id,
+            writer,
+            pda_translation,
+            system_program,
+            IntroTranslation::ACCOUNT_LEN,
+            &[pda_intro.key.as_ref(), lang.as_ref(), &[bump_seed]],
+        )?;
+    }
+
+    let translation_data = IntroTranslation {
+        discriminator: IntroTranslation::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: *pda_intro.key,
+        lang,
+        message,
+    };
+    translation_data.serialize(&mut &mut pda_translation.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn set_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params: ProfileParams,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_profile = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(writer, "writer"), (pda_profile, "profile_pda")])?;
+
+    let intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *writer.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let profile_data = IntroProfile {
+        discriminator: IntroProfile::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: *pda_intro.key,
+        pronouns: params.pronouns,
+        timezone: params.timezone,
+        country: params.country,
+        public_fields: params.public_fields,
+    };
+
+    if !profile_data.is_valid() {
+        msg!("Invalid profile");
+        return Err(IntroError::InvalidProfile.into());
+    }
+
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), b"profile"], program_id);
+
+    if pda != *pda_profile.key {
+        log_account_check("profile_pda", pda_profile.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_profile.owner != program_id {
+        create_pda_account(
+            program_id,
+            writer,
+            pda_profile,
+            system_program,
+            IntroProfile::SIZE,
+            &[pda_intro.key.as_ref(), b"profile", &[bump_seed]],
+        )?;
+    }
+
+    profile_data.serialize(&mut &mut pda_profile.data.borrow_mut()[..])?;
+
+    let redacted = profile_data.redacted();
+    events::emit(ProgramEvent::ProfileUpdated {
+        intro: redacted.intro,
+        pronouns: redacted.pronouns,
+        timezone: redacted.timezone,
+        country: redacted.country,
+    });
+
+    Ok(())
+}
+
+/// Claims `["email", hash]` for the signer. One wallet per hash across the program; the
+/// wallet that already holds it may call again without effect.
+pub fn register_email_hash(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wallet = next_account_info(account_info_iter)?;
+    let pda_entry = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !wallet.is_signer {
+        log_account_check("wallet", wallet.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(wallet, "wallet"), (pda_entry, "entry_pda")])?;
+
+    let (pda, bump_seed) = Pubkey::find_program_address(&[b"email", &hash], program_id);
+
+    if pda != *pda_entry.key {
+        log_account_check("entry_pda", pda_entry.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_entry.owner == program_id {
+        let entry = load_email_hash(program_id, pda_entry)?;
+        if entry.wallet != *wallet.key {
+            msg!("Email hash already registered to {}", entry.wallet);
+            return Err(IntroError::EmailHashTaken.into());
+        }
+        return Ok(());
+    }
+
+    create_pda_account(
+        program_id,
+        wallet,
+        pda_entry,
+        system_program,
+        EmailHashEntry::SIZE,
+        &[b"email", &hash, &[bump_seed]],
+    )?;
+
+    let entry = EmailHashEntry {
+        discriminator: EmailHashEntry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        wallet: *wallet.key,
+        registered_at: Clock::get()?.unix_timestamp,
+    };
+    entry.serialize(&mut &mut pda_entry.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Admin-only: closes an email hash entry, refunding its rent to the wallet that claimed it, so
+/// the hash can be registered again.
+pub fn release_email_hash(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_entry = next_account_info(account_info_iter)?;
+    let wallet = next_account_info(account_info_iter)?;
+
+    authorize_admin(program_id, config_data, admin)?;
+
+    ensure_writable(&[(pda_entry, "entry_pda"), (wallet, "wallet")])?;
+
+    let entry = load_email_hash(program_id, pda_entry)?;
+
+    if entry.wallet != *wallet.key {
+        msg!("Rent goes back to the registered wallet {}", entry.wallet);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    close_account(pda_entry, wallet)?;
+    msg!("Email hash released: {}", pda_entry.key);
+
+    events::emit(ProgramEvent::EmailHashReleased {
+        entry: *pda_entry.key,
+        wallet: entry.wallet,
+        admin: *admin.key,
+    });
+
+    Ok(())
+}
+
+fn load_email_hash(
+    program_id: &Pubkey,
+    pda_entry: &AccountInfo,
+) -> Result<EmailHashEntry, ProgramError> {
+    if pda_entry.owner != program_id {
+        log_account_check("entry_pda", pda_entry.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let entry = try_from_slice_unchecked::<EmailHashEntry>(&pda_entry.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !entry.is_initialized() || entry.discriminator != EmailHashEntry::DISCRIMINATOR {
+        log_account_check("entry_pda", pda_entry.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    Ok(entry)
+}
+
+pub fn create_category(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    name: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let registry_pda = next_account_info(account_info_iter)?;
+    let category_pda = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    authorize_admin(program_id, config_data, admin)?;
+
+    ensure_writable(&[
+        (admin, "admin"),
+        (registry_pda, "registry_pda"),
+        (category_pda, "category_pda"),
+    ])?;
+
+    if name.is_empty() || name.len() > Category::MAX_NAME_LEN {
+        msg!("Category name must be 1-{} bytes", Category::MAX_NAME_LEN);
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    let (registry_key, registry_bump) = Pubkey::find_program_address(&[b"categories"], program_id);
+
+    if registry_key != *registry_pda.key {
+        log_account_check("registry_pda", registry_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if registry_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            admin,
+            registry_pda,
+            system_program,
+            CategoryRegistry::SIZE,
+            &[b"categories", &[registry_bump]],
+        )?;
+    }
+
+    let mut registry_data =
+        try_from_slice_unchecked::<CategoryRegistry>(&registry_pda.data.borrow()).unwrap();
+
+    if !registry_data.is_initialized() {
+        registry_data.discriminator = CategoryRegistry::DISCRIMINATOR.to_string();
+        registry_data.is_initialized = true;
+    }
+
+    let index = registry_data.count;
+    let (category_key, category_bump) =
+        Pubkey::find_program_address(&[b"category", &index.to_be_bytes()], program_id);
+
+    if category_key != *category_pda.key {
+        log_account_check("category_pda", category_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        admin,
+        category_pda,
+        system_program,
+        Category::SIZE,
+        &[b"category", &index.to_be_bytes(), &[category_bump]],
+    )?;
+
+    let category_data = Category {
+        discriminator: Category::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        index,
+        name,
+        intro_count: 0,
+    };
+    category_data.serialize(&mut &mut category_pda.data.borrow_mut()[..])?;
+
+    registry_data.count += 1;
+    registry_data.serialize(&mut &mut registry_pda.data.borrow_mut()[..])?;
+    msg!("Category {} created: {}", index, category_pda.key);
+
+    Ok(())
+}
+
+/// Moves the signer's intro into the category passed third, keeping every category's
+/// `intro_count` in step. The intro's current category, if any, follows as the last account.
+pub fn set_category(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let new_category = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[(pda_intro, "intro_pda")])?;
+
+    let mut intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *writer.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let category = if new_category.key == program_id {
+        None
+    } else {
+        Some(*new_category.key)
+    };
+
+    if intro_data.category == category {
+        return Ok(());
+    }
+
+    if StudentIntroState::get_account_size(&intro_data.name, &intro_data.message)
+        > StudentIntroState::ACCOUNT_LEN
+    {
+        msg!("Intro is too long to hold a category");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    if let Some(current) = intro_data.category {
+        let current_category = next_account_info(account_info_iter)?;
+        if *current_category.key != current {
+            msg!("Current category does not match the intro");
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+        update_category_count(program_id, current_category, false)?;
+    }
+
+    if category.is_some() {
+        update_category_count(program_id, new_category, true)?;
+    }
+
+    intro_data.category = category;
+    intro_data.serialize(&mut &mut pda_intro.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Adds the intro to, or removes it from, `category_pda`'s count.
+fn update_category_count(
+    program_id: &Pubkey,
+    category_pda: &AccountInfo,
+    add: bool,
+) -> ProgramResult {
+    ensure_writable(&[(category_pda, "category_pda")])?;
+
+    if category_pda.owner != program_id {
+        log_account_check("category_pda", category_pda.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut category_data = try_from_slice_unchecked::<Category>(&category_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !category_data.is_initialized() || category_data.discriminator != Category::DISCRIMINATOR {
+        log_account_check("category_pda", category_pda.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    category_data.intro_count = if add {
+        category_data.intro_count + 1
+    } else {
+        category_data.intro_count.saturating_sub(1)
+    };
+    category_data.serialize(&mut &mut category_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Permissionless: once an intro has expired anyone may close it, refunding rent to the writer.
+/// An intro with a slug also takes its current slug entry along, freeing the slug, and one with
+/// a category needs that category next so it stops counting the intro.
+pub fn close_expired_intro(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+
+    ensure_writable(&[
+        (writer, "writer"),
+        (pda_intro, "intro_pda"),
+        (pda_counter, "counter_pda"),
+    ])?;
+
+    let intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *writer.key {
+        msg!("Rent must be returned to the intro writer");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    match intro_data.expires_at {
+        Some(expires_at) if Clock::get()?.unix_timestamp >= expires_at => {}
+        _ => {
+            msg!("Intro has not expired yet");
+            return Err(IntroError::IntroNotExpired.into());
+        }
+    }
+
+    let (pda_count, _counter_bump_seed) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), "counter".as_ref()], program_id);
+
+    if pda_count != *pda_counter.key {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Pda);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_counter.owner != program_id {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Owner);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if !intro_data.slug.is_empty() {
+        let pda_slug = next_account_info(account_info_iter)?;
+        ensure_writable(&[(pda_slug, "slug_pda")])?;
+
+        let (slug_pda, _slug_bump) =
+            Pubkey::find_program_address(&[b"slug", intro_data.slug.as_bytes()], program_id);
+
+        if slug_pda != *pda_slug.key {
+            log_account_check("slug_pda", pda_slug.key, AccountCheck::Pda);
+
+            return Err(IntroError::InvalidPDA.into());
+        }
+
+        if pda_slug.owner != program_id {
+            log_account_check("slug_pda", pda_slug.key, AccountCheck::Owner);
+
+            return Err(IntroError::InvalidPDA.into());
+        }
+
+        close_account(pda_slug, writer)?;
+    }
+
+    if let Some(category) = intro_data.category {
+        let category_pda = next_account_info(account_info_iter)?;
+        if *category_pda.key != category {
+            msg!("Category does not match the intro");
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+        update_category_count(program_id, category_pda, false)?;
+    }
+
+    for account in [pda_intro, pda_counter] {
+        close_account(account, writer)?;
+    }
+    msg!("Expired Intro Closed: {}", pda_intro.key);
+
+    Ok(())
+}
+
+/// Permissionless crank: closes the reply counter of an intro that no longer exists, was
+/// closed, or was never initialized. Rent goes to the config's `orphan_rent_destination`, or
+/// the admin while none is set, so the cranker gains nothing by picking targets.
+pub fn gc_orphans(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+
+    ensure_writable(&[(pda_counter, "counter_pda"), (destination, "destination")])?;
+
+    if *destination.key
+        != config_data
+            .orphan_rent_destination
+            .unwrap_or(config_data.admin)
+    {
+        msg!("Rent must go to the configured destination");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let (pda_count, _counter_bump_seed) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), "counter".as_ref()], program_id);
+
+    if pda_count != *pda_counter.key {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_counter.owner != program_id {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let counter_data = try_from_slice_unchecked::<ReplyCount>(&pda_counter.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if counter_data.discriminator == CLOSED_ACCOUNT_DISCRIMINATOR {
+        msg!("Reply counter has been closed");
+        return Err(IntroError::AccountClosed.into());
+    }
+
+    // Only this program can own the intro PDA, so anything else means it was never created.
+    if pda_intro.owner == program_id {
+        match load_intro(program_id, pda_intro) {
+            Ok(_) => {
+                msg!("Intro is still open");
+                return Err(IntroError::NotOrphaned.into());
+            }
+            Err(err)
+                if err == IntroError::AccountClosed.into()
+                    || err == IntroError::UninitializedAccount.into() => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    let lamports = pda_counter.lamports();
+    close_account(pda_counter, destination)?;
+    msg!("Orphaned reply counter closed: {}", pda_counter.key);
+
+    events::emit(ProgramEvent::OrphanCollected {
+        account: *pda_counter.key,
+        intro: *pda_intro.key,
+        destination: *destination.key,
+        lamports,
+    });
+
+    Ok(())
+}
+
+/// Drains `account` into `destination`, zeroes its data and writes the closed tombstone, so a
+/// same-transaction top-up cannot revive it as a loadable account.
+fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? -= lamports;
+    **destination.try_borrow_mut_lamports()? += lamports;
+
+    let mut data = account.data.borrow_mut();
+    data.fill(0);
+    CLOSED_ACCOUNT_DISCRIMINATOR.serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+/// Picks this epoch's featured intro from the registry in two calls. The first commits the
+/// draw to a slot `FeaturedDraw::DELAY_SLOTS` ahead; once that slot's SlotHashes entry exists,
+/// the second draws from it, so anyone can recompute the draw and no cranker can choose it by
+/// timing the call. Draws landing on private or spam-scored intros move on to the next
+/// candidate index, up to `FeaturedDraw::MAX_CANDIDATES`. A draw not made while its slot is
+/// still in SlotHashes is committed again.
+#[cfg_attr(feature = "no-rewards", allow(unused_variables))]
+pub fn select_featured(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let cranker = next_account_info(account_info_iter)?;
+    let featured_pda = next_account_info(account_info_iter)?;
+    let registry_pda = next_account_info(account_info_iter)?;
+    let draw_pda = next_account_info(account_info_iter)?;
+    let slot_hashes = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !cranker.is_signer {
+        log_account_check("cranker", cranker.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (cranker, "cranker"),
+        (featured_pda, "featured_pda"),
+        (draw_pda, "draw_pda"),
+    ])?;
+
+    if *slot_hashes.key != SLOT_HASHES_ID {
+        log_account_check("slot_hashes", slot_hashes.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let (featured_key, featured_bump) = Pubkey::find_program_address(&[b"featured"], program_id);
+
+    if featured_key != *featured_pda.key {
+        log_account_check("featured_pda", featured_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let (draw_key, draw_bump) = Pubkey::find_program_address(&[b"featured_draw"], program_id);
+
+    if draw_key != *draw_pda.key {
+        log_account_check("draw_pda", draw_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let (registry_key, _registry_bump) = Pubkey::find_program_address(&[b"registry"], program_id);
+
+    if registry_key != *registry_pda.key {
+        log_account_check("registry_pda", registry_pda.key, AccountCheck::Pda);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if registry_pda.owner != program_id {
+        log_account_check("registry_pda", registry_pda.key, AccountCheck::Owner);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let registry_data =
+        try_from_slice_unchecked::<IntroRegistry>(&registry_pda.data.borrow()).unwrap();
+
+    if registry_data.count == 0 {
+        msg!("Registry is empty");
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    let clock = Clock::get()?;
+
+    if featured_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            cranker,
+            featured_pda,
+            system_program,
+            Featured::SIZE,
+            &[b"featured", &[featured_bump]],
+        )?;
+    }
+
+    let mut featured_data =
+        try_from_slice_unchecked::<Featured>(&featured_pda.data.borrow()).unwrap();
+
+    if featured_data.is_initialized() && featured_data.epoch == clock.epoch {
+        msg!("Featured intro already selected for epoch {}", clock.epoch);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    if draw_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            cranker,
+            draw_pda,
+            system_program,
+            FeaturedDraw::SIZE,
+            &[b"featured_draw", &[draw_bump]],
+        )?;
+    }
+
+    let draw_data = try_from_slice_unchecked::<FeaturedDraw>(&draw_pda.data.borrow()).unwrap();
+
+    let draw_hash = if draw_data.is_initialized() && draw_data.epoch == clock.epoch {
+        find_draw_hash(slot_hashes, draw_data.draw_slot)?
+    } else {
+        None
+    };
+
+    let draw_hash = match draw_hash {
+        Some(draw_hash) => draw_hash,
+        None => {
+            let draw_data = FeaturedDraw {
+                discriminator: FeaturedDraw::DISCRIMINATOR.to_string(),
+                is_initialized: true,
+                epoch: clock.epoch,
+                draw_slot: clock.slot + FeaturedDraw::DELAY_SLOTS,
+                registry_count: registry_data.count,
+            };
+            draw_data.serialize(&mut &mut draw_pda.data.borrow_mut()[..])?;
+            msg!(
+                "Featured draw for epoch {} committed to slot {}",
+                clock.epoch,
+                draw_data.draw_slot
+            );
+            return Ok(());
+        }
+    };
+
+    // Each candidate is a (registry entry, intro) pair for the next drawn index.
+    let mut candidate = 0;
+    let (pda_intro, intro_data) = loop {
+        if candidate == FeaturedDraw::MAX_CANDIDATES {
+            msg!("No eligible intro in {} draws", candidate);
+            return Err(IntroError::NoEligibleIntro.into());
+        }
+
+        let registry_entry_pda = next_account_info(account_info_iter)?;
+        let pda_intro = next_account_info(account_info_iter)?;
+
+        let seed = hashv(&[
+            &draw_hash,
+            &clock.epoch.to_le_bytes(),
+            &candidate.to_le_bytes(),
+        ])
+        .to_bytes();
+        let index = u64::from_le_bytes(seed[..8].try_into().unwrap()) % draw_data.registry_count;
+
+        let (entry_key, _entry_bump) =
+            Pubkey::find_program_address(&[b"registry", &index.to_be_bytes()], program_id);
+
+        if entry_key != *registry_entry_pda.key || registry_entry_pda.owner != program_id {
+            msg!("Registry entry does not match the drawn index {}", index);
+            return Err(IntroError::InvalidPDA.into());
+        }
+
+        let entry_data =
+            try_from_slice_unchecked::<RegistryEntry>(&registry_entry_pda.data.borrow()).unwrap();
+
+        if entry_data.intro != *pda_intro.key {
+            msg!("Intro does not match the registry entry");
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        // Closed intros no longer load and are passed over like ineligible ones.
+        let eligible = load_intro(program_id, pda_intro).ok().filter(|intro_data| {
+            intro_data.visibility != StudentIntroState::VISIBILITY_PRIVATE
+                && !(config.spam_classifier.is_some()
+                    && intro_data.spam_score > config.spam_threshold)
+        });
+
+        match eligible {
+            Some(intro_data) => break (pda_intro, intro_data),
+            None => {
+                msg!("Passing over {}", pda_intro.key);
+                candidate += 1;
+            }
+        }
+    };
+
+    #[cfg(not(feature = "no-rewards"))]
+    let (token_mint, mint_auth, writer_ata, token_program) = (
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+        next_account_info(account_info_iter)?,
+    );
+
+    #[cfg(not(feature = "no-rewards"))]
+    let mint_auth_bump = {
+        let (mint_pda, _mint_bump) = find_reward_mint(program_id, config.mint_version);
+        let (mint_auth_pda, mint_auth_bump) =
+            Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+        if *token_mint.key != mint_pda {
+            log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if *mint_auth.key != mint_auth_pda {
+            log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if *writer_ata.key != get_associated_token_address(&intro_data.writer, token_mint.key) {
+            log_account_check("writer_ata", writer_ata.key, AccountCheck::Address);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if *token_program.key != TOKEN_PROGRAM_ID {
+            log_account_check("token_program", token_program.key, AccountCheck::Address);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        mint_auth_bump
+    };
+
+    featured_data.discriminator = Featured::DISCRIMINATOR.to_string();
+    featured_data.epoch = clock.epoch;
+    featured_data.intro = *pda_intro.key;
+    featured_data.writer = intro_data.writer;
+    featured_data.selected_slot = clock.slot;
+    featured_data.is_initialized = true;
+    featured_data.serialize(&mut &mut featured_pda.data.borrow_mut()[..])?;
+    msg!(
+        "Featured Intro for epoch {}: {}",
+        clock.epoch,
+        pda_intro.key
+    );
+
+    #[cfg(not(feature = "no-rewards"))]
+    if rewards_held(program_id, config, &intro_data.writer, account_info_iter)? {
+        msg!("Rewards are held for review");
+    } else {
+        msg!("Minting 20 bonus tokens to featured writer");
+        mint_bonus_reward(
+            program_id,
+            config,
+            account_info_iter,
+            cranker,
+            &intro_data.writer,
+            writer_ata,
+            token_mint,
+            mint_auth,
+            mint_auth_bump,
+            system_program,
+            token_program,
+            20 * LAMPORTS_PER_SOL,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The hash of the first slot at or after `draw_slot` in SlotHashes, whose data is a u64
+/// length prefix followed by (slot, hash) pairs, newest first. `None` once the entries no
+/// longer reach back to `draw_slot`, as that hash may have been evicted; fails while no slot
+/// at or after it has a hash yet.
+fn find_draw_hash(
+    slot_hashes: &AccountInfo,
+    draw_slot: u64,
+) -> Result<Option<[u8; 32]>, ProgramError> {
+    let data = slot_hashes.data.borrow();
+    let entries = data.get(8..).ok_or(ProgramError::InvalidAccountData)?;
+
+    let mut draw_hash = None;
+    let mut reaches_back = false;
+    for entry in entries.chunks_exact(40) {
+        let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        reaches_back |= slot <= draw_slot;
+        if slot < draw_slot {
+            break;
+        }
+        draw_hash = Some(entry[8..].try_into().unwrap());
+    }
+
+    match (draw_hash, reaches_back) {
+        (Some(draw_hash), true) => Ok(Some(draw_hash)),
+        (Some(_), false) => {
+            msg!("Featured draw slot {} has left SlotHashes", draw_slot);
+            Ok(None)
+        }
+        (None, _) => {
+            msg!("Featured draw waits for slot {}", draw_slot);
+            Err(IntroError::FeaturedDrawPending.into())
+        }
+    }
+}
+
+/// Posts a Wormhole message carrying the intro's content hash so a foreign chain can mirror it.
+pub fn emit_cross_chain_intro(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+    nonce: u32,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let writer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let bridge = next_account_info(account_info_iter)?;
+    let message = next_account_info(account_info_iter)?;
+    let emitter = next_account_info(account_info_iter)?;
+    let sequence = next_account_info(account_info_iter)?;
+    let fee_collector = next_account_info(account_info_iter)?;
+    let clock_sysvar = next_account_info(account_info_iter)?;
+    let rent_sysvar = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let wormhole_program = next_account_info(account_info_iter)?;
+
+    if !writer.is_signer {
+        log_account_check("writer", writer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !message.is_signer {
+        log_account_check("message", message.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let wormhole_id = config
+        .wormhole_program
+        .ok_or(IntroError::InvalidWormholeAccount)?;
+
+    if *wormhole_program.key != wormhole_id {
+        log_account_check(
+            "wormhole_program",
+            wormhole_program.key,
+            AccountCheck::Address,
+        );
+        return Err(IntroError::InvalidWormholeAccount.into());
+    }
+
+    if *bridge.key != wormhole::bridge_address(&wormhole_id) {
+        log_account_check("bridge", bridge.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidWormholeAccount.into());
+    }
+
+    if *fee_collector.key != wormhole::fee_collector_address(&wormhole_id) {
+        log_account_check("fee_collector", fee_collector.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidWormholeAccount.into());
+    }
+
+    let (emitter_pda, emitter_bump) = Pubkey::find_program_address(&[b"emitter"], program_id);
+
+    if emitter_pda != *emitter.key {
+        log_account_check("emitter", emitter.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *sequence.key != wormhole::sequence_address(&wormhole_id, &emitter_pda) {
+        log_account_check("sequence", sequence.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let intro_data = load_intro(program_id, pda_intro)?;
+
+    if intro_data.writer != *writer.key {
+        msg!("Signer is not the intro writer");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let fee = wormhole::read_bridge_fee(&bridge.data.borrow())?;
+    if fee > 0 {
+        invoke(
+            &system_instruction::transfer(writer.key, fee_collector.key, fee),
+            &[
+                writer.clone(),
+                fee_collector.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    let payload = CrossChainIntroPayload {
+        intro: *pda_intro.key,
+        writer: intro_data.writer,
+        content_hash: hashv(&[intro_data.name.as_bytes(), intro_data.message.as_bytes()])
+            .to_bytes(),
+    };
+    let mut payload_bytes = vec![wormhole::PAYLOAD_VERSION];
+    payload_bytes.extend(payload.try_to_vec()?);
+
+    invoke_signed(
+        &wormhole::post_message(
+            &wormhole_id,
+            bridge.key,
+            message.key,
+            emitter.key,
+            sequence.key,
+            writer.key,
+            fee_collector.key,
+            nonce,
+            payload_bytes,
+        ),
+        &[
+            bridge.clone(),
+            message.clone(),
+            emitter.clone(),
+            sequence.clone(),
+            writer.clone(),
+            fee_collector.clone(),
+            clock_sysvar.clone(),
+            rent_sysvar.clone(),
+            system_program.clone(),
+            wormhole_program.clone(),
+        ],
+        &[&[b"emitter", &[emitter_bump]]],
+    )?;
+    msg!("Cross-chain intro posted: {}", pda_intro.key);
+
+    Ok(())
+}
+
+/// Materializes a reply from a VAA that the core bridge has already verified and posted.
+pub fn receive_cross_chain_reply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_reply = next_account_info(account_info_iter)?;
+    let posted_vaa = next_account_info(account_info_iter)?;
+    let pda_received = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        log_account_check("payer", payer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (payer, "payer"),
+        (pda_counter, "counter_pda"),
+        (pda_reply, "reply_pda"),
+        (pda_received, "received_pda"),
+    ])?;
+
+    let wormhole_id = config
+        .wormhole_program
+        .ok_or(IntroError::InvalidWormholeAccount)?;
+
+    if *posted_vaa.owner != wormhole_id {
+        msg!("VAA account is not owned by the core bridge");
+        return Err(IntroError::InvalidWormholeAccount.into());
+    }
+
+    let vaa = wormhole::parse_posted_vaa(&posted_vaa.data.borrow())?;
+
+    if vaa.emitter_chain != config.foreign_emitter_chain
+        || vaa.emitter_address != config.foreign_emitter_address
+    {
+        msg!("VAA was not emitted by the registered foreign emitter");
+        return Err(IntroError::UnknownEmitter.into());
+    }
+
+    let (payload_version, payload_bytes) = vaa
+        .payload
+        .split_first()
+        .ok_or(IntroError::InvalidWormholeAccount)?;
+
+    if *payload_version != wormhole::PAYLOAD_VERSION {
+        msg!("Unsupported payload version {}", payload_version);
+        return Err(IntroError::InvalidWormholeAccount.into());
+    }
+
+    let payload = CrossChainReplyPayload::try_from_slice(payload_bytes)
+        .map_err(|_| IntroError::InvalidWormholeAccount)?;
+
+    if payload.intro != *pda_intro.key {
+        msg!("VAA targets another intro");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if payload.name.len() > CrossChainReplyPayload::MAX_NAME_LEN
+        || payload.message.len() > CrossChainReplyPayload::MAX_MESSAGE_LEN
+    {
+        msg!(
+            "Relayed replies take names up to {} bytes and messages up to {}",
+            CrossChainReplyPayload::MAX_NAME_LEN,
+            CrossChainReplyPayload::MAX_MESSAGE_LEN
+        );
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    let sender = Pubkey::new_from_array(payload.sender);
+    let intro_data = load_intro(program_id, pda_intro)?;
+    ensure_not_expired(&intro_data)?;
+    ensure_question_open(&intro_data)?;
+    verify_reply_audience(
+        program_id,
+        account_info_iter,
+        &intro_data,
+        &sender,
+        payer,
+        system_program,
+        &payload.name,
+        &payload.message,
+    )?;
+
+    let (received_pda, received_bump) = Pubkey::find_program_address(
+        &[
+            b"received",
+            &vaa.emitter_chain.to_be_bytes(),
+            &vaa.sequence.to_be_bytes(),
+        ],
+        program_id,
+    );
+
+    if received_pda != *pda_received.key {
+        log_account_check("received_pda", pda_received.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    // Creating the receipt fails if this sequence was already consumed.
+    create_pda_account(
+        program_id,
+        payer,
+        pda_received,
+        system_program,
+        ReceivedMessage::SIZE,
+        &[
+            b"received",
+            &vaa.emitter_chain.to_be_bytes(),
+            &vaa.sequence.to_be_bytes(),
+            &[received_bump],
+        ],
+    )?;
+
+    let received_data = ReceivedMessage {
+        discriminator: ReceivedMessage::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        emitter_chain: vaa.emitter_chain,
+        sequence: vaa.sequence,
+        reply: *pda_reply.key,
+    };
+    received_data.serialize(&mut &mut pda_received.data.borrow_mut()[..])?;
+
+    create_reply(
+        program_id,
+        payer,
+        pda_intro,
+        pda_counter,
+        pda_reply,
+        system_program,
+        sender,
+        payload.name,
+        payload.message,
+        false,
+        None,
+        lang::UNSPECIFIED,
+        format::PLAIN,
+    )?;
+
+    Ok(())
+}
+
+/// Appends an intro to the global registry and its page of the intro feed, creating the
+/// registry and the page on first use, and marks the intro as indexed.
+fn append_to_registry<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    registry_pda: &AccountInfo<'a>,
+    registry_entry_pda: &AccountInfo<'a>,
+    feed_page_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    intro_pda: &AccountInfo<'a>,
+) -> ProgramResult {
+    let intro = intro_pda.key;
+
+    let (registry_key, registry_bump) = Pubkey::find_program_address(&[b"registry"], program_id);
+
+    if registry_key != *registry_pda.key {
+        log_account_check("registry_pda", registry_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if registry_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            registry_pda,
+            system_program,
+            IntroRegistry::SIZE,
+            &[b"registry", &[registry_bump]],
+        )?;
+    }
+
+    let mut registry_data =
+        try_from_slice_unchecked::<IntroRegistry>(&registry_pda.data.borrow()).unwrap();
+
+    if !registry_data.is_initialized() {
+        registry_data.discriminator = IntroRegistry::DISCRIMINATOR.to_string();
+        registry_data.is_initialized = true;
+    }
+
+    let index = registry_data.count;
+    let (entry_key, entry_bump) =
+        Pubkey::find_program_address(&[b"registry", &index.to_be_bytes()], program_id);
+
+    if entry_key != *registry_entry_pda.key {
+        log_account_check(
+            "registry_entry_pda",
+            registry_entry_pda.key,
+            AccountCheck::Pda,
+        );
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        payer,
+        registry_entry_pda,
+        system_program,
+        RegistryEntry::SIZE,
+        &[b"registry", &index.to_be_bytes(), &[entry_bump]],
+    )?;
+
+    let entry_data = RegistryEntry {
+        discriminator: RegistryEntry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        index,
+        intro: *intro,
+    };
+    entry_data.serialize(&mut &mut registry_entry_pda.data.borrow_mut()[..])?;
+
+    append_to_feed(
+        program_id,
+        payer,
+        feed_page_pda,
+        system_program,
+        index,
+        intro,
+    )?;
+
+    registry_data.count += 1;
+    registry_data.serialize(&mut &mut registry_pda.data.borrow_mut()[..])?;
+
+    let mut intro_data = try_from_slice_unchecked::<StudentIntroState>(&intro_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+    intro_data.indexed |= 1 << StudentIntroState::INDEX_REGISTRY;
+    store_intro(intro_pda, &intro_data)
+}
+
+/// Adds an intro created before the registry existed to the registry and the intro feed,
+/// paid by the signer. An intro registered before registration marked intros is passed with
+/// its own registry entry instead of the next one, and is only marked.
+pub fn index_existing(program_id: &Pubkey, accounts: &[AccountInfo], target: u8) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let intro_pda = next_account_info(account_info_iter)?;
+    let registry_pda = next_account_info(account_info_iter)?;
+    let registry_entry_pda = next_account_info(account_info_iter)?;
+    let feed_page_pda = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        log_account_check("payer", payer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if target != StudentIntroState::INDEX_REGISTRY {
+        msg!("Unknown index {}", target);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    ensure_writable(&[
+        (payer, "payer"),
+        (intro_pda, "intro_pda"),
+        (registry_pda, "registry_pda"),
+        (registry_entry_pda, "registry_entry_pda"),
+        (feed_page_pda, "feed_page_pda"),
+    ])?;
+
+    let mut intro_data = load_intro(program_id, intro_pda)?;
+
+    // Intros filled to capacity before the mark existed have no room for it.
+    if intro_data.try_to_vec()?.len() > intro_pda.data_len() {
+        msg!("Intro text leaves no room for an index mark");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    let bit = 1 << target;
+    if intro_data.indexed & bit != 0 {
+        msg!("Intro {} is already indexed", intro_pda.key);
+        return Err(IntroError::AlreadyIndexed.into());
+    }
+
+    if registry_entry_pda.owner == program_id {
+        let entry_data =
+            try_from_slice_unchecked::<RegistryEntry>(&registry_entry_pda.data.borrow())
+                .map_err(|_| IntroError::UninitializedAccount)?;
+        let (entry_key, _entry_bump) = Pubkey::find_program_address(
+            &[b"registry", &entry_data.index.to_be_bytes()],
+            program_id,
+        );
+
+        if entry_key != *registry_entry_pda.key || entry_data.intro != *intro_pda.key {
+            msg!("Registry entry does not list this intro");
+            return Err(IntroError::InvalidPDA.into());
+        }
+
+        msg!(
+            "Intro {} already registered at {}",
+            intro_pda.key,
+            entry_data.index
+        );
+        intro_data.indexed |= bit;
+        return store_intro(intro_pda, &intro_data);
+    }
+
+    append_to_registry(
+        program_id,
+        payer,
+        registry_pda,
+        registry_entry_pda,
+        feed_page_pda,
+        system_program,
+        intro_pda,
+    )
+}
+
+/// Writes registry index `index` into its slot of the intro feed.
+fn append_to_feed<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    feed_page_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    index: u64,
+    intro: &Pubkey,
+) -> ProgramResult {
+    let page = IntroFeedPage::page_of(index);
+    let (page_key, page_bump) =
+        Pubkey::find_program_address(&[b"intro_feed", &page.to_be_bytes()], program_id);
+
+    if page_key != *feed_page_pda.key {
+        log_account_check("feed_page_pda", feed_page_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if feed_page_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            feed_page_pda,
+            system_program,
+            IntroFeedPage::SIZE,
+            &[b"intro_feed", &page.to_be_bytes(), &[page_bump]],
+        )?;
+    }
+
+    let mut page_data =
+        try_from_slice_unchecked::<IntroFeedPage>(&feed_page_pda.data.borrow()).unwrap();
+
+    if !page_data.is_initialized() {
+        page_data.discriminator = IntroFeedPage::DISCRIMINATOR.to_string();
+        page_data.is_initialized = true;
+        page_data.page = page;
+    }
+
+    let position = (index % IntroFeedPage::CAPACITY as u64) as usize;
+    page_data.entries[position] = FeedEntry {
+        intro: *intro,
+        slot: Clock::get()?.slot,
+    };
+    page_data.len = page_data.len.max(position as u8 + 1);
+    page_data.serialize(&mut &mut feed_page_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Checks the account passed as the `["questions"]` feed: the PDA itself, either not created
+/// yet or holding a `QuestionFeed`. Returns its bump.
+fn verify_questions_feed(
+    program_id: &Pubkey,
+    questions_pda: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (questions_key, questions_bump) = Pubkey::find_program_address(&[b"questions"], program_id);
+
+    if questions_key != *questions_pda.key {
+        log_account_check("questions_pda", questions_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if questions_pda.owner == program_id {
+        let feed_data = try_from_slice_unchecked::<QuestionFeed>(&questions_pda.data.borrow())
+            .map_err(|_| IntroError::IncorrectAccountError)?;
+        if feed_data.discriminator != QuestionFeed::DISCRIMINATOR {
+            log_account_check(
+                "questions_pda",
+                questions_pda.key,
+                AccountCheck::Initialized,
+            );
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+    } else if *questions_pda.owner != SYSTEM_PROGRAM_ID {
+        log_account_check("questions_pda", questions_pda.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    Ok(questions_bump)
+}
+
+/// Adds a question intro to the `["questions"]` feed, creating the feed on first use.
+fn append_to_questions<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    questions_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    intro: &Pubkey,
+) -> ProgramResult {
+    let questions_bump = verify_questions_feed(program_id, questions_pda)?;
+
+    ensure_writable(&[(questions_pda, "questions_pda")])?;
+
+    if questions_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            questions_pda,
+            system_program,
+            QuestionFeed::SIZE,
+            &[b"questions", &[questions_bump]],
+        )?;
+    }
+
+    let mut feed_data =
+        try_from_slice_unchecked::<QuestionFeed>(&questions_pda.data.borrow()).unwrap();
+
+    if !feed_data.is_initialized() {
+        feed_data.discriminator = QuestionFeed::DISCRIMINATOR.to_string();
+        feed_data.is_initialized = true;
+    }
+
+    feed_data.record(FeedEntry {
+        intro: *intro,
+        slot: Clock::get()?.slot,
+    });
+    feed_data.serialize(&mut &mut questions_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Rejects new replies to a question whose best answer was selected more than
+/// `ANSWER_GRACE_SECS` ago.
+fn ensure_question_open(intro_data: &StudentIntroState) -> ProgramResult {
+    if intro_data.kind != StudentIntroState::KIND_QUESTION {
+        return Ok(());
+    }
+
+    let answered_at = intro_data.answered_at;
+    if answered_at != 0
+        && Clock::get()?.unix_timestamp >= answered_at + StudentIntroState::ANSWER_GRACE_SECS
+    {
+        msg!("Question was answered at {}", answered_at);
+        return Err(IntroError::QuestionClosed.into());
+    }
+
+    Ok(())
+}
+
+fn ensure_not_expired(intro_data: &StudentIntroState) -> ProgramResult {
+    if let Some(expires_at) = intro_data.expires_at {
+        if Clock::get()?.unix_timestamp >= expires_at {
+            msg!("Intro has expired");
+            return Err(IntroError::IntroExpired.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects read-only metas for accounts the handler is about to write, naming the account,
+/// instead of letting the write fail later inside a CPI.
+fn ensure_writable(accounts: &[(&AccountInfo, &str)]) -> ProgramResult {
+    for (account, role) in accounts {
+        if !account.is_writable {
+            log_account_check(role, account.key, AccountCheck::Writable);
+            return Err(IntroError::AccountNotWritable.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Reward mint `version` and its bump. Version 0 is the original `["token_mint"]` mint; later
+/// ones, created by `MigrateMint`, use `["token_mint", version]`.
+#[cfg(not(feature = "no-rewards"))]
+fn find_reward_mint(program_id: &Pubkey, version: u8) -> (Pubkey, u8) {
+    if version == 0 {
+        Pubkey::find_program_address(&[b"token_mint"], program_id)
+    } else {
+        Pubkey::find_program_address(&[b"token_mint", &[version]], program_id)
+    }
+}
+
+/// Reads an intro account after checking it is an initialized intro owned by this program.
+fn load_intro(
+    program_id: &Pubkey,
+    pda_intro: &AccountInfo,
+) -> Result<StudentIntroState, ProgramError> {
+    if pda_intro.owner != program_id {
+        log_account_check("intro_pda", pda_intro.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let intro_data = try_from_slice_unchecked::<StudentIntroState>(&pda_intro.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if intro_data.discriminator == CLOSED_ACCOUNT_DISCRIMINATOR {
+        msg!("Intro has been closed");
+        return Err(IntroError::AccountClosed.into());
+    }
+
+    if !intro_data.is_initialized() || intro_data.discriminator != StudentIntroState::DISCRIMINATOR
+    {
+        log_account_check("intro_pda", pda_intro.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    Ok(intro_data)
+}
+
+/// Writes an intro back, failing with `InvalidDataLength` when an intro filled to capacity
+/// before its later fields existed has no room for them.
+fn store_intro(pda_intro: &AccountInfo, intro_data: &StudentIntroState) -> ProgramResult {
+    let data = intro_data.try_to_vec()?;
+    let mut intro_bytes = pda_intro.data.borrow_mut();
+
+    if data.len() > intro_bytes.len() {
+        msg!("Intro text leaves no room for its fields");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    intro_bytes[..data.len()].copy_from_slice(&data);
+    Ok(())
+}
+
+fn load_course(program_id: &Pubkey, pda_course: &AccountInfo) -> Result<Course, ProgramError> {
+    if pda_course.owner != program_id {
+        log_account_check("course_pda", pda_course.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let course_data = try_from_slice_unchecked::<Course>(&pda_course.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !course_data.is_initialized() || course_data.discriminator != Course::DISCRIMINATOR {
+        log_account_check("course_pda", pda_course.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    Ok(course_data)
+}
+
+fn load_team(program_id: &Pubkey, pda_team: &AccountInfo) -> Result<TeamState, ProgramError> {
+    if pda_team.owner != program_id {
+        log_account_check("team_pda", pda_team.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let team_data = try_from_slice_unchecked::<TeamState>(&pda_team.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !team_data.is_initialized() || team_data.discriminator != TeamState::DISCRIMINATOR {
+        log_account_check("team_pda", pda_team.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    Ok(team_data)
+}
+
+/// Reads a quiz after checking it is an initialized quiz of `pda_course`.
+fn load_quiz(
+    program_id: &Pubkey,
+    pda_course: &AccountInfo,
+    pda_quiz: &AccountInfo,
+) -> Result<Quiz, ProgramError> {
+    if pda_quiz.owner != program_id {
+        log_account_check("quiz_pda", pda_quiz.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let quiz_data = try_from_slice_unchecked::<Quiz>(&pda_quiz.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !quiz_data.is_initialized() || quiz_data.discriminator != Quiz::DISCRIMINATOR {
+        log_account_check("quiz_pda", pda_quiz.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if quiz_data.course != *pda_course.key {
+        msg!("Quiz does not belong to the course");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    Ok(quiz_data)
+}
+
+/// Reads `pda_course`'s office-hours queue, which exists once a student has joined it.
+fn load_queue(
+    program_id: &Pubkey,
+    pda_course: &AccountInfo,
+    pda_queue: &AccountInfo,
+) -> Result<OfficeHoursQueue, ProgramError> {
+    let (pda, _bump_seed) =
+        Pubkey::find_program_address(&[pda_course.key.as_ref(), b"queue"], program_id);
+
+    if pda != *pda_queue.key {
+        log_account_check("queue_pda", pda_queue.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_queue.owner != program_id {
+        log_account_check("queue_pda", pda_queue.key, AccountCheck::Owner);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    let queue_data = try_from_slice_unchecked::<OfficeHoursQueue>(&pda_queue.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !queue_data.is_initialized() || queue_data.discriminator != OfficeHoursQueue::DISCRIMINATOR {
+        log_account_check("queue_pda", pda_queue.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    Ok(queue_data)
+}
+
+/// For a course that requires student IDs, claims `student_id_hash` in it for `student`. The
+/// course account follows in `iter`, then the `["sid", course, hash]` entry; a
+/// student re-registering their own hash is a no-op. Returns the course.
+fn register_student_id<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    program_id: &Pubkey,
+    student: &AccountInfo<'b>,
+    course: &Pubkey,
+    system_program: &AccountInfo<'b>,
+    iter: &mut I,
+    student_id_hash: Option<[u8; 32]>,
+) -> Result<Course, ProgramError> {
+    let pda_course = next_account_info(iter)?;
+
+    if *pda_course.key != *course {
+        msg!("Course does not match the enrollment");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let course_data = load_course(program_id, pda_course)?;
+    if !course_data.requires_student_id {
+        return Ok(course_data);
+    }
+
+    let hash = student_id_hash.ok_or(IntroError::StudentIdRequired)?;
+    let pda_student_id = next_account_info(iter)?;
+
+    ensure_writable(&[(student, "student"), (pda_student_id, "student_id_pda")])?;
+
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[b"sid", course.as_ref(), &hash], program_id);
+
+    if pda != *pda_student_id.key {
+        log_account_check("student_id_pda", pda_student_id.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_student_id.owner == program_id {
+        let entry = try_from_slice_unchecked::<StudentIdEntry>(&pda_student_id.data.borrow())
+            .map_err(|_| IntroError::UninitializedAccount)?;
+        if entry.student != *student.key {
+            msg!("Student ID already registered to {}", entry.student);
+            return Err(IntroError::StudentIdTaken.into());
+        }
+        return Ok(course_data);
+    }
+
+    create_pda_account(
+        program_id,
+        student,
+        pda_student_id,
+        system_program,
+        StudentIdEntry::SIZE,
+        &[b"sid", course.as_ref(), &hash, &[bump_seed]],
+    )?;
+
+    let entry = StudentIdEntry {
+        discriminator: StudentIdEntry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: *course,
+        student: *student.key,
+    };
+    entry.serialize(&mut &mut pda_student_id.data.borrow_mut()[..])?;
+
+    Ok(course_data)
+}
+
+fn verify_enrollment(
+    program_id: &Pubkey,
+    pda_enrollment: &AccountInfo,
+    course: &Pubkey,
+    student: &Pubkey,
+) -> ProgramResult {
+    let (pda, _bump_seed) = Pubkey::find_program_address(
+        &[course.as_ref(), student.as_ref(), "enrollment".as_ref()],
+        program_id,
+    );
+
+    if pda != *pda_enrollment.key || pda_enrollment.owner != program_id {
+        msg!("Student is not enrolled in the course");
+        return Err(IntroError::NotEnrolled.into());
+    }
+
+    let enrollment_data = try_from_slice_unchecked::<Enrollment>(&pda_enrollment.data.borrow())
+        .map_err(|_| IntroError::NotEnrolled)?;
+
+    if !enrollment_data.is_initialized() {
+        msg!("Student is not enrolled in the course");
+        return Err(IntroError::NotEnrolled.into());
+    }
+
+    Ok(())
+}
+
+enum CourseActivity {
+    Intro,
+    /// The intro was already scoped to this course; only the student's activity counts.
+    Rescoped,
+    Reply,
+}
+
+/// Updates the `[course, "stats"]` aggregates and counts `pda_enrollment`'s student as active
+/// once per epoch via the `[enrollment, "activity"]` PDA. Both are created on first use.
+#[allow(clippy::too_many_arguments)]
+fn record_course_activity<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    course: &Pubkey,
+    pda_enrollment: &AccountInfo<'a>,
+    stats_pda: &AccountInfo<'a>,
+    activity_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    activity: CourseActivity,
+) -> ProgramResult {
+    let (stats_key, stats_bump) =
+        Pubkey::find_program_address(&[course.as_ref(), "stats".as_ref()], program_id);
+
+    if stats_key != *stats_pda.key {
+        log_account_check("stats_pda", stats_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let (activity_key, activity_bump) = Pubkey::find_program_address(
+        &[pda_enrollment.key.as_ref(), "activity".as_ref()],
+        program_id,
+    );
+
+    if activity_key != *activity_pda.key {
+        log_account_check("activity_pda", activity_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if stats_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            stats_pda,
+            system_program,
+            CourseStats::SIZE,
+            &[course.as_ref(), "stats".as_ref(), &[stats_bump]],
+        )?;
+    }
+
+    if activity_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            activity_pda,
+            system_program,
+            StudentActivity::SIZE,
+            &[
+                pda_enrollment.key.as_ref(),
+                "activity".as_ref(),
+                &[activity_bump],
+            ],
+        )?;
+    }
+
+    let mut stats_data = try_from_slice_unchecked::<CourseStats>(&stats_pda.data.borrow()).unwrap();
+    let mut activity_data =
+        try_from_slice_unchecked::<StudentActivity>(&activity_pda.data.borrow()).unwrap();
+
+    if !stats_data.is_initialized() {
+        stats_data.discriminator = CourseStats::DISCRIMINATOR.to_string();
+        stats_data.course = *course;
+        stats_data.is_initialized = true;
+    }
+
+    let epoch = Clock::get()?.epoch;
+
+    if stats_data.epoch != epoch {
+        stats_data.epoch = epoch;
+        stats_data.active_students = 0;
+    }
+
+    if !activity_data.is_initialized() || activity_data.last_active_epoch != epoch {
+        activity_data.discriminator = StudentActivity::DISCRIMINATOR.to_string();
+        activity_data.last_active_epoch = epoch;
+        activity_data.is_initialized = true;
+        stats_data.active_students += 1;
+    }
+
+    match activity {
+        CourseActivity::Intro => stats_data.intros += 1,
+        CourseActivity::Rescoped => {}
+        CourseActivity::Reply => stats_data.replies += 1,
+    }
+
+    stats_data.serialize(&mut &mut stats_pda.data.borrow_mut()[..])?;
+    activity_data.serialize(&mut &mut activity_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Mints a bonus reward straight to `beneficiary_ata`, or, when it exceeds the configured vesting
+/// threshold, into the beneficiary's vesting vault. Vesting takes two trailing accounts: the
+/// `[beneficiary, "vesting"]` PDA and its associated token account.
+#[cfg(not(feature = "no-rewards"))]
+#[allow(clippy::too_many_arguments)]
+fn mint_bonus_reward<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    program_id: &Pubkey,
+    config: &ProgramConfig,
+    account_info_iter: &mut I,
+    payer: &AccountInfo<'b>,
+    beneficiary: &Pubkey,
+    beneficiary_ata: &AccountInfo<'b>,
+    token_mint: &AccountInfo<'b>,
+    mint_auth: &AccountInfo<'b>,
+    mint_auth_bump: u8,
+    system_program: &AccountInfo<'b>,
+    token_program: &AccountInfo<'b>,
+    amount: u64,
+) -> ProgramResult {
+    let destination = if config.vesting_threshold > 0 && amount > config.vesting_threshold {
+        let vesting_pda = next_account_info(account_info_iter)?;
+        let vesting_vault = next_account_info(account_info_iter)?;
+
+        let (vesting_key, vesting_bump) =
+            Pubkey::find_program_address(&[beneficiary.as_ref(), "vesting".as_ref()], program_id);
+
+        if vesting_key != *vesting_pda.key {
+            log_account_check("vesting_pda", vesting_pda.key, AccountCheck::Pda);
+            return Err(IntroError::InvalidPDA.into());
+        }
+
+        if *vesting_vault.key != get_associated_token_address(&vesting_key, token_mint.key) {
+            log_account_check("vesting_vault", vesting_vault.key, AccountCheck::Address);
+            return Err(IntroError::IncorrectAccountError.into());
+        }
+
+        if vesting_pda.owner != program_id {
+            create_pda_account(
+                program_id,
+                payer,
+                vesting_pda,
+                system_program,
+                VestingSchedule::SIZE,
+                &[beneficiary.as_ref(), "vesting".as_ref(), &[vesting_bump]],
+            )?;
+        }
+
+        let mut vesting_data =
+            try_from_slice_unchecked::<VestingSchedule>(&vesting_pda.data.borrow()).unwrap();
+
+        if !vesting_data.is_initialized() {
+            vesting_data.discriminator = VestingSchedule::DISCRIMINATOR.to_string();
+            vesting_data.beneficiary = *beneficiary;
+            vesting_data.is_initialized = true;
+        }
+
+        vesting_data.add_grant(
+            amount,
+            Clock::get()?.unix_timestamp,
+            config.vesting_duration_secs,
+        );
+        vesting_data.serialize(&mut &mut vesting_pda.data.borrow_mut()[..])?;
+        msg!(
+            "Vesting {} tokens until {}",
+            vesting_data.total,
+            vesting_data.start_ts + vesting_data.duration_secs
+        );
+
+        vesting_vault
+    } else {
+        beneficiary_ata
+    };
+
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            token_mint.key,
+            destination.key,
+            mint_auth.key,
+            &[],
+            amount,
+        )?,
+        &[token_mint.clone(), destination.clone(), mint_auth.clone()],
+        &[&[b"token_auth", &[mint_auth_bump]]],
+    )?;
+
+    Ok(())
+}
+
+/// Counts this reply against `identity`'s tally on the intro, creating it on first use with
+/// `replier` paying, and returns `reward` decayed for the replies any of the identity's wallets
+/// already posted there.
+#[cfg(not(feature = "no-rewards"))]
+fn record_reply_tally<'a>(
+    program_id: &Pubkey,
+    replier: &AccountInfo<'a>,
+    identity: &Pubkey,
+    pda_intro: &AccountInfo<'a>,
+    tally_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    reward: u64,
+) -> Result<u64, ProgramError> {
+    let (tally_key, tally_bump) = Pubkey::find_program_address(
+        &[pda_intro.key.as_ref(), identity.as_ref(), "tally".as_ref()],
+        program_id,
+    );
+
+    if tally_key != *tally_pda.key {
+        log_account_check("tally_pda", tally_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if tally_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            replier,
+            tally_pda,
+            system_program,
+            ReplyTally::SIZE,
+            &[
+                pda_intro.key.as_ref(),
+                identity.as_ref(),
+                "tally".as_ref(),
+                &[tally_bump],
+            ],
+        )?;
+    }
+
+    let mut tally_data = try_from_slice_unchecked::<ReplyTally>(&tally_pda.data.borrow()).unwrap();
+
+    if !tally_data.is_initialized() {
+        tally_data.discriminator = ReplyTally::DISCRIMINATOR.to_string();
+        tally_data.is_initialized = true;
+    }
+
+    let reward = tally_data.decay(reward);
+    tally_data.replies += 1;
+    tally_data.serialize(&mut &mut tally_pda.data.borrow_mut()[..])?;
+
+    Ok(reward)
+}
+
+/// Allocates the next reply PDA for an intro and bumps its reply counter, returning the new
+/// reply's index.
+#[allow(clippy::too_many_arguments)]
+fn create_reply<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_intro: &AccountInfo<'a>,
+    pda_counter: &AccountInfo<'a>,
+    pda_reply: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    replier: Pubkey,
+    name: String,
+    message: String,
+    is_bot: bool,
+    quote: Option<ReplyQuote>,
+    lang: [u8; 2],
+    format: u8,
+) -> Result<u64, ProgramError> {
+    let (pda_count, _counter_bump_seed) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), "counter".as_ref()], program_id);
+
+    if pda_count != *pda_counter.key {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pda_counter.owner != program_id {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut counter_data = try_from_slice_unchecked::<ReplyCount>(&pda_counter.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if counter_data.discriminator == CLOSED_ACCOUNT_DISCRIMINATOR {
+        msg!("Reply counter has been closed");
+        return Err(IntroError::AccountClosed.into());
+    }
+
+    if !counter_data.is_initialized() || counter_data.discriminator != ReplyCount::DISCRIMINATOR {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    let links = format::extract_links(&message, StudentReplyState::MAX_LINKS);
+    let account_len = StudentReplyState::get_account_size(&name, &message)
+        + quote.as_ref().map_or(0, |_| ReplyQuote::SIZE)
+        + StudentReplyState::links_size(&links);
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(account_len);
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[
+            pda_intro.key.as_ref(),
+            counter_data.counter.to_be_bytes().as_ref(),
+        ],
+        program_id,
+    );
+
+    if pda != *pda_reply.key {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    // Replies 0..counter are materialized, so the slot at `counter` must still be free.
+    #[cfg(feature = "invariant-checks")]
+    if !pda_reply.data_is_empty() || pda_reply.owner == program_id {
+        msg!(
+            "Invariant violated: reply {} already exists for counter {}",
+            pda_reply.key,
+            counter_data.counter
+        );
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_reply.key,
+            rent_lamports,
+            account_len.try_into().unwrap(),
+            program_id,
+        ),
+        &[payer.clone(), pda_reply.clone(), system_program.clone()],
+        &[&[
+            pda_intro.key.as_ref(),
+            counter_data.counter.to_be_bytes().as_ref(),
+            &[bump_seed],
+        ]],
+    )?;
+    msg!("Created Reply Account");
+
+    let mut reply_data =
+        try_from_slice_unchecked::<StudentReplyState>(&pda_reply.data.borrow()).unwrap();
+
+    if reply_data.is_initialized() {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Uninitialized);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    reply_data.discriminator = StudentReplyState::DISCRIMINATOR.to_string();
+    reply_data.intro = *pda_intro.key;
+    reply_data.replier = replier;
+    reply_data.name = name;
+    reply_data.message = message;
+    reply_data.is_bot = is_bot;
+    reply_data.quote = quote;
+    reply_data.lang = lang;
+    reply_data.format = format;
+    reply_data.links = links;
+    reply_data.read = false;
+    reply_data.is_initialized = true;
+
+    counter_data.counter += 1;
+
+    reply_data.serialize(&mut &mut pda_reply.data.borrow_mut()[..])?;
+    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::ReplyPosted {
+        intro: *pda_intro.key,
+        reply: *pda_reply.key,
+        replier,
+        lang,
+    });
+    if !reply_data.links.is_empty() {
+        events::emit(ProgramEvent::ReplyLinks {
+            reply: *pda_reply.key,
+            links: reply_data.links,
+        });
+    }
+
+    Ok(counter_data.counter - 1)
+}
+
+/// Checks that `quoted_reply` is a reply on `pda_intro` whose message contains the excerpt, and
+/// records where.
+fn verify_quote(
+    program_id: &Pubkey,
+    pda_intro: &AccountInfo,
+    quoted_reply: &AccountInfo,
+    quote: QuoteParams,
+) -> Result<ReplyQuote, ProgramError> {
+    if quote.excerpt.is_empty() || quote.excerpt.len() > ReplyQuote::MAX_EXCERPT_LEN {
+        msg!(
+            "Quoted excerpt must be 1-{} bytes",
+            ReplyQuote::MAX_EXCERPT_LEN
+        );
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    if *quoted_reply.key != quote.reply || quoted_reply.owner != program_id {
+        msg!("Quoted reply account does not match the quote");
+        return Err(IntroError::InvalidQuote.into());
+    }
+
+    let reply_data = try_from_slice_unchecked::<StudentReplyState>(&quoted_reply.data.borrow())
+        .map_err(|_| IntroError::InvalidQuote)?;
+
+    if !reply_data.is_initialized()
+        || reply_data.discriminator != StudentReplyState::DISCRIMINATOR
+        || reply_data.intro != *pda_intro.key
+    {
+        msg!("Quoted reply is not a reply on this intro");
+        return Err(IntroError::InvalidQuote.into());
+    }
+
+    let excerpt_start = reply_data.message.find(&quote.excerpt).ok_or_else(|| {
+        msg!("Quoted reply does not contain the excerpt");
+        IntroError::InvalidQuote
+    })?;
+
+    Ok(ReplyQuote {
+        reply: quote.reply,
+        excerpt_start: excerpt_start as u16,
+        excerpt_len: quote.excerpt.len() as u16,
+        excerpt_hash: hash(quote.excerpt.as_bytes()).to_bytes(),
+    })
+}
+
+/// Returns the next account, treating a missing account or the program id as "not provided".
+#[cfg(not(feature = "no-rewards"))]
+fn next_optional_account<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    iter: &mut I,
+    program_id: &Pubkey,
+) -> Option<I::Item> {
+    iter.next().filter(|account| account.key != program_id)
+}
+
+/// Rescales a base reward against the configured price feed, which must be the next account.
+#[cfg(not(feature = "no-rewards"))]
+fn priced_reward<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    config: &ProgramConfig,
+    iter: &mut I,
+    base_reward: u64,
+) -> Result<u64, ProgramError> {
+    let price_feed = match config.price_feed {
+        Some(price_feed) => price_feed,
+        None => return Ok(base_reward),
+    };
+
+    let price_account = next_account_info(iter)?;
+
+    if *price_account.key != price_feed {
+        log_account_check("price_account", price_account.key, AccountCheck::Address);
+        return Err(IntroError::InvalidOracleAccount.into());
+    }
+
+    let price = read_pyth_price(
+        price_account,
+        config.max_price_staleness_slots,
+        config.max_price_confidence_bps,
+    )?;
+
+    Ok(scale_reward(
+        base_reward,
+        config.reference_price_cents,
+        &price,
+    )?)
+}
+
+/// Applies the configured multiplier when the signer proves membership in the credential
+/// collection with an optional (NFT token account, metadata account) pair.
+#[cfg(not(feature = "no-rewards"))]
+fn credential_reward<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    config: &ProgramConfig,
+    wallet: &Pubkey,
+    iter: &mut I,
+    program_id: &Pubkey,
+    base_reward: u64,
+) -> Result<u64, ProgramError> {
+    let collection = match config.credential_collection {
+        Some(collection) => collection,
+        None => return Ok(base_reward),
+    };
+
+    let nft_token_account = match next_optional_account(iter, program_id) {
+        Some(account) => account,
+        None => return Ok(base_reward),
+    };
+    let nft_metadata = next_account_info(iter)?;
+
+    verify_collection_nft(nft_token_account, nft_metadata, wallet, &collection)?;
+
+    Ok(config.apply_credential_multiplier(base_reward))
+}
+
+/// Maps `wallet` to the primary it is linked to through `link_pda`, which must be its
+/// `["link"]` address whether or not a link lives there.
+fn resolve_link_address(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    link_pda: &AccountInfo,
+) -> Result<Pubkey, ProgramError> {
+    let (pda, _bump_seed) =
+        Pubkey::find_program_address(&[wallet.as_ref(), "link".as_ref()], program_id);
+
+    if pda != *link_pda.key {
+        log_account_check("link_pda", link_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if link_pda.owner != program_id {
+        return Ok(*wallet);
+    }
+
+    // A link closed earlier in the transaction leaves a tombstone that names no primary.
+    match try_from_slice_unchecked::<WalletLink>(&link_pda.data.borrow()) {
+        Ok(link_data) if link_data.is_initialized() && link_data.linked == *wallet => {
+            Ok(link_data.primary)
+        }
+        _ => Ok(*wallet),
+    }
+}
+
+/// Maps a signing wallet to the primary wallet it is linked to, if a link account was passed.
+#[cfg(not(feature = "no-rewards"))]
+fn resolve_identity(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    link_pda: Option<&AccountInfo>,
+) -> Result<Pubkey, ProgramError> {
+    let link_pda = match link_pda {
+        Some(link_pda) => link_pda,
+        None => return Ok(*wallet),
+    };
+
+    let (pda, _bump_seed) =
+        Pubkey::find_program_address(&[wallet.as_ref(), "link".as_ref()], program_id);
+
+    if pda != *link_pda.key {
+        log_account_check("link_pda", link_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if link_pda.owner != program_id {
+        log_account_check("link_pda", link_pda.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let link_data = try_from_slice_unchecked::<WalletLink>(&link_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !link_data.is_initialized() || link_data.linked != *wallet {
+        log_account_check("link_pda", link_pda.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    Ok(link_data.primary)
+}
+
+pub fn propose_config_change(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    params: ConfigParams,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pending_pda = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    authorize_admin(program_id, config_data, admin)?;
+
+    let (pda, bump_seed) = Pubkey::find_program_address(&[b"pending_config"], program_id);
+
+    if pda != *pending_pda.key {
+        log_account_check("pending_pda", pending_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pending_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            admin,
+            pending_pda,
+            system_program,
+            PendingConfigChange::ACCOUNT_LEN,
+            &[b"pending_config", &[bump_seed]],
+        )?;
+    }
+
+    let slot = Clock::get()?.slot;
+    let eta_slot = slot + config_data.config_timelock_slots;
+    let params_hash = hash(&params.try_to_vec()?).to_bytes();
+
+    // A new proposal replaces whatever was pending and restarts the delay.
+    let pending_data = PendingConfigChange {
+        discriminator: PendingConfigChange::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        proposer: *admin.key,
+        proposed_slot: slot,
+        eta_slot,
+        params,
+    };
+    pending_data.serialize(&mut &mut pending_pda.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::ConfigChangeProposed {
+        proposer: *admin.key,
+        params_hash,
+        eta_slot,
+    });
+
+    Ok(())
+}
+
+/// Permissionless: applies the pending config change once its timelock has elapsed.
+pub fn execute_config_change(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_pda: &AccountInfo,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let executor = next_account_info(account_info_iter)?;
+    let pending_pda = next_account_info(account_info_iter)?;
+    let proposer = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config(program_id, config_pda)?;
+
+    let (pda, _bump_seed) = Pubkey::find_program_address(&[b"pending_config"], program_id);
+
+    if pda != *pending_pda.key {
+        log_account_check("pending_pda", pending_pda.key, AccountCheck::Pda);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pending_pda.owner != program_id {
+        log_account_check("pending_pda", pending_pda.key, AccountCheck::Owner);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let pending_data = try_from_slice_unchecked::<PendingConfigChange>(&pending_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !pending_data.is_initialized()
+        || pending_data.discriminator != PendingConfigChange::DISCRIMINATOR
+    {
+        msg!("No config change is pending");
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if pending_data.proposer != *proposer.key {
+        msg!("Rent must be returned to the proposer");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let slot = Clock::get()?.slot;
+
+    if slot < pending_data.eta_slot {
+        msg!("Config change executable at slot {}", pending_data.eta_slot);
+        return Err(IntroError::TimelockNotElapsed.into());
+    }
+
+    let params_hash = hash(&pending_data.params.try_to_vec()?).to_bytes();
+
+    apply_config_params(&mut config_data, pending_data.params)?;
+    config_data.serialize(&mut &mut config_pda.data.borrow_mut()[..])?;
+
+    close_account(pending_pda, proposer)?;
+
+    events::emit(ProgramEvent::ConfigChangeExecuted {
+        executor: *executor.key,
+        params_hash,
+        slot,
+    });
+
+    Ok(())
+}
+
+/// Stores an admin instruction for the multisig to approve; the proposer's approval is implied.
+pub fn propose_admin_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    action_id: u64,
+    action_accounts: Vec<ActionAccount>,
+    instruction_data: Vec<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let proposer = next_account_info(account_info_iter)?;
+    let action_pda = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    authorize_admin_signer(config_data, proposer)?;
+
+    if action_accounts.len() > PendingAction::MAX_ACCOUNTS
+        || instruction_data.len() > PendingAction::MAX_DATA_LEN
+    {
+        msg!("Admin action is too large");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[b"action", &action_id.to_be_bytes()], program_id);
+
+    if pda != *action_pda.key {
+        log_account_check("action_pda", action_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    create_pda_account(
+        program_id,
+        proposer,
+        action_pda,
+        system_program,
+        PendingAction::ACCOUNT_LEN,
+        &[b"action", &action_id.to_be_bytes(), &[bump_seed]],
+    )?;
+
+    let action_data = PendingAction {
+        discriminator: PendingAction::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        action_id,
+        proposer: *proposer.key,
+        approvals: vec![*proposer.key],
+        accounts: action_accounts,
+        instruction_data,
+    };
+    action_data.serialize(&mut &mut action_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn approve_admin_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let approver = next_account_info(account_info_iter)?;
+    let action_pda = next_account_info(account_info_iter)?;
+
+    authorize_admin_signer(config_data, approver)?;
+
+    let mut action_data = load_pending_action(program_id, action_pda)?;
+
+    if !action_data.approvals.contains(approver.key) {
+        action_data.approvals.push(*approver.key);
+    }
+    msg!("Admin action approvals: {}", action_data.approvals.len());
+
+    action_data.serialize(&mut &mut action_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Once enough current admin signers approved, re-enters this program with the stored
+/// instruction, signed by the `["multisig"]` authority PDA.
+pub fn execute_admin_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let _executor = next_account_info(account_info_iter)?;
+    let action_pda = next_account_info(account_info_iter)?;
+    let proposer = next_account_info(account_info_iter)?;
+    let this_program = next_account_info(account_info_iter)?;
+    let action_infos: Vec<AccountInfo> = account_info_iter.cloned().collect();
+
+    let action_data = load_pending_action(program_id, action_pda)?;
+
+    let approvals = action_data
+        .approvals
+        .iter()
+        .filter(|approver| config_data.admin_signers.contains(approver))
+        .count();
+
+    if config_data.admin_threshold == 0 || approvals < config_data.admin_threshold as usize {
+        msg!(
+            "Admin action has {} of {} approvals",
+            approvals,
+            config_data.admin_threshold
+        );
+        return Err(IntroError::ThresholdNotMet.into());
+    }
+
+    if action_data.proposer != *proposer.key || this_program.key != program_id {
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if action_infos.len() != action_data.accounts.len()
+        || action_infos
+            .iter()
+            .zip(action_data.accounts.iter())
+            .any(|(info, meta)| *info.key != meta.pubkey)
+    {
+        msg!("Accounts do not match the approved action");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    // Close before re-entering so the same approvals can never execute twice.
+    close_account(action_pda, proposer)?;
+
+    let (_multisig_authority, multisig_bump) =
+        Pubkey::find_program_address(&[b"multisig"], program_id);
+
+    let instruction = Instruction {
+        program_id: *program_id,
+        accounts: action_data
+            .accounts
+            .iter()
+            .map(|meta| AccountMeta {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: action_data.instruction_data,
+    };
+
+    let mut invoke_infos = action_infos;
+    invoke_infos.push(this_program.clone());
+
+    invoke_signed(
+        &instruction,
+        &invoke_infos,
+        &[&[b"multisig", &[multisig_bump]]],
+    )?;
+    msg!("Admin action {} executed", action_data.action_id);
+
+    Ok(())
+}
+
+/// First half of an admin rotation; the current admin stays in control until the nominee accepts.
+/// Nominating again replaces the previous nominee.
+pub fn nominate_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_pda: &AccountInfo,
+    new_admin: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config(program_id, config_pda)?;
+    authorize_admin(program_id, &config_data, admin)?;
+
+    config_data.pending_admin = Some(new_admin);
+    config_data.serialize(&mut &mut config_pda.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::AdminNominated {
+        admin: config_data.admin,
+        nominee: new_admin,
+    });
+
+    Ok(())
+}
+
+pub fn accept_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_pda: &AccountInfo,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let nominee = next_account_info(account_info_iter)?;
+
+    if !nominee.is_signer {
+        log_account_check("nominee", nominee.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config_data = load_config(program_id, config_pda)?;
+
+    if !config_data.is_initialized() {
+        log_account_check("config_pda", config_pda.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if config_data.pending_admin != Some(*nominee.key) {
+        msg!("Signer is not the nominated admin");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    let previous_admin = config_data.admin;
+    config_data.admin = *nominee.key;
+    config_data.pending_admin = None;
+    config_data.serialize(&mut &mut config_pda.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::AdminTransferred {
+        previous_admin,
+        new_admin: *nominee.key,
+    });
+
+    Ok(())
+}
+
+/// Admin freeze/thaw of a reward token account. Only works on mints created with
+/// `enable_freeze`; the token program rejects it otherwise.
+#[cfg(not(feature = "no-rewards"))]
+pub fn set_reward_account_frozen(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    freeze: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    authorize_admin(program_id, config_data, admin)?;
+
+    let (mint_pda, _mint_bump) = find_reward_mint(program_id, config_data.mint_version);
+    let (mint_auth_pda, mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    if *token_mint.key != mint_pda {
+        log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let instruction = if freeze {
+        freeze_account(
+            token_program.key,
+            token_account.key,
+            token_mint.key,
+            mint_auth.key,
+            &[],
+        )?
+    } else {
+        thaw_account(
+            token_program.key,
+            token_account.key,
+            token_mint.key,
+            mint_auth.key,
+            &[],
+        )?
+    };
+
+    invoke_signed(
+        &instruction,
+        &[token_account.clone(), token_mint.clone(), mint_auth.clone()],
+        &[&[b"token_auth", &[mint_auth_bump]]],
+    )?;
+    msg!(
+        "Reward account {} {}",
+        token_account.key,
+        if freeze { "frozen" } else { "thawed" }
+    );
+
+    Ok(())
+}
+
+/// Token sink: burns reward tokens held by the treasury, i.e. the associated token account of
+/// the `["treasury"]` PDA.
+#[cfg(not(feature = "no-rewards"))]
+pub fn burn_treasury_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let treasury_auth = next_account_info(account_info_iter)?;
+    let treasury_ata = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    authorize_admin(program_id, config_data, admin)?;
+
+    let (mint_pda, _mint_bump) = find_reward_mint(program_id, config_data.mint_version);
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+
+    if *token_mint.key != mint_pda {
+        log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *treasury_auth.key != treasury_pda {
+        log_account_check("treasury_auth", treasury_auth.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *treasury_ata.key != get_associated_token_address(&treasury_pda, &mint_pda) {
+        log_account_check("treasury_ata", treasury_ata.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    invoke_signed(
+        &burn(
+            token_program.key,
+            treasury_ata.key,
+            token_mint.key,
+            treasury_auth.key,
+            &[],
+            amount,
+        )?,
+        &[
+            treasury_ata.clone(),
+            token_mint.clone(),
+            treasury_auth.clone(),
+        ],
+        &[&[b"treasury", &[treasury_bump]]],
+    )?;
+
+    events::emit(ProgramEvent::Burned {
+        authority: *admin.key,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Creates reward mint `config.mint_version + 1` at `["token_mint", version]`, with the same mint
+/// authority, and makes it the one every reward path pays from. Earlier mints stay live so
+/// holders can `SwapOldTokens`; nothing new is minted from them.
+#[cfg(not(feature = "no-rewards"))]
+pub fn migrate_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_pda: &AccountInfo,
+    enable_freeze: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let new_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let mut config_data = load_config(program_id, config_pda)?;
+    authorize_admin(program_id, &config_data, admin)?;
+
+    let version = config_data.mint_version.checked_add(1).ok_or_else(|| {
+        msg!("No mint versions left");
+        ProgramError::InvalidArgument
+    })?;
+    let (mint_pda, mint_bump) = find_reward_mint(program_id, version);
+    let (mint_auth_pda, _mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    if mint_pda != *new_mint.key {
+        log_account_check("new_mint", new_mint.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        log_account_check("system_program", system_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            new_mint.key,
+            Rent::get()?.minimum_balance(82),
+            82, // Size of the token mint account
+            token_program.key,
+        ),
+        &[admin.clone(), new_mint.clone(), system_program.clone()],
+        &[&[b"token_mint", &[version], &[mint_bump]]],
+    )?;
+
+    invoke(
+        &initialize_mint2(
+            token_program.key,
+            new_mint.key,
+            mint_auth.key,
+            enable_freeze.then_some(mint_auth.key),
+            9,
+        )?,
+        &[new_mint.clone(), token_program.clone()],
+    )?;
+
+    config_data.mint_version = version;
+    config_data.serialize(&mut &mut config_pda.data.borrow_mut()[..])?;
+    msg!("Reward mint is now version {}: {}", version, new_mint.key);
+
+    events::emit(ProgramEvent::MintMigrated {
+        version,
+        mint: *new_mint.key,
+    });
+
+    Ok(())
+}
+
+/// Burns `amount` of the `from_version` reward mint from the owner's token account and mints
+/// the same amount of the current mint to their associated token account.
+#[cfg(not(feature = "no-rewards"))]
+pub fn swap_old_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    from_version: u8,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner = next_account_info(account_info_iter)?;
+    let old_mint = next_account_info(account_info_iter)?;
+    let old_token_account = next_account_info(account_info_iter)?;
+    let new_mint = next_account_info(account_info_iter)?;
+    let new_ata = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !owner.is_signer {
+        log_account_check("owner", owner.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if from_version >= config_data.mint_version {
+        msg!(
+            "Mint version {} is not older than the current {}",
+            from_version,
+            config_data.mint_version
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (old_mint_pda, _old_bump) = find_reward_mint(program_id, from_version);
+    let (new_mint_pda, _new_bump) = find_reward_mint(program_id, config_data.mint_version);
+    let (mint_auth_pda, mint_auth_bump) =
+        Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    if *old_mint.key != old_mint_pda {
+        log_account_check("old_mint", old_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *new_mint.key != new_mint_pda {
+        log_account_check("new_mint", new_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *new_ata.key != get_associated_token_address(owner.key, &new_mint_pda) {
+        log_account_check("new_ata", new_ata.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    // The token program checks `owner` actually owns (or is delegated) the old tokens.
+    invoke(
+        &burn(
+            token_program.key,
+            old_token_account.key,
+            old_mint.key,
+            owner.key,
+            &[],
+            amount,
+        )?,
+        &[old_token_account.clone(), old_mint.clone(), owner.clone()],
+    )?;
+
+    mint_reward(
+        token_program,
+        new_mint,
+        new_ata,
+        mint_auth,
+        mint_auth_bump,
+        amount,
+    )?;
+
+    events::emit(ProgramEvent::OldTokensSwapped {
+        owner: *owner.key,
+        from_version,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Queues a transfer of `amount` reward tokens from the treasury to `destination`, executable
+/// `config_timelock_slots` from now. Replaces any withdrawal already pending.
+#[cfg(not(feature = "no-rewards"))]
+pub fn propose_treasury_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pending_pda = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    authorize_admin(program_id, config_data, admin)?;
+
+    if amount == 0 {
+        msg!("Withdrawal amount must be positive");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(&[b"pending_withdrawal"], program_id);
+
+    if pda != *pending_pda.key {
+        log_account_check("pending_pda", pending_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pending_pda.owner != program_id {
+        create_pda_account(
+            program_id,
+            admin,
+            pending_pda,
+            system_program,
+            PendingWithdrawal::SIZE,
+            &[b"pending_withdrawal", &[bump_seed]],
+        )?;
+    }
+
+    let slot = Clock::get()?.slot;
+    let eta_slot = slot + config_data.config_timelock_slots;
+
+    let pending_data = PendingWithdrawal {
+        discriminator: PendingWithdrawal::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        proposer: *admin.key,
+        destination: *destination.key,
+        amount,
+        proposed_slot: slot,
+        eta_slot,
+    };
+    pending_data.serialize(&mut &mut pending_pda.data.borrow_mut()[..])?;
+
+    events::emit(ProgramEvent::TreasuryWithdrawalProposed {
+        proposer: *admin.key,
+        destination: *destination.key,
+        amount,
+        eta_slot,
+    });
+
+    Ok(())
+}
+
+/// Permissionless: pays out the pending treasury withdrawal once its timelock has elapsed and
+/// returns the pending account's rent to the proposer.
+#[cfg(not(feature = "no-rewards"))]
+pub fn execute_treasury_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let executor = next_account_info(account_info_iter)?;
+    let pending_pda = next_account_info(account_info_iter)?;
+    let proposer = next_account_info(account_info_iter)?;
+    let treasury_auth = next_account_info(account_info_iter)?;
+    let treasury_ata = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    let (pda, _bump_seed) = Pubkey::find_program_address(&[b"pending_withdrawal"], program_id);
+
+    if pda != *pending_pda.key {
+        log_account_check("pending_pda", pending_pda.key, AccountCheck::Pda);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if pending_pda.owner != program_id {
+        log_account_check("pending_pda", pending_pda.key, AccountCheck::Owner);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let pending_data = try_from_slice_unchecked::<PendingWithdrawal>(&pending_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !pending_data.is_initialized()
+        || pending_data.discriminator != PendingWithdrawal::DISCRIMINATOR
+    {
+        msg!("No treasury withdrawal is pending");
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if pending_data.proposer != *proposer.key {
+        msg!("Rent must be returned to the proposer");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if pending_data.destination != *destination.key {
+        msg!("Destination does not match the pending withdrawal");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let slot = Clock::get()?.slot;
+
+    if slot < pending_data.eta_slot {
+        msg!("Withdrawal executable at slot {}", pending_data.eta_slot);
+        return Err(IntroError::TimelockNotElapsed.into());
+    }
+
+    let (mint_pda, _mint_bump) = find_reward_mint(program_id, config_data.mint_version);
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+
+    if *treasury_auth.key != treasury_pda {
+        log_account_check("treasury_auth", treasury_auth.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *treasury_ata.key != get_associated_token_address(&treasury_pda, &mint_pda) {
+        log_account_check("treasury_ata", treasury_ata.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            treasury_ata.key,
+            destination.key,
+            treasury_auth.key,
+            &[],
+            pending_data.amount,
+        )?,
+        &[
+            treasury_ata.clone(),
+            destination.clone(),
+            treasury_auth.clone(),
+        ],
+        &[&[b"treasury", &[treasury_bump]]],
+    )?;
+
+    close_account(pending_pda, proposer)?;
+
+    events::emit(ProgramEvent::TreasuryWithdrawn {
+        executor: *executor.key,
+        destination: *destination.key,
+        amount: pending_data.amount,
+    });
+
+    Ok(())
+}
+
+/// Moves `amount` reward tokens from the tipper to the author of a reply. The config's
+/// `protocol_fee_bps` of it goes to the treasury token account instead, where only a
+/// timelocked withdrawal can move it.
+#[cfg(not(feature = "no-rewards"))]
+pub fn tip_reply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    config: Option<&ProgramConfig>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let tipper = next_account_info(account_info_iter)?;
+    let tipper_ata = next_account_info(account_info_iter)?;
+    let pda_reply = next_account_info(account_info_iter)?;
+    let replier_ata = next_account_info(account_info_iter)?;
+    let treasury_auth = next_account_info(account_info_iter)?;
+    let treasury_ata = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !tipper.is_signer {
+        log_account_check("tipper", tipper.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if amount == 0 {
+        msg!("Tip amount must be positive");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if pda_reply.owner != program_id {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let reply_data = try_from_slice_unchecked::<StudentReplyState>(&pda_reply.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !reply_data.is_initialized() || reply_data.discriminator != StudentReplyState::DISCRIMINATOR
+    {
+        log_account_check("reply_pda", pda_reply.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    if reply_data.replier == ANONYMOUS_REPLIER {
+        msg!("Anonymous replies cannot be tipped");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let mint_version = config.map_or(0, |config_data| config_data.mint_version);
+    let fee = config.map_or(0, |config_data| config_data.protocol_fee(amount));
+    let (mint_pda, _mint_bump) = find_reward_mint(program_id, mint_version);
+    let (treasury_pda, _treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+
+    if *token_mint.key != mint_pda {
+        log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *replier_ata.key != get_associated_token_address(&reply_data.replier, &mint_pda) {
+        log_account_check("replier_ata", replier_ata.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *treasury_auth.key != treasury_pda {
+        log_account_check("treasury_auth", treasury_auth.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if *treasury_ata.key != get_associated_token_address(&treasury_pda, &mint_pda) {
+        log_account_check("treasury_ata", treasury_ata.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    for (destination, share) in [(replier_ata, amount - fee), (treasury_ata, fee)] {
+        if share == 0 {
+            continue;
+        }
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                tipper_ata.key,
+                destination.key,
+                tipper.key,
+                &[],
+                share,
+            )?,
+            &[tipper_ata.clone(), destination.clone(), tipper.clone()],
+        )?;
+    }
+
+    events::emit(ProgramEvent::ReplyTipped {
+        reply: *pda_reply.key,
+        tipper: *tipper.key,
+        replier: reply_data.replier,
+        amount,
+        fee,
+    });
+
+    Ok(())
+}
 
-pub fn process_instruction(
+/// Releases everything vested so far from the caller's vesting vault to their token account.
+#[cfg(not(feature = "no-rewards"))]
+pub fn claim_vested(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    config: &ProgramConfig,
 ) -> ProgramResult {
-    let instruction = StudentInstruction::unpack(instruction_data)?;
+    let account_info_iter = &mut accounts.iter();
 
-    match instruction {
-        StudentInstruction::StudentIntro { name, message } => {
-            student_intro(program_id, accounts, name, message)
-        }
+    let beneficiary = next_account_info(account_info_iter)?;
+    let vesting_pda = next_account_info(account_info_iter)?;
+    let vesting_vault = next_account_info(account_info_iter)?;
+    let beneficiary_ata = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
 
-        StudentInstruction::UpdateIntro { name, message } => {
-            update_intro(program_id, accounts, name, message)
-        }
+    if !beneficiary.is_signer {
+        log_account_check("beneficiary", beneficiary.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
-        StudentInstruction::ReplyIntro { name, message } => {
-            reply_intro(program_id, accounts, name, message)
-        }
+    let (vesting_key, vesting_bump) =
+        Pubkey::find_program_address(&[beneficiary.key.as_ref(), "vesting".as_ref()], program_id);
+
+    if vesting_key != *vesting_pda.key {
+        log_account_check("vesting_pda", vesting_pda.key, AccountCheck::Pda);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if vesting_pda.owner != program_id {
+        log_account_check("vesting_pda", vesting_pda.key, AccountCheck::Owner);
+
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    // Grants vest in whichever mint was current when they were made, and claims come from the
+    // current mint's vault.
+    let (mint_pda, _mint_bump) = find_reward_mint(program_id, config.mint_version);
+
+    if *token_mint.key != mint_pda {
+        log_account_check("token_mint", token_mint.key, AccountCheck::Pda);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if *vesting_vault.key != get_associated_token_address(&vesting_key, &mint_pda) {
+        log_account_check("vesting_vault", vesting_vault.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
 
-        StudentInstruction::InitializeMint => initialize_token_mint(program_id, accounts),
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    let mut vesting_data =
+        try_from_slice_unchecked::<VestingSchedule>(&vesting_pda.data.borrow()).unwrap();
+
+    if !vesting_data.is_initialized() || vesting_data.beneficiary != *beneficiary.key {
+        msg!("Nothing is vesting for this wallet");
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    let amount = vesting_data.claim(Clock::get()?.unix_timestamp);
+    vesting_data.serialize(&mut &mut vesting_pda.data.borrow_mut()[..])?;
+    msg!("Claiming {} vested tokens", amount);
+
+    if amount == 0 {
+        return Ok(());
     }
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            vesting_vault.key,
+            beneficiary_ata.key,
+            vesting_pda.key,
+            &[],
+            amount,
+        )?,
+        &[
+            vesting_vault.clone(),
+            beneficiary_ata.clone(),
+            vesting_pda.clone(),
+        ],
+        &[&[
+            beneficiary.key.as_ref(),
+            "vesting".as_ref(),
+            &[vesting_bump],
+        ]],
+    )?;
+
+    Ok(())
 }
 
-pub fn student_intro(
+/// Permissionless: once an intro has `badge_reply_threshold` replies, mints one badge token
+/// (0 decimals, `["badge_mint"]`, created on first use) to the writer.
+#[cfg(not(feature = "no-rewards"))]
+pub fn mint_milestone_badge(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    name: String,
-    message: String,
+    config: &ProgramConfig,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
-    let writer = next_account_info(account_info_iter)?;
-    let intro_pda = next_account_info(account_info_iter)?;
-    let counter_pda = next_account_info(account_info_iter)?;
-    let token_mint = next_account_info(account_info_iter)?;
+    let payer = next_account_info(account_info_iter)?;
+    let pda_intro = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let badge_pda = next_account_info(account_info_iter)?;
+    let badge_mint = next_account_info(account_info_iter)?;
     let mint_auth = next_account_info(account_info_iter)?;
-    let user_ata = next_account_info(account_info_iter)?;
+    let writer_badge_ata = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
 
-    if !writer.is_signer {
-        msg!("Missing required signature");
-        return Err(solana_program::program_error::ProgramError::MissingRequiredSignature);
+    if !payer.is_signer {
+        log_account_check("payer", payer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let (pda, bump_seed) =
-        Pubkey::find_program_address(&[writer.key.as_ref(), "intro".as_ref()], program_id);
+    if config.badge_reply_threshold == 0 {
+        msg!("Milestone badges are disabled");
+        return Err(IntroError::Unauthorized.into());
+    }
 
-    if pda != *intro_pda.key {
-        msg!("Invalid seeds for PDA");
-        return Err(ProgramError::InvalidArgument);
+    let intro_data = load_intro(program_id, pda_intro)?;
+
+    let (counter_key, _counter_bump) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), "counter".as_ref()], program_id);
+
+    if counter_key != *pda_counter.key {
+        log_account_check("counter_pda", pda_counter.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
     }
 
-    let (pda_count, _counter_bump_seed) =
-        Pubkey::find_program_address(&[pda.as_ref(), "counter".as_ref()], program_id);
+    let counter_data = try_from_slice_unchecked::<ReplyCount>(&pda_counter.data.borrow()).unwrap();
 
-    if pda_count != *counter_pda.key {
-        msg!("Invalid seeds for counter PDA.");
-        return Err(ProgramError::InvalidArgument);
+    if counter_data.counter < config.badge_reply_threshold {
+        msg!(
+            "Intro has {} of {} replies needed for a badge",
+            counter_data.counter,
+            config.badge_reply_threshold
+        );
+        return Err(IntroError::MilestoneNotReached.into());
+    }
+
+    let (badge_key, badge_bump) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), "badge".as_ref()], program_id);
+
+    if badge_key != *badge_pda.key {
+        log_account_check("badge_pda", badge_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if badge_pda.owner == program_id {
+        msg!("Badge already minted for this intro");
+        return Err(ProgramError::AccountAlreadyInitialized);
     }
 
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+    let (badge_mint_key, badge_mint_bump) =
+        Pubkey::find_program_address(&[b"badge_mint"], program_id);
     let (mint_auth_pda, mint_auth_bump) =
         Pubkey::find_program_address(&[b"token_auth"], program_id);
 
-    if mint_pda != *token_mint.key {
-        msg!("Incorrect token mint");
+    if badge_mint_key != *badge_mint.key {
+        log_account_check("badge_mint", badge_mint.key, AccountCheck::Address);
         return Err(IntroError::IncorrectAccountError.into());
     }
 
     if mint_auth_pda != *mint_auth.key {
-        msg!("Mint passed in and mint derived do not match");
+        log_account_check("mint_auth", mint_auth.key, AccountCheck::Pda);
         return Err(IntroError::InvalidPDA.into());
     }
 
-    if *user_ata.key != get_associated_token_address(writer.key, token_mint.key) {
-        msg!("Incorrect token mint");
+    if *writer_badge_ata.key != get_associated_token_address(&intro_data.writer, &badge_mint_key) {
+        log_account_check(
+            "writer_badge_ata",
+            writer_badge_ata.key,
+            AccountCheck::Address,
+        );
         return Err(IntroError::IncorrectAccountError.into());
     }
 
     if *token_program.key != TOKEN_PROGRAM_ID {
-        msg!("Incorrect token program");
+        log_account_check("token_program", token_program.key, AccountCheck::Address);
         return Err(IntroError::IncorrectAccountError.into());
     }
 
-    let account_len: usize = 1000;
+    if *badge_mint.owner != TOKEN_PROGRAM_ID {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                badge_mint.key,
+                Rent::get()?.minimum_balance(82),
+                82,
+                token_program.key,
+            ),
+            &[payer.clone(), badge_mint.clone(), system_program.clone()],
+            &[&[b"badge_mint", &[badge_mint_bump]]],
+        )?;
 
-    if (StudentIntroState::get_account_size(name.clone(), message.clone())) > account_len {
-        msg!("Data length is larger than 1000 bytes");
-        return Err(IntroError::InvalidDataLength.into());
+        invoke(
+            &initialize_mint2(token_program.key, badge_mint.key, mint_auth.key, None, 0)?,
+            &[badge_mint.clone(), token_program.clone()],
+        )?;
+        msg!("Initialized badge mint");
     }
 
-    let rent = Rent::get()?;
-    let rent_lamports = rent.minimum_balance(account_len);
-    let counter_rent_lamp = rent.minimum_balance(ReplyCount::SIZE);
+    create_pda_account(
+        program_id,
+        payer,
+        badge_pda,
+        system_program,
+        BadgeAward::SIZE,
+        &[pda_intro.key.as_ref(), "badge".as_ref(), &[badge_bump]],
+    )?;
+
+    let badge_data = BadgeAward {
+        discriminator: BadgeAward::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: *pda_intro.key,
+        writer: intro_data.writer,
+        slot: Clock::get()?.slot,
+    };
+    badge_data.serialize(&mut &mut badge_pda.data.borrow_mut()[..])?;
 
     invoke_signed(
-        &system_instruction::create_account(
-            writer.key,
-            intro_pda.key,
-            rent_lamports,
-            account_len.try_into().unwrap(),
-            program_id,
-        ),
-        &[writer.clone(), intro_pda.clone(), system_program.clone()],
-        &[&[
-            writer.key.as_ref(),
-            "intro".as_bytes().as_ref(),
-            &[bump_seed],
-        ]],
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            badge_mint.key,
+            writer_badge_ata.key,
+            mint_auth.key,
+            &[],
+            1,
+        )?,
+        &[
+            badge_mint.clone(),
+            writer_badge_ata.clone(),
+            mint_auth.clone(),
+        ],
+        &[&[b"token_auth", &[mint_auth_bump]]],
     )?;
-    msg!("Intro PDA Created: {}", pda);
 
-    invoke_signed(
-        &system_instruction::create_account(
-            writer.key,
-            counter_pda.key,
-            counter_rent_lamp,
-            ReplyCount::SIZE.try_into().unwrap(),
+    events::emit(ProgramEvent::BadgeMinted {
+        intro: *pda_intro.key,
+        author: intro_data.writer,
+        reply_count: counter_data.counter,
+    });
+
+    Ok(())
+}
+
+pub fn get_config(config: &ConfigReturn) -> ProgramResult {
+    return_data::set(config)
+}
+
+pub fn get_course_stats(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let stats_pda = next_account_info(account_info_iter)?;
+
+    if stats_pda.owner != program_id {
+        log_account_check("stats_pda", stats_pda.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let stats_data: CourseStatsReturn =
+        try_from_slice_unchecked::<CourseStats>(&stats_pda.data.borrow())
+            .map_err(|_| IntroError::UninitializedAccount)?;
+
+    if !stats_data.is_initialized() || stats_data.discriminator != CourseStats::DISCRIMINATOR {
+        log_account_check("stats_pda", stats_pda.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
+
+    return_data::set(&stats_data)
+}
+
+/// Read-only: re-encodes any account of this program canonically, for auditors and backup
+/// tooling that should not track padding, legacy layouts and fields older accounts lack.
+pub fn export_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    target: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let account = next_account_info(account_info_iter)?;
+
+    if *account.key != target {
+        msg!("Account does not match the export target");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if account.owner != program_id {
+        log_account_check("account", account.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let (kind, data) = export::canonical(&account.data.borrow())?;
+    let export = ExportReturn::new(kind, data).try_to_vec()?;
+
+    if export.len() > MAX_RETURN_DATA {
+        msg!(
+            "Export of {} bytes exceeds the return data limit",
+            export.len()
+        );
+        return Err(IntroError::ExportTooLarge.into());
+    }
+
+    set_return_data(&export);
+    Ok(())
+}
+
+pub fn advance_nonce(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let user = next_account_info(account_info_iter)?;
+    let nonce_pda = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !user.is_signer {
+        log_account_check("user", user.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut nonce_data = load_nonce(program_id, user, user.key, nonce_pda, system_program)?;
+    nonce_data.next_nonce += 1;
+    msg!("Next nonce: {}", nonce_data.next_nonce);
+    nonce_data.serialize(&mut &mut nonce_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Accepts `nonce` only if it is `user`'s next nonce and advances it, so a relayed payload
+/// signed by `user` can be submitted at most once. Handlers verifying such payloads must call
+/// this with the nonce the user signed over.
+pub fn consume_nonce<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    user: &Pubkey,
+    nonce_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    nonce: u64,
+) -> ProgramResult {
+    let mut nonce_data = load_nonce(program_id, payer, user, nonce_pda, system_program)?;
+
+    if nonce != nonce_data.next_nonce {
+        msg!("Expected nonce {}, got {}", nonce_data.next_nonce, nonce);
+        return Err(IntroError::InvalidNonce.into());
+    }
+
+    nonce_data.next_nonce += 1;
+    nonce_data.serialize(&mut &mut nonce_pda.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// Loads `user`'s `[user, "nonce"]` PDA, creating it at nonce 0 on first use.
+fn load_nonce<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    user: &Pubkey,
+    nonce_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<UserNonce, ProgramError> {
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[user.as_ref(), "nonce".as_ref()], program_id);
+
+    if pda != *nonce_pda.key {
+        log_account_check("nonce_pda", nonce_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if nonce_pda.owner != program_id {
+        create_pda_account(
             program_id,
-        ),
-        &[writer.clone(), counter_pda.clone(), system_program.clone()],
-        &[&[pda.as_ref(), "counter".as_ref(), &[_counter_bump_seed]]],
+            payer,
+            nonce_pda,
+            system_program,
+            UserNonce::SIZE,
+            &[user.as_ref(), "nonce".as_ref(), &[bump_seed]],
+        )?;
+    }
+
+    let mut nonce_data = try_from_slice_unchecked::<UserNonce>(&nonce_pda.data.borrow()).unwrap();
+
+    if !nonce_data.is_initialized() {
+        nonce_data.discriminator = UserNonce::DISCRIMINATOR.to_string();
+        nonce_data.user = *user;
+        nonce_data.is_initialized = true;
+    }
+
+    Ok(nonce_data)
+}
+
+/// Admin-submitted migration of an intro from another deployment. The writer's Ed25519
+/// signature over the attestation, in the preceding instruction, proves the content is theirs;
+/// their nonce keeps the attestation from being imported twice. No reward is minted.
+pub fn import_intro(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    attestation: IntroAttestation,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let writer = next_account_info(account_info_iter)?;
+    let intro_pda = next_account_info(account_info_iter)?;
+    let counter_pda = next_account_info(account_info_iter)?;
+    let registry_pda = next_account_info(account_info_iter)?;
+    let registry_entry_pda = next_account_info(account_info_iter)?;
+    let feed_page_pda = next_account_info(account_info_iter)?;
+    let nonce_pda = next_account_info(account_info_iter)?;
+    let instructions_sysvar = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    authorize_admin(program_id, config_data, admin)?;
+
+    if attestation.program_id != *program_id || attestation.writer != *writer.key {
+        msg!("Attestation is for another deployment or writer");
+        return Err(IntroError::InvalidAttestation.into());
+    }
+
+    verify_ed25519_signature(instructions_sysvar, writer.key, &attestation.try_to_vec()?)?;
+    consume_nonce(
+        program_id,
+        admin,
+        writer.key,
+        nonce_pda,
+        system_program,
+        attestation.nonce,
+    )?;
+
+    let (pda, bump_seed) =
+        Pubkey::find_program_address(&[writer.key.as_ref(), "intro".as_ref()], program_id);
+
+    if pda != *intro_pda.key {
+        log_account_check("intro_pda", intro_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let (pda_count, counter_bump_seed) =
+        Pubkey::find_program_address(&[pda.as_ref(), "counter".as_ref()], program_id);
+
+    if pda_count != *counter_pda.key {
+        log_account_check("counter_pda", counter_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    let account_len = StudentIntroState::ACCOUNT_LEN;
+
+    if StudentIntroState::get_account_size(&attestation.name, &attestation.message) > account_len {
+        msg!("Data length is larger than 1000 bytes");
+        return Err(IntroError::InvalidDataLength.into());
+    }
+
+    create_pda_account(
+        program_id,
+        admin,
+        intro_pda,
+        system_program,
+        account_len,
+        &[writer.key.as_ref(), "intro".as_ref(), &[bump_seed]],
+    )?;
+    create_pda_account(
+        program_id,
+        admin,
+        counter_pda,
+        system_program,
+        ReplyCount::SIZE,
+        &[pda.as_ref(), "counter".as_ref(), &[counter_bump_seed]],
     )?;
-    msg!("Reply Counter Created: {}", pda_count);
 
     let mut intro_data =
         try_from_slice_unchecked::<StudentIntroState>(&intro_pda.data.borrow()).unwrap();
-    let mut counter_data =
-        try_from_slice_unchecked::<ReplyCount>(&counter_pda.data.borrow()).unwrap();
+    intro_data.discriminator = StudentIntroState::DISCRIMINATOR.to_string();
+    intro_data.writer = *writer.key;
+    intro_data.name = attestation.name;
+    intro_data.message = attestation.message;
+    intro_data.expires_at = attestation.expires_at;
+    intro_data.created_at = attestation.created_at;
+    intro_data.layout_version = StudentIntroState::LAYOUT_VERSION;
+    intro_data.is_initialized = true;
 
-    if intro_data.is_initialized() {
-        msg!("Account already initialized!");
-        return Err(ProgramError::AccountAlreadyInitialized);
-    }
+    let counter_data = ReplyCount {
+        discriminator: ReplyCount::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        counter: 0,
+    };
 
-    if counter_data.is_initialized() {
-        msg!("Counter already initialized!");
-        return Err(ProgramError::AccountAlreadyInitialized);
+    intro_data.serialize(&mut &mut intro_pda.data.borrow_mut()[..])?;
+    counter_data.serialize(&mut &mut counter_pda.data.borrow_mut()[..])?;
+    msg!("Imported intro {} for {}", pda, writer.key);
+    // The attestation carries no language, so imported intros start untagged.
+    events::emit(ProgramEvent::IntroPosted {
+        intro: pda,
+        author: *writer.key,
+        lang: lang::UNSPECIFIED,
+    });
+
+    append_to_registry(
+        program_id,
+        admin,
+        registry_pda,
+        registry_entry_pda,
+        feed_page_pda,
+        system_program,
+        intro_pda,
+    )?;
+
+    Ok(())
+}
+
+/// Sets the identity checkmark in the intro's trailer. The config's `identity_verifier` signs
+/// the attestation in the preceding Ed25519 instruction, so anyone may relay it; relaying it
+/// again only repeats the event.
+pub fn verify_identity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config_data: &ProgramConfig,
+    attestation: IdentityAttestation,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let intro_pda = next_account_info(account_info_iter)?;
+    let instructions_sysvar = next_account_info(account_info_iter)?;
+
+    ensure_writable(&[(intro_pda, "intro_pda")])?;
+
+    let verifier = match config_data.identity_verifier {
+        Some(verifier) => verifier,
+        None => {
+            msg!("No identity verifier is configured");
+            return Err(IntroError::Unauthorized.into());
+        }
+    };
+
+    if attestation.program_id != *program_id {
+        msg!("Attestation is for another deployment");
+        return Err(IntroError::InvalidAttestation.into());
     }
 
-    intro_data.discriminator = StudentIntroState::DISCRIMINATOR.to_string();
-    intro_data.writer = *writer.key;
-    intro_data.name = name;
-    intro_data.message = message;
-    intro_data.is_initialized = true;
+    let mut intro_data = load_intro(program_id, intro_pda)?;
 
-    counter_data.discriminator = ReplyCount::DISCRIMINATOR.to_string();
-    counter_data.counter = 0;
-    counter_data.is_initialized = true;
+    if intro_data.writer != attestation.writer {
+        msg!("Attestation is for another writer");
+        return Err(IntroError::InvalidAttestation.into());
+    }
 
-    msg!("Reply Count: {}", counter_data.counter);
+    verify_ed25519_signature(instructions_sysvar, &verifier, &attestation.try_to_vec()?)?;
 
-    intro_data.serialize(&mut &mut intro_pda.data.borrow_mut()[..])?;
-    counter_data.serialize(&mut &mut counter_pda.data.borrow_mut()[..])?;
+    intro_data.verified = true;
+    store_intro(intro_pda, &intro_data)?;
+    msg!("Verified intro {}", intro_pda.key);
 
-    msg!("Minting 10 tokens to user associated token account.");
-    invoke_signed(
-        &spl_token::instruction::mint_to(
-            token_program.key,
-            token_mint.key,
-            user_ata.key,
-            mint_auth.key,
-            &[],
-            10 * LAMPORTS_PER_SOL,
-        )?,
-        &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
-        &[&[b"token_auth", &[mint_auth_bump]]],
-    )?;
+    events::emit(ProgramEvent::IdentityVerified {
+        intro: *intro_pda.key,
+        author: attestation.writer,
+        verifier,
+        identity_hash: attestation.identity_hash,
+    });
 
     Ok(())
 }
 
-pub fn update_intro(
+/// Spam classifier only: stores `score` on an intro, in its trailer, or on a reply, as its
+/// last field. A score above the config's `spam_threshold` also puts the author's rewards on
+/// hold, creating their `RewardHold` with the classifier paying; lower scores leave an
+/// existing hold for the admin to review.
+pub fn set_spam_score(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    name: String,
-    message: String,
+    config: &ProgramConfig,
+    target: Pubkey,
+    score: u8,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
-    let writer = next_account_info(account_info_iter)?;
-    let pda_intro = next_account_info(account_info_iter)?;
+    let classifier = next_account_info(account_info_iter)?;
+    let pda_target = next_account_info(account_info_iter)?;
+    let pda_hold = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
 
-    if pda_intro.owner != program_id {
+    if !classifier.is_signer {
+        log_account_check("classifier", classifier.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    ensure_writable(&[
+        (classifier, "classifier"),
+        (pda_target, "target_pda"),
+        (pda_hold, "hold_pda"),
+    ])?;
+
+    if config.spam_classifier != Some(*classifier.key) {
+        msg!("Signer is not the spam classifier");
+        return Err(IntroError::Unauthorized.into());
+    }
+
+    if *pda_target.key != target {
+        msg!("Target account does not match the instruction");
+        return Err(IntroError::IncorrectAccountError.into());
+    }
+
+    if pda_target.owner != program_id {
+        log_account_check("target_pda", pda_target.key, AccountCheck::Owner);
         return Err(ProgramError::IllegalOwner);
     }
 
-    if !writer.is_signer {
-        msg!("Missing required signature");
-        return Err(ProgramError::MissingRequiredSignature);
+    let discriminator = String::deserialize(&mut &pda_target.data.borrow()[..]).ok();
+    let author = match discriminator.as_deref() {
+        Some(StudentIntroState::DISCRIMINATOR) => {
+            let mut intro_data = load_intro(program_id, pda_target)?;
+            intro_data.spam_score = score;
+            store_intro(pda_target, &intro_data)?;
+            intro_data.writer
+        }
+        Some(StudentReplyState::DISCRIMINATOR) => {
+            let mut reply_data =
+                try_from_slice_unchecked::<StudentReplyState>(&pda_target.data.borrow())
+                    .map_err(|_| IntroError::UninitializedAccount)?;
+
+            if !reply_data.is_initialized() {
+                log_account_check("target_pda", pda_target.key, AccountCheck::Initialized);
+                return Err(IntroError::UninitializedAccount.into());
+            }
+
+            reply_data.spam_score = score;
+            let data = reply_data.try_to_vec()?;
+
+            if data.len() > pda_target.data_len() {
+                return Err(IntroError::SpamScoreUnsupported.into());
+            }
+            pda_target.data.borrow_mut()[..data.len()].copy_from_slice(&data);
+            reply_data.replier
+        }
+        _ => {
+            msg!("Target is not an intro or a reply");
+            return Err(IntroError::UninitializedAccount.into());
+        }
+    };
+
+    let held = score > config.spam_threshold;
+
+    if held {
+        hold_rewards(
+            program_id,
+            classifier,
+            &author,
+            &target,
+            score,
+            pda_hold,
+            system_program,
+        )?;
     }
 
-    let mut intro_data =
-        try_from_slice_unchecked::<StudentIntroState>(&pda_intro.data.borrow()).unwrap();
+    events::emit(ProgramEvent::SpamScoreSet {
+        target,
+        author,
+        score,
+        held,
+    });
 
-    let (pda, _bump_seed) =
-        Pubkey::find_program_address(&[writer.key.as_ref(), "intro".as_ref()], program_id);
+    Ok(())
+}
 
-    if pda != *pda_intro.key {
-        msg!("Invalid seeds for PDA");
+/// Puts `author`'s rewards on hold for `target`, creating the hold on first use.
+fn hold_rewards<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    author: &Pubkey,
+    target: &Pubkey,
+    score: u8,
+    pda_hold: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (hold_key, hold_bump) =
+        Pubkey::find_program_address(&[author.as_ref(), b"reward_hold"], program_id);
+
+    if hold_key != *pda_hold.key {
+        log_account_check("hold_pda", pda_hold.key, AccountCheck::Pda);
         return Err(IntroError::InvalidPDA.into());
     }
 
-    if !intro_data.is_initialized() {
-        msg!("Account is not initialized");
-        return Err(IntroError::UninitializedAccount.into());
+    if pda_hold.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            pda_hold,
+            system_program,
+            RewardHold::SIZE,
+            &[author.as_ref(), b"reward_hold", &[hold_bump]],
+        )?;
     }
 
-    if StudentIntroState::get_account_size(name.clone(), message.clone()) > 1000 {
-        msg!("Data length is larger than 1000 bytes");
-        return Err(IntroError::InvalidDataLength.into());
+    let mut hold_data = try_from_slice_unchecked::<RewardHold>(&pda_hold.data.borrow()).unwrap();
+
+    if !hold_data.is_initialized() {
+        hold_data.discriminator = RewardHold::DISCRIMINATOR.to_string();
+        hold_data.author = *author;
+        hold_data.is_initialized = true;
     }
 
-    intro_data.message = message;
-    intro_data.serialize(&mut &mut pda_intro.data.borrow_mut()[..])?;
+    msg!("Rewards held for {}", author);
+    hold_data.held = true;
+    hold_data.target = *target;
+    hold_data.score = score;
+    hold_data.serialize(&mut &mut pda_hold.data.borrow_mut()[..])?;
 
     Ok(())
 }
 
-pub fn reply_intro(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    name: String,
-    message: String,
-) -> ProgramResult {
-    msg!("{}, {}, {}", program_id, name, message);
+/// Permissionless: rewrites the intro writer's profile card from the accounts it summarizes,
+/// creating it on first use with `payer` funding it. Every source account is checked against
+/// its address, so the card can only ever show what they hold.
+pub fn sync_profile_card(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
-    let replier = next_account_info(account_info_iter)?;
+    let payer = next_account_info(account_info_iter)?;
     let pda_intro = next_account_info(account_info_iter)?;
-    let pda_counter = next_account_info(account_info_iter)?;
-    let pda_reply = next_account_info(account_info_iter)?;
-    let token_mint = next_account_info(account_info_iter)?;
-    let mint_auth = next_account_info(account_info_iter)?;
-    let user_ata = next_account_info(account_info_iter)?;
+    let pda_card = next_account_info(account_info_iter)?;
+    let pda_reputation = next_account_info(account_info_iter)?;
+    let pda_badge = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
 
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
-    let (mint_auth_pda, mint_auth_bump) =
-        Pubkey::find_program_address(&[b"token_auth"], program_id);
+    if !payer.is_signer {
+        log_account_check("payer", payer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
-    if *token_mint.key != mint_pda {
-        msg!("Incorrect token mint");
-        return Err(IntroError::IncorrectAccountError.into());
+    ensure_writable(&[(payer, "payer"), (pda_card, "card_pda")])?;
+
+    let intro_data = load_intro(program_id, pda_intro)?;
+    let student = intro_data.writer;
+
+    let (card_key, card_bump) =
+        Pubkey::find_program_address(&[student.as_ref(), b"card"], program_id);
+
+    if card_key != *pda_card.key {
+        log_account_check("card_pda", pda_card.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
     }
 
-    if *mint_auth.key != mint_auth_pda {
-        msg!("Mint passed in and mint derived do not match");
-        return Err(IntroError::IncorrectAccountError.into());
+    let (reputation_key, _reputation_bump) =
+        Pubkey::find_program_address(&[student.as_ref(), b"reputation"], program_id);
+
+    if reputation_key != *pda_reputation.key {
+        log_account_check("reputation_pda", pda_reputation.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
     }
 
-    if *user_ata.key != get_associated_token_address(replier.key, token_mint.key) {
-        msg!("Incorrect token mint");
-        return Err(IntroError::IncorrectAccountError.into());
+    let (badge_key, _badge_bump) =
+        Pubkey::find_program_address(&[pda_intro.key.as_ref(), b"badge"], program_id);
+
+    if badge_key != *pda_badge.key {
+        log_account_check("badge_pda", pda_badge.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
     }
 
-    if *token_program.key != TOKEN_PROGRAM_ID {
-        msg!("Incorrect token program");
-        return Err(IntroError::IncorrectAccountError.into());
+    let reputation = if pda_reputation.owner == program_id {
+        try_from_slice_unchecked::<Reputation>(&pda_reputation.data.borrow())
+            .map_err(|_| IntroError::UninitializedAccount)?
+            .points
+    } else {
+        0
+    };
+
+    let mut badges = 0;
+    // Only `MintMilestoneBadge` ever creates the badge address.
+    if pda_badge.owner == program_id {
+        badges |= ProfileCard::BADGE_MILESTONE;
+    }
+    if intro_data.verified {
+        badges |= ProfileCard::BADGE_VERIFIED;
+    }
+    if intro_data.status == StudentIntroState::STATUS_GRADUATED {
+        badges |= ProfileCard::BADGE_GRADUATED;
     }
 
-    let mut counter_data =
-        try_from_slice_unchecked::<ReplyCount>(&pda_counter.data.borrow()).unwrap();
+    if pda_card.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            pda_card,
+            system_program,
+            ProfileCard::SIZE,
+            &[student.as_ref(), b"card", &[card_bump]],
+        )?;
+    }
 
-    let account_len = StudentReplyState::get_account_size(name.clone(), message.clone());
-    let rent = Rent::get()?;
-    let rent_lamports = rent.minimum_balance(account_len);
+    let card_data = ProfileCard {
+        discriminator: ProfileCard::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        student,
+        intro: *pda_intro.key,
+        name: ProfileCard::fixed_text(&intro_data.name),
+        handle: ProfileCard::fixed_text(&intro_data.slug),
+        reputation,
+        badges,
+        synced_slot: Clock::get()?.slot,
+    };
+    card_data.serialize(&mut &mut pda_card.data.borrow_mut()[..])?;
+    msg!("Synced profile card of {}", student);
 
-    let (pda, bump_seed) = Pubkey::find_program_address(
-        &[
-            pda_intro.key.as_ref(),
-            counter_data.counter.to_be_bytes().as_ref(),
-        ],
-        program_id,
-    );
+    Ok(())
+}
 
-    if pda != *pda_reply.key {
-        msg!("Invalid seeds for PDA");
-        return Err(IntroError::InvalidPDA.into());
+/// Admin, acting as moderator: lifts an author's reward hold once their flagged posts have been
+/// reviewed. The hold account stays, so a later high score reuses it.
+pub fn release_reward_hold(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    config: &ProgramConfig,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_hold = next_account_info(account_info_iter)?;
+
+    ensure_writable(&[(pda_hold, "hold_pda")])?;
+
+    authorize_admin(program_id, config, admin)?;
+
+    if pda_hold.owner != program_id {
+        log_account_check("hold_pda", pda_hold.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
     }
 
-    invoke_signed(
-        &system_instruction::create_account(
-            replier.key,
-            pda_reply.key,
-            rent_lamports,
-            account_len.try_into().unwrap(),
-            program_id,
-        ),
-        &[replier.clone(), pda_reply.clone(), system_program.clone()],
-        &[&[
-            pda_intro.key.as_ref(),
-            counter_data.counter.to_be_bytes().as_ref(),
-            &[bump_seed],
-        ]],
-    )?;
-    msg!("Created Reply Account");
+    let mut hold_data = try_from_slice_unchecked::<RewardHold>(&pda_hold.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
 
-    let mut reply_data =
-        try_from_slice_unchecked::<StudentReplyState>(&pda_reply.data.borrow()).unwrap();
+    if !hold_data.is_initialized() || hold_data.discriminator != RewardHold::DISCRIMINATOR {
+        log_account_check("hold_pda", pda_hold.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
 
-    if reply_data.is_initialized() {
-        msg!("Account already intitialized");
-        return Err(ProgramError::AccountAlreadyInitialized);
+    if !hold_data.held {
+        msg!("Rewards are not held");
+        return Ok(());
     }
 
-    reply_data.discriminator = StudentReplyState::DISCRIMINATOR.to_string();
-    reply_data.intro = *pda_intro.key;
-    reply_data.replier = *replier.key;
-    reply_data.name = name;
-    reply_data.message = message;
-    reply_data.is_initialized = true;
+    hold_data.held = false;
+    hold_data.serialize(&mut &mut pda_hold.data.borrow_mut()[..])?;
 
-    counter_data.counter += 1;
+    events::emit(ProgramEvent::RewardHoldReleased {
+        author: hold_data.author,
+        admin: *admin.key,
+    });
 
-    reply_data.serialize(&mut &mut pda_reply.data.borrow_mut()[..])?;
-    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
+    Ok(())
+}
 
-    msg!("Minting 5 tokens to user associated token account");
-    invoke_signed(
-        &spl_token::instruction::mint_to(
-            token_program.key,
-            token_mint.key,
-            user_ata.key,
-            mint_auth.key,
-            &[],
-            5 * LAMPORTS_PER_SOL,
-        )?,
-        &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
-        &[&[b"token_auth", &[mint_auth_bump]]],
-    )?;
+fn authorize_admin_signer(config_data: &ProgramConfig, signer: &AccountInfo) -> ProgramResult {
+    if !signer.is_signer {
+        log_account_check("signer", signer.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !config_data.admin_signers.contains(signer.key) {
+        msg!("Signer is not in the admin signer set");
+        return Err(IntroError::Unauthorized.into());
+    }
 
     Ok(())
 }
 
-pub fn initialize_token_mint(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+fn load_pending_action(
+    program_id: &Pubkey,
+    action_pda: &AccountInfo,
+) -> Result<PendingAction, ProgramError> {
+    if action_pda.owner != program_id {
+        log_account_check("action_pda", action_pda.key, AccountCheck::Owner);
+        return Err(ProgramError::IllegalOwner);
+    }
 
-    let initializer = next_account_info(account_info_iter)?;
-    let token_mint = next_account_info(account_info_iter)?;
-    let mint_auth = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
-    let sysvar_rent = next_account_info(account_info_iter)?;
+    let action_data = try_from_slice_unchecked::<PendingAction>(&action_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount)?;
 
-    let (mint_pda, mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
-    let (mint_auth_pda, _mint_auth_bump) =
-        Pubkey::find_program_address(&[b"token_auth"], program_id);
+    if !action_data.is_initialized() || action_data.discriminator != PendingAction::DISCRIMINATOR {
+        log_account_check("action_pda", action_pda.key, AccountCheck::Initialized);
+        return Err(IntroError::UninitializedAccount.into());
+    }
 
-    msg!("Token mint: {:?}", mint_pda);
-    msg!("Mint authority: {:?}", mint_auth_pda);
+    Ok(action_data)
+}
 
-    if mint_pda != *token_mint.key {
-        msg!("Incorrect token mint account");
-        return Err(IntroError::IncorrectAccountError.into());
+/// Rejects unless `authority` signed and is allowed to act as the config admin.
+fn authorize_admin(
+    program_id: &Pubkey,
+    config_data: &ProgramConfig,
+    authority: &AccountInfo,
+) -> ProgramResult {
+    if !authority.is_signer {
+        log_account_check("authority", authority.key, AccountCheck::Signer);
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if *token_program.key != TOKEN_PROGRAM_ID {
-        msg!("Incorrect token program");
-        return Err(IntroError::IncorrectAccountError.into());
+    if !config_data.is_initialized() {
+        msg!("Config is not initialized");
+        return Err(IntroError::UninitializedAccount.into());
     }
 
-    if *mint_auth.key != mint_auth_pda {
-        msg!("Incorrect mint auth account");
-        return Err(IntroError::IncorrectAccountError.into());
+    if !config_data.is_admin(authority.key, program_id) {
+        msg!("Signer is not the config admin");
+        return Err(IntroError::Unauthorized.into());
     }
 
-    if *system_program.key != SYSTEM_PROGRAM_ID {
-        msg!("Incorrect system program");
-        return Err(IntroError::IncorrectAccountError.into());
+    Ok(())
+}
+
+fn apply_config_params(config_data: &mut ProgramConfig, params: ConfigParams) -> ProgramResult {
+    if params.vesting_duration_secs < 0 || params.first_reply_window_secs < 0 {
+        msg!("Durations cannot be negative");
+        return Err(ProgramError::InvalidArgument);
     }
 
-    if *sysvar_rent.key != RENT_PROGRAM_ID {
-        msg!("Incorrect rent program");
-        return Err(IntroError::IncorrectAccountError.into());
+    if params.admin_signers.len() > ProgramConfig::MAX_ADMIN_SIGNERS
+        || params.admin_threshold as usize > params.admin_signers.len()
+    {
+        msg!("Invalid admin signer set or threshold");
+        return Err(IntroError::InvalidMultisig.into());
     }
 
-    let rent = Rent::get()?;
-    let rent_lamports = rent.minimum_balance(82);
+    if params.reply_reward_tiers.len() > ProgramConfig::MAX_REPLY_REWARD_TIERS
+        || params
+            .reply_reward_tiers
+            .windows(2)
+            .any(|pair| pair[0].below_len >= pair[1].below_len)
+    {
+        msg!(
+            "Reply reward tiers must be ascending, at most {}",
+            ProgramConfig::MAX_REPLY_REWARD_TIERS
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    // create the token mint PDA.
-    invoke_signed(
-        &system_instruction::create_account(
-            initializer.key,
-            token_mint.key,
-            rent_lamports,
-            82, // Size of the token mint account
-            token_program.key,
-        ),
-        // Accounts we're reading from or writing to
-        &[
-            initializer.clone(),
-            token_mint.clone(),
-            system_program.clone(),
-        ],
-        // Seeds for our token mint account
-        &[&[b"token_mint", &[mint_bump]]],
-    )?;
-    msg!("Created token mint account");
+    if params.welcome_bots.len() > ProgramConfig::MAX_WELCOME_BOTS {
+        msg!("At most {} welcome bots", ProgramConfig::MAX_WELCOME_BOTS);
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    // Initialize the mint account
-    invoke_signed(
-        &initialize_mint(
-            token_program.key,
-            token_mint.key,
-            mint_auth.key,
-            Option::None, // Freeze authority - we don't want anyone to be able to freeze!
-            9,            // Number of decimals
-        )?,
-        // Which accounts we're reading from or writing to
-        &[token_mint.clone(), sysvar_rent.clone(), mint_auth.clone()],
-        // The seeds for our token mint PDA
-        &[&[b"token_mint", &[mint_bump]]],
-    )?;
-    msg!("Initialized token mint");
+    if params.credential_collection.is_some()
+        && !(1..=ProgramConfig::MAX_CREDENTIAL_MULTIPLIER_BPS)
+            .contains(&params.credential_multiplier_bps)
+    {
+        msg!(
+            "Credential multiplier must be between 1 and {} bps",
+            ProgramConfig::MAX_CREDENTIAL_MULTIPLIER_BPS
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if params.protocol_fee_bps > 10_000 {
+        msg!("Protocol fee cannot exceed 10000 bps");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    config_data.gatekeeper_network = params.gatekeeper_network;
+    config_data.reveal_delay_slots = params.reveal_delay_slots;
+    config_data.credential_collection = params.credential_collection;
+    config_data.credential_multiplier_bps = params.credential_multiplier_bps;
+    config_data.price_feed = params.price_feed;
+    config_data.reference_price_cents = params.reference_price_cents;
+    config_data.max_price_staleness_slots = params.max_price_staleness_slots;
+    config_data.max_price_confidence_bps = params.max_price_confidence_bps;
+    config_data.wormhole_program = params.wormhole_program;
+    config_data.foreign_emitter_chain = params.foreign_emitter_chain;
+    config_data.foreign_emitter_address = params.foreign_emitter_address;
+    config_data.governance_program = params.governance_program;
+    config_data.governance = params.governance;
+    config_data.config_timelock_slots = params.config_timelock_slots;
+    config_data.admin_signers = params.admin_signers;
+    config_data.admin_threshold = params.admin_threshold;
+    config_data.vesting_threshold = params.vesting_threshold;
+    config_data.vesting_duration_secs = params.vesting_duration_secs;
+    config_data.badge_reply_threshold = params.badge_reply_threshold;
+    config_data.reply_reward_tiers = params.reply_reward_tiers;
+    config_data.first_reply_bonus = params.first_reply_bonus;
+    config_data.first_reply_window_secs = params.first_reply_window_secs;
+    config_data.welcome_bots = params.welcome_bots;
+    config_data.view_indexer = params.view_indexer;
+    config_data.identity_verifier = params.identity_verifier;
+    config_data.spam_classifier = params.spam_classifier;
+    config_data.spam_threshold = params.spam_threshold;
+    config_data.orphan_rent_destination = params.orphan_rent_destination;
+    config_data.protocol_fee_bps = params.protocol_fee_bps;
+    config_data.course_reward_cap = params.course_reward_cap;
 
     Ok(())
 }
+
+/// Reads the program config, falling back to defaults while it has not been created yet.
+fn load_config(
+    program_id: &Pubkey,
+    config_pda: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+    let (pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+
+    if pda != *config_pda.key {
+        log_account_check("config_pda", config_pda.key, AccountCheck::Pda);
+        return Err(IntroError::InvalidPDA.into());
+    }
+
+    if config_pda.owner != program_id {
+        return Ok(ProgramConfig::default());
+    }
+
+    try_from_slice_unchecked::<ProgramConfig>(&config_pda.data.borrow())
+        .map_err(|_| IntroError::UninitializedAccount.into())
+}
@@ -0,0 +1,76 @@
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, hash::hash, program::set_return_data};
+
+use crate::state::{CourseStats, ProgramConfig};
+
+/// Return data of `GetConfig`: the config account exactly as stored.
+pub type ConfigReturn = ProgramConfig;
+
+/// Return data of `GetCourseStats`: the course stats account exactly as stored.
+pub type CourseStatsReturn = CourseStats;
+
+/// Return data of `GetVersion`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct VersionReturn {
+    pub program_version: String,
+    /// Newest instruction envelope version `unpack` understands.
+    pub instruction_version: u8,
+    /// `ProgramConfig::DATA_VERSION` of this binary.
+    pub data_version: u32,
+}
+
+/// Return data of `ValidateIntroPayload`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Debug, PartialEq)]
+pub struct IntroValidationReturn {
+    /// The error `StudentIntro` would fail with, as `u64::from(ProgramError)`; 0 when it would
+    /// succeed.
+    pub error: u64,
+    /// Reward tokens the intro would earn when it succeeds.
+    pub reward: u64,
+}
+
+/// Return data of `ExportAccount`: an account in the canonical encoding of `export::canonical`.
+/// It names the account's type but not its address, which the caller already has.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Debug, PartialEq)]
+pub struct ExportReturn {
+    /// `VERSION` of the encodings the export uses.
+    pub version: u8,
+    /// The account's discriminator, naming its type.
+    pub kind: String,
+    /// Borsh encoding of the account's type.
+    pub data: Vec<u8>,
+    /// First 8 bytes, little-endian, of the SHA-256 of `(version, kind, data)` Borsh-encoded.
+    pub checksum: u64,
+}
+
+impl ExportReturn {
+    /// Version of the canonical encodings, bumped whenever one of them changes.
+    pub const VERSION: u8 = 4;
+
+    pub fn new(kind: String, data: Vec<u8>) -> Self {
+        let checksum = Self::compute_checksum(Self::VERSION, &kind, &data);
+        Self {
+            version: Self::VERSION,
+            kind,
+            data,
+            checksum,
+        }
+    }
+
+    /// Whether `checksum` matches the rest of the export.
+    pub fn is_intact(&self) -> bool {
+        self.checksum == Self::compute_checksum(self.version, &self.kind, &self.data)
+    }
+
+    fn compute_checksum(version: u8, kind: &str, data: &[u8]) -> u64 {
+        let digest = hash(&(version, kind, data).try_to_vec().unwrap()).to_bytes();
+        u64::from_le_bytes(digest[..8].try_into().unwrap())
+    }
+}
+
+/// Borsh-encodes `value` as the instruction's return data, which `simulateTransaction` reports
+/// alongside the logs.
+pub fn set<T: BorshSerialize>(value: &T) -> ProgramResult {
+    set_return_data(&value.try_to_vec()?);
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Borsh schemas of everything a client decodes or encodes: instruction data, accounts,
+//! events and return data. Non-Rust clients generate their decoders from `serialized()`, so
+//! a layout change shows up there as soon as it lands.
+
+use borsh::{schema::BorshSchemaContainer, BorshSchema, BorshSerialize};
+
+use crate::{
+    events::ProgramEvent,
+    instruction::StudentInstruction,
+    return_data::{ExportReturn, IntroValidationReturn, VersionReturn},
+    state::*,
+};
+
+/// One container per top-level type. `StudentInstruction` is the v1 payload after the
+/// variant byte (its variants are in wire order) and accounts are their stored layouts;
+/// types nested in these are defined inside the containers that use them.
+pub fn containers() -> Vec<BorshSchemaContainer> {
+    vec![
+        StudentInstruction::schema_container(),
+        ProgramEvent::schema_container(),
+        VersionReturn::schema_container(),
+        IntroValidationReturn::schema_container(),
+        ExportReturn::schema_container(),
+        StudentIntroState::schema_container(),
+        StudentReplyState::schema_container(),
+        ReplyCount::schema_container(),
+        ProgramConfig::schema_container(),
+        WalletLink::schema_container(),
+        WalletLinkCount::schema_container(),
+        ReplyCommitment::schema_container(),
+        ReplyPreparation::schema_container(),
+        DirectMessage::schema_container(),
+        DirectMessageCount::schema_container(),
+        ReplyVote::schema_container(),
+        ReplyScore::schema_container(),
+        Reputation::schema_container(),
+        TopReplies::schema_container(),
+        Course::schema_container(),
+        IntroEndorsement::schema_container(),
+        Certificate::schema_container(),
+        Session::schema_container(),
+        Attendance::schema_container(),
+        Quiz::schema_container(),
+        QuizSubmission::schema_container(),
+        TeamState::schema_container(),
+        TeamMembership::schema_container(),
+        ActivityFeed::schema_container(),
+        OfficeHoursQueue::schema_container(),
+        EmailHashEntry::schema_container(),
+        StudentIdEntry::schema_container(),
+        Enrollment::schema_container(),
+        CourseStats::schema_container(),
+        UserNonce::schema_container(),
+        StudentActivity::schema_container(),
+        ReplyTally::schema_container(),
+        IntroTally::schema_container(),
+        EditHistory::schema_container(),
+        IntroRegistry::schema_container(),
+        RegistryEntry::schema_container(),
+        IntroFeedPage::schema_container(),
+        QuestionFeed::schema_container(),
+        RewardHold::schema_container(),
+        ProfileCard::schema_container(),
+        Featured::schema_container(),
+        FeaturedDraw::schema_container(),
+        ReceivedMessage::schema_container(),
+        PendingConfigChange::schema_container(),
+        PendingWithdrawal::schema_container(),
+        IntroTranslation::schema_container(),
+        IntroProfile::schema_container(),
+        CategoryRegistry::schema_container(),
+        Category::schema_container(),
+        IntroSlug::schema_container(),
+        PendingAction::schema_container(),
+        VestingSchedule::schema_container(),
+        BadgeAward::schema_container(),
+    ]
+}
+
+/// `containers()` Borsh-encoded, as a `Vec<BorshSchemaContainer>`.
+pub fn serialized() -> Vec<u8> {
+    containers().try_to_vec().unwrap()
+}
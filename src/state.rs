@@ -1,19 +1,144 @@
-use borsh::{BorshDeserialize, BorshSerialize};
+//! Account layouts. Layouts only change by appending fields, and there is no instruction that
+//! rewrites or reallocates existing accounts; older accounts stay as they are and are read
+//! tolerantly:
+//!
+//! - Accounts allocated at an `ACCOUNT_LEN` (configs, courses, ...) have headroom, so an
+//!   appended field lands in zeroed bytes and reads as its default.
+//! - Intros are allocated at `ACCOUNT_LEN` too, but their text can fill it to the last byte, so
+//!   every field after the message reads as its default wherever the account runs out, even
+//!   partway through the field. Writing a field such an intro has no room for fails with
+//!   `InvalidDataLength` (see `store_intro`) until the writer shortens the text.
+//! - Exactly-sized accounts that gained fields (replies, `ReplyScore`) have hand-written
+//!   `BorshDeserialize` impls reading missing trailing fields as their defaults. Writers clip
+//!   the encoding to the account's length, so an older account never holds the new fields, and
+//!   instructions that need one (such as `MarkReplyRead`) refuse older accounts.
+//!
+//! A change this can't express, such as reordering, widening or removing a field, needs a
+//! per-account migration instruction shipped with it, and a bump of
+//! `ProgramConfig::DATA_VERSION` so nothing runs until it has been cranked over every account.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use solana_program::{
+    hash::hashv,
+    native_token::LAMPORTS_PER_SOL,
     program_pack::{IsInitialized, Sealed},
     pubkey::Pubkey,
 };
 
-#[derive(BorshDeserialize, BorshSerialize)]
+use crate::{format, instruction::ConfigParams, lang};
+
+/// Written into `StudentReplyState::replier` for replies materialized from a commitment.
+pub const ANONYMOUS_REPLIER: Pubkey = Pubkey::new_from_array([0xff; 32]);
+
+/// Discriminator left in a closed account's otherwise zeroed data. Until the runtime purges the
+/// account at the end of the transaction it can be topped back up, and must not load as anything.
+pub const CLOSED_ACCOUNT_DISCRIMINATOR: &str = "closed";
+
+/// Most students an office-hours queue holds at once.
+pub const QUEUE_CAPACITY: usize = 32;
+
+#[derive(BorshSchema, BorshSerialize)]
 pub struct StudentIntroState {
     pub discriminator: String,
     pub is_initialized: bool,
     pub writer: Pubkey,
     pub name: String,
     pub message: String,
+    pub visibility: u8,
+    /// Course whose enrolled students may reply while `visibility` is course-only.
+    pub course: Option<Pubkey>,
+    /// Unix timestamp after which the intro stops accepting replies and may be closed.
+    pub expires_at: Option<i64>,
+    /// Unix timestamp of creation; imported intros keep the original one. 0 on older intros.
+    pub created_at: i64,
+    /// Current `SetSlug` handle, empty when the intro has none (including older intros).
+    pub slug: String,
+    /// `Category` account the writer filed the intro under.
+    pub category: Option<Pubkey>,
+    /// `LAYOUT_VERSION` on intros created with every field below; 0 on older intros, which read
+    /// the fields they lack as defaults.
+    pub layout_version: u8,
+    /// ISO 639-1 language tag, `lang::UNSPECIFIED` when untagged.
+    pub lang: [u8; 2],
+    /// `format::PLAIN` or `format::MARKDOWN`; edits are held to the same format.
+    pub format: u8,
+    /// The writer's standing in the intro's course, one of the `STATUS_*` values, set by the
+    /// course teacher.
+    pub status: u8,
+    /// Views batched in by the config's `view_indexer`.
+    pub views: u64,
+    /// Epoch of the last `RecordViews` batch.
+    pub views_epoch: u64,
+    /// Bitmask of the indexes the intro is in, bit `1 << target` per `IndexExisting` target.
+    pub indexed: u8,
+    /// Set by `VerifyIdentity` once the config's `identity_verifier` attested to the writer's
+    /// identity.
+    pub verified: bool,
+    /// 0–255 score from the config's `spam_classifier`, 0 until it scores the intro.
+    pub spam_score: u8,
+    /// `KIND_INTRODUCTION` or `KIND_QUESTION`, chosen at creation.
+    pub kind: u8,
+    /// Unix timestamp at which the writer selected the best answer, 0 while none is selected.
+    pub answered_at: i64,
+    /// Reply index of the best answer; only meaningful once `answered_at` is set.
+    pub best_answer: u64,
+    /// Set by the first `PrepareReply` made while the intro had no replies, which then holds
+    /// the first-reply position in place of whichever reply lands at index 0.
+    pub first_reply_claimed: bool,
+}
+
+// Intros are padded with zeros to `ACCOUNT_LEN`, so a field appended after `message` reads as
+// its default on intros from before it. An intro whose text nearly fills the account may end
+// partway through one; the zeros left there read as the default too.
+impl BorshDeserialize for StudentIntroState {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        Ok(Self {
+            discriminator: String::deserialize(buf)?,
+            is_initialized: bool::deserialize(buf)?,
+            writer: Pubkey::deserialize(buf)?,
+            name: String::deserialize(buf)?,
+            message: String::deserialize(buf)?,
+            visibility: read_appended(buf)?,
+            course: read_appended(buf)?,
+            expires_at: read_appended(buf)?,
+            created_at: read_appended(buf)?,
+            slug: read_appended(buf)?,
+            category: read_appended(buf)?,
+            layout_version: read_appended(buf)?,
+            lang: read_appended(buf)?,
+            format: read_appended(buf)?,
+            status: read_appended(buf)?,
+            views: read_appended(buf)?,
+            views_epoch: read_appended(buf)?,
+            indexed: read_appended(buf)?,
+            verified: read_appended(buf)?,
+            spam_score: read_appended(buf)?,
+            kind: read_appended(buf)?,
+            answered_at: read_appended(buf)?,
+            best_answer: read_appended(buf)?,
+            first_reply_claimed: read_appended(buf)?,
+        })
+    }
 }
 
-#[derive(BorshDeserialize, BorshSerialize)]
+/// Reads a field appended to a zero-padded layout, as its default when `buf` holds only zeros
+/// too few to encode it. The defaults of appended fields are what their zero encoding means.
+fn read_appended<T: BorshDeserialize + Default>(buf: &mut &[u8]) -> std::io::Result<T> {
+    let mut bytes = *buf;
+    match T::deserialize(&mut bytes) {
+        Ok(value) => {
+            *buf = bytes;
+            Ok(value)
+        }
+        Err(_) if buf.iter().all(|byte| *byte == 0) => {
+            *buf = &[];
+            Ok(T::default())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[derive(BorshSchema, BorshSerialize)]
 pub struct StudentReplyState {
     pub discriminator: String,
     pub is_initialized: bool,
@@ -21,63 +146,1945 @@ pub struct StudentReplyState {
     pub replier: Pubkey,
     pub name: String,
     pub message: String,
+    /// Posted by an allowlisted welcome bot: never rewarded and kept off leaderboards.
+    pub is_bot: bool,
+    pub quote: Option<ReplyQuote>,
+    /// ISO 639-1 language tag, `lang::UNSPECIFIED` when untagged.
+    pub lang: [u8; 2],
+    /// `format::PLAIN` or `format::MARKDOWN`.
+    pub format: u8,
+    /// Up to `MAX_LINKS` https URLs extracted from the message when it was posted.
+    pub links: Vec<String>,
+    /// Set by the intro writer with `MarkReplyRead`.
+    pub read: bool,
+    /// 0–255 score from the config's `spam_classifier`, 0 until it scores the reply.
+    pub spam_score: u8,
+}
+
+/// Part of an earlier reply on the same intro that a reply quotes. The excerpt is checked
+/// against the quoted message when the reply is posted; clients render
+/// `message[excerpt_start..excerpt_start + excerpt_len]` and can compare it to the hash.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct ReplyQuote {
+    pub reply: Pubkey,
+    pub excerpt_start: u16,
+    pub excerpt_len: u16,
+    /// SHA-256 of the excerpt bytes.
+    pub excerpt_hash: [u8; 32],
+}
+
+// Replies are allocated at their exact size, so ones created before `is_bot` end right after
+// the message and read as human replies; ones created before `quote` end after `is_bot`, ones
+// created before `lang` after the quote, ones created before `format` after the tag, ones
+// created before `links` after the format, ones created before `read` after the links, and
+// ones created before `spam_score` after the read receipt.
+impl BorshDeserialize for StudentReplyState {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        Ok(Self {
+            discriminator: String::deserialize(buf)?,
+            is_initialized: bool::deserialize(buf)?,
+            intro: Pubkey::deserialize(buf)?,
+            replier: Pubkey::deserialize(buf)?,
+            name: String::deserialize(buf)?,
+            message: String::deserialize(buf)?,
+            is_bot: if buf.is_empty() {
+                false
+            } else {
+                bool::deserialize(buf)?
+            },
+            quote: if buf.is_empty() {
+                None
+            } else {
+                Option::<ReplyQuote>::deserialize(buf)?
+            },
+            lang: if buf.is_empty() {
+                lang::UNSPECIFIED
+            } else {
+                <[u8; 2]>::deserialize(buf)?
+            },
+            format: if buf.is_empty() {
+                format::PLAIN
+            } else {
+                u8::deserialize(buf)?
+            },
+            links: if buf.is_empty() {
+                Vec::new()
+            } else {
+                Vec::<String>::deserialize(buf)?
+            },
+            read: !buf.is_empty() && bool::deserialize(buf)?,
+            spam_score: if buf.is_empty() {
+                0
+            } else {
+                u8::deserialize(buf)?
+            },
+        })
+    }
 }
 
-#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
 pub struct ReplyCount {
     pub discriminator: String,
     pub is_initialized: bool,
     pub counter: u64,
 }
 
-impl Sealed for StudentIntroState {}
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Default)]
+pub struct ProgramConfig {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    /// When set, `StudentIntro` requires a Civic gateway token issued by this network.
+    pub gatekeeper_network: Option<Pubkey>,
+    /// Minimum number of slots between `CommitReply` and `RevealReply`.
+    pub reveal_delay_slots: u64,
+    /// Verified collection whose holders earn boosted intro and reply rewards.
+    pub credential_collection: Option<Pubkey>,
+    /// Reward multiplier for credential holders, in basis points (10_000 = 1x).
+    pub credential_multiplier_bps: u16,
+    /// When set, rewards are rescaled against this Pyth price feed for the reward token.
+    pub price_feed: Option<Pubkey>,
+    /// Token price, in US cents, at which the base reward amounts are correct.
+    pub reference_price_cents: u64,
+    pub max_price_staleness_slots: u64,
+    pub max_price_confidence_bps: u16,
+    /// Wormhole core bridge used for cross-chain mirroring; `None` disables it.
+    pub wormhole_program: Option<Pubkey>,
+    pub foreign_emitter_chain: u16,
+    pub foreign_emitter_address: [u8; 32],
+    /// SPL Governance program whose `governance` account may act as admin through proposals.
+    pub governance_program: Option<Pubkey>,
+    pub governance: Pubkey,
+    /// Slots a proposed config change must wait before it can be executed; 0 disables the timelock.
+    pub config_timelock_slots: u64,
+    /// When `admin_threshold` is non-zero, admin actions need this many approvals from
+    /// `admin_signers` and execute through the `["multisig"]` authority PDA.
+    pub admin_signers: Vec<Pubkey>,
+    pub admin_threshold: u8,
+    /// Nominated by `NominateAdmin`; becomes `admin` only once it signs `AcceptAdmin`.
+    pub pending_admin: Option<Pubkey>,
+    /// Bonus rewards above this amount vest linearly over `vesting_duration_secs`; 0 disables.
+    pub vesting_threshold: u64,
+    pub vesting_duration_secs: i64,
+    /// Replies an intro needs before anyone can mint its writer a milestone badge; 0 disables.
+    pub badge_reply_threshold: u64,
+    /// Reply rewards by message length, ascending by `below_len`. A reply earns the first tier
+    /// its message is shorter than, or the flat reply reward past the last one; empty disables.
+    pub reply_reward_tiers: Vec<ReplyRewardTier>,
+    /// Extra reward for the first reply to an intro posted within `first_reply_window_secs` of
+    /// the intro's creation; 0 disables.
+    pub first_reply_bonus: u64,
+    pub first_reply_window_secs: i64,
+    /// Wallets whose replies are accepted but never rewarded, e.g. automated welcome messages.
+    pub welcome_bots: Vec<Pubkey>,
+    /// Reward mint currently paid out; bumped by `MigrateMint`. 0 is the original mint.
+    pub mint_version: u8,
+    /// Off-chain indexer allowed to batch view counts into intros with `RecordViews`.
+    pub view_indexer: Option<Pubkey>,
+    /// One bit per instruction variant, set when `SetInstructionEnabled` switched it off.
+    /// Older configs read as all zero, leaving everything enabled.
+    pub disabled_instructions: [u8; 32],
+    /// Key whose Ed25519 attestations `VerifyIdentity` accepts; none verifies while unset.
+    pub identity_verifier: Option<Pubkey>,
+    /// Key allowed to score intros and replies with `SetSpamScore`. While set, `StudentIntro`
+    /// and `ReplyIntro` read the author's `RewardHold` after their credential accounts.
+    pub spam_classifier: Option<Pubkey>,
+    /// Scores above this hold the author's rewards until an admin reviews them.
+    pub spam_threshold: u8,
+    /// Account layout version the on-chain data has been migrated to. Set to `DATA_VERSION`
+    /// at init and by `CompleteMigration`; configs from before the guard read 0.
+    pub data_version: u32,
+    /// Receives the rent `GcOrphans` recovers; the admin when unset.
+    pub orphan_rent_destination: Option<Pubkey>,
+    /// Cut of every `TipReply` paid to the treasury, in basis points; 0 takes nothing.
+    pub protocol_fee_bps: u16,
+    /// Most each course's sessions and quizzes may mint per epoch, across all its students, so
+    /// a teacher can't mint without limit; 0, as on older configs, mints none.
+    pub course_reward_cap: u64,
+}
 
-impl Sealed for ReplyCount {}
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct ReplyRewardTier {
+    pub below_len: u32,
+    /// Base units, before price scaling and the credential multiplier.
+    pub amount: u64,
+}
 
-impl IsInitialized for StudentIntroState {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
-    }
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct ActionAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
 }
 
-impl IsInitialized for StudentReplyState {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
-    }
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct PendingAction {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub action_id: u64,
+    pub proposer: Pubkey,
+    pub approvals: Vec<Pubkey>,
+    pub accounts: Vec<ActionAccount>,
+    pub instruction_data: Vec<u8>,
 }
 
-impl IsInitialized for ReplyCount {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
-    }
+/// Per-beneficiary vesting state; the PDA also owns the vault token account holding the tokens.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct VestingSchedule {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub beneficiary: Pubkey,
+    /// Vested under earlier schedules and claimable regardless of the current one.
+    pub unlocked: u64,
+    pub total: u64,
+    pub released: u64,
+    pub start_ts: i64,
+    pub duration_secs: i64,
 }
 
-impl StudentIntroState {
-    pub const DISCRIMINATOR: &'static str = "intro";
+/// Marks that an intro's milestone badge was minted so it can only happen once.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct BadgeAward {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    pub writer: Pubkey,
+    pub slot: u64,
+}
 
-    pub fn get_account_size(name: String, message: String) -> usize {
-        return (4 + StudentIntroState::DISCRIMINATOR.len())
-            + 1
-            + 32
-            + (4 + name.len())
-            + (4 + message.len());
-    }
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct PendingConfigChange {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub proposer: Pubkey,
+    pub proposed_slot: u64,
+    pub eta_slot: u64,
+    pub params: ConfigParams,
 }
 
-impl StudentReplyState {
-    pub const DISCRIMINATOR: &'static str = "reply";
+/// The writer's own version of their intro's message in another language, at
+/// `[intro, lang]`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct IntroTranslation {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    pub lang: [u8; 2],
+    pub message: String,
+}
 
-    pub fn get_account_size(name: String, message: String) -> usize {
-        return (4 + StudentReplyState::DISCRIMINATOR.len())
-            + 1
-            + 32
-            + 32
-            + (4 + name.len())
-            + (4 + message.len());
+/// Optional personal details the writer attaches to their intro, at `[intro, "profile"]`. Every
+/// field is stored as given, but only those whose bit is set in `public_fields` go out in
+/// `ProfileUpdated` events; the rest are left to clients that fetch the account directly.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct IntroProfile {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    /// Free text such as "they/them"; empty when unset.
+    pub pronouns: String,
+    /// IANA time zone name such as "Europe/Istanbul"; empty when unset.
+    pub timezone: String,
+    /// ISO 3166-1 alpha-2 code in upper case, `[0, 0]` when unset.
+    pub country: [u8; 2],
+    /// `PRONOUNS_PUBLIC | TIMEZONE_PUBLIC | COUNTRY_PUBLIC` bits.
+    pub public_fields: u8,
+}
+
+/// Number of categories created so far; category `i` lives at `["category", i]`, so clients
+/// can enumerate them without a scan.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct CategoryRegistry {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub count: u64,
+}
+
+/// An admin-created track, such as "DeFi", that intros can be filed under.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Category {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub index: u64,
+    pub name: String,
+    /// Open intros currently filed under the category.
+    pub intro_count: u64,
+}
+
+/// Registry entry at `["slug", slug]`. When its intro moves to another slug the entry stays
+/// behind as a redirect to the new one, so shared links keep resolving for a while.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct IntroSlug {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    pub slug: String,
+    /// Slug entry the intro moved to; `None` while this is the intro's current slug.
+    pub redirect_to: Option<Pubkey>,
+    /// Last epoch in which the redirect holds the slug; afterwards anyone may claim it.
+    pub redirect_until_epoch: u64,
+}
+
+/// A treasury withdrawal waiting out `config_timelock_slots`; at most one at a time.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct PendingWithdrawal {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub proposer: Pubkey,
+    /// Token account of the current reward mint that receives the tokens.
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub proposed_slot: u64,
+    pub eta_slot: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct ReceivedMessage {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub emitter_chain: u16,
+    pub sequence: u64,
+    pub reply: Pubkey,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct ReplyCommitment {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    pub hash: [u8; 32],
+    pub committed_slot: u64,
+}
+
+/// A reply `replier` has drafted but not posted yet, at `[intro, replier, "prepared"]`.
+/// `FinalizeReply` posts the text hashing to `content_hash` and closes it; the reply counts as
+/// written when it was prepared for the first-reply bonus, however late it lands.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct ReplyPreparation {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    pub replier: Pubkey,
+    /// `ReplyPreparation::hash_reply` of the name and message.
+    pub content_hash: [u8; 32],
+    pub prepared_slot: u64,
+    pub prepared_at: i64,
+    /// Prepared before the intro had any reply or other first-reply claim; see
+    /// `StudentIntroState::first_reply_claimed`.
+    pub first: bool,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct WalletLink {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub primary: Pubkey,
+    pub linked: Pubkey,
+}
+
+/// How many wallets are linked to a primary, so a primary can't itself be linked elsewhere.
+/// Primaries linked before it existed read as having none.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct WalletLinkCount {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub links: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct DirectMessage {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub index: u64,
+    pub nonce: [u8; 24],
+    pub ciphertext: Vec<u8>,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct DirectMessageCount {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub counter: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct ReplyVote {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub reply: Pubkey,
+    pub voter: Pubkey,
+    pub upvote: bool,
+}
+
+#[derive(BorshSchema, BorshSerialize)]
+pub struct ReplyScore {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub reply: Pubkey,
+    pub score: i64,
+    /// How many of `ReplyScore::MILESTONES` have been credited to the replier's reputation.
+    /// Scores created before reputation existed have no room for it and read as 0.
+    pub milestones: u8,
+}
+
+impl BorshDeserialize for ReplyScore {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        Ok(Self {
+            discriminator: String::deserialize(buf)?,
+            is_initialized: bool::deserialize(buf)?,
+            reply: Pubkey::deserialize(buf)?,
+            score: i64::deserialize(buf)?,
+            milestones: if buf.is_empty() {
+                0
+            } else {
+                u8::deserialize(buf)?
+            },
+        })
     }
 }
 
-impl ReplyCount {
-    pub const DISCRIMINATOR: &'static str = "counter";
-    pub const SIZE: usize = (4 + ReplyCount::DISCRIMINATOR.len()) + 1 + 8;
+/// A replier's standing, at `[replier, "reputation"]`, built up as their replies reach vote
+/// milestones.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Reputation {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub user: Pubkey,
+    pub points: u64,
+    /// Reward tokens accrued from milestones and not yet claimed.
+    pub claimable: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct RankedReply {
+    pub reply: Pubkey,
+    pub score: i64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct TopReplies {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    /// Highest score first, at most `TopReplies::MAX_ENTRIES` long.
+    pub entries: Vec<RankedReply>,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Course {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub teacher: Pubkey,
+    pub name: String,
+    /// Set by the teacher to stop reward minting for replies scoped to this course. Stored
+    /// inverted so courses created before the field existed read as enabled.
+    pub rewards_disabled: bool,
+    /// Scoping an intro to the course needs a student ID hash no other wallet has registered
+    /// in it. Fixed when the course is created.
+    pub requires_student_id: bool,
+    /// What a graduated student needs before `ClaimCertificate`; none on older courses.
+    pub certificate: CertificateRequirements,
+    /// Unix timestamp the course ends at; 0 while it has no end date.
+    pub ends_at: i64,
+    /// Once the course has ended, `AnonymizeReplies` may strip repliers and names from replies
+    /// to intros scoped to it.
+    pub anonymize_replies: bool,
+    /// Rules the text of intros scoped to the course and of replies to its course-only intros
+    /// must follow; older courses allow anything.
+    pub character_policy: CharacterPolicy,
+    /// Intros the teacher pinned to the top of the course board, oldest pin first; at most
+    /// `MAX_PINNED_INTROS`.
+    pub pinned_intros: Vec<Pubkey>,
+    /// Epoch `rewards_minted` counts, against the config's `course_reward_cap`.
+    pub reward_epoch: u64,
+    /// Session and quiz rewards minted in `reward_epoch`.
+    pub rewards_minted: u64,
+}
+
+/// Course-level limits on the characters intro and reply text may use, checked by
+/// `format::verify_character_policy`. The default allows anything.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CharacterPolicy {
+    pub ascii_only: bool,
+    /// Rejects text with a URL, of any scheme or starting with `www.`.
+    pub disallow_urls: bool,
+    /// Most emoji a name and message may hold between them; `None` for no limit.
+    pub max_emoji: Option<u16>,
+}
+
+/// Course-level conditions for claiming a completion certificate, on top of the teacher
+/// marking the intro graduated.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CertificateRequirements {
+    /// Replies the student's intro must have received.
+    pub min_replies: u64,
+    /// Whether the teacher must have endorsed the intro with `EndorseIntro`.
+    pub requires_endorsement: bool,
+}
+
+/// The course teacher's endorsement of an intro, at `[course, intro, "endorsement"]`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct IntroEndorsement {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub course: Pubkey,
+    pub intro: Pubkey,
+    pub slot: u64,
+}
+
+/// A claimed completion certificate, at `[course, intro, "certificate"]`; its NFT mint is
+/// `[certificate, "mint"]`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Certificate {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub course: Pubkey,
+    pub intro: Pubkey,
+    pub student: Pubkey,
+    pub mint: Pubkey,
+    /// Epoch the certificate was claimed in, also written into the NFT metadata.
+    pub completion_epoch: u64,
+}
+
+/// A teacher-opened attendance session, at `[course, "session", session_id]` with the id as
+/// little-endian bytes. Check-ins are accepted from `opens_at` up to, not including,
+/// `closes_at`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Session {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub course: Pubkey,
+    pub session_id: u64,
+    pub opens_at: i64,
+    pub closes_at: i64,
+    /// Reward base units minted to each student who checks in; 0 for none.
+    pub reward: u64,
+    pub check_ins: u64,
+}
+
+/// A student's check-in to a session, at `[session, student, "attendance"]`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Attendance {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub session: Pubkey,
+    pub student: Pubkey,
+    pub checked_in_at: i64,
+}
+
+/// A teacher's quiz, at `[course, "quiz", quiz_id]` with the id as little-endian bytes.
+/// Submissions are accepted until `closes_at`; the answer stays hidden behind `answer_hash`
+/// until the teacher reveals it.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Quiz {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub course: Pubkey,
+    pub quiz_id: u64,
+    pub closes_at: i64,
+    /// `Quiz::commit_answer(answer, salt)`, fixed when the quiz is created.
+    pub answer_hash: [u8; 32],
+    /// Reward base units minted to each correct submitter; 0 for none.
+    pub reward: u64,
+    pub revealed: bool,
+    pub submissions: u64,
+    pub correct: u64,
+}
+
+/// A student's hashed answer to a quiz, at `[quiz, student, "quiz_answer"]`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct QuizSubmission {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub quiz: Pubkey,
+    pub student: Pubkey,
+    pub answer_hash: [u8; 32],
+    pub graded: bool,
+    pub correct: bool,
+}
+
+/// A project team, at `["team", name]`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct TeamState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub name: String,
+    pub creator: Pubkey,
+    /// In join order, at most `TeamState::MAX_MEMBERS` long.
+    pub members: Vec<Pubkey>,
+}
+
+/// The one team a student is on, at `[student, "team_member"]`, so their replies can be
+/// attributed to it without scanning every team.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct TeamMembership {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub team: Pubkey,
+    pub student: Pubkey,
+}
+
+/// One instruction in a user's activity feed.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ActivityEntry {
+    /// `StudentInstruction` variant byte.
+    pub action: u8,
+    /// The instruction's second account: the intro, course or other account it acted on.
+    pub target: Pubkey,
+    pub slot: u64,
+}
+
+/// A user's most recent instructions, at `[user, "feed"]`: a ring buffer that overwrites the
+/// oldest entry once full.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct ActivityFeed {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub user: Pubkey,
+    /// Slot of the oldest entry.
+    pub head: u8,
+    pub len: u8,
+    pub entries: [ActivityEntry; ActivityFeed::CAPACITY],
+}
+
+/// The newest question intros, at `["questions"]`: a ring buffer that overwrites the oldest
+/// entry once full, so clients can list open questions without scanning every intro.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct QuestionFeed {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    /// Slot of the oldest entry.
+    pub head: u8,
+    pub len: u8,
+    pub entries: [FeedEntry; QuestionFeed::CAPACITY],
+}
+
+/// A course's office-hours help queue, at `[course, "queue"]`: a ring buffer of students in
+/// the order they joined.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct OfficeHoursQueue {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub course: Pubkey,
+    /// Slot of the student at the front of the queue.
+    pub head: u8,
+    pub len: u8,
+    pub slots: [Pubkey; QUEUE_CAPACITY],
+}
+
+/// Claims a salted email hash for one wallet across the whole program, at `["email", hash]`.
+/// Clients hash the lower-cased address with the deployment's published salt.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct EmailHashEntry {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    /// Unix timestamp of registration.
+    pub registered_at: i64,
+}
+
+/// Claims a student ID hash within a course for one wallet, at `["sid", course, hash]`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct StudentIdEntry {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub course: Pubkey,
+    pub student: Pubkey,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Enrollment {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub course: Pubkey,
+    pub student: Pubkey,
+}
+
+/// Cumulative per-course counters, so instructors can read a dashboard without an indexer.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct CourseStats {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub course: Pubkey,
+    /// Intros scoped to the course.
+    pub intros: u64,
+    /// Replies posted on course-scoped intros.
+    pub replies: u64,
+    pub epoch: u64,
+    /// Distinct enrolled students who posted during `epoch`.
+    pub active_students: u64,
+}
+
+/// Last epoch an enrolled student was active in, so each student is counted once per epoch.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct StudentActivity {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub last_active_epoch: u64,
+}
+
+/// How many rewarded replies a wallet has posted on one intro, for decaying repeat rewards.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct ReplyTally {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub replies: u64,
+}
+
+/// Whether a writer's intro was already rewarded. It outlives the intro, so closing an intro
+/// and posting another one earns nothing the second time.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct IntroTally {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub rewarded_intros: u64,
+}
+
+/// Per-user replay guard for signed payloads that someone else submits on the user's behalf.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct UserNonce {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub user: Pubkey,
+    /// The only nonce a payload signed by `user` is accepted with next.
+    pub next_nonce: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone)]
+pub struct EditRecord {
+    pub slot: u64,
+    /// Hash of the message text that this edit replaced.
+    pub message_hash: [u8; 32],
+    /// Number of replies the intro had when it was edited.
+    pub reply_count: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct EditHistory {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub intro: Pubkey,
+    pub edit_count: u64,
+    /// Oldest first; only the latest `EditHistory::MAX_ENTRIES` edits are kept.
+    pub entries: Vec<EditRecord>,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct IntroRegistry {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub count: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct RegistryEntry {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub index: u64,
+    pub intro: Pubkey,
+}
+
+/// One page of the global feed of new intros, at `[b"intro_feed", page]`: the intros at
+/// registry indexes `page * CAPACITY..`, slot `index % CAPACITY`, so the newest are on page
+/// `(registry count - 1) / CAPACITY`. Closed intros stay listed, and pages begun after an
+/// upgrade leave default entries ahead of their first intro.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct IntroFeedPage {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub page: u64,
+    /// Slots filled so far, counting any skipped ones ahead of them.
+    pub len: u8,
+    pub entries: [FeedEntry; IntroFeedPage::CAPACITY],
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct FeedEntry {
+    pub intro: Pubkey,
+    /// Slot the intro was registered in.
+    pub slot: u64,
+}
+
+/// Set on an author at `[author, "reward_hold"]` when the spam classifier scores one of their
+/// intros or replies above the config's `spam_threshold`. While `held`, their intros and replies
+/// earn nothing; an admin lifts it with `ReleaseRewardHold` after reviewing `target`.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct RewardHold {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub author: Pubkey,
+    pub held: bool,
+    /// The intro or reply whose score last placed the hold, and that score.
+    pub target: Pubkey,
+    pub score: u8,
+}
+
+/// Compact summary of a student at `[student, "card"]`, so wallets and other programs can
+/// render a profile from one small account. Every field is fixed-size and sits at a fixed
+/// offset (see the `*_OFFSET` constants) for RPC `memcmp` filters. `SyncProfileCard` rebuilds
+/// it from the intro, reputation and badge accounts; anyone may call it after they change.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct ProfileCard {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub student: Pubkey,
+    pub intro: Pubkey,
+    /// The intro's name in UTF-8, cut at a character boundary and zero-padded.
+    pub name: [u8; ProfileCard::NAME_LEN],
+    /// The intro's current slug, zero-padded; all zero when it has none.
+    pub handle: [u8; IntroSlug::MAX_LEN],
+    /// `Reputation::points`, 0 before the student has a reputation.
+    pub reputation: u64,
+    /// `BADGE_*` bits.
+    pub badges: u64,
+    /// Slot of the last `SyncProfileCard`.
+    pub synced_slot: u64,
+}
+
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct Featured {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub epoch: u64,
+    pub intro: Pubkey,
+    pub writer: Pubkey,
+    pub selected_slot: u64,
+}
+
+/// The draw behind an epoch's `Featured` intro, at `["featured_draw"]`. `SelectFeatured`
+/// commits to a slot before its hash exists and draws from that hash once it does, so no
+/// cranker can pick the moment of the draw.
+#[derive(BorshDeserialize, BorshSchema, BorshSerialize)]
+pub struct FeaturedDraw {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub epoch: u64,
+    /// The draw uses the hash of the first slot at or after this one.
+    pub draw_slot: u64,
+    /// Registry size when committed; intros registered later are not drawn this epoch.
+    pub registry_count: u64,
+}
+
+impl Sealed for StudentIntroState {}
+
+impl Sealed for ReplyCount {}
+
+impl IsInitialized for StudentIntroState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for StudentReplyState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ProgramConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for WalletLink {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for WalletLinkCount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReplyPreparation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReplyCommitment {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for DirectMessage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for DirectMessageCount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReplyVote {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReplyScore {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for TopReplies {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Course {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Enrollment {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for CourseStats {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for UserNonce {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for StudentActivity {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReplyTally {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroTally {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for EditHistory {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroRegistry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for RegistryEntry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IntroFeedPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for RewardHold {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ProfileCard {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for QuestionFeed {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Featured {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for FeaturedDraw {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReceivedMessage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for PendingConfigChange {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for BadgeAward {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for VestingSchedule {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for PendingAction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReplyCount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl StudentIntroState {
+    pub const DISCRIMINATOR: &'static str = "intro";
+    /// Every intro is allocated at this size, leaving room for edits and appended fields.
+    pub const ACCOUNT_LEN: usize = 1000;
+
+    pub const VISIBILITY_PUBLIC: u8 = 0;
+    pub const VISIBILITY_COURSE_ONLY: u8 = 1;
+    pub const VISIBILITY_PRIVATE: u8 = 2;
+
+    pub const STATUS_ACTIVE: u8 = 0;
+    pub const STATUS_GRADUATED: u8 = 1;
+    pub const STATUS_WITHDRAWN: u8 = 2;
+
+    pub const KIND_INTRODUCTION: u8 = 0;
+    /// Opens the best-answer flow: see `SelectBestAnswer`.
+    pub const KIND_QUESTION: u8 = 1;
+
+    /// How long a question keeps taking replies after its best answer was selected.
+    pub const ANSWER_GRACE_SECS: i64 = 24 * 60 * 60;
+
+    /// `IndexExisting` target for the registry and the intro feed; its `indexed` bit is
+    /// `1 << INDEX_REGISTRY`.
+    pub const INDEX_REGISTRY: u8 = 0;
+
+    /// `layout_version` of intros written with every field in the Borsh layout.
+    pub const LAYOUT_VERSION: u8 = 1;
+
+    /// Space an intro with this text needs, counting a full-length slug and a category so later
+    /// changes always fit.
+    pub fn get_account_size(name: &str, message: &str) -> usize {
+        (4 + StudentIntroState::DISCRIMINATOR.len())
+            + 1
+            + 32
+            + (4 + name.len())
+            + (4 + message.len())
+            + 1
+            + (1 + 32)
+            + (1 + 8)
+            + 8
+            + (4 + IntroSlug::MAX_LEN)
+            + (1 + 32)
+            + 1
+            + 2
+            + 1
+            + 1
+            + 8
+            + 8
+            + 1
+            + 1
+            + 1
+            + 1
+            + 8
+            + 8
+            + 1
+    }
+}
+
+impl StudentReplyState {
+    pub const DISCRIMINATOR: &'static str = "reply";
+
+    pub const MAX_LINKS: usize = 3;
+    /// Co-recipients a `ReplyIntro` reward can be split with.
+    pub const MAX_REWARD_SPLITS: usize = 4;
+
+    /// Size of a reply without a quote or links; a quoting one needs `ReplyQuote::SIZE` more,
+    /// and one with links `links_size` more.
+    pub fn get_account_size(name: &str, message: &str) -> usize {
+        StudentReplyState::required_space(name.len(), message.len(), false, &[])
+    }
+
+    /// Exact size of a reply with this much text, a quote if `quoted`, and links of
+    /// `link_lens` bytes each (what `format::extract_links` finds, at most `MAX_LINKS`).
+    /// Replies are allocated unpadded, so this is the space to fund rent for.
+    pub const fn required_space(
+        name_len: usize,
+        message_len: usize,
+        quoted: bool,
+        link_lens: &[usize],
+    ) -> usize {
+        let mut space = (4 + StudentReplyState::DISCRIMINATOR.len())
+            + 1
+            + 32
+            + 32
+            + (4 + name_len)
+            + (4 + message_len)
+            + 1
+            + 1
+            + 2
+            + 1
+            + 4
+            + 1
+            + 1;
+        if quoted {
+            space += ReplyQuote::SIZE;
+        }
+
+        let mut i = 0;
+        while i < link_lens.len() {
+            space += 4 + link_lens[i];
+            i += 1;
+        }
+        space
+    }
+
+    pub fn links_size(links: &[String]) -> usize {
+        links.iter().map(|link| 4 + link.len()).sum()
+    }
+}
+
+impl ReplyQuote {
+    pub const MAX_EXCERPT_LEN: usize = 140;
+    pub const SIZE: usize = 32 + 2 + 2 + 32;
+}
+
+impl ReplyCount {
+    pub const DISCRIMINATOR: &'static str = "counter";
+    pub const SIZE: usize = (4 + ReplyCount::DISCRIMINATOR.len()) + 1 + 8;
+}
+
+impl ProgramConfig {
+    pub const DISCRIMINATOR: &'static str = "config";
+    /// Allocated up front so new settings can be appended without a realloc.
+    pub const ACCOUNT_LEN: usize = 1000;
+
+    pub const MAX_ADMIN_SIGNERS: usize = 5;
+    pub const MAX_REPLY_REWARD_TIERS: usize = 4;
+    pub const MAX_WELCOME_BOTS: usize = 5;
+    /// Largest `credential_multiplier_bps` a config with a credential collection may set (3x).
+    pub const MAX_CREDENTIAL_MULTIPLIER_BPS: u16 = 30_000;
+    /// Account layout version this binary reads and writes. Appended fields don't change it
+    /// (see the module docs); bump it with any change that needs a migration crank, and
+    /// instructions taking the config then refuse to run until `CompleteMigration` records
+    /// that the cranks are done.
+    pub const DATA_VERSION: u32 = 1;
+
+    /// Whether `authority` may perform admin actions: the admin key (or the multisig authority
+    /// once a threshold is set), or, when governance is configured, the governance account or
+    /// its native treasury signing from a proposal.
+    pub fn is_admin(&self, authority: &Pubkey, program_id: &Pubkey) -> bool {
+        if self.admin_threshold > 0 {
+            let (multisig_authority, _bump) =
+                Pubkey::find_program_address(&[b"multisig"], program_id);
+            if *authority == multisig_authority {
+                return true;
+            }
+        } else if self.admin == *authority {
+            return true;
+        }
+
+        match self.governance_program {
+            Some(governance_program) => {
+                let (native_treasury, _bump) = Pubkey::find_program_address(
+                    &[b"native-treasury", self.governance.as_ref()],
+                    &governance_program,
+                );
+                *authority == self.governance || *authority == native_treasury
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_instruction_enabled(&self, variant: u8) -> bool {
+        self.disabled_instructions[variant as usize / 8] & (1 << (variant % 8)) == 0
+    }
+
+    pub fn set_instruction_enabled(&mut self, variant: u8, enabled: bool) {
+        let bit = 1 << (variant % 8);
+        let byte = &mut self.disabled_instructions[variant as usize / 8];
+        if enabled {
+            *byte &= !bit;
+        } else {
+            *byte |= bit;
+        }
+    }
+
+    pub fn apply_credential_multiplier(&self, amount: u64) -> u64 {
+        (amount as u128 * self.credential_multiplier_bps as u128 / 10_000) as u64
+    }
+
+    /// The treasury's share of a tip of `amount`, rounded down.
+    pub fn protocol_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.protocol_fee_bps as u128 / 10_000) as u64
+    }
+
+    pub fn is_welcome_bot(&self, wallet: &Pubkey) -> bool {
+        self.welcome_bots.contains(wallet)
+    }
+
+    /// Base reward for a reply of `message_len` bytes; `default` applies past the last tier.
+    pub fn reply_reward(&self, message_len: usize, default: u64) -> u64 {
+        self.reply_reward_tiers
+            .iter()
+            .find(|tier| message_len < tier.below_len as usize)
+            .map_or(default, |tier| tier.amount)
+    }
+}
+
+impl WalletLink {
+    pub const DISCRIMINATOR: &'static str = "link";
+    pub const SIZE: usize = (4 + WalletLink::DISCRIMINATOR.len()) + 1 + 32 + 32;
+}
+
+impl WalletLinkCount {
+    pub const DISCRIMINATOR: &'static str = "link_count";
+    pub const SIZE: usize = (4 + WalletLinkCount::DISCRIMINATOR.len()) + 1 + 8;
+}
+
+impl ReplyCommitment {
+    pub const DISCRIMINATOR: &'static str = "commitment";
+    pub const SIZE: usize = (4 + ReplyCommitment::DISCRIMINATOR.len()) + 1 + 32 + 32 + 8;
+
+    /// SHA-256 of the Borsh encoding of `(name, message, salt)`. The length prefixes keep
+    /// `("ab", "c")` and `("a", "bc")` from committing to the same hash.
+    pub fn hash_reply(name: &str, message: &str, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[
+            &(name.len() as u32).to_le_bytes(),
+            name.as_bytes(),
+            &(message.len() as u32).to_le_bytes(),
+            message.as_bytes(),
+            salt,
+        ])
+        .to_bytes()
+    }
+}
+
+impl ReplyPreparation {
+    pub const DISCRIMINATOR: &'static str = "prepared";
+    pub const SIZE: usize =
+        (4 + ReplyPreparation::DISCRIMINATOR.len()) + 1 + 32 + 32 + 32 + 8 + 8 + 1;
+
+    /// SHA-256 of the Borsh encoding of `(name, message)`.
+    pub fn hash_reply(name: &str, message: &str) -> [u8; 32] {
+        hashv(&[
+            &(name.len() as u32).to_le_bytes(),
+            name.as_bytes(),
+            &(message.len() as u32).to_le_bytes(),
+            message.as_bytes(),
+        ])
+        .to_bytes()
+    }
+}
+
+impl DirectMessage {
+    pub const DISCRIMINATOR: &'static str = "dm";
+    pub const MAX_CIPHERTEXT_LEN: usize = 512;
+
+    pub fn get_account_size(ciphertext_len: usize) -> usize {
+        (4 + DirectMessage::DISCRIMINATOR.len()) + 1 + 32 + 32 + 8 + 24 + (4 + ciphertext_len)
+    }
+}
+
+impl DirectMessageCount {
+    pub const DISCRIMINATOR: &'static str = "dm_counter";
+    pub const SIZE: usize = (4 + DirectMessageCount::DISCRIMINATOR.len()) + 1 + 8;
+}
+
+impl ReplyVote {
+    pub const DISCRIMINATOR: &'static str = "vote";
+    pub const SIZE: usize = (4 + ReplyVote::DISCRIMINATOR.len()) + 1 + 32 + 32 + 1;
+}
+
+impl ReplyScore {
+    pub const DISCRIMINATOR: &'static str = "score";
+    pub const SIZE: usize = (4 + ReplyScore::DISCRIMINATOR.len()) + 1 + 32 + 8 + 1;
+    /// Scores at which a reply credits its replier, in increasing order.
+    pub const MILESTONES: [i64; 3] = [5, 10, 25];
+
+    /// Number of milestones the current score has reached.
+    pub fn milestones_reached(&self) -> u8 {
+        ReplyScore::MILESTONES
+            .iter()
+            .filter(|&&milestone| self.score >= milestone)
+            .count() as u8
+    }
+}
+
+impl Reputation {
+    pub const DISCRIMINATOR: &'static str = "reputation";
+    pub const SIZE: usize = (4 + Reputation::DISCRIMINATOR.len()) + 1 + 32 + 8 + 8;
+    pub const POINTS_PER_MILESTONE: u64 = 10;
+    /// Reward tokens accrued per milestone, in base units.
+    pub const MILESTONE_REWARD: u64 = LAMPORTS_PER_SOL;
+}
+
+impl IsInitialized for Reputation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl TopReplies {
+    pub const DISCRIMINATOR: &'static str = "top_replies";
+    pub const MAX_ENTRIES: usize = 10;
+    pub const SIZE: usize =
+        (4 + TopReplies::DISCRIMINATOR.len()) + 1 + 32 + (4 + TopReplies::MAX_ENTRIES * (32 + 8));
+
+    /// Records the latest score for `reply` and keeps the list sorted and bounded.
+    pub fn upsert(&mut self, reply: Pubkey, score: i64) {
+        self.entries.retain(|entry| entry.reply != reply);
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.score < score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(position, RankedReply { reply, score });
+        self.entries.truncate(TopReplies::MAX_ENTRIES);
+    }
+}
+
+impl Course {
+    pub const DISCRIMINATOR: &'static str = "course";
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_PINNED_INTROS: usize = 8;
+    /// Allocated up front so course settings can be appended without a realloc.
+    pub const ACCOUNT_LEN: usize = 1000;
+}
+
+impl IntroEndorsement {
+    pub const DISCRIMINATOR: &'static str = "endorsement";
+    pub const SIZE: usize = (4 + IntroEndorsement::DISCRIMINATOR.len()) + 1 + 32 + 32 + 8;
+}
+
+impl IsInitialized for IntroEndorsement {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Certificate {
+    pub const DISCRIMINATOR: &'static str = "certificate";
+    pub const SIZE: usize = (4 + Certificate::DISCRIMINATOR.len()) + 1 + 32 + 32 + 32 + 32 + 8;
+}
+
+impl IsInitialized for Certificate {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Session {
+    pub const DISCRIMINATOR: &'static str = "session";
+    pub const SIZE: usize = (4 + Session::DISCRIMINATOR.len()) + 1 + 32 + 8 + 8 + 8 + 8 + 8;
+    /// Largest per-student attendance reward a teacher can set: one whole token.
+    pub const MAX_REWARD: u64 = LAMPORTS_PER_SOL;
+
+    pub fn is_open(&self, now: i64) -> bool {
+        self.opens_at <= now && now < self.closes_at
+    }
+}
+
+impl IsInitialized for Session {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Attendance {
+    pub const DISCRIMINATOR: &'static str = "attendance";
+    pub const SIZE: usize = (4 + Attendance::DISCRIMINATOR.len()) + 1 + 32 + 32 + 8;
+}
+
+impl IsInitialized for Attendance {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Quiz {
+    pub const DISCRIMINATOR: &'static str = "quiz";
+    pub const SIZE: usize = (4 + Quiz::DISCRIMINATOR.len()) + 1 + 32 + 8 + 8 + 32 + 8 + 1 + 8 + 8;
+    /// Largest per-student quiz reward a teacher can set: one whole token.
+    pub const MAX_REWARD: u64 = LAMPORTS_PER_SOL;
+
+    pub fn commit_answer(answer: &str, salt: &[u8; 32]) -> [u8; 32] {
+        hashv(&[answer.as_bytes(), salt]).to_bytes()
+    }
+}
+
+impl IsInitialized for Quiz {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl QuizSubmission {
+    pub const DISCRIMINATOR: &'static str = "quiz_answer";
+    pub const SIZE: usize = (4 + QuizSubmission::DISCRIMINATOR.len()) + 1 + 32 + 32 + 32 + 1 + 1;
+
+    /// Binds the answer to the quiz and student, so identical answers hash differently. Small
+    /// answer spaces can still be guessed from the hash; the quiz window is what protects them.
+    pub fn hash_answer(quiz: &Pubkey, student: &Pubkey, answer: &str) -> [u8; 32] {
+        hashv(&[quiz.as_ref(), student.as_ref(), answer.as_bytes()]).to_bytes()
+    }
+}
+
+impl IsInitialized for QuizSubmission {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl TeamState {
+    pub const DISCRIMINATOR: &'static str = "team";
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_MEMBERS: usize = 8;
+    pub const SIZE: usize = (4 + TeamState::DISCRIMINATOR.len())
+        + 1
+        + (4 + TeamState::MAX_NAME_LEN)
+        + 32
+        + (4 + TeamState::MAX_MEMBERS * 32);
+}
+
+impl IsInitialized for TeamState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl TeamMembership {
+    pub const DISCRIMINATOR: &'static str = "team_member";
+    pub const SIZE: usize = (4 + TeamMembership::DISCRIMINATOR.len()) + 1 + 32 + 32;
+}
+
+impl IsInitialized for TeamMembership {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl ActivityFeed {
+    pub const DISCRIMINATOR: &'static str = "feed";
+    pub const CAPACITY: usize = 16;
+    pub const SIZE: usize = (4 + ActivityFeed::DISCRIMINATOR.len())
+        + 1
+        + 32
+        + 1
+        + 1
+        + ActivityFeed::CAPACITY * (1 + 32 + 8);
+
+    pub fn record(&mut self, entry: ActivityEntry) {
+        let len = self.len as usize;
+        if len < ActivityFeed::CAPACITY {
+            self.entries[(self.head as usize + len) % ActivityFeed::CAPACITY] = entry;
+            self.len += 1;
+        } else {
+            self.entries[self.head as usize] = entry;
+            self.head = ((self.head as usize + 1) % ActivityFeed::CAPACITY) as u8;
+        }
+    }
+
+    /// Recorded entries, newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &ActivityEntry> + '_ {
+        (0..self.len as usize).rev().map(move |offset| {
+            &self.entries[(self.head as usize + offset) % ActivityFeed::CAPACITY]
+        })
+    }
+}
+
+impl IsInitialized for ActivityFeed {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl OfficeHoursQueue {
+    pub const DISCRIMINATOR: &'static str = "queue";
+    pub const SIZE: usize =
+        (4 + OfficeHoursQueue::DISCRIMINATOR.len()) + 1 + 32 + 1 + 1 + 32 * QUEUE_CAPACITY;
+
+    /// Queued students, front first.
+    pub fn students(&self) -> impl Iterator<Item = &Pubkey> + '_ {
+        (0..self.len as usize).map(move |offset| &self.slots[self.slot(offset)])
+    }
+
+    /// Adds `student` at the back; false when the queue is full.
+    pub fn push(&mut self, student: Pubkey) -> bool {
+        if self.len as usize == QUEUE_CAPACITY {
+            return false;
+        }
+        let slot = self.slot(self.len as usize);
+        self.slots[slot] = student;
+        self.len += 1;
+        true
+    }
+
+    /// Takes the student at the front.
+    pub fn pop(&mut self) -> Option<Pubkey> {
+        if self.len == 0 {
+            return None;
+        }
+        let student = std::mem::take(&mut self.slots[self.head as usize]);
+        self.head = ((self.head as usize + 1) % QUEUE_CAPACITY) as u8;
+        self.len -= 1;
+        Some(student)
+    }
+
+    /// Takes `student` out from anywhere in the queue, keeping everyone behind them in order;
+    /// false when they are not queued.
+    pub fn remove(&mut self, student: &Pubkey) -> bool {
+        let position = match self.students().position(|queued| queued == student) {
+            Some(position) => position,
+            None => return false,
+        };
+        for offset in position..self.len as usize - 1 {
+            self.slots[self.slot(offset)] = self.slots[self.slot(offset + 1)];
+        }
+        self.len -= 1;
+        self.slots[self.slot(self.len as usize)] = Pubkey::default();
+        true
+    }
+
+    fn slot(&self, offset: usize) -> usize {
+        (self.head as usize + offset) % QUEUE_CAPACITY
+    }
+}
+
+impl IsInitialized for OfficeHoursQueue {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl EmailHashEntry {
+    pub const DISCRIMINATOR: &'static str = "email";
+    pub const SIZE: usize = (4 + EmailHashEntry::DISCRIMINATOR.len()) + 1 + 32 + 8;
+}
+
+impl IsInitialized for EmailHashEntry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl StudentIdEntry {
+    pub const DISCRIMINATOR: &'static str = "sid";
+    pub const SIZE: usize = (4 + StudentIdEntry::DISCRIMINATOR.len()) + 1 + 32 + 32;
+}
+
+impl IsInitialized for StudentIdEntry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Enrollment {
+    pub const DISCRIMINATOR: &'static str = "enrollment";
+    pub const SIZE: usize = (4 + Enrollment::DISCRIMINATOR.len()) + 1 + 32 + 32;
+}
+
+impl CourseStats {
+    pub const DISCRIMINATOR: &'static str = "course_stats";
+    pub const SIZE: usize = (4 + CourseStats::DISCRIMINATOR.len()) + 1 + 32 + 8 * 4;
+}
+
+impl IntroTally {
+    pub const DISCRIMINATOR: &'static str = "intro_tally";
+    pub const SIZE: usize = (4 + IntroTally::DISCRIMINATOR.len()) + 1 + 8;
+}
+
+impl UserNonce {
+    pub const DISCRIMINATOR: &'static str = "nonce";
+    pub const SIZE: usize = (4 + UserNonce::DISCRIMINATOR.len()) + 1 + 32 + 8;
+}
+
+impl StudentActivity {
+    pub const DISCRIMINATOR: &'static str = "activity";
+    pub const SIZE: usize = (4 + StudentActivity::DISCRIMINATOR.len()) + 1 + 8;
+}
+
+impl ReplyTally {
+    pub const DISCRIMINATOR: &'static str = "tally";
+    pub const SIZE: usize = (4 + ReplyTally::DISCRIMINATOR.len()) + 1 + 8;
+
+    /// Full reward for a wallet's first reply to an intro, half for the second, none after.
+    pub fn decay(&self, reward: u64) -> u64 {
+        match self.replies {
+            0 => reward,
+            1 => reward / 2,
+            _ => 0,
+        }
+    }
+}
+
+impl EditHistory {
+    pub const DISCRIMINATOR: &'static str = "history";
+    pub const MAX_ENTRIES: usize = 16;
+    pub const SIZE: usize = (4 + EditHistory::DISCRIMINATOR.len())
+        + 1
+        + 32
+        + 8
+        + (4 + EditHistory::MAX_ENTRIES * (8 + 32 + 8));
+
+    pub fn record(&mut self, record: EditRecord) {
+        if self.entries.len() == EditHistory::MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(record);
+        self.edit_count += 1;
+    }
+}
+
+impl IntroRegistry {
+    pub const DISCRIMINATOR: &'static str = "registry";
+    pub const SIZE: usize = (4 + IntroRegistry::DISCRIMINATOR.len()) + 1 + 8;
+}
+
+impl RegistryEntry {
+    pub const DISCRIMINATOR: &'static str = "registry_entry";
+    pub const SIZE: usize = (4 + RegistryEntry::DISCRIMINATOR.len()) + 1 + 8 + 32;
+}
+
+impl IntroFeedPage {
+    pub const DISCRIMINATOR: &'static str = "intro_feed";
+    pub const CAPACITY: usize = 32;
+    pub const SIZE: usize =
+        (4 + IntroFeedPage::DISCRIMINATOR.len()) + 1 + 8 + 1 + IntroFeedPage::CAPACITY * (32 + 8);
+
+    /// The page holding registry index `index`.
+    pub fn page_of(index: u64) -> u64 {
+        index / IntroFeedPage::CAPACITY as u64
+    }
+}
+
+impl QuestionFeed {
+    pub const DISCRIMINATOR: &'static str = "questions";
+    pub const CAPACITY: usize = 32;
+    pub const SIZE: usize =
+        (4 + QuestionFeed::DISCRIMINATOR.len()) + 1 + 1 + 1 + QuestionFeed::CAPACITY * (32 + 8);
+
+    pub fn record(&mut self, entry: FeedEntry) {
+        let len = self.len as usize;
+        if len < QuestionFeed::CAPACITY {
+            self.entries[(self.head as usize + len) % QuestionFeed::CAPACITY] = entry;
+            self.len += 1;
+        } else {
+            self.entries[self.head as usize] = entry;
+            self.head = ((self.head as usize + 1) % QuestionFeed::CAPACITY) as u8;
+        }
+    }
+
+    /// Recorded entries, newest first.
+    pub fn recent(&self) -> impl Iterator<Item = &FeedEntry> + '_ {
+        (0..self.len as usize).rev().map(move |offset| {
+            &self.entries[(self.head as usize + offset) % QuestionFeed::CAPACITY]
+        })
+    }
+}
+
+impl RewardHold {
+    pub const DISCRIMINATOR: &'static str = "reward_hold";
+    pub const SIZE: usize = (4 + RewardHold::DISCRIMINATOR.len()) + 1 + 32 + 1 + 32 + 1;
+}
+
+impl ProfileCard {
+    pub const DISCRIMINATOR: &'static str = "card";
+    pub const NAME_LEN: usize = 32;
+    pub const STUDENT_OFFSET: usize = (4 + ProfileCard::DISCRIMINATOR.len()) + 1;
+    pub const INTRO_OFFSET: usize = ProfileCard::STUDENT_OFFSET + 32;
+    pub const NAME_OFFSET: usize = ProfileCard::INTRO_OFFSET + 32;
+    pub const HANDLE_OFFSET: usize = ProfileCard::NAME_OFFSET + ProfileCard::NAME_LEN;
+    pub const REPUTATION_OFFSET: usize = ProfileCard::HANDLE_OFFSET + IntroSlug::MAX_LEN;
+    pub const BADGES_OFFSET: usize = ProfileCard::REPUTATION_OFFSET + 8;
+    pub const SIZE: usize = ProfileCard::BADGES_OFFSET + 8 + 8;
+
+    /// The intro's milestone badge was minted.
+    pub const BADGE_MILESTONE: u64 = 1 << 0;
+    /// The intro carries the identity checkmark.
+    pub const BADGE_VERIFIED: u64 = 1 << 1;
+    /// The course teacher marked the student graduated.
+    pub const BADGE_GRADUATED: u64 = 1 << 2;
+
+    /// `text` zero-padded to `N` bytes, or cut to the last character boundary that fits.
+    pub fn fixed_text<const N: usize>(text: &str) -> [u8; N] {
+        let mut len = text.len().min(N);
+        while !text.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let mut field = [0; N];
+        field[..len].copy_from_slice(&text.as_bytes()[..len]);
+        field
+    }
+}
+
+impl Featured {
+    pub const DISCRIMINATOR: &'static str = "featured";
+    pub const SIZE: usize = (4 + Featured::DISCRIMINATOR.len()) + 1 + 8 + 32 + 32 + 8;
+}
+
+impl FeaturedDraw {
+    pub const DISCRIMINATOR: &'static str = "featured_draw";
+    pub const SIZE: usize = (4 + FeaturedDraw::DISCRIMINATOR.len()) + 1 + 8 + 8 + 8;
+    /// Slots between committing a draw and the slot whose hash decides it.
+    pub const DELAY_SLOTS: u64 = 32;
+    /// Most registry indexes a draw tries, in turn, while they land on ineligible intros.
+    pub const MAX_CANDIDATES: u64 = 8;
+}
+
+impl ReceivedMessage {
+    pub const DISCRIMINATOR: &'static str = "received";
+    pub const SIZE: usize = (4 + ReceivedMessage::DISCRIMINATOR.len()) + 1 + 2 + 8 + 32;
+}
+
+impl PendingConfigChange {
+    pub const DISCRIMINATOR: &'static str = "pending_config";
+    pub const ACCOUNT_LEN: usize = 1000;
+}
+
+impl PendingWithdrawal {
+    pub const DISCRIMINATOR: &'static str = "withdrawal";
+    pub const SIZE: usize = (4 + 10) + 1 + 32 + 32 + 8 + 8 + 8;
+}
+
+impl IntroTranslation {
+    pub const DISCRIMINATOR: &'static str = "translation";
+    /// Allocated at this size like intros, so a translation can be rewritten at any length.
+    pub const ACCOUNT_LEN: usize = 1000;
+
+    pub fn get_account_size(message: &str) -> usize {
+        (4 + IntroTranslation::DISCRIMINATOR.len()) + 1 + 32 + 2 + (4 + message.len())
+    }
+}
+
+impl IsInitialized for IntroTranslation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IntroProfile {
+    pub const DISCRIMINATOR: &'static str = "profile";
+    pub const MAX_PRONOUNS_LEN: usize = 24;
+    pub const MAX_TIMEZONE_LEN: usize = 32;
+    pub const UNSET_COUNTRY: [u8; 2] = [0, 0];
+
+    pub const PRONOUNS_PUBLIC: u8 = 1 << 0;
+    pub const TIMEZONE_PUBLIC: u8 = 1 << 1;
+    pub const COUNTRY_PUBLIC: u8 = 1 << 2;
+    pub const ALL_FIELDS: u8 = IntroProfile::PRONOUNS_PUBLIC
+        | IntroProfile::TIMEZONE_PUBLIC
+        | IntroProfile::COUNTRY_PUBLIC;
+
+    /// Allocated for the longest fields, so a profile can always be rewritten in place.
+    pub const SIZE: usize = (4 + IntroProfile::DISCRIMINATOR.len())
+        + 1
+        + 32
+        + (4 + IntroProfile::MAX_PRONOUNS_LEN)
+        + (4 + IntroProfile::MAX_TIMEZONE_LEN)
+        + 2
+        + 1;
+
+    /// Bounded text, a time zone made of IANA name characters, a country of two upper-case
+    /// letters, and no unknown visibility bits.
+    pub fn is_valid(&self) -> bool {
+        self.pronouns.len() <= IntroProfile::MAX_PRONOUNS_LEN
+            && self.timezone.len() <= IntroProfile::MAX_TIMEZONE_LEN
+            && self
+                .timezone
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"/_+-".contains(&b))
+            && (self.country == IntroProfile::UNSET_COUNTRY
+                || self.country.iter().all(u8::is_ascii_uppercase))
+            && self.public_fields & !IntroProfile::ALL_FIELDS == 0
+    }
+
+    /// The profile as events publish it: fields without their public bit blanked.
+    pub fn redacted(&self) -> IntroProfile {
+        let public = |bit: u8| self.public_fields & bit != 0;
+        IntroProfile {
+            discriminator: self.discriminator.clone(),
+            is_initialized: self.is_initialized,
+            intro: self.intro,
+            pronouns: if public(IntroProfile::PRONOUNS_PUBLIC) {
+                self.pronouns.clone()
+            } else {
+                String::new()
+            },
+            timezone: if public(IntroProfile::TIMEZONE_PUBLIC) {
+                self.timezone.clone()
+            } else {
+                String::new()
+            },
+            country: if public(IntroProfile::COUNTRY_PUBLIC) {
+                self.country
+            } else {
+                IntroProfile::UNSET_COUNTRY
+            },
+            public_fields: self.public_fields,
+        }
+    }
+}
+
+impl IsInitialized for IntroProfile {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl CategoryRegistry {
+    pub const DISCRIMINATOR: &'static str = "categories";
+    pub const SIZE: usize = (4 + CategoryRegistry::DISCRIMINATOR.len()) + 1 + 8;
+}
+
+impl IsInitialized for CategoryRegistry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Category {
+    pub const DISCRIMINATOR: &'static str = "category";
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const SIZE: usize =
+        (4 + Category::DISCRIMINATOR.len()) + 1 + 8 + (4 + Category::MAX_NAME_LEN) + 8;
+}
+
+impl IsInitialized for Category {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IntroSlug {
+    pub const DISCRIMINATOR: &'static str = "slug";
+    pub const MIN_LEN: usize = 3;
+    /// Bounded by the 32-byte limit on a single PDA seed.
+    pub const MAX_LEN: usize = 32;
+    /// Epochs an abandoned slug keeps forwarding, counted after the one it was abandoned in.
+    pub const REDIRECT_EPOCHS: u64 = 1;
+    pub const SIZE: usize = (4 + 4) + 1 + 32 + (4 + IntroSlug::MAX_LEN) + (1 + 32) + 8;
+
+    /// Lowercase ASCII letters, digits and inner hyphens, so the slug can go into a URL as is.
+    pub fn is_valid(slug: &str) -> bool {
+        (Self::MIN_LEN..=Self::MAX_LEN).contains(&slug.len())
+            && slug
+                .bytes()
+                .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-')
+            && !slug.starts_with('-')
+            && !slug.ends_with('-')
+    }
+
+    /// Whether the entry still belongs to its intro in `epoch`, as its slug or as a redirect.
+    /// `intro` is the entry's intro as loaded now, `None` once it is closed; an intro written
+    /// again at the same address starts without the slug and holds none of its old entries.
+    pub fn is_held(&self, epoch: u64, intro: Option<&StudentIntroState>) -> bool {
+        match (intro, self.redirect_to) {
+            (None, _) => false,
+            (Some(intro), None) => intro.slug == self.slug,
+            (Some(_), Some(_)) => epoch <= self.redirect_until_epoch,
+        }
+    }
+}
+
+impl IsInitialized for IntroSlug {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for PendingWithdrawal {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl PendingAction {
+    pub const DISCRIMINATOR: &'static str = "action";
+    pub const MAX_ACCOUNTS: usize = 10;
+    pub const MAX_DATA_LEN: usize = 400;
+    pub const ACCOUNT_LEN: usize = 1000;
+}
+
+impl VestingSchedule {
+    pub const DISCRIMINATOR: &'static str = "vesting";
+    pub const SIZE: usize = (4 + VestingSchedule::DISCRIMINATOR.len()) + 1 + 32 + 8 * 5;
+
+    /// Amount of the current schedule vested at `now`.
+    pub fn vested(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.start_ts);
+        if elapsed >= self.duration_secs {
+            return self.total;
+        }
+        if elapsed <= 0 {
+            return 0;
+        }
+        (self.total as u128 * elapsed as u128 / self.duration_secs as u128) as u64
+    }
+
+    /// Restarts the schedule at `now` with `amount` added to whatever has not vested yet;
+    /// anything already vested but unclaimed moves to `unlocked`.
+    pub fn add_grant(&mut self, amount: u64, now: i64, duration_secs: i64) {
+        let vested = self.vested(now);
+        self.unlocked += vested - self.released;
+        self.total = self.total - vested + amount;
+        self.released = 0;
+        self.start_ts = now;
+        self.duration_secs = duration_secs;
+    }
+
+    /// Takes everything claimable at `now` and marks it released.
+    pub fn claim(&mut self, now: i64) -> u64 {
+        let vested = self.vested(now);
+        let amount = self.unlocked + vested - self.released;
+        self.unlocked = 0;
+        self.released = vested;
+        amount
+    }
+}
+
+impl BadgeAward {
+    pub const DISCRIMINATOR: &'static str = "badge";
+    pub const SIZE: usize = (4 + BadgeAward::DISCRIMINATOR.len()) + 1 + 32 + 32 + 8;
 }
@@ -0,0 +1,197 @@
+//! Instruction builders for integration tests against this program, so downstream crates can
+//! drive it from their own `solana-program-test` setup without re-deriving every account list.
+//! Builders assume a default config: no gateway, price feed or credential accounts, and the
+//! original reward mint (no `MigrateMint` yet). Under `no-rewards` they leave out the token
+//! accounts, matching the handlers. Every account a handler writes is marked writable; handlers
+//! reject read-only ones with `AccountNotWritable`.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+#[cfg(not(feature = "no-rewards"))]
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::{instruction::config_account, pda, state::IntroFeedPage};
+
+/// `InitializeMint` without a freeze authority; any funded keypair can pay. Uses the current
+/// layout, without the deprecated trailing rent sysvar.
+#[cfg(not(feature = "no-rewards"))]
+pub fn initialize_mint(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(pda::token_mint(program_id), false),
+            AccountMeta::new_readonly(pda::mint_auth(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            config_account(program_id),
+        ],
+        data: vec![3],
+    }
+}
+
+/// `StudentIntro` for `writer`, whose reward associated token account must already exist.
+/// `registry_index` is the registry's current count, i.e. the slot this intro will take.
+pub fn student_intro(
+    program_id: &Pubkey,
+    writer: &Pubkey,
+    registry_index: u64,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let intro = pda::intro(program_id, writer);
+
+    let mut accounts = vec![
+        AccountMeta::new(*writer, true),
+        AccountMeta::new(intro, false),
+        AccountMeta::new(pda::reply_counter(program_id, &intro), false),
+    ];
+    #[cfg(not(feature = "no-rewards"))]
+    accounts.extend(reward_accounts(program_id, writer));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    #[cfg(not(feature = "no-rewards"))]
+    accounts.extend([
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(pda::intro_tally(program_id, writer), false),
+    ]);
+    accounts.extend([
+        AccountMeta::new(pda::registry(program_id), false),
+        AccountMeta::new(pda::registry_entry(program_id, registry_index), false),
+        AccountMeta::new(
+            pda::intro_feed_page(program_id, IntroFeedPage::page_of(registry_index)),
+            false,
+        ),
+    ]);
+
+    accounts.push(config_account(program_id));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data(0, &(name, message)),
+    }
+}
+
+/// `ReplyIntro` on `intro_writer`'s intro. `reply_index` is the intro's current reply count.
+pub fn reply_intro(
+    program_id: &Pubkey,
+    replier: &Pubkey,
+    intro_writer: &Pubkey,
+    reply_index: u64,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let intro = pda::intro(program_id, intro_writer);
+
+    let mut accounts = vec![
+        AccountMeta::new(*replier, true),
+        AccountMeta::new_readonly(intro, false),
+        AccountMeta::new(pda::reply_counter(program_id, &intro), false),
+        AccountMeta::new(pda::reply(program_id, &intro, reply_index), false),
+    ];
+    #[cfg(not(feature = "no-rewards"))]
+    accounts.extend(reward_accounts(program_id, replier));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    #[cfg(not(feature = "no-rewards"))]
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    #[cfg(not(feature = "no-rewards"))]
+    accounts.push(AccountMeta::new(
+        pda::reply_tally(program_id, &intro, replier),
+        false,
+    ));
+
+    accounts.push(config_account(program_id));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data(2, &(name, message)),
+    }
+}
+
+/// `UpdateIntro` for `writer`'s intro.
+pub fn update_intro(
+    program_id: &Pubkey,
+    writer: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let intro = pda::intro(program_id, writer);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*writer, true),
+            AccountMeta::new(intro, false),
+            AccountMeta::new_readonly(pda::reply_counter(program_id, &intro), false),
+            AccountMeta::new(pda::edit_history(program_id, &intro), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            config_account(program_id),
+        ],
+        data: instruction_data(1, &(name, message)),
+    }
+}
+
+/// `SetSlug` for `writer`'s intro. `previous` is the intro's current slug, if it has one.
+pub fn set_slug(
+    program_id: &Pubkey,
+    writer: &Pubkey,
+    slug: &str,
+    previous: Option<&str>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*writer, true),
+        AccountMeta::new(pda::intro(program_id, writer), false),
+        AccountMeta::new(pda::slug(program_id, slug), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(previous) = previous {
+        accounts.push(AccountMeta::new(pda::slug(program_id, previous), false));
+    }
+
+    accounts.push(config_account(program_id));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data(43, &slug),
+    }
+}
+
+/// `CloseExpiredIntro` for `writer`'s intro; anyone may send it, rent goes back to `writer`.
+/// An intro with a slug also needs its `pda::slug` entry, writable, ahead of the config.
+pub fn close_expired_intro(program_id: &Pubkey, writer: &Pubkey) -> Instruction {
+    let intro = pda::intro(program_id, writer);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*writer, false),
+            AccountMeta::new(intro, false),
+            AccountMeta::new(pda::reply_counter(program_id, &intro), false),
+            config_account(program_id),
+        ],
+        data: vec![15],
+    }
+}
+
+/// Mint, mint authority and `recipient`'s associated token account.
+#[cfg(not(feature = "no-rewards"))]
+fn reward_accounts(program_id: &Pubkey, recipient: &Pubkey) -> [AccountMeta; 3] {
+    let token_mint = pda::token_mint(program_id);
+    [
+        AccountMeta::new(token_mint, false),
+        AccountMeta::new_readonly(pda::mint_auth(program_id), false),
+        AccountMeta::new(get_associated_token_address(recipient, &token_mint), false),
+    ]
+}
+
+fn instruction_data<T: BorshSerialize>(variant: u8, payload: &T) -> Vec<u8> {
+    let mut data = vec![variant];
+    data.extend(payload.try_to_vec().unwrap());
+    data
+}
@@ -0,0 +1,132 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+use crate::error::IntroError;
+
+const POST_MESSAGE_INSTRUCTION: u8 = 1;
+const CONSISTENCY_LEVEL_FINALIZED: u8 = 1;
+const POSTED_VAA_MAGIC: &[u8] = b"vaa";
+// BridgeData: guardian_set_index (u32), last_lamports (u64), expiration (u32), fee (u64)
+const BRIDGE_FEE_OFFSET: usize = 16;
+
+/// Version byte prefixed to every payload this program emits or accepts.
+pub const PAYLOAD_VERSION: u8 = 1;
+
+#[derive(BorshSerialize)]
+struct PostMessageData {
+    nonce: u32,
+    payload: Vec<u8>,
+    consistency_level: u8,
+}
+
+/// Body of a `PostedVAA` account written by the core bridge after signature verification.
+#[derive(BorshDeserialize)]
+pub struct PostedVaa {
+    pub vaa_version: u8,
+    pub consistency_level: u8,
+    pub vaa_time: u32,
+    pub vaa_signature_account: Pubkey,
+    pub submission_time: u32,
+    pub nonce: u32,
+    pub sequence: u64,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub payload: Vec<u8>,
+}
+
+/// Payload posted by `EmitCrossChainIntro`.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CrossChainIntroPayload {
+    pub intro: Pubkey,
+    pub writer: Pubkey,
+    pub content_hash: [u8; 32],
+}
+
+/// Payload a foreign emitter sends to materialize a reply here.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CrossChainReplyPayload {
+    pub intro: Pubkey,
+    /// Foreign sender address, left-padded to 32 bytes.
+    pub sender: [u8; 32],
+    pub name: String,
+    pub message: String,
+}
+
+impl CrossChainReplyPayload {
+    /// Longest name and message a relayed reply may carry, about what fits in a `ReplyIntro`
+    /// transaction.
+    pub const MAX_NAME_LEN: usize = 64;
+    pub const MAX_MESSAGE_LEN: usize = 800;
+}
+
+pub fn bridge_address(wormhole_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"Bridge"], wormhole_program).0
+}
+
+pub fn fee_collector_address(wormhole_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_collector"], wormhole_program).0
+}
+
+pub fn sequence_address(wormhole_program: &Pubkey, emitter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"Sequence", emitter.as_ref()], wormhole_program).0
+}
+
+pub fn read_bridge_fee(bridge_data: &[u8]) -> Result<u64, IntroError> {
+    bridge_data
+        .get(BRIDGE_FEE_OFFSET..BRIDGE_FEE_OFFSET + 8)
+        .map(|fee| u64::from_le_bytes(fee.try_into().unwrap()))
+        .ok_or(IntroError::InvalidWormholeAccount)
+}
+
+pub fn parse_posted_vaa(data: &[u8]) -> Result<PostedVaa, IntroError> {
+    if !data.starts_with(POSTED_VAA_MAGIC) {
+        return Err(IntroError::InvalidWormholeAccount);
+    }
+
+    PostedVaa::deserialize(&mut &data[POSTED_VAA_MAGIC.len()..])
+        .map_err(|_| IntroError::InvalidWormholeAccount)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn post_message(
+    wormhole_program: &Pubkey,
+    bridge: &Pubkey,
+    message: &Pubkey,
+    emitter: &Pubkey,
+    sequence: &Pubkey,
+    payer: &Pubkey,
+    fee_collector: &Pubkey,
+    nonce: u32,
+    payload: Vec<u8>,
+) -> Instruction {
+    let mut data = vec![POST_MESSAGE_INSTRUCTION];
+    data.extend(
+        PostMessageData {
+            nonce,
+            payload,
+            consistency_level: CONSISTENCY_LEVEL_FINALIZED,
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+
+    Instruction {
+        program_id: *wormhole_program,
+        accounts: vec![
+            AccountMeta::new(*bridge, false),
+            AccountMeta::new(*message, true),
+            AccountMeta::new_readonly(*emitter, true),
+            AccountMeta::new(*sequence, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*fee_collector, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
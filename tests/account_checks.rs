@@ -0,0 +1,100 @@
+//! Failed account checks are logged as `account_check_failed role=... key=... expected=...`,
+//! and `client::parse_account_check` reads them back.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+use student_intro_sol::{
+    client::{first_account_check_failure, parse_account_check, AccountCheckFailure},
+    error::AccountCheck,
+    pda,
+};
+
+fn failure(role: &str, key: Pubkey, expected: AccountCheck) -> Option<AccountCheckFailure> {
+    Some(AccountCheckFailure {
+        role: role.to_string(),
+        key,
+        expected,
+    })
+}
+
+#[test]
+fn read_only_accounts_log_their_role() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let mut ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Hi");
+    let counter = pda::reply_counter(&PROGRAM_ID, &intro);
+    ix.accounts[2] = AccountMeta::new_readonly(counter, false);
+
+    assert!(ledger.process(&ix).is_err());
+    assert_eq!(
+        first_account_check_failure(&ledger.logs()),
+        failure("counter_pda", counter, AccountCheck::Writable)
+    );
+}
+
+#[test]
+fn mismatched_pdas_log_their_role() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let mut ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Hi");
+    let stray = ledger.wallet();
+    ix.accounts[2] = AccountMeta::new(stray, false);
+
+    assert!(ledger.process(&ix).is_err());
+    assert_eq!(
+        first_account_check_failure(&ledger.logs()),
+        failure("counter_pda", stray, AccountCheck::Pda)
+    );
+}
+
+#[test]
+fn token_accounts_of_other_wallets_are_named_as_such() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let other = ledger.student();
+    let mut ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Hi");
+    let other_ata = reply_intro_ix(&ledger, &other, &intro, "Grace", "Hi").accounts[6].pubkey;
+    ix.accounts[6] = AccountMeta::new(other_ata, false);
+
+    assert!(ledger.process(&ix).is_err());
+    assert_eq!(
+        first_account_check_failure(&ledger.logs()),
+        failure("user_ata", other_ata, AccountCheck::Address)
+    );
+}
+
+#[test]
+fn parser_accepts_bare_lines_and_skips_unknown_keys() {
+    let key = Pubkey::new_unique();
+
+    assert_eq!(
+        parse_account_check(&format!(
+            "account_check_failed role=writer key={key} expected=signer"
+        )),
+        failure("writer", key, AccountCheck::Signer)
+    );
+    assert_eq!(
+        parse_account_check(&format!(
+            "Program log: account_check_failed expected=owner hint=x role=intro_pda key={key}"
+        )),
+        failure("intro_pda", key, AccountCheck::Owner)
+    );
+    assert_eq!(parse_account_check("Program log: Intro has expired"), None);
+    assert_eq!(
+        parse_account_check(&format!(
+            "account_check_failed role=writer key={key} expected=sorted"
+        )),
+        None
+    );
+}
+
+#[test]
+fn checks_round_trip_through_their_names() {
+    for check in AccountCheck::ALL {
+        assert_eq!(AccountCheck::parse(check.as_str()), Some(check));
+    }
+}
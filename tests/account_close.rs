@@ -0,0 +1,148 @@
+//! Closing accounts. The ledger never purges drained accounts, which is how the runtime behaves
+//! until the transaction ends, so every test here runs inside that window: a closed account
+//! that gets topped back up must not load as anything.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{StudentIntroState, CLOSED_ACCOUNT_DISCRIMINATOR},
+};
+
+fn err(error: IntroError) -> ProgramError {
+    error.into()
+}
+
+fn close_expired_intro_ix(writer: &Pubkey) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*writer, false),
+            AccountMeta::new(intro, false),
+            AccountMeta::new(pda::reply_counter(&PROGRAM_ID, &intro), false),
+        ],
+        data: vec![15],
+    }
+}
+
+/// A ledger with one posted intro whose expiry has already passed.
+fn expired_intro() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let mut data = ledger.data(&intro);
+    let mut state = try_from_slice_unchecked::<StudentIntroState>(&data).unwrap();
+    state.expires_at = Some(UNIX_TIMESTAMP);
+    state.serialize(&mut &mut data[..]).unwrap();
+    let lamports = ledger.info(&intro).lamports();
+    ledger.set(intro, PROGRAM_ID, lamports, data);
+
+    (ledger, writer, intro)
+}
+
+/// What a system transfer to the drained account later in the same transaction would do.
+fn top_up(ledger: &mut Ledger, key: &Pubkey) {
+    let data = ledger.data(key);
+    let lamports = Rent::default().minimum_balance(data.len());
+    ledger.set(*key, ledger.owner(key), lamports, data);
+}
+
+#[test]
+fn close_drains_zeroes_and_tombstones() {
+    let (mut ledger, writer, intro) = expired_intro();
+    let counter = pda::reply_counter(&PROGRAM_ID, &intro);
+    let rent = ledger.info(&intro).lamports() + ledger.info(&counter).lamports();
+    let balance = ledger.info(&writer).lamports();
+
+    ledger.process(&close_expired_intro_ix(&writer)).unwrap();
+
+    assert_eq!(ledger.info(&writer).lamports(), balance + rent);
+    let tombstone = CLOSED_ACCOUNT_DISCRIMINATOR.try_to_vec().unwrap();
+    for key in [intro, counter] {
+        assert!(!ledger.exists(&key));
+        let data = ledger.data(&key);
+        assert_eq!(&data[..tombstone.len()], &tombstone[..]);
+        assert!(data[tombstone.len()..].iter().all(|byte| *byte == 0));
+    }
+}
+
+#[test]
+fn revived_intro_cannot_be_replied_to() {
+    let (mut ledger, writer, intro) = expired_intro();
+    ledger.process(&close_expired_intro_ix(&writer)).unwrap();
+    top_up(&mut ledger, &intro);
+    top_up(&mut ledger, &pda::reply_counter(&PROGRAM_ID, &intro));
+
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    assert_eq!(ledger.process(&ix), Err(err(IntroError::AccountClosed)));
+    assert!(!ledger.exists(&pda::reply(&PROGRAM_ID, &intro, 0)));
+}
+
+#[test]
+fn revived_intro_cannot_be_updated() {
+    let (mut ledger, writer, intro) = expired_intro();
+    ledger.process(&close_expired_intro_ix(&writer)).unwrap();
+    top_up(&mut ledger, &intro);
+
+    let ix = update_intro_ix(&writer, "Ada", "Back again");
+    assert_eq!(ledger.process(&ix), Err(err(IntroError::AccountClosed)));
+}
+
+#[test]
+fn revived_intro_cannot_be_closed_twice() {
+    let (mut ledger, writer, intro) = expired_intro();
+    ledger.process(&close_expired_intro_ix(&writer)).unwrap();
+    top_up(&mut ledger, &intro);
+    top_up(&mut ledger, &pda::reply_counter(&PROGRAM_ID, &intro));
+
+    let ix = close_expired_intro_ix(&writer);
+    assert_eq!(ledger.process(&ix), Err(err(IntroError::AccountClosed)));
+}
+
+#[test]
+fn reply_batched_after_close_is_rejected() {
+    let (mut ledger, writer, intro) = expired_intro();
+    let replier = ledger.student();
+    let reply = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+
+    let ix = batch_ix(&[close_expired_intro_ix(&writer), reply]);
+    assert_eq!(ledger.process(&ix), Err(err(IntroError::AccountClosed)));
+    assert!(ledger.exists(&intro));
+}
+
+#[test]
+fn reposting_after_close_earns_no_second_reward() {
+    let (mut ledger, writer, intro) = expired_intro();
+    let supply = ledger.mint_supply();
+    ledger.process(&close_expired_intro_ix(&writer)).unwrap();
+    // The runtime purges the drained accounts once the transaction ends.
+    for key in [intro, pda::reply_counter(&PROGRAM_ID, &intro)] {
+        ledger.set(key, system_program::id(), 0, Vec::new());
+    }
+
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello again");
+    ledger.process(&ix).unwrap();
+
+    assert!(ledger.exists(&intro));
+    assert_eq!(ledger.mint_supply(), supply);
+}
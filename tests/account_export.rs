@@ -0,0 +1,120 @@
+//! `ExportAccount`: any program account re-encoded canonically, with a version and checksum,
+//! through return data.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshDeserialize;
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::MAX_RETURN_DATA,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    return_data::ExportReturn,
+    state::{IntroSlug, StudentIntroState, StudentReplyState},
+};
+
+fn export_ix(target: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![AccountMeta::new_readonly(*target, false)],
+        data: payload(78, target),
+    }
+}
+
+fn export(ledger: &mut Ledger, target: &Pubkey) -> ExportReturn {
+    ledger.process(&export_ix(target)).unwrap();
+    ExportReturn::try_from_slice(&ledger.return_data()).unwrap()
+}
+
+#[test]
+fn intros_export_every_field() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.status = StudentIntroState::STATUS_GRADUATED;
+    intro_data.spam_score = 42;
+    ledger.set_intro(&intro, &intro_data);
+
+    let export = export(&mut ledger, &intro);
+
+    assert_eq!(export.version, ExportReturn::VERSION);
+    assert_eq!(export.kind, StudentIntroState::DISCRIMINATOR);
+    assert!(export.is_intact());
+    let exported = StudentIntroState::try_from_slice(&export.data).unwrap();
+    assert_eq!(exported.name, "Ada");
+    assert_eq!(exported.message, "Hello");
+    assert_eq!(exported.status, StudentIntroState::STATUS_GRADUATED);
+    assert_eq!(exported.spam_score, 42);
+    assert!(!exported.verified);
+}
+
+#[test]
+fn older_replies_export_like_current_ones() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+    let reply = pda::reply(&PROGRAM_ID, &intro, 0);
+    let current = export(&mut ledger, &reply);
+
+    // Drop the read receipt and spam score, as a reply posted before them would lack.
+    let mut data = ledger.data(&reply);
+    data.truncate(data.len() - 2);
+    let lamports = ledger.info(&reply).lamports();
+    ledger.set(reply, PROGRAM_ID, lamports, data);
+
+    assert_eq!(export(&mut ledger, &reply), current);
+    let reply_data = StudentReplyState::try_from_slice(&current.data).unwrap();
+    assert_eq!(reply_data.replier, replier);
+    assert_eq!(reply_data.spam_score, 0);
+}
+
+#[test]
+fn the_largest_intro_fits_in_return_data() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let fixed = StudentIntroState::get_account_size("", "");
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.writer = Pubkey::new_unique();
+    intro_data.name = String::new();
+    intro_data.message = "a".repeat(StudentIntroState::ACCOUNT_LEN - fixed);
+    intro_data.course = Some(Pubkey::new_unique());
+    intro_data.expires_at = Some(i64::MAX);
+    intro_data.slug = "a".repeat(IntroSlug::MAX_LEN);
+    intro_data.category = Some(Pubkey::new_unique());
+    ledger.set_intro(&intro, &intro_data);
+
+    ledger.process(&export_ix(&intro)).unwrap();
+    assert!(ledger.return_data().len() <= MAX_RETURN_DATA);
+}
+
+#[test]
+fn tampered_exports_fail_the_checksum() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let mut export = export(&mut ledger, &intro);
+
+    export.data[0] ^= 1;
+    assert!(!export.is_intact());
+}
+
+#[test]
+fn only_the_named_program_account_is_exported() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let wallet = ledger.wallet();
+
+    let mut ix = export_ix(&intro);
+    ix.data = payload(78, &wallet);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+    assert_eq!(
+        ledger.process(&export_ix(&wallet)),
+        Err(ProgramError::IllegalOwner)
+    );
+}
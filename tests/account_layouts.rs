@@ -0,0 +1,1130 @@
+//! Byte snapshots of every account layout. A failure here means a change alters what is stored
+//! on chain; existing accounts would no longer decode. If the change is intentional and comes
+//! with a migration, regenerate with `UPDATE_SNAPSHOTS=1 cargo test --test account_layouts`.
+
+use std::{env, fs, path::PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use student_intro_sol::{client::AnyIntro, format, instruction::ConfigParams, lang, state::*};
+
+fn key(seed: u8) -> Pubkey {
+    Pubkey::new_from_array([seed; 32])
+}
+
+fn assert_snapshot<T: BorshSerialize>(name: &str, value: &T) {
+    let actual = hex(&value.try_to_vec().unwrap());
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.hex", name));
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, format!("{}\n", actual)).unwrap();
+        return;
+    }
+
+    let expected =
+        fs::read_to_string(&path).unwrap_or_else(|_| panic!("missing snapshot {}", path.display()));
+    assert_eq!(
+        expected.trim(),
+        actual,
+        "on-chain layout of {} changed",
+        name
+    );
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn config_params() -> ConfigParams {
+    ConfigParams {
+        gatekeeper_network: Some(key(1)),
+        reveal_delay_slots: 2,
+        credential_collection: Some(key(3)),
+        credential_multiplier_bps: 15_000,
+        price_feed: Some(key(4)),
+        reference_price_cents: 5,
+        max_price_staleness_slots: 6,
+        max_price_confidence_bps: 7,
+        wormhole_program: Some(key(8)),
+        foreign_emitter_chain: 9,
+        foreign_emitter_address: [10; 32],
+        governance_program: Some(key(11)),
+        governance: key(12),
+        config_timelock_slots: 13,
+        admin_signers: vec![key(14), key(15)],
+        admin_threshold: 2,
+        vesting_threshold: 16,
+        vesting_duration_secs: 17,
+        badge_reply_threshold: 18,
+        reply_reward_tiers: vec![
+            ReplyRewardTier {
+                below_len: 50,
+                amount: 19,
+            },
+            ReplyRewardTier {
+                below_len: 200,
+                amount: 20,
+            },
+        ],
+        first_reply_bonus: 21,
+        first_reply_window_secs: 22,
+        welcome_bots: vec![key(23)],
+        view_indexer: Some(key(25)),
+        identity_verifier: Some(key(27)),
+        spam_classifier: Some(key(28)),
+        spam_threshold: 29,
+        orphan_rent_destination: Some(key(32)),
+        protocol_fee_bps: 33,
+        course_reward_cap: 34,
+    }
+}
+
+fn intro(slug: &str) -> StudentIntroState {
+    StudentIntroState {
+        discriminator: StudentIntroState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        writer: key(1),
+        name: "Ada".to_string(),
+        message: "Hello".to_string(),
+        visibility: StudentIntroState::VISIBILITY_COURSE_ONLY,
+        course: Some(key(2)),
+        expires_at: Some(1_700_000_000),
+        created_at: 1_600_000_000,
+        slug: slug.to_string(),
+        category: Some(key(3)),
+        layout_version: StudentIntroState::LAYOUT_VERSION,
+        lang: *b"tr",
+        format: format::MARKDOWN,
+        status: StudentIntroState::STATUS_GRADUATED,
+        views: 4,
+        views_epoch: 5,
+        indexed: 1,
+        verified: true,
+        spam_score: 6,
+        kind: StudentIntroState::KIND_QUESTION,
+        answered_at: 7,
+        best_answer: 8,
+        first_reply_claimed: true,
+    }
+}
+
+#[test]
+fn student_intro_layout() {
+    assert_snapshot("student_intro", &intro("ada"));
+}
+
+#[test]
+fn legacy_intro_still_decodes() {
+    let mut data = fs::read_to_string(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/student_intro_v1.hex"),
+    )
+    .unwrap()
+    .trim()
+    .as_bytes()
+    .chunks(2)
+    .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+    .collect::<Vec<u8>>();
+    data.resize(1000, 0);
+
+    match AnyIntro::decode(&data).unwrap() {
+        AnyIntro::V1(intro) => {
+            assert_eq!(intro.writer, key(1));
+            assert_eq!(intro.name, "Ada");
+            assert_eq!(intro.message, "Hello");
+        }
+        AnyIntro::V2(_) => panic!("legacy intro decoded as the current layout"),
+    }
+}
+
+#[test]
+fn intro_space_counts_a_full_slug() {
+    let longest = intro(&"a".repeat(IntroSlug::MAX_LEN));
+    assert_eq!(
+        StudentIntroState::get_account_size("Ada", "Hello"),
+        longest.try_to_vec().unwrap().len()
+    );
+}
+
+#[test]
+fn intros_from_before_appended_fields_read_them_as_defaults() {
+    let writer = key(1);
+    // Every message length a baseline intro could hold, up to filling the account exactly.
+    let longest = StudentIntroState::ACCOUNT_LEN - (4 + 5 + 1 + 32 + (4 + 3) + 4);
+    for len in 0..=longest {
+        let message = "m".repeat(len);
+        let mut data = (
+            StudentIntroState::DISCRIMINATOR,
+            true,
+            writer,
+            "Ada",
+            &message,
+        )
+            .try_to_vec()
+            .unwrap();
+        data.resize(StudentIntroState::ACCOUNT_LEN, 0);
+
+        let decoded = StudentIntroState::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(decoded.writer, writer);
+        assert_eq!(decoded.message, message);
+        assert_eq!(decoded.visibility, StudentIntroState::VISIBILITY_PUBLIC);
+        assert_eq!(decoded.created_at, 0);
+        assert_eq!(decoded.layout_version, 0);
+        assert_eq!(decoded.status, StudentIntroState::STATUS_ACTIVE);
+        assert_eq!(decoded.kind, StudentIntroState::KIND_INTRODUCTION);
+        assert_eq!(decoded.best_answer, 0);
+        assert!(!decoded.first_reply_claimed);
+    }
+}
+
+#[test]
+fn student_reply_layout() {
+    assert_snapshot(
+        "student_reply",
+        &StudentReplyState {
+            discriminator: StudentReplyState::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: key(1),
+            replier: key(2),
+            name: "Grace".to_string(),
+            message: "Welcome".to_string(),
+            is_bot: true,
+            quote: Some(ReplyQuote {
+                reply: key(3),
+                excerpt_start: 4,
+                excerpt_len: 5,
+                excerpt_hash: [6; 32],
+            }),
+            lang: *b"en",
+            format: format::MARKDOWN,
+            links: vec!["https://example.com".to_string()],
+            read: true,
+            spam_score: 200,
+        },
+    );
+}
+
+/// A current reply without a quote, cut back to what an older layout wrote.
+fn reply_bytes_without(trailing_bytes: usize) -> Vec<u8> {
+    let reply = StudentReplyState {
+        discriminator: StudentReplyState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: key(1),
+        replier: key(2),
+        name: "Grace".to_string(),
+        message: "Welcome".to_string(),
+        is_bot: true,
+        quote: None,
+        lang: *b"en",
+        format: format::MARKDOWN,
+        links: Vec::new(),
+        read: true,
+        spam_score: 200,
+    };
+    let mut data = reply.try_to_vec().unwrap();
+    assert_eq!(
+        data.len(),
+        StudentReplyState::get_account_size("Grace", "Welcome")
+    );
+    data.truncate(data.len() - trailing_bytes);
+    data
+}
+
+#[test]
+fn reply_required_space_is_exact() {
+    let mut reply = StudentReplyState::try_from_slice(&reply_bytes_without(0)).unwrap();
+    assert_eq!(
+        StudentReplyState::required_space(5, 7, false, &[]),
+        reply.try_to_vec().unwrap().len()
+    );
+
+    reply.quote = Some(ReplyQuote {
+        reply: key(3),
+        excerpt_start: 0,
+        excerpt_len: 4,
+        excerpt_hash: [6; 32],
+    });
+    reply.links = vec![
+        "https://example.com".to_string(),
+        "https://a.io".to_string(),
+    ];
+    assert_eq!(
+        StudentReplyState::required_space(5, 7, true, &[19, 12]),
+        reply.try_to_vec().unwrap().len()
+    );
+}
+
+#[test]
+fn legacy_reply_reads_as_human() {
+    // No `is_bot`, `quote`, `lang`, `format`, `links`, `read` or `spam_score`.
+    let decoded = StudentReplyState::try_from_slice(&reply_bytes_without(11)).unwrap();
+    assert_eq!(decoded.message, "Welcome");
+    assert!(!decoded.is_bot);
+    assert_eq!(decoded.quote, None);
+    assert_eq!(decoded.lang, lang::UNSPECIFIED);
+}
+
+#[test]
+fn reply_from_before_quotes_decodes() {
+    let decoded = StudentReplyState::try_from_slice(&reply_bytes_without(10)).unwrap();
+    assert!(decoded.is_bot);
+    assert_eq!(decoded.quote, None);
+    assert_eq!(decoded.lang, lang::UNSPECIFIED);
+}
+
+#[test]
+fn reply_from_before_lang_decodes() {
+    let decoded = StudentReplyState::try_from_slice(&reply_bytes_without(9)).unwrap();
+    assert!(decoded.is_bot);
+    assert_eq!(decoded.lang, lang::UNSPECIFIED);
+    assert_eq!(decoded.format, format::PLAIN);
+}
+
+#[test]
+fn reply_from_before_format_decodes() {
+    let decoded = StudentReplyState::try_from_slice(&reply_bytes_without(7)).unwrap();
+    assert_eq!(decoded.lang, *b"en");
+    assert_eq!(decoded.format, format::PLAIN);
+}
+
+#[test]
+fn reply_from_before_links_decodes() {
+    let decoded = StudentReplyState::try_from_slice(&reply_bytes_without(6)).unwrap();
+    assert_eq!(decoded.format, format::MARKDOWN);
+    assert!(decoded.links.is_empty());
+    assert!(!decoded.read);
+}
+
+#[test]
+fn reply_from_before_read_decodes_unread() {
+    let decoded = StudentReplyState::try_from_slice(&reply_bytes_without(2)).unwrap();
+    assert!(decoded.is_bot);
+    assert!(!decoded.read);
+}
+
+#[test]
+fn reply_from_before_spam_scores_decodes_unscored() {
+    let decoded = StudentReplyState::try_from_slice(&reply_bytes_without(1)).unwrap();
+    assert!(decoded.read);
+    assert_eq!(decoded.spam_score, 0);
+}
+
+#[test]
+fn reply_count_layout() {
+    assert_snapshot(
+        "reply_count",
+        &ReplyCount {
+            discriminator: ReplyCount::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            counter: 3,
+        },
+    );
+}
+
+#[test]
+fn program_config_layout() {
+    let params = config_params();
+    assert_snapshot(
+        "program_config",
+        &ProgramConfig {
+            discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            admin: key(20),
+            gatekeeper_network: params.gatekeeper_network,
+            reveal_delay_slots: params.reveal_delay_slots,
+            credential_collection: params.credential_collection,
+            credential_multiplier_bps: params.credential_multiplier_bps,
+            price_feed: params.price_feed,
+            reference_price_cents: params.reference_price_cents,
+            max_price_staleness_slots: params.max_price_staleness_slots,
+            max_price_confidence_bps: params.max_price_confidence_bps,
+            wormhole_program: params.wormhole_program,
+            foreign_emitter_chain: params.foreign_emitter_chain,
+            foreign_emitter_address: params.foreign_emitter_address,
+            governance_program: params.governance_program,
+            governance: params.governance,
+            config_timelock_slots: params.config_timelock_slots,
+            admin_signers: params.admin_signers,
+            admin_threshold: params.admin_threshold,
+            pending_admin: Some(key(21)),
+            vesting_threshold: params.vesting_threshold,
+            vesting_duration_secs: params.vesting_duration_secs,
+            badge_reply_threshold: params.badge_reply_threshold,
+            reply_reward_tiers: params.reply_reward_tiers,
+            first_reply_bonus: params.first_reply_bonus,
+            first_reply_window_secs: params.first_reply_window_secs,
+            welcome_bots: params.welcome_bots,
+            mint_version: 24,
+            view_indexer: params.view_indexer,
+            disabled_instructions: [26; 32],
+            identity_verifier: params.identity_verifier,
+            spam_classifier: params.spam_classifier,
+            spam_threshold: params.spam_threshold,
+            data_version: 31,
+            orphan_rent_destination: params.orphan_rent_destination,
+            protocol_fee_bps: params.protocol_fee_bps,
+            course_reward_cap: params.course_reward_cap,
+        },
+    );
+}
+
+#[test]
+fn pending_action_layout() {
+    assert_snapshot(
+        "pending_action",
+        &PendingAction {
+            discriminator: PendingAction::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            action_id: 7,
+            proposer: key(1),
+            approvals: vec![key(1), key(2)],
+            accounts: vec![ActionAccount {
+                pubkey: key(3),
+                is_signer: true,
+                is_writable: false,
+            }],
+            instruction_data: vec![5, 0, 1],
+        },
+    );
+}
+
+#[test]
+fn vesting_schedule_layout() {
+    assert_snapshot(
+        "vesting_schedule",
+        &VestingSchedule {
+            discriminator: VestingSchedule::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            beneficiary: key(1),
+            unlocked: 2,
+            total: 3,
+            released: 4,
+            start_ts: 5,
+            duration_secs: 6,
+        },
+    );
+}
+
+#[test]
+fn badge_award_layout() {
+    assert_snapshot(
+        "badge_award",
+        &BadgeAward {
+            discriminator: BadgeAward::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: key(1),
+            writer: key(2),
+            slot: 3,
+        },
+    );
+}
+
+#[test]
+fn pending_config_change_layout() {
+    assert_snapshot(
+        "pending_config_change",
+        &PendingConfigChange {
+            discriminator: PendingConfigChange::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            proposer: key(1),
+            proposed_slot: 2,
+            eta_slot: 3,
+            params: config_params(),
+        },
+    );
+}
+
+#[test]
+fn intro_profile_layout() {
+    let profile = IntroProfile {
+        discriminator: IntroProfile::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: key(1),
+        pronouns: "they/them".to_string(),
+        timezone: "Europe/Istanbul".to_string(),
+        country: *b"TR",
+        public_fields: IntroProfile::PRONOUNS_PUBLIC | IntroProfile::COUNTRY_PUBLIC,
+    };
+    assert!(profile.try_to_vec().unwrap().len() <= IntroProfile::SIZE);
+    assert_snapshot("intro_profile", &profile);
+}
+
+#[test]
+fn intro_translation_layout() {
+    assert_snapshot(
+        "intro_translation",
+        &IntroTranslation {
+            discriminator: IntroTranslation::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: key(1),
+            lang: *b"tr",
+            message: "Merhaba".to_string(),
+        },
+    );
+}
+
+#[test]
+fn category_registry_layout() {
+    let registry = CategoryRegistry {
+        discriminator: CategoryRegistry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        count: 1,
+    };
+    assert_eq!(registry.try_to_vec().unwrap().len(), CategoryRegistry::SIZE);
+    assert_snapshot("category_registry", &registry);
+}
+
+#[test]
+fn category_layout() {
+    let category = Category {
+        discriminator: Category::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        index: 1,
+        name: "x".repeat(Category::MAX_NAME_LEN),
+        intro_count: 2,
+    };
+    assert_eq!(category.try_to_vec().unwrap().len(), Category::SIZE);
+    assert_snapshot("category", &category);
+}
+
+#[test]
+fn intro_slug_layout() {
+    let entry = IntroSlug {
+        discriminator: IntroSlug::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: key(1),
+        slug: "x".repeat(IntroSlug::MAX_LEN),
+        redirect_to: Some(key(2)),
+        redirect_until_epoch: 3,
+    };
+    assert_eq!(entry.try_to_vec().unwrap().len(), IntroSlug::SIZE);
+    assert_snapshot("intro_slug", &entry);
+}
+
+#[test]
+fn pending_withdrawal_layout() {
+    let pending = PendingWithdrawal {
+        discriminator: PendingWithdrawal::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        proposer: key(1),
+        destination: key(2),
+        amount: 3,
+        proposed_slot: 4,
+        eta_slot: 5,
+    };
+    assert_eq!(pending.try_to_vec().unwrap().len(), PendingWithdrawal::SIZE);
+    assert_snapshot("pending_withdrawal", &pending);
+}
+
+#[test]
+fn received_message_layout() {
+    assert_snapshot(
+        "received_message",
+        &ReceivedMessage {
+            discriminator: ReceivedMessage::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            emitter_chain: 2,
+            sequence: 3,
+            reply: key(4),
+        },
+    );
+}
+
+#[test]
+fn reply_commitment_layout() {
+    assert_snapshot(
+        "reply_commitment",
+        &ReplyCommitment {
+            discriminator: ReplyCommitment::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: key(1),
+            hash: [2; 32],
+            committed_slot: 3,
+        },
+    );
+}
+
+#[test]
+fn reply_preparation_layout() {
+    let preparation = ReplyPreparation {
+        discriminator: ReplyPreparation::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: key(1),
+        replier: key(2),
+        content_hash: ReplyPreparation::hash_reply("Grace", "Welcome"),
+        prepared_slot: 3,
+        prepared_at: 4,
+        first: true,
+    };
+    assert_eq!(
+        preparation.try_to_vec().unwrap().len(),
+        ReplyPreparation::SIZE
+    );
+    assert_eq!(
+        preparation.content_hash,
+        solana_program::hash::hash(&("Grace", "Welcome").try_to_vec().unwrap()).to_bytes()
+    );
+    assert_snapshot("reply_preparation", &preparation);
+}
+
+#[test]
+fn wallet_link_layout() {
+    assert_snapshot(
+        "wallet_link",
+        &WalletLink {
+            discriminator: WalletLink::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            primary: key(1),
+            linked: key(2),
+        },
+    );
+}
+
+#[test]
+fn wallet_link_count_layout() {
+    assert_snapshot(
+        "wallet_link_count",
+        &WalletLinkCount {
+            discriminator: WalletLinkCount::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            links: 2,
+        },
+    );
+}
+
+#[test]
+fn direct_message_layout() {
+    assert_snapshot(
+        "direct_message",
+        &DirectMessage {
+            discriminator: DirectMessage::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            sender: key(1),
+            recipient: key(2),
+            index: 3,
+            nonce: [4; 24],
+            ciphertext: vec![5, 6, 7],
+        },
+    );
+}
+
+#[test]
+fn direct_message_count_layout() {
+    assert_snapshot(
+        "direct_message_count",
+        &DirectMessageCount {
+            discriminator: DirectMessageCount::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            counter: 2,
+        },
+    );
+}
+
+#[test]
+fn reply_vote_layout() {
+    assert_snapshot(
+        "reply_vote",
+        &ReplyVote {
+            discriminator: ReplyVote::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            reply: key(1),
+            voter: key(2),
+            upvote: true,
+        },
+    );
+}
+
+#[test]
+fn reply_score_layout() {
+    assert_snapshot(
+        "reply_score",
+        &ReplyScore {
+            discriminator: ReplyScore::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            reply: key(1),
+            score: -2,
+            milestones: 1,
+        },
+    );
+}
+
+#[test]
+fn score_from_before_reputation_decodes_uncredited() {
+    let score = ReplyScore {
+        discriminator: ReplyScore::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        reply: key(1),
+        score: 7,
+        milestones: 0,
+    };
+    let mut bytes = score.try_to_vec().unwrap();
+    assert_eq!(bytes.len(), ReplyScore::SIZE);
+    bytes.pop();
+
+    let decoded = ReplyScore::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.score, 7);
+    assert_eq!(decoded.milestones, 0);
+}
+
+#[test]
+fn reputation_layout() {
+    let reputation = Reputation {
+        discriminator: Reputation::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        user: key(1),
+        points: 20,
+        claimable: 3,
+    };
+    assert_eq!(reputation.try_to_vec().unwrap().len(), Reputation::SIZE);
+    assert_snapshot("reputation", &reputation);
+}
+
+#[test]
+fn top_replies_layout() {
+    assert_snapshot(
+        "top_replies",
+        &TopReplies {
+            discriminator: TopReplies::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: key(1),
+            entries: vec![
+                RankedReply {
+                    reply: key(2),
+                    score: 5,
+                },
+                RankedReply {
+                    reply: key(3),
+                    score: -1,
+                },
+            ],
+        },
+    );
+}
+
+#[test]
+fn course_layout() {
+    assert_snapshot(
+        "course",
+        &Course {
+            discriminator: Course::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            teacher: key(1),
+            name: "Solana 101".to_string(),
+            rewards_disabled: true,
+            requires_student_id: true,
+            certificate: CertificateRequirements {
+                min_replies: 3,
+                requires_endorsement: true,
+            },
+            ends_at: 1_700_000_000,
+            anonymize_replies: true,
+            character_policy: CharacterPolicy {
+                ascii_only: true,
+                disallow_urls: true,
+                max_emoji: Some(4),
+            },
+            pinned_intros: vec![key(2)],
+            reward_epoch: 5,
+            rewards_minted: 6,
+        },
+    );
+}
+
+#[test]
+fn intro_endorsement_layout() {
+    let endorsement = IntroEndorsement {
+        discriminator: IntroEndorsement::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: key(1),
+        intro: key(2),
+        slot: 1_000,
+    };
+    assert_eq!(
+        endorsement.try_to_vec().unwrap().len(),
+        IntroEndorsement::SIZE
+    );
+    assert_snapshot("intro_endorsement", &endorsement);
+}
+
+#[test]
+fn certificate_layout() {
+    let certificate = Certificate {
+        discriminator: Certificate::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: key(1),
+        intro: key(2),
+        student: key(3),
+        mint: key(4),
+        completion_epoch: 5,
+    };
+    assert_eq!(certificate.try_to_vec().unwrap().len(), Certificate::SIZE);
+    assert_snapshot("certificate", &certificate);
+}
+
+#[test]
+fn session_layout() {
+    let session = Session {
+        discriminator: Session::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: key(1),
+        session_id: 2,
+        opens_at: 1_700_000_000,
+        closes_at: 1_700_003_600,
+        reward: 500_000_000,
+        check_ins: 3,
+    };
+    assert_eq!(session.try_to_vec().unwrap().len(), Session::SIZE);
+    assert_snapshot("session", &session);
+}
+
+#[test]
+fn attendance_layout() {
+    let attendance = Attendance {
+        discriminator: Attendance::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        session: key(1),
+        student: key(2),
+        checked_in_at: 1_700_000_000,
+    };
+    assert_eq!(attendance.try_to_vec().unwrap().len(), Attendance::SIZE);
+    assert_snapshot("attendance", &attendance);
+}
+
+#[test]
+fn quiz_layout() {
+    let quiz = Quiz {
+        discriminator: Quiz::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: key(1),
+        quiz_id: 2,
+        closes_at: 1_700_003_600,
+        answer_hash: [3; 32],
+        reward: 500_000_000,
+        revealed: true,
+        submissions: 4,
+        correct: 1,
+    };
+    assert_eq!(quiz.try_to_vec().unwrap().len(), Quiz::SIZE);
+    assert_snapshot("quiz", &quiz);
+}
+
+#[test]
+fn quiz_submission_layout() {
+    let submission = QuizSubmission {
+        discriminator: QuizSubmission::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        quiz: key(1),
+        student: key(2),
+        answer_hash: [3; 32],
+        graded: true,
+        correct: true,
+    };
+    assert_eq!(submission.try_to_vec().unwrap().len(), QuizSubmission::SIZE);
+    assert_snapshot("quiz_submission", &submission);
+}
+
+#[test]
+fn team_layout() {
+    let team = TeamState {
+        discriminator: TeamState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        name: "Validators".to_string(),
+        creator: key(1),
+        members: vec![key(1), key(2)],
+    };
+    assert!(team.try_to_vec().unwrap().len() <= TeamState::SIZE);
+    assert_snapshot("team", &team);
+}
+
+#[test]
+fn team_membership_layout() {
+    let membership = TeamMembership {
+        discriminator: TeamMembership::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        team: key(1),
+        student: key(2),
+    };
+    assert_eq!(membership.try_to_vec().unwrap().len(), TeamMembership::SIZE);
+    assert_snapshot("team_membership", &membership);
+}
+
+#[test]
+fn activity_feed_layout() {
+    let mut feed = ActivityFeed {
+        discriminator: ActivityFeed::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        user: key(1),
+        head: 0,
+        len: 0,
+        entries: Default::default(),
+    };
+    feed.record(ActivityEntry {
+        action: 2,
+        target: key(3),
+        slot: 1_000,
+    });
+    assert_eq!(feed.try_to_vec().unwrap().len(), ActivityFeed::SIZE);
+    assert_snapshot("activity_feed", &feed);
+}
+
+#[test]
+fn office_hours_queue_layout() {
+    let mut queue = OfficeHoursQueue {
+        discriminator: OfficeHoursQueue::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: key(1),
+        head: 0,
+        len: 0,
+        slots: Default::default(),
+    };
+    queue.push(key(2));
+    queue.push(key(3));
+    queue.pop();
+    assert_eq!(queue.try_to_vec().unwrap().len(), OfficeHoursQueue::SIZE);
+    assert_snapshot("office_hours_queue", &queue);
+}
+
+#[test]
+fn email_hash_entry_layout() {
+    let entry = EmailHashEntry {
+        discriminator: EmailHashEntry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        wallet: key(1),
+        registered_at: 1_700_000_000,
+    };
+    assert_eq!(entry.try_to_vec().unwrap().len(), EmailHashEntry::SIZE);
+    assert_snapshot("email_hash_entry", &entry);
+}
+
+#[test]
+fn student_id_entry_layout() {
+    let entry = StudentIdEntry {
+        discriminator: StudentIdEntry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        course: key(1),
+        student: key(2),
+    };
+    assert_eq!(entry.try_to_vec().unwrap().len(), StudentIdEntry::SIZE);
+    assert_snapshot("student_id_entry", &entry);
+}
+
+#[test]
+fn enrollment_layout() {
+    assert_snapshot(
+        "enrollment",
+        &Enrollment {
+            discriminator: Enrollment::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            course: key(1),
+            student: key(2),
+        },
+    );
+}
+
+#[test]
+fn course_stats_layout() {
+    assert_snapshot(
+        "course_stats",
+        &CourseStats {
+            discriminator: CourseStats::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            course: key(1),
+            intros: 2,
+            replies: 3,
+            epoch: 4,
+            active_students: 5,
+        },
+    );
+}
+
+#[test]
+fn student_activity_layout() {
+    assert_snapshot(
+        "student_activity",
+        &StudentActivity {
+            discriminator: StudentActivity::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            last_active_epoch: 2,
+        },
+    );
+}
+
+#[test]
+fn reply_tally_layout() {
+    assert_snapshot(
+        "reply_tally",
+        &ReplyTally {
+            discriminator: ReplyTally::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            replies: 2,
+        },
+    );
+}
+
+#[test]
+fn intro_tally_layout() {
+    assert_snapshot(
+        "intro_tally",
+        &IntroTally {
+            discriminator: IntroTally::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            rewarded_intros: 1,
+        },
+    );
+}
+
+#[test]
+fn user_nonce_layout() {
+    assert_snapshot(
+        "user_nonce",
+        &UserNonce {
+            discriminator: UserNonce::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            user: key(1),
+            next_nonce: 2,
+        },
+    );
+}
+
+#[test]
+fn edit_history_layout() {
+    assert_snapshot(
+        "edit_history",
+        &EditHistory {
+            discriminator: EditHistory::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            intro: key(1),
+            edit_count: 2,
+            entries: vec![EditRecord {
+                slot: 3,
+                message_hash: [4; 32],
+                reply_count: 5,
+            }],
+        },
+    );
+}
+
+#[test]
+fn intro_registry_layout() {
+    assert_snapshot(
+        "intro_registry",
+        &IntroRegistry {
+            discriminator: IntroRegistry::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            count: 2,
+        },
+    );
+}
+
+#[test]
+fn registry_entry_layout() {
+    assert_snapshot(
+        "registry_entry",
+        &RegistryEntry {
+            discriminator: RegistryEntry::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            index: 2,
+            intro: key(3),
+        },
+    );
+}
+
+#[test]
+fn intro_feed_page_layout() {
+    let mut page = IntroFeedPage {
+        discriminator: IntroFeedPage::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        page: 1,
+        len: 1,
+        entries: Default::default(),
+    };
+    page.entries[0] = FeedEntry {
+        intro: key(2),
+        slot: 1_000,
+    };
+    assert_eq!(page.try_to_vec().unwrap().len(), IntroFeedPage::SIZE);
+    assert_snapshot("intro_feed_page", &page);
+}
+
+#[test]
+fn question_feed_layout() {
+    let mut feed = QuestionFeed {
+        discriminator: QuestionFeed::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        head: 0,
+        len: 0,
+        entries: Default::default(),
+    };
+    feed.record(FeedEntry {
+        intro: key(2),
+        slot: 1_000,
+    });
+    assert_eq!(feed.try_to_vec().unwrap().len(), QuestionFeed::SIZE);
+    assert_snapshot("question_feed", &feed);
+}
+
+#[test]
+fn reward_hold_layout() {
+    let hold = RewardHold {
+        discriminator: RewardHold::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        author: key(1),
+        held: true,
+        target: key(2),
+        score: 3,
+    };
+    assert_eq!(hold.try_to_vec().unwrap().len(), RewardHold::SIZE);
+    assert_snapshot("reward_hold", &hold);
+}
+
+#[test]
+fn profile_card_layout() {
+    let card = ProfileCard {
+        discriminator: ProfileCard::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        student: key(1),
+        intro: key(2),
+        name: ProfileCard::fixed_text("Ada"),
+        handle: ProfileCard::fixed_text("ada-l"),
+        reputation: 3,
+        badges: ProfileCard::BADGE_VERIFIED,
+        synced_slot: 4,
+    };
+    let bytes = card.try_to_vec().unwrap();
+    assert_eq!(bytes.len(), ProfileCard::SIZE);
+    assert_eq!(
+        bytes[ProfileCard::REPUTATION_OFFSET..ProfileCard::BADGES_OFFSET],
+        3u64.to_le_bytes()
+    );
+    assert_snapshot("profile_card", &card);
+}
+
+#[test]
+fn featured_layout() {
+    assert_snapshot(
+        "featured",
+        &Featured {
+            discriminator: Featured::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            epoch: 2,
+            intro: key(3),
+            writer: key(4),
+            selected_slot: 5,
+        },
+    );
+}
+
+#[test]
+fn featured_draw_layout() {
+    assert_snapshot(
+        "featured_draw",
+        &FeaturedDraw {
+            discriminator: FeaturedDraw::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            epoch: 2,
+            draw_slot: 3,
+            registry_count: 4,
+        },
+    );
+}
@@ -0,0 +1,161 @@
+//! `InitActivityFeed`: a user's ring buffer of recent instructions, filled in by any instruction
+//! they sign with the feed passed writable after its other accounts.
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    pda,
+    state::{ActivityEntry, ActivityFeed},
+};
+
+const COURSE: &str = "Solana 101";
+const JOIN_QUEUE: u8 = 53;
+const LEAVE_QUEUE: u8 = 54;
+
+/// A ledger with a course and an enrolled student who has a feed, returning the teacher,
+/// course and student.
+fn setup() -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+
+    let student = ledger.wallet();
+    ledger.enroll(&teacher, &course, &student);
+    ledger
+        .process(&Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(student, true),
+                AccountMeta::new(pda::activity_feed(&PROGRAM_ID, &student), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: vec![64],
+        })
+        .unwrap();
+
+    (ledger, teacher, course, student)
+}
+
+fn join_ix(student: &Pubkey, course: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*student, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new_readonly(pda::enrollment(&PROGRAM_ID, course, student), false),
+            AccountMeta::new(pda::office_hours_queue(&PROGRAM_ID, course), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![JOIN_QUEUE],
+    }
+}
+
+fn leave_ix(student: &Pubkey, course: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*student, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new(pda::office_hours_queue(&PROGRAM_ID, course), false),
+        ],
+        data: vec![LEAVE_QUEUE],
+    }
+}
+
+fn with_feed(mut ix: Instruction, feed: Pubkey) -> Instruction {
+    ix.accounts.push(AccountMeta::new(feed, false));
+    ix
+}
+
+fn feed(ledger: &Ledger, user: &Pubkey) -> ActivityFeed {
+    try_from_slice_unchecked(&ledger.data(&pda::activity_feed(&PROGRAM_ID, user))).unwrap()
+}
+
+#[test]
+fn instructions_with_the_feed_are_recorded_newest_first() {
+    let (mut ledger, _teacher, course, student) = setup();
+    let student_feed = pda::activity_feed(&PROGRAM_ID, &student);
+
+    ledger
+        .process(&with_feed(join_ix(&student, &course), student_feed))
+        .unwrap();
+    ledger
+        .process(&with_feed(leave_ix(&student, &course), student_feed))
+        .unwrap();
+
+    let recent: Vec<ActivityEntry> = feed(&ledger, &student).recent().copied().collect();
+    let entry = |action| ActivityEntry {
+        action,
+        target: course,
+        slot: SLOT,
+    };
+    // The feed's own creation comes first.
+    assert_eq!(recent[..2], [entry(LEAVE_QUEUE), entry(JOIN_QUEUE)]);
+    assert_eq!(recent.len(), 3);
+}
+
+#[test]
+fn full_feeds_drop_the_oldest_entries() {
+    let (mut ledger, _teacher, course, student) = setup();
+    let student_feed = pda::activity_feed(&PROGRAM_ID, &student);
+
+    for _ in 0..ActivityFeed::CAPACITY / 2 + 1 {
+        ledger
+            .process(&with_feed(join_ix(&student, &course), student_feed))
+            .unwrap();
+        ledger
+            .process(&with_feed(leave_ix(&student, &course), student_feed))
+            .unwrap();
+    }
+
+    let feed = feed(&ledger, &student);
+    assert_eq!(feed.recent().count(), ActivityFeed::CAPACITY);
+    let actions: Vec<u8> = feed.recent().map(|entry| entry.action).collect();
+    assert_eq!(actions[..2], [LEAVE_QUEUE, JOIN_QUEUE]);
+    assert_eq!(actions[ActivityFeed::CAPACITY - 1], JOIN_QUEUE);
+}
+
+#[test]
+fn other_users_feeds_and_read_only_feeds_are_left_alone() {
+    let (mut ledger, teacher, course, student) = setup();
+    let student_feed = pda::activity_feed(&PROGRAM_ID, &student);
+    let before = ledger.data(&student_feed);
+
+    let other = ledger.wallet();
+    ledger
+        .process(&Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(teacher, true),
+                AccountMeta::new_readonly(course, false),
+                AccountMeta::new_readonly(other, false),
+                AccountMeta::new(pda::enrollment(&PROGRAM_ID, &course, &other), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(student_feed, false),
+            ],
+            data: vec![13],
+        })
+        .unwrap();
+    let mut ix = join_ix(&student, &course);
+    ix.accounts
+        .push(AccountMeta::new_readonly(student_feed, false));
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(ledger.data(&student_feed), before);
+}
+
+#[test]
+fn instructions_without_the_feed_still_run() {
+    let (mut ledger, _teacher, course, student) = setup();
+
+    ledger.process(&join_ix(&student, &course)).unwrap();
+
+    assert_eq!(feed(&ledger, &student).recent().count(), 1);
+}
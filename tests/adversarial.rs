@@ -0,0 +1,398 @@
+//! Account-substitution and malformed-meta attacks against the handlers. Each must fail with
+//! the specific error asserted here and leave no reward minted.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{error::IntroError, pda};
+
+fn err(error: IntroError) -> ProgramError {
+    error.into()
+}
+
+fn assert_rejected(ledger: &mut Ledger, ix: &Instruction, expected: ProgramError) {
+    let supply = ledger.mint_supply();
+    assert_eq!(ledger.process(ix), Err(expected));
+    assert_eq!(
+        ledger.mint_supply(),
+        supply,
+        "a rejected instruction minted"
+    );
+}
+
+/// A mint with the same shape as ours, controlled by someone else.
+fn foreign_mint(ledger: &mut Ledger) -> Pubkey {
+    let key = Pubkey::new_unique();
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: Some(Pubkey::new_unique()).into(),
+        decimals: 9,
+        is_initialized: true,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    ledger.set(key, spl_token::id(), 1_000_000, data);
+    key
+}
+
+/// Copies `key`'s data into a new account owned by `owner`.
+fn clone_with_owner(ledger: &mut Ledger, key: &Pubkey, owner: Pubkey) -> Pubkey {
+    let data = ledger.data(key);
+    let clone = Pubkey::new_unique();
+    ledger.set(clone, owner, 1_000_000, data);
+    clone
+}
+
+#[test]
+fn valid_intro_and_reply_mint_rewards() {
+    let (mut ledger, writer, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    let mint = pda::token_mint(&PROGRAM_ID);
+    assert!(ledger.token_balance(&get_associated_token_address(&writer, &mint)) > 0);
+    assert!(ledger.token_balance(&get_associated_token_address(&replier, &mint)) > 0);
+    assert_eq!(ledger.reply_count(&intro), 1);
+}
+
+// StudentIntro: 0 writer, 1 intro, 2 counter, 3 mint, 4 mint auth, 5 ATA, 6 system,
+// 7 token program, 8 config, 9 registry, 10 registry entry.
+
+#[test]
+fn intro_rejects_unsigned_writer() {
+    let (mut ledger, _, _) = ledger_with_intro();
+    let writer = ledger.student();
+    let mut ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ix.accounts[0].is_signer = false;
+    assert_rejected(&mut ledger, &ix, ProgramError::MissingRequiredSignature);
+}
+
+#[test]
+fn intro_rejects_someone_elses_intro_pda() {
+    let (mut ledger, _, _) = ledger_with_intro();
+    let writer = ledger.student();
+    let victim = ledger.wallet();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    let ix = substitute(&ix, 1, pda::intro(&PROGRAM_ID, &victim));
+    assert_rejected(&mut ledger, &ix, ProgramError::InvalidArgument);
+}
+
+#[test]
+fn intro_rejects_foreign_mint() {
+    let (mut ledger, _, _) = ledger_with_intro();
+    let writer = ledger.student();
+    let mint = foreign_mint(&mut ledger);
+    let ix = substitute(&student_intro_ix(&ledger, &writer, "Ada", "Hello"), 3, mint);
+    assert_rejected(&mut ledger, &ix, err(IntroError::IncorrectAccountError));
+}
+
+#[test]
+fn intro_rejects_substituted_mint_authority() {
+    let (mut ledger, _, _) = ledger_with_intro();
+    let writer = ledger.student();
+    let authority = ledger.wallet();
+    let ix = substitute(
+        &student_intro_ix(&ledger, &writer, "Ada", "Hello"),
+        4,
+        authority,
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::InvalidPDA));
+}
+
+#[test]
+fn intro_rejects_substituted_reward_account() {
+    let (mut ledger, _, _) = ledger_with_intro();
+    let writer = ledger.student();
+    let attacker = ledger.student();
+    let mint = pda::token_mint(&PROGRAM_ID);
+    let ix = substitute(
+        &student_intro_ix(&ledger, &writer, "Ada", "Hello"),
+        5,
+        get_associated_token_address(&attacker, &mint),
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::IncorrectAccountError));
+}
+
+#[test]
+fn intro_rejects_fake_token_program() {
+    let (mut ledger, _, _) = ledger_with_intro();
+    let writer = ledger.student();
+    let fake = ledger.wallet();
+    let ix = substitute(&student_intro_ix(&ledger, &writer, "Ada", "Hello"), 7, fake);
+    assert_rejected(&mut ledger, &ix, err(IntroError::IncorrectAccountError));
+}
+
+#[test]
+fn intro_rejects_fake_config() {
+    let (mut ledger, _, _) = ledger_with_intro();
+    let writer = ledger.student();
+    let fake = ledger.wallet();
+    let ix = substitute(&student_intro_ix(&ledger, &writer, "Ada", "Hello"), 8, fake);
+    assert_rejected(&mut ledger, &ix, err(IntroError::InvalidPDA));
+}
+
+#[test]
+fn intro_rejects_second_intro_for_same_writer() {
+    let (mut ledger, writer, _) = ledger_with_intro();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Again");
+    // The system program refuses to create an account that already exists.
+    assert_rejected(&mut ledger, &ix, ProgramError::Custom(0));
+}
+
+// ReplyIntro: 0 replier, 1 intro, 2 counter, 3 reply, 4 mint, 5 mint auth, 6 ATA, 7 system,
+// 8 token program, 9 config.
+
+#[test]
+fn reply_rejects_reply_passed_as_intro() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    let reply = pda::reply(&PROGRAM_ID, &intro, 0);
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Again"),
+        1,
+        reply,
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::UninitializedAccount));
+}
+
+#[test]
+fn reply_rejects_intro_owned_by_another_program() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let forged = clone_with_owner(&mut ledger, &intro, Pubkey::new_unique());
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome"),
+        1,
+        forged,
+    );
+    assert_rejected(&mut ledger, &ix, ProgramError::IllegalOwner);
+}
+
+#[test]
+fn reply_rejects_counter_owned_by_another_program() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let counter = pda::reply_counter(&PROGRAM_ID, &intro);
+    let data = ledger.data(&counter);
+    ledger.set(counter, Pubkey::new_unique(), 1_000_000, data);
+
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    assert_rejected(&mut ledger, &ix, ProgramError::IllegalOwner);
+}
+
+#[test]
+fn reply_rejects_another_intros_counter() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let other_writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &other_writer, "Bob", "Hi");
+    ledger.process(&ix).unwrap();
+    let other_intro = pda::intro(&PROGRAM_ID, &other_writer);
+
+    let replier = ledger.student();
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome"),
+        2,
+        pda::reply_counter(&PROGRAM_ID, &other_intro),
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::InvalidPDA));
+}
+
+#[test]
+fn reply_rejects_stale_reply_index() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let first = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&first).unwrap();
+
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Again"),
+        3,
+        pda::reply(&PROGRAM_ID, &intro, 0),
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::InvalidPDA));
+}
+
+#[test]
+fn reply_rejects_foreign_mint() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let mint = foreign_mint(&mut ledger);
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome"),
+        4,
+        mint,
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::IncorrectAccountError));
+}
+
+#[test]
+fn reply_rejects_substituted_mint_authority() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let authority = ledger.wallet();
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome"),
+        5,
+        authority,
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::IncorrectAccountError));
+}
+
+#[test]
+fn reply_rejects_substituted_reward_account() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let attacker = ledger.student();
+    let mint = pda::token_mint(&PROGRAM_ID);
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome"),
+        6,
+        get_associated_token_address(&attacker, &mint),
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::IncorrectAccountError));
+}
+
+#[test]
+fn reply_rejects_fake_token_program() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let fake = ledger.wallet();
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome"),
+        8,
+        fake,
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::IncorrectAccountError));
+}
+
+// UpdateIntro: 0 writer, 1 intro, 2 counter, 3 history, 4 system.
+
+#[test]
+fn update_rejects_someone_elses_intro() {
+    let (mut ledger, _, intro) = ledger_with_intro();
+    let attacker = ledger.student();
+    let ix = substitute(&update_intro_ix(&attacker, "Ada", "Pwned"), 1, intro);
+    assert_rejected(&mut ledger, &ix, err(IntroError::InvalidPDA));
+}
+
+#[test]
+fn update_rejects_reply_passed_as_intro() {
+    let (mut ledger, writer, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    let ix = substitute(
+        &update_intro_ix(&writer, "Ada", "Edited"),
+        1,
+        pda::reply(&PROGRAM_ID, &intro, 0),
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::UninitializedAccount));
+}
+
+#[test]
+fn update_rejects_intro_owned_by_another_program() {
+    let (mut ledger, writer, intro) = ledger_with_intro();
+    let forged = clone_with_owner(&mut ledger, &intro, Pubkey::new_unique());
+    let ix = substitute(&update_intro_ix(&writer, "Ada", "Edited"), 1, forged);
+    assert_rejected(&mut ledger, &ix, ProgramError::IllegalOwner);
+}
+
+#[test]
+fn update_rejects_another_intros_counter() {
+    let (mut ledger, writer, _) = ledger_with_intro();
+    let other_writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &other_writer, "Bob", "Hi");
+    ledger.process(&ix).unwrap();
+    let other_intro = pda::intro(&PROGRAM_ID, &other_writer);
+
+    let ix = substitute(
+        &update_intro_ix(&writer, "Ada", "Edited"),
+        2,
+        pda::reply_counter(&PROGRAM_ID, &other_intro),
+    );
+    assert_rejected(&mut ledger, &ix, err(IntroError::InvalidPDA));
+}
+
+/// Same instruction with the account at `index` passed read-only.
+fn readonly(instruction: &Instruction, index: usize) -> Instruction {
+    let mut instruction = instruction.clone();
+    instruction.accounts[index].is_writable = false;
+    instruction
+}
+
+#[test]
+fn read_only_reply_accounts_are_named_early() {
+    let (mut ledger, _writer, intro) = ledger_with_intro();
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+
+    // Counter, reply, mint and reward token account.
+    for index in [2, 3, 4, 6] {
+        assert_rejected(
+            &mut ledger,
+            &readonly(&ix, index),
+            err(IntroError::AccountNotWritable),
+        );
+    }
+    assert_eq!(ledger.reply_count(&intro), 0);
+}
+
+#[test]
+fn read_only_intro_accounts_are_named_early() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+
+    // Intro, counter, mint and reward token account.
+    for index in [1, 2, 3, 5] {
+        assert_rejected(
+            &mut ledger,
+            &readonly(&ix, index),
+            err(IntroError::AccountNotWritable),
+        );
+    }
+    assert!(!ledger.exists(&pda::intro(&PROGRAM_ID, &writer)));
+}
+
+#[test]
+fn legacy_initialize_mint_still_takes_the_rent_sysvar() {
+    let mut ledger = Ledger::new();
+    let payer = ledger.wallet();
+    let mut ix = initialize_mint_ix(&payer);
+    ix.accounts
+        .push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+
+    ledger.process(&ix).unwrap();
+    assert_eq!(ledger.mint_supply(), 0);
+}
+
+#[test]
+fn legacy_initialize_mint_rejects_a_fake_rent_sysvar() {
+    let mut ledger = Ledger::new();
+    let payer = ledger.wallet();
+    let mut ix = initialize_mint_ix(&payer);
+    ix.accounts
+        .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+
+    assert_eq!(
+        ledger.process(&ix),
+        Err(err(IntroError::IncorrectAccountError))
+    );
+    assert!(!ledger.exists(&pda::token_mint(&PROGRAM_ID)));
+}
@@ -0,0 +1,292 @@
+//! `OpenSession` / `CheckIn`: teacher-opened attendance sessions that enrolled students check in
+//! to once, within the session's window.
+
+mod common;
+
+#[cfg(not(feature = "no-rewards"))]
+use borsh::BorshSerialize;
+use common::*;
+#[cfg(not(feature = "no-rewards"))]
+use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+#[cfg(not(feature = "no-rewards"))]
+use spl_associated_token_account::get_associated_token_address;
+#[cfg(not(feature = "no-rewards"))]
+use student_intro_sol::state::{Course, ProgramConfig, RewardHold};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{Attendance, Session},
+};
+
+const COURSE: &str = "Solana 101";
+
+/// A ledger with a course and an enrolled student, returning the teacher, course and student.
+fn setup() -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+
+    let student = ledger.student();
+    ledger.enroll(&teacher, &course, &student);
+
+    (ledger, teacher, course, student)
+}
+
+fn open_ix(
+    teacher: &Pubkey,
+    course: &Pubkey,
+    session_id: u64,
+    window: (i64, i64),
+    reward: u64,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*teacher, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new(pda::session(&PROGRAM_ID, course, session_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(56, &(session_id, window.0, window.1, reward)),
+    }
+}
+
+fn check_in_ix(student: &Pubkey, course: &Pubkey, session_id: u64) -> Instruction {
+    let session = pda::session(&PROGRAM_ID, course, session_id);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*student, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new_readonly(pda::enrollment(&PROGRAM_ID, course, student), false),
+            AccountMeta::new(session, false),
+            AccountMeta::new(pda::attendance(&PROGRAM_ID, &session, student), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![57],
+    }
+}
+
+/// `CheckIn` with the reward accounts a paying session needs.
+#[cfg(not(feature = "no-rewards"))]
+fn rewarded_check_in_ix(student: &Pubkey, course: &Pubkey, session_id: u64) -> Instruction {
+    let mint = pda::token_mint(&PROGRAM_ID);
+    let mut ix = check_in_ix(student, course, session_id);
+    ix.accounts[1].is_writable = true;
+    ix.accounts.extend([
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+        AccountMeta::new(get_associated_token_address(student, &mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]);
+    ix
+}
+
+const OPEN_NOW: (i64, i64) = (UNIX_TIMESTAMP - 60, UNIX_TIMESTAMP + 3_600);
+
+#[test]
+fn enrolled_students_check_in_once() {
+    let (mut ledger, teacher, course, student) = setup();
+    ledger
+        .process(&open_ix(&teacher, &course, 1, OPEN_NOW, 0))
+        .unwrap();
+
+    ledger.process(&check_in_ix(&student, &course, 1)).unwrap();
+
+    let session = pda::session(&PROGRAM_ID, &course, 1);
+    let attendance: Attendance =
+        try_from_slice_unchecked(&ledger.data(&pda::attendance(&PROGRAM_ID, &session, &student)))
+            .unwrap();
+    assert_eq!(attendance.student, student);
+    assert_eq!(attendance.checked_in_at, UNIX_TIMESTAMP);
+    let session_data: Session = try_from_slice_unchecked(&ledger.data(&session)).unwrap();
+    assert_eq!(session_data.check_ins, 1);
+
+    assert!(ledger.process(&check_in_ix(&student, &course, 1)).is_err());
+}
+
+#[test]
+fn check_ins_outside_the_window_are_rejected() {
+    let (mut ledger, teacher, course, student) = setup();
+    let later = (UNIX_TIMESTAMP + 60, UNIX_TIMESTAMP + 3_600);
+    ledger
+        .process(&open_ix(&teacher, &course, 1, later, 0))
+        .unwrap();
+
+    assert_eq!(
+        ledger.process(&check_in_ix(&student, &course, 1)),
+        Err(IntroError::SessionClosed.into())
+    );
+}
+
+#[test]
+fn sessions_need_a_future_window_and_a_small_reward() {
+    let (mut ledger, teacher, course, _student) = setup();
+    let past = (UNIX_TIMESTAMP - 3_600, UNIX_TIMESTAMP - 60);
+
+    assert_eq!(
+        ledger.process(&open_ix(&teacher, &course, 1, past, 0)),
+        Err(IntroError::InvalidSession.into())
+    );
+    assert_eq!(
+        ledger.process(&open_ix(
+            &teacher,
+            &course,
+            1,
+            OPEN_NOW,
+            Session::MAX_REWARD + 1
+        )),
+        Err(IntroError::InvalidSession.into())
+    );
+}
+
+#[test]
+fn only_the_teacher_opens_sessions() {
+    let (mut ledger, _teacher, course, student) = setup();
+
+    assert_eq!(
+        ledger.process(&open_ix(&student, &course, 1, OPEN_NOW, 0)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn checking_in_needs_an_enrollment() {
+    let (mut ledger, teacher, course, _student) = setup();
+    ledger
+        .process(&open_ix(&teacher, &course, 1, OPEN_NOW, 0))
+        .unwrap();
+    let outsider = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&check_in_ix(&outsider, &course, 1)),
+        Err(IntroError::NotEnrolled.into())
+    );
+}
+
+/// Caps each course's session and quiz rewards at `course_reward_cap` per epoch.
+#[cfg(not(feature = "no-rewards"))]
+fn install_reward_cap(ledger: &mut Ledger, course_reward_cap: u64) {
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        course_reward_cap,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+}
+
+#[cfg(not(feature = "no-rewards"))]
+#[test]
+fn paying_sessions_mint_the_attendance_reward() {
+    let (mut ledger, teacher, course, student) = setup();
+    ledger.initialize_mint();
+    let reward = LAMPORTS_PER_SOL / 2;
+    install_reward_cap(&mut ledger, reward);
+    ledger
+        .process(&open_ix(&teacher, &course, 1, OPEN_NOW, reward))
+        .unwrap();
+
+    ledger
+        .process(&rewarded_check_in_ix(&student, &course, 1))
+        .unwrap();
+
+    let ata = get_associated_token_address(&student, &pda::token_mint(&PROGRAM_ID));
+    assert_eq!(ledger.token_balance(&ata), reward);
+}
+
+#[cfg(not(feature = "no-rewards"))]
+#[test]
+fn course_rewards_stop_at_the_epoch_cap() {
+    let (mut ledger, teacher, course, student) = setup();
+    ledger.initialize_mint();
+    let reward = LAMPORTS_PER_SOL / 2;
+    install_reward_cap(&mut ledger, reward + reward / 2);
+    ledger
+        .process(&open_ix(&teacher, &course, 1, OPEN_NOW, reward))
+        .unwrap();
+    let sybil = ledger.student();
+    ledger.enroll(&teacher, &course, &sybil);
+    let late = ledger.student();
+    ledger.enroll(&teacher, &course, &late);
+
+    let mint = pda::token_mint(&PROGRAM_ID);
+    for (wallet, paid) in [(student, reward), (sybil, reward / 2), (late, 0)] {
+        ledger
+            .process(&rewarded_check_in_ix(&wallet, &course, 1))
+            .unwrap();
+        let ata = get_associated_token_address(&wallet, &mint);
+        assert_eq!(ledger.token_balance(&ata), paid);
+    }
+
+    let course_data: Course = try_from_slice_unchecked(&ledger.data(&course)).unwrap();
+    assert_eq!(course_data.reward_epoch, EPOCH);
+    assert_eq!(course_data.rewards_minted, reward + reward / 2);
+}
+
+#[cfg(not(feature = "no-rewards"))]
+#[test]
+fn paying_sessions_need_the_course_writable() {
+    let (mut ledger, teacher, course, student) = setup();
+    ledger.initialize_mint();
+    install_reward_cap(&mut ledger, LAMPORTS_PER_SOL);
+    ledger
+        .process(&open_ix(&teacher, &course, 1, OPEN_NOW, LAMPORTS_PER_SOL))
+        .unwrap();
+
+    let mut ix = rewarded_check_in_ix(&student, &course, 1);
+    ix.accounts[1].is_writable = false;
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::AccountNotWritable.into())
+    );
+}
+
+#[cfg(not(feature = "no-rewards"))]
+#[test]
+fn held_students_check_in_without_reward() {
+    let (mut ledger, teacher, course, student) = setup();
+    ledger.initialize_mint();
+    let reward = LAMPORTS_PER_SOL / 2;
+    let admin = ledger.wallet();
+    let classifier = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        course_reward_cap: reward,
+        spam_classifier: Some(classifier),
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+    ledger
+        .process(&open_ix(&teacher, &course, 1, OPEN_NOW, reward))
+        .unwrap();
+    let hold = pda::reward_hold(&PROGRAM_ID, &student);
+    let hold_data = RewardHold {
+        discriminator: RewardHold::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        author: student,
+        held: true,
+        target: course,
+        score: 200,
+    };
+    ledger.set(hold, PROGRAM_ID, 1_000_000, hold_data.try_to_vec().unwrap());
+
+    let mut ix = rewarded_check_in_ix(&student, &course, 1);
+    ix.accounts.push(AccountMeta::new_readonly(hold, false));
+    ledger.process(&ix).unwrap();
+
+    let ata = get_associated_token_address(&student, &pda::token_mint(&PROGRAM_ID));
+    assert_eq!(ledger.token_balance(&ata), 0);
+    let course_data: Course = try_from_slice_unchecked(&ledger.data(&course)).unwrap();
+    assert_eq!(course_data.rewards_minted, 0);
+}
@@ -0,0 +1,61 @@
+//! Intros written by the first release of the program: just the text, padded to
+//! `StudentIntroState::ACCOUNT_LEN`, with none of the fields appended since.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::pubkey::Pubkey;
+use student_intro_sol::{pda, state::StudentIntroState};
+
+/// Replaces the writer's intro with a baseline one whose message leaves `spare` bytes free.
+fn baseline_intro(ledger: &mut Ledger, writer: &Pubkey, spare: usize) -> Pubkey {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    let len = StudentIntroState::ACCOUNT_LEN - (4 + 5 + 1 + 32 + (4 + 3) + 4) - spare;
+    let mut data = (
+        StudentIntroState::DISCRIMINATOR,
+        true,
+        *writer,
+        "Ada",
+        "m".repeat(len),
+    )
+        .try_to_vec()
+        .unwrap();
+    data.resize(StudentIntroState::ACCOUNT_LEN, 0);
+    let lamports = ledger.info(&intro).lamports();
+    ledger.set(intro, PROGRAM_ID, lamports, data);
+    intro
+}
+
+#[test]
+fn full_baseline_intros_take_replies() {
+    for spare in [0, 3, 20, 60] {
+        let (mut ledger, writer, _intro) = ledger_with_intro();
+        let intro = baseline_intro(&mut ledger, &writer, spare);
+        let replier = ledger.student();
+
+        ledger
+            .process(&reply_intro_ix(
+                &ledger, &replier, &intro, "Grace", "Welcome",
+            ))
+            .unwrap();
+
+        assert_eq!(ledger.reply_count(&intro), 1);
+    }
+}
+
+#[test]
+fn full_baseline_intros_can_be_shortened() {
+    let (mut ledger, writer, _intro) = ledger_with_intro();
+    let intro = baseline_intro(&mut ledger, &writer, 0);
+
+    ledger
+        .process(&update_intro_ix(&writer, "Ada", "Hello again"))
+        .unwrap();
+
+    let updated = ledger.intro(&intro);
+    assert_eq!(updated.message, "Hello again");
+    assert_eq!(updated.visibility, StudentIntroState::VISIBILITY_PUBLIC);
+}
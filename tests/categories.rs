@@ -0,0 +1,265 @@
+//! Categories: created by the admin at enumerable addresses, and counting the intros filed
+//! under them as writers move between them.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{Category, CategoryRegistry, ProgramConfig, StudentIntroState},
+};
+
+/// A ledger with the mint and a config administered by the returned wallet.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    (ledger, admin)
+}
+
+fn create_category_ix(admin: &Pubkey, index: u64, name: &str) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(pda::category_registry(&PROGRAM_ID), false),
+            AccountMeta::new(pda::category(&PROGRAM_ID, index), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(44, &name),
+    }
+}
+
+/// `SetCategory` moving `writer`'s intro from `current` to `category` (`None` clears it).
+fn set_category_ix(
+    writer: &Pubkey,
+    category: Option<Pubkey>,
+    current: Option<Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*writer, true),
+        AccountMeta::new(pda::intro(&PROGRAM_ID, writer), false),
+        AccountMeta::new(category.unwrap_or(PROGRAM_ID), false),
+    ];
+    if let Some(current) = current {
+        accounts.push(AccountMeta::new(current, false));
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: vec![45],
+    }
+}
+
+fn category(ledger: &Ledger, index: u64) -> Category {
+    try_from_slice_unchecked(&ledger.data(&pda::category(&PROGRAM_ID, index))).unwrap()
+}
+
+fn intro_category(ledger: &Ledger, writer: &Pubkey) -> Option<Pubkey> {
+    try_from_slice_unchecked::<StudentIntroState>(&ledger.data(&pda::intro(&PROGRAM_ID, writer)))
+        .unwrap()
+        .category
+}
+
+fn post_intro(ledger: &mut Ledger) -> Pubkey {
+    let writer = ledger.student();
+    let ix = student_intro_ix(ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    writer
+}
+
+#[test]
+fn categories_are_enumerable() {
+    let (mut ledger, admin) = setup();
+
+    for (index, name) in ["Backend", "DeFi", "NFT"].iter().enumerate() {
+        ledger
+            .process(&create_category_ix(&admin, index as u64, name))
+            .unwrap();
+    }
+
+    let registry: CategoryRegistry =
+        try_from_slice_unchecked(&ledger.data(&pda::category_registry(&PROGRAM_ID))).unwrap();
+    assert_eq!(registry.count, 3);
+    let defi = category(&ledger, 1);
+    assert_eq!(defi.index, 1);
+    assert_eq!(defi.name, "DeFi");
+    assert_eq!(defi.intro_count, 0);
+}
+
+#[test]
+fn categories_are_created_in_order() {
+    let (mut ledger, admin) = setup();
+
+    assert_eq!(
+        ledger.process(&create_category_ix(&admin, 1, "DeFi")),
+        Err(IntroError::InvalidPDA.into())
+    );
+}
+
+#[test]
+fn only_the_admin_creates_categories() {
+    let (mut ledger, _admin) = setup();
+    let intruder = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&create_category_ix(&intruder, 0, "DeFi")),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn category_names_are_bounded() {
+    let (mut ledger, admin) = setup();
+    let name = "x".repeat(Category::MAX_NAME_LEN + 1);
+
+    assert_eq!(
+        ledger.process(&create_category_ix(&admin, 0, &name)),
+        Err(IntroError::InvalidDataLength.into())
+    );
+}
+
+#[test]
+fn moving_an_intro_moves_its_count() {
+    let (mut ledger, admin) = setup();
+    ledger
+        .process(&create_category_ix(&admin, 0, "Backend"))
+        .unwrap();
+    ledger
+        .process(&create_category_ix(&admin, 1, "DeFi"))
+        .unwrap();
+    let backend = pda::category(&PROGRAM_ID, 0);
+    let defi = pda::category(&PROGRAM_ID, 1);
+    let writer = post_intro(&mut ledger);
+
+    ledger
+        .process(&set_category_ix(&writer, Some(backend), None))
+        .unwrap();
+    assert_eq!(intro_category(&ledger, &writer), Some(backend));
+    assert_eq!(category(&ledger, 0).intro_count, 1);
+
+    ledger
+        .process(&set_category_ix(&writer, Some(defi), Some(backend)))
+        .unwrap();
+    assert_eq!(category(&ledger, 0).intro_count, 0);
+    assert_eq!(category(&ledger, 1).intro_count, 1);
+
+    ledger
+        .process(&set_category_ix(&writer, None, Some(defi)))
+        .unwrap();
+    assert_eq!(intro_category(&ledger, &writer), None);
+    assert_eq!(category(&ledger, 1).intro_count, 0);
+}
+
+#[test]
+fn current_category_must_be_passed() {
+    let (mut ledger, admin) = setup();
+    ledger
+        .process(&create_category_ix(&admin, 0, "Backend"))
+        .unwrap();
+    ledger
+        .process(&create_category_ix(&admin, 1, "DeFi"))
+        .unwrap();
+    let backend = pda::category(&PROGRAM_ID, 0);
+    let defi = pda::category(&PROGRAM_ID, 1);
+    let writer = post_intro(&mut ledger);
+    ledger
+        .process(&set_category_ix(&writer, Some(backend), None))
+        .unwrap();
+
+    // Naming the destination as the current category would leave Backend counting the intro.
+    assert_eq!(
+        ledger.process(&set_category_ix(&writer, Some(defi), Some(defi))),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
+
+#[test]
+fn only_categories_can_be_set() {
+    let (mut ledger, _admin) = setup();
+    let writer = post_intro(&mut ledger);
+    let other = post_intro(&mut ledger);
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&set_category_ix(&writer, Some(stranger), None)),
+        Err(ProgramError::IllegalOwner)
+    );
+    let other_intro = pda::intro(&PROGRAM_ID, &other);
+    assert_eq!(
+        ledger.process(&set_category_ix(&writer, Some(other_intro), None)),
+        Err(IntroError::UninitializedAccount.into())
+    );
+}
+
+#[test]
+fn only_the_writer_sets_the_category() {
+    let (mut ledger, admin) = setup();
+    ledger
+        .process(&create_category_ix(&admin, 0, "Backend"))
+        .unwrap();
+    let writer = post_intro(&mut ledger);
+    let intruder = ledger.wallet();
+
+    let ix = substitute(
+        &set_category_ix(&writer, Some(pda::category(&PROGRAM_ID, 0)), None),
+        0,
+        intruder,
+    );
+    assert_eq!(ledger.process(&ix), Err(IntroError::Unauthorized.into()));
+}
+
+#[test]
+fn closing_an_intro_leaves_its_category() {
+    let (mut ledger, admin) = setup();
+    ledger
+        .process(&create_category_ix(&admin, 0, "Backend"))
+        .unwrap();
+    let backend = pda::category(&PROGRAM_ID, 0);
+    let writer = post_intro(&mut ledger);
+    ledger
+        .process(&set_category_ix(&writer, Some(backend), None))
+        .unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let mut data = ledger.data(&intro);
+    let mut state = try_from_slice_unchecked::<StudentIntroState>(&data).unwrap();
+    state.expires_at = Some(UNIX_TIMESTAMP);
+    state.serialize(&mut &mut data[..]).unwrap();
+    let lamports = ledger.info(&intro).lamports();
+    ledger.set(intro, PROGRAM_ID, lamports, data);
+
+    let close = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(writer, false),
+            AccountMeta::new(intro, false),
+            AccountMeta::new(pda::reply_counter(&PROGRAM_ID, &intro), false),
+            AccountMeta::new(backend, false),
+        ],
+        data: vec![15],
+    };
+    ledger.process(&close).unwrap();
+    assert_eq!(category(&ledger, 0).intro_count, 0);
+}
@@ -0,0 +1,230 @@
+//! `ClaimCertificate`: a graduated student mints a one-of-one certificate NFT once the course's
+//! reply and endorsement requirements hold, and `EndorseIntro` records the teacher's sign-off.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Mint;
+use student_intro_sol::{
+    error::IntroError,
+    nft, pda,
+    state::{Certificate, CertificateRequirements, StudentIntroState},
+};
+
+const COURSE: &str = "Solana 101";
+
+/// A ledger with a course holding `requirements` and a public intro scoped to it, returning
+/// the teacher, course and writer.
+fn setup(requirements: CertificateRequirements) -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let teacher = ledger.wallet();
+    let course = pda::course(&PROGRAM_ID, &teacher, COURSE);
+    ledger
+        .process(&create_course_ix(&teacher, COURSE, false, &requirements))
+        .unwrap();
+
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let enrollment = pda::enrollment(&PROGRAM_ID, &course, &writer);
+    ledger.enroll(&teacher, &course, &writer);
+    for visibility in [
+        StudentIntroState::VISIBILITY_COURSE_ONLY,
+        StudentIntroState::VISIBILITY_PUBLIC,
+    ] {
+        ledger
+            .process(&Instruction {
+                program_id: PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(writer, true),
+                    AccountMeta::new(pda::intro(&PROGRAM_ID, &writer), false),
+                    AccountMeta::new_readonly(enrollment, false),
+                    AccountMeta::new(pda::course_stats(&PROGRAM_ID, &course), false),
+                    AccountMeta::new(pda::student_activity(&PROGRAM_ID, &enrollment), false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new_readonly(course, false),
+                ],
+                data: payload(14, &visibility),
+            })
+            .unwrap();
+    }
+
+    (ledger, teacher, course, writer)
+}
+
+fn reply(ledger: &mut Ledger, writer: &Pubkey) {
+    let replier = ledger.student();
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    let ix = reply_intro_ix(ledger, &replier, &intro, "Grace", "Well done");
+    ledger.process(&ix).unwrap();
+}
+
+fn graduate(ledger: &mut Ledger, teacher: &Pubkey, course: &Pubkey, writer: &Pubkey) {
+    ledger
+        .process(&Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*teacher, true),
+                AccountMeta::new_readonly(*course, false),
+                AccountMeta::new(pda::intro(&PROGRAM_ID, writer), false),
+            ],
+            data: payload(50, &StudentIntroState::STATUS_GRADUATED),
+        })
+        .unwrap();
+}
+
+fn endorse_ix(teacher: &Pubkey, course: &Pubkey, writer: &Pubkey) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*teacher, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new_readonly(intro, false),
+            AccountMeta::new(pda::endorsement(&PROGRAM_ID, course, &intro), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![51],
+    }
+}
+
+fn claim_ix(course: &Pubkey, writer: &Pubkey) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    let certificate = pda::certificate(&PROGRAM_ID, course, &intro);
+    let mint = pda::certificate_mint(&PROGRAM_ID, &certificate);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*writer, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new_readonly(intro, false),
+            AccountMeta::new_readonly(pda::reply_counter(&PROGRAM_ID, &intro), false),
+            AccountMeta::new_readonly(pda::endorsement(&PROGRAM_ID, course, &intro), false),
+            AccountMeta::new(certificate, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+            AccountMeta::new(get_associated_token_address(writer, &mint), false),
+            AccountMeta::new(nft::find_metadata_address(&mint).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(nft::TOKEN_METADATA_PROGRAM_ID, false),
+        ],
+        data: vec![52],
+    }
+}
+
+#[test]
+fn graduates_claim_a_one_of_one_certificate() {
+    let requirements = CertificateRequirements {
+        min_replies: 2,
+        requires_endorsement: true,
+    };
+    let (mut ledger, teacher, course, writer) = setup(requirements);
+    reply(&mut ledger, &writer);
+    reply(&mut ledger, &writer);
+    ledger
+        .process(&endorse_ix(&teacher, &course, &writer))
+        .unwrap();
+    graduate(&mut ledger, &teacher, &course, &writer);
+
+    ledger.process(&claim_ix(&course, &writer)).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let certificate = pda::certificate(&PROGRAM_ID, &course, &intro);
+    let record: Certificate = try_from_slice_unchecked(&ledger.data(&certificate)).unwrap();
+    let mint = pda::certificate_mint(&PROGRAM_ID, &certificate);
+    assert_eq!(record.student, writer);
+    assert_eq!(record.mint, mint);
+    assert_eq!(record.completion_epoch, EPOCH);
+
+    let mint_state = Mint::unpack(&ledger.data(&mint)).unwrap();
+    assert_eq!(mint_state.supply, 1);
+    assert_eq!(mint_state.decimals, 0);
+    assert_eq!(mint_state.mint_authority, COption::None);
+    let ata = get_associated_token_address(&writer, &mint);
+    assert_eq!(ledger.token_balance(&ata), 1);
+
+    let metadata = nft::find_metadata_address(&mint).0;
+    assert_eq!(ledger.owner(&metadata), nft::TOKEN_METADATA_PROGRAM_ID);
+    let uri = nft::certificate_uri(COURSE, EPOCH);
+    assert!(uri.len() <= nft::MAX_METADATA_URI_LEN);
+    let data = ledger.data(&metadata);
+    assert!(data
+        .windows(uri.len())
+        .any(|window| window == uri.as_bytes()));
+}
+
+#[test]
+fn certificates_are_claimed_once() {
+    let (mut ledger, teacher, course, writer) = setup(CertificateRequirements::default());
+    graduate(&mut ledger, &teacher, &course, &writer);
+    ledger.process(&claim_ix(&course, &writer)).unwrap();
+
+    assert!(ledger.process(&claim_ix(&course, &writer)).is_err());
+}
+
+#[test]
+fn active_intros_cannot_claim() {
+    let (mut ledger, _teacher, course, writer) = setup(CertificateRequirements::default());
+
+    assert_eq!(
+        ledger.process(&claim_ix(&course, &writer)),
+        Err(IntroError::CertificateNotEarned.into())
+    );
+}
+
+#[test]
+fn claims_need_the_minimum_replies() {
+    let requirements = CertificateRequirements {
+        min_replies: 2,
+        requires_endorsement: false,
+    };
+    let (mut ledger, teacher, course, writer) = setup(requirements);
+    reply(&mut ledger, &writer);
+    graduate(&mut ledger, &teacher, &course, &writer);
+
+    assert_eq!(
+        ledger.process(&claim_ix(&course, &writer)),
+        Err(IntroError::CertificateNotEarned.into())
+    );
+}
+
+#[test]
+fn claims_need_an_endorsement_when_required() {
+    let requirements = CertificateRequirements {
+        min_replies: 0,
+        requires_endorsement: true,
+    };
+    let (mut ledger, teacher, course, writer) = setup(requirements);
+    graduate(&mut ledger, &teacher, &course, &writer);
+
+    assert_eq!(
+        ledger.process(&claim_ix(&course, &writer)),
+        Err(IntroError::CertificateNotEarned.into())
+    );
+}
+
+#[test]
+fn only_the_course_teacher_endorses() {
+    let (mut ledger, _teacher, course, writer) = setup(CertificateRequirements::default());
+    let intruder = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&endorse_ix(&intruder, &course, &writer)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
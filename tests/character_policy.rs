@@ -0,0 +1,182 @@
+//! `SetCharacterPolicy`: a teacher limits the characters of intros scoped to the course and of
+//! replies to its course-only intros.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    format, pda,
+    state::{CharacterPolicy, StudentIntroState},
+};
+
+const COURSE: &str = "Solana 101";
+
+const POLICY: CharacterPolicy = CharacterPolicy {
+    ascii_only: true,
+    disallow_urls: true,
+    max_emoji: Some(1),
+};
+
+fn policy_ix(teacher: &Pubkey, course: &Pubkey, policy: CharacterPolicy) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*teacher, true),
+            AccountMeta::new(*course, false),
+        ],
+        data: payload(80, &policy),
+    }
+}
+
+/// The writer's `SetVisibility` scoping their intro to `course`, course-only.
+fn scope_ix(course: &Pubkey, writer: &Pubkey) -> Instruction {
+    let enrollment = pda::enrollment(&PROGRAM_ID, course, writer);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*writer, true),
+            AccountMeta::new(pda::intro(&PROGRAM_ID, writer), false),
+            AccountMeta::new_readonly(enrollment, false),
+            AccountMeta::new(pda::course_stats(&PROGRAM_ID, course), false),
+            AccountMeta::new(pda::student_activity(&PROGRAM_ID, &enrollment), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*course, false),
+        ],
+        data: payload(14, &StudentIntroState::VISIBILITY_COURSE_ONLY),
+    }
+}
+
+/// `ReplyIntro` from an enrolled `replier` to a course-only intro.
+fn course_reply_ix(
+    ledger: &Ledger,
+    course: &Pubkey,
+    replier: &Pubkey,
+    intro: &Pubkey,
+    message: &str,
+) -> Instruction {
+    let enrollment = pda::enrollment(&PROGRAM_ID, course, replier);
+    let mut ix = reply_intro_ix(ledger, replier, intro, "Grace", message);
+    ix.accounts.extend([
+        AccountMeta::new_readonly(enrollment, false),
+        AccountMeta::new(pda::course_stats(&PROGRAM_ID, course), false),
+        AccountMeta::new(pda::student_activity(&PROGRAM_ID, &enrollment), false),
+        AccountMeta::new_readonly(*course, false),
+    ]);
+    ix
+}
+
+/// A ledger with a course under `POLICY`, returning the teacher and course.
+fn setup() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+    ledger
+        .process(&policy_ix(&teacher, &course, POLICY))
+        .unwrap();
+
+    (ledger, teacher, course)
+}
+
+/// Posts an intro and scopes it to `course`, returning the writer.
+fn scoped_writer(ledger: &mut Ledger, teacher: &Pubkey, course: &Pubkey) -> Pubkey {
+    let writer = ledger.student();
+    let ix = student_intro_ix(ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    ledger.enroll(teacher, course, &writer);
+    ledger.process(&scope_ix(course, &writer)).unwrap();
+    writer
+}
+
+#[test]
+fn replies_follow_the_course_policy() {
+    let (mut ledger, teacher, course) = setup();
+    let writer = scoped_writer(&mut ledger, &teacher, &course);
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+    ledger.enroll(&teacher, &course, &replier);
+
+    for message in ["Café?", "See www.example.com", "Hi 👋👋"] {
+        let ix = course_reply_ix(&ledger, &course, &replier, &intro, message);
+        assert_eq!(
+            ledger.process(&ix),
+            Err(IntroError::CharacterPolicyViolation.into()),
+            "{message}"
+        );
+    }
+
+    let ix = course_reply_ix(&ledger, &course, &replier, &intro, "Hi 👋");
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::CharacterPolicyViolation.into())
+    );
+
+    ledger
+        .process(&policy_ix(
+            &teacher,
+            &course,
+            CharacterPolicy {
+                ascii_only: false,
+                ..POLICY
+            },
+        ))
+        .unwrap();
+    ledger.process(&ix).unwrap();
+    assert_eq!(ledger.reply_count(&intro), 1);
+}
+
+#[test]
+fn edits_of_scoped_intros_follow_the_course_policy() {
+    let (mut ledger, teacher, course) = setup();
+    let writer = scoped_writer(&mut ledger, &teacher, &course);
+
+    let mut ix = update_intro_ix(&writer, "Ada", "Visit https://example.com");
+    ix.accounts.push(AccountMeta::new_readonly(course, false));
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::CharacterPolicyViolation.into())
+    );
+
+    let mut ix = update_intro_ix(&writer, "Ada", "Hello again");
+    ix.accounts.push(AccountMeta::new_readonly(course, false));
+    ledger.process(&ix).unwrap();
+}
+
+#[test]
+fn scoping_checks_the_text_already_posted() {
+    let (mut ledger, teacher, course) = setup();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Adá", "Hello");
+    ledger.process(&ix).unwrap();
+    ledger.enroll(&teacher, &course, &writer);
+
+    assert_eq!(
+        ledger.process(&scope_ix(&course, &writer)),
+        Err(IntroError::CharacterPolicyViolation.into())
+    );
+}
+
+#[test]
+fn only_the_teacher_sets_the_policy() {
+    let (mut ledger, _teacher, course) = setup();
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&policy_ix(&stranger, &course, CharacterPolicy::default())),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn modified_emoji_count_once() {
+    assert_eq!(format::emoji_count("👋🏽 ok ✨"), 2);
+    assert_eq!(format::emoji_count("plain text"), 0);
+}
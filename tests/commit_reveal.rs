@@ -0,0 +1,215 @@
+//! `CommitReply` and `RevealReply`: a reply is committed by hash and posted later by anyone
+//! holding its contents, under the same audience checks as the committer's own reply.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{ProgramConfig, ReplyCommitment, StudentIntroState, CLOSED_ACCOUNT_DISCRIMINATOR},
+};
+
+const SALT: [u8; 32] = [7; 32];
+
+fn err(error: IntroError) -> ProgramError {
+    error.into()
+}
+
+fn commit_ix(committer: &Pubkey, intro: &Pubkey, hash: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*committer, true),
+            AccountMeta::new_readonly(*intro, false),
+            AccountMeta::new(
+                pda::reply_commitment(&PROGRAM_ID, intro, committer, &hash),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(8, &hash),
+    }
+}
+
+fn reveal_ix(
+    ledger: &Ledger,
+    payer: &Pubkey,
+    intro: &Pubkey,
+    committer: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let counter = pda::reply_counter(&PROGRAM_ID, intro);
+    let hash = ReplyCommitment::hash_reply(name, message, &SALT);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*intro, false),
+            AccountMeta::new(counter, false),
+            AccountMeta::new(*committer, false),
+            AccountMeta::new(
+                pda::reply_commitment(&PROGRAM_ID, intro, committer, &hash),
+                false,
+            ),
+            AccountMeta::new(
+                pda::reply(&PROGRAM_ID, intro, ledger.reply_count(intro)),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(9, &(name, message, SALT)),
+    }
+}
+
+/// A ledger with one intro, returning the intro.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    (ledger, pda::intro(&PROGRAM_ID, &writer))
+}
+
+#[test]
+fn committed_reply_is_revealed() {
+    let (mut ledger, intro) = setup();
+    let committer = ledger.student();
+    let hash = ReplyCommitment::hash_reply("Grace", "Welcome", &SALT);
+    ledger
+        .process(&commit_ix(&committer, &intro, hash))
+        .unwrap();
+
+    let payer = ledger.student();
+    let committed = pda::reply_commitment(&PROGRAM_ID, &intro, &committer, &hash);
+    let rent = ledger.info(&committed).lamports();
+    let before = ledger.info(&committer).lamports();
+    let ix = reveal_ix(&ledger, &payer, &intro, &committer, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(ledger.reply_count(&intro), 1);
+    assert_eq!(ledger.info(&committer).lamports(), before + rent);
+    let tombstone = CLOSED_ACCOUNT_DISCRIMINATOR.try_to_vec().unwrap();
+    assert!(ledger.data(&committed).starts_with(&tombstone));
+}
+
+#[test]
+fn commitments_reveal_only_for_their_committer() {
+    let (mut ledger, intro) = setup();
+    let committer = ledger.student();
+    let hash = ReplyCommitment::hash_reply("Grace", "Welcome", &SALT);
+    ledger
+        .process(&commit_ix(&committer, &intro, hash))
+        .unwrap();
+
+    let other = ledger.student();
+    let mut ix = reveal_ix(&ledger, &other, &intro, &other, "Grace", "Welcome");
+    ix.accounts[4].pubkey = pda::reply_commitment(&PROGRAM_ID, &intro, &committer, &hash);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(err(IntroError::CommitmentMismatch))
+    );
+    assert_eq!(ledger.reply_count(&intro), 0);
+}
+
+#[test]
+fn reveals_to_course_only_intros_need_the_committer_enrolled() {
+    let (mut ledger, intro) = setup();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, "Solana 101");
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.visibility = StudentIntroState::VISIBILITY_COURSE_ONLY;
+    intro_data.course = Some(course);
+    ledger.set_intro(&intro, &intro_data);
+
+    let committer = ledger.student();
+    let hash = ReplyCommitment::hash_reply("Grace", "Welcome", &SALT);
+    ledger
+        .process(&commit_ix(&committer, &intro, hash))
+        .unwrap();
+
+    let payer = ledger.student();
+    let enrollment = pda::enrollment(&PROGRAM_ID, &course, &committer);
+    let mut ix = reveal_ix(&ledger, &payer, &intro, &committer, "Grace", "Welcome");
+    ix.accounts.extend([
+        AccountMeta::new_readonly(enrollment, false),
+        AccountMeta::new(pda::course_stats(&PROGRAM_ID, &course), false),
+        AccountMeta::new(pda::student_activity(&PROGRAM_ID, &enrollment), false),
+        AccountMeta::new_readonly(course, false),
+    ]);
+    assert_eq!(ledger.process(&ix), Err(err(IntroError::NotEnrolled)));
+
+    ledger.enroll(&teacher, &course, &committer);
+    ledger.process(&ix).unwrap();
+    assert_eq!(ledger.reply_count(&intro), 1);
+}
+
+#[test]
+fn private_intros_take_no_reveals() {
+    let (mut ledger, intro) = setup();
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.visibility = StudentIntroState::VISIBILITY_PRIVATE;
+    ledger.set_intro(&intro, &intro_data);
+
+    let committer = ledger.student();
+    let hash = ReplyCommitment::hash_reply("Grace", "Welcome", &SALT);
+    ledger
+        .process(&commit_ix(&committer, &intro, hash))
+        .unwrap();
+
+    let ix = reveal_ix(&ledger, &committer, &intro, &committer, "Grace", "Welcome");
+    assert_eq!(ledger.process(&ix), Err(err(IntroError::RepliesNotAllowed)));
+}
+
+#[test]
+fn shifting_bytes_between_fields_does_not_match_the_commitment() {
+    let (mut ledger, intro) = setup();
+    let committer = ledger.student();
+    let hash = ReplyCommitment::hash_reply("ab", "c", &SALT);
+    ledger
+        .process(&commit_ix(&committer, &intro, hash))
+        .unwrap();
+
+    let mut ix = reveal_ix(&ledger, &committer, &intro, &committer, "a", "bc");
+    ix.accounts[4].pubkey = pda::reply_commitment(&PROGRAM_ID, &intro, &committer, &hash);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(err(IntroError::CommitmentMismatch))
+    );
+    assert_eq!(ledger.reply_count(&intro), 0);
+}
+
+#[test]
+fn huge_reveal_delay_keeps_the_reply_sealed() {
+    let (mut ledger, intro) = setup();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        reveal_delay_slots: u64::MAX,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    let committer = ledger.student();
+    let hash = ReplyCommitment::hash_reply("Grace", "Welcome", &SALT);
+    ledger
+        .process(&commit_ix(&committer, &intro, hash))
+        .unwrap();
+
+    let ix = reveal_ix(&ledger, &committer, &intro, &committer, "Grace", "Welcome");
+    assert_eq!(ledger.process(&ix), Err(err(IntroError::RevealTooEarly)));
+}
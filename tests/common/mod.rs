@@ -0,0 +1,638 @@
+//! In-process harness for driving the processor from integration tests: an account store with
+//! per-instruction rollback, and syscall stubs that provide the clock and rent sysvars and
+//! execute system program, SPL Token, associated token account, Token Metadata and
+//! this program's own CPIs.
+
+#![allow(dead_code)]
+
+use std::{cell::RefCell, collections::HashMap, sync::Once};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo,
+    borsh::try_from_slice_unchecked,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use student_intro_sol::{
+    instruction::{config_account, BatchOp},
+    nft, pda,
+    processor::process_instruction,
+    state::{CertificateRequirements, IntroFeedPage, ProgramConfig, StudentIntroState},
+};
+
+pub const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+pub const SLOT: u64 = 1_000;
+pub const EPOCH: u64 = 5;
+pub const UNIX_TIMESTAMP: i64 = 1_700_000_000;
+
+thread_local! {
+    /// Programs on the CPI stack; PDA signatures are derived from the innermost caller.
+    static CALLERS: RefCell<Vec<Pubkey>> = RefCell::new(vec![PROGRAM_ID]);
+    /// Return data set by the last processed instruction.
+    static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    /// `sol_log_data` payloads of the last processed instruction, in order.
+    static LOGGED_DATA: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+    /// `msg!` lines of the last processed instruction, prefixed as the runtime prefixes them.
+    static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+// SystemInstruction tags (bincode u32).
+const CREATE_ACCOUNT: u32 = 0;
+const TRANSFER: u32 = 2;
+
+pub struct Ledger {
+    accounts: HashMap<Pubkey, AccountInfo<'static>>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(TestStubs));
+        });
+
+        let mut ledger = Self {
+            accounts: HashMap::new(),
+        };
+        ledger.set(system_program::id(), Pubkey::default(), 1, vec![]);
+        ledger.set(spl_token::id(), Pubkey::default(), 1, vec![]);
+        ledger.set(PROGRAM_ID, Pubkey::default(), 1, vec![]);
+        ledger.set(
+            sysvar::rent::id(),
+            sysvar::id(),
+            1,
+            bincode_rent(&Rent::default()),
+        );
+        ledger
+    }
+
+    /// Creates or overwrites an account.
+    pub fn set(&mut self, key: Pubkey, owner: Pubkey, lamports: u64, data: Vec<u8>) {
+        let key = Box::leak(Box::new(key));
+        let info = AccountInfo::new(
+            key,
+            false,
+            false,
+            Box::leak(Box::new(lamports)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        );
+        self.accounts.insert(*key, info);
+    }
+
+    /// Writes `config` to the config PDA at its full account length, as `InitializeConfig` would.
+    pub fn install_config(&mut self, config: ProgramConfig) {
+        let mut data = config.try_to_vec().unwrap();
+        data.resize(ProgramConfig::ACCOUNT_LEN, 0);
+        self.set(pda::config(&PROGRAM_ID), PROGRAM_ID, LAMPORTS_PER_SOL, data);
+    }
+
+    pub fn wallet(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.set(key, system_program::id(), 100 * LAMPORTS_PER_SOL, vec![]);
+        key
+    }
+
+    /// An initialized, empty token account for `owner` at its associated address for `mint`.
+    pub fn token_account(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let key = get_associated_token_address(owner, mint);
+        self.raw_token_account(key, owner, mint);
+        key
+    }
+
+    pub fn raw_token_account(&mut self, key: Pubkey, owner: &Pubkey, mint: &Pubkey) {
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            state: AccountState::Initialized,
+            ..TokenAccount::default()
+        }
+        .pack_into_slice(&mut data);
+        self.set(
+            key,
+            spl_token::id(),
+            Rent::default().minimum_balance(TokenAccount::LEN),
+            data,
+        );
+    }
+
+    pub fn info(&self, key: &Pubkey) -> &AccountInfo<'static> {
+        &self.accounts[key]
+    }
+
+    pub fn exists(&self, key: &Pubkey) -> bool {
+        self.accounts
+            .get(key)
+            .is_some_and(|info| info.lamports() > 0)
+    }
+
+    pub fn keys_owned_by(&self, owner: &Pubkey) -> Vec<Pubkey> {
+        self.accounts
+            .iter()
+            .filter(|(_, info)| info.owner == owner && info.lamports() > 0)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    pub fn owner(&self, key: &Pubkey) -> Pubkey {
+        *self.accounts[key].owner
+    }
+
+    pub fn data(&self, key: &Pubkey) -> Vec<u8> {
+        self.accounts[key].data.borrow().to_vec()
+    }
+
+    pub fn intro(&self, key: &Pubkey) -> StudentIntroState {
+        try_from_slice_unchecked(&self.data(key)).unwrap()
+    }
+
+    /// Overwrites an intro in place, padded to its account length.
+    pub fn set_intro(&mut self, key: &Pubkey, intro_data: &StudentIntroState) {
+        let mut data = intro_data.try_to_vec().unwrap();
+        data.resize(StudentIntroState::ACCOUNT_LEN, 0);
+        let lamports = self.info(key).lamports();
+        self.set(*key, PROGRAM_ID, lamports, data);
+    }
+
+    pub fn token_balance(&self, key: &Pubkey) -> u64 {
+        TokenAccount::unpack(&self.data(key)).unwrap().amount
+    }
+
+    pub fn mint_supply(&self) -> u64 {
+        Mint::unpack(&self.data(&pda::token_mint(&PROGRAM_ID)))
+            .unwrap()
+            .supply
+    }
+
+    /// Runs `instruction` through the processor, appending the trailing config account every
+    /// client passes unless the instruction already ends with it (writable, for instructions
+    /// that change the config). On failure every account it touched is restored, like a failed
+    /// transaction.
+    pub fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+        let config = config_account(&PROGRAM_ID);
+        let has_config = instruction
+            .accounts
+            .last()
+            .is_some_and(|meta| meta.pubkey == config.pubkey);
+        let infos: Vec<AccountInfo<'static>> = instruction
+            .accounts
+            .iter()
+            .chain((!has_config).then_some(&config))
+            .map(|meta| {
+                if !self.accounts.contains_key(&meta.pubkey) {
+                    self.set(meta.pubkey, system_program::id(), 0, vec![]);
+                }
+                let mut info = self.accounts[&meta.pubkey].clone();
+                info.is_signer = meta.is_signer;
+                info.is_writable = meta.is_writable;
+                info
+            })
+            .collect();
+
+        let snapshot: Vec<(u64, Vec<u8>, Pubkey)> = infos
+            .iter()
+            .map(|info| (info.lamports(), info.data.borrow().to_vec(), *info.owner))
+            .collect();
+
+        RETURN_DATA.with(|data| data.borrow_mut().clear());
+        LOGGED_DATA.with(|logged| logged.borrow_mut().clear());
+        LOGS.with(|logs| logs.borrow_mut().clear());
+        let result = process_instruction(&instruction.program_id, &infos, &instruction.data);
+
+        if result.is_err() {
+            for (info, (lamports, data, owner)) in infos.iter().zip(snapshot) {
+                **info.lamports.borrow_mut() = lamports;
+                *info.data.borrow_mut() = Box::leak(data.into_boxed_slice());
+                info.assign(&owner);
+            }
+        }
+
+        result
+    }
+
+    /// Runs `f` as program `caller` would run, handing it the accounts at `keys` (writable,
+    /// unsigned), so its CPIs into this program sign with `caller`'s PDAs. Nothing is rolled
+    /// back on failure.
+    pub fn run_as<R>(
+        &mut self,
+        caller: Pubkey,
+        keys: &[Pubkey],
+        f: impl FnOnce(&[AccountInfo<'static>]) -> R,
+    ) -> R {
+        let infos: Vec<AccountInfo<'static>> = keys
+            .iter()
+            .map(|key| {
+                if !self.accounts.contains_key(key) {
+                    self.set(*key, system_program::id(), 0, vec![]);
+                }
+                let mut info = self.accounts[key].clone();
+                info.is_signer = false;
+                info.is_writable = true;
+                info
+            })
+            .collect();
+
+        CALLERS.with(|callers| callers.borrow_mut().push(caller));
+        let result = f(&infos);
+        CALLERS.with(|callers| callers.borrow_mut().pop());
+        result
+    }
+
+    /// Return data of the last processed instruction, empty when it set none.
+    pub fn return_data(&self) -> Vec<u8> {
+        RETURN_DATA.with(|data| data.borrow().clone())
+    }
+
+    /// Events the last processed instruction logged, Borsh-encoded, including when it failed.
+    pub fn logged_data(&self) -> Vec<Vec<u8>> {
+        LOGGED_DATA.with(|logged| logged.borrow().clone())
+    }
+
+    /// Log messages of the last processed instruction, including when it failed.
+    pub fn logs(&self) -> Vec<String> {
+        LOGS.with(|logs| logs.borrow().clone())
+    }
+
+    pub fn initialize_mint(&mut self) {
+        let payer = self.wallet();
+        self.process(&initialize_mint_ix(&payer)).unwrap();
+    }
+
+    /// A wallet with a reward token account, ready to post.
+    pub fn student(&mut self) -> Pubkey {
+        let student = self.wallet();
+        self.token_account(&student, &pda::token_mint(&PROGRAM_ID));
+        student
+    }
+
+    /// Creates `name` for `teacher` without student IDs or certificate requirements, returning
+    /// the course.
+    pub fn create_course(&mut self, teacher: &Pubkey, name: &str) -> Pubkey {
+        let certificate = CertificateRequirements::default();
+        self.process(&create_course_ix(teacher, name, false, &certificate))
+            .unwrap();
+        pda::course(&PROGRAM_ID, teacher, name)
+    }
+
+    /// `EnrollStudent`, signed by the course's teacher.
+    pub fn enroll(&mut self, teacher: &Pubkey, course: &Pubkey, student: &Pubkey) {
+        self.process(&Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*teacher, true),
+                AccountMeta::new_readonly(*course, false),
+                AccountMeta::new_readonly(*student, false),
+                AccountMeta::new(pda::enrollment(&PROGRAM_ID, course, student), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: vec![13],
+        })
+        .unwrap();
+    }
+
+    pub fn registry_count(&self) -> u64 {
+        let registry = pda::registry(&PROGRAM_ID);
+        if !self.exists(&registry) {
+            return 0;
+        }
+        let data = self.data(&registry);
+        let mut rest = &data[..];
+        let _: (String, bool) = borsh::BorshDeserialize::deserialize(&mut rest).unwrap();
+        u64::from_le_bytes(rest[..8].try_into().unwrap())
+    }
+
+    pub fn reply_count(&self, intro: &Pubkey) -> u64 {
+        let counter = pda::reply_counter(&PROGRAM_ID, intro);
+        let data = self.data(&counter);
+        let mut rest = &data[..];
+        let _: (String, bool) = borsh::BorshDeserialize::deserialize(&mut rest).unwrap();
+        u64::from_le_bytes(rest[..8].try_into().unwrap())
+    }
+}
+
+/// Account list and data for `StudentIntro` under the default config.
+pub fn student_intro_ix(
+    ledger: &Ledger,
+    writer: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    let mint = pda::token_mint(&PROGRAM_ID);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*writer, true),
+            AccountMeta::new(intro, false),
+            AccountMeta::new(pda::reply_counter(&PROGRAM_ID, &intro), false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+            AccountMeta::new(get_associated_token_address(writer, &mint), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(pda::intro_tally(&PROGRAM_ID, writer), false),
+            AccountMeta::new(pda::registry(&PROGRAM_ID), false),
+            AccountMeta::new(
+                pda::registry_entry(&PROGRAM_ID, ledger.registry_count()),
+                false,
+            ),
+            AccountMeta::new(
+                pda::intro_feed_page(&PROGRAM_ID, IntroFeedPage::page_of(ledger.registry_count())),
+                false,
+            ),
+        ],
+        data: payload(0, &(name, message)),
+    }
+}
+
+/// Account list and data for `ReplyIntro` under the default config, as the next reply.
+pub fn reply_intro_ix(
+    ledger: &Ledger,
+    replier: &Pubkey,
+    intro: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let mint = pda::token_mint(&PROGRAM_ID);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*replier, true),
+            AccountMeta::new(*intro, false),
+            AccountMeta::new(pda::reply_counter(&PROGRAM_ID, intro), false),
+            AccountMeta::new(
+                pda::reply(&PROGRAM_ID, intro, ledger.reply_count(intro)),
+                false,
+            ),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+            AccountMeta::new(get_associated_token_address(replier, &mint), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(pda::reply_tally(&PROGRAM_ID, intro, replier), false),
+        ],
+        data: payload(2, &(name, message)),
+    }
+}
+
+/// Account list and data for `InitializeMint`, without the deprecated rent sysvar.
+pub fn initialize_mint_ix(payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(pda::token_mint(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: vec![3],
+    }
+}
+
+/// A ledger with the reward mint and one posted intro, returning the writer and intro.
+pub fn ledger_with_intro() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    (ledger, writer, pda::intro(&PROGRAM_ID, &writer))
+}
+
+/// Account list and data for `CreateCourse`.
+pub fn create_course_ix(
+    teacher: &Pubkey,
+    name: &str,
+    requires_student_id: bool,
+    certificate: &CertificateRequirements,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*teacher, true),
+            AccountMeta::new(pda::course(&PROGRAM_ID, teacher, name), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(12, &(name, requires_student_id, certificate)),
+    }
+}
+
+/// Account list and data for `UpdateIntro`.
+pub fn update_intro_ix(writer: &Pubkey, name: &str, message: &str) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*writer, true),
+            AccountMeta::new(intro, false),
+            AccountMeta::new_readonly(pda::reply_counter(&PROGRAM_ID, &intro), false),
+            AccountMeta::new(
+                Pubkey::find_program_address(&[intro.as_ref(), b"history"], &PROGRAM_ID).0,
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(1, &(name, message)),
+    }
+}
+
+/// Wraps `instructions` into one `Batch`, concatenating their account lists.
+pub fn batch_ix(instructions: &[Instruction]) -> Instruction {
+    let ops: Vec<BatchOp> = instructions
+        .iter()
+        .map(|ix| BatchOp {
+            account_count: ix.accounts.len() as u8,
+            data: ix.data.clone(),
+        })
+        .collect();
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: instructions
+            .iter()
+            .flat_map(|ix| ix.accounts.clone())
+            .collect(),
+        data: payload(31, &ops),
+    }
+}
+
+pub fn payload<T: BorshSerialize>(variant: u8, value: &T) -> Vec<u8> {
+    let mut data = vec![variant];
+    data.extend(value.try_to_vec().unwrap());
+    data
+}
+
+/// Replaces the account at `index` of `instruction`, keeping its signer/writable flags.
+pub fn substitute(instruction: &Instruction, index: usize, key: Pubkey) -> Instruction {
+    let mut instruction = instruction.clone();
+    instruction.accounts[index].pubkey = key;
+    instruction
+}
+
+fn bincode_rent(rent: &Rent) -> Vec<u8> {
+    let mut data = rent.lamports_per_byte_year.to_le_bytes().to_vec();
+    data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+    data.push(rent.burn_percent);
+    data
+}
+
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_log(&self, message: &str) {
+        LOGS.with(|logs| logs.borrow_mut().push(format!("Program log: {message}")));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        LOGGED_DATA.with(|logged| logged.borrow_mut().push(fields.concat()));
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT,
+            epoch: EPOCH,
+            unix_timestamp: UNIX_TIMESTAMP,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = CALLERS.with(|callers| *callers.borrow().last().unwrap());
+        let signers: Vec<Pubkey> = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller).unwrap())
+            .collect();
+
+        // The callee sees the accounts in instruction order, with PDA signatures applied.
+        let callee_infos: Vec<AccountInfo> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let mut info = account_infos
+                    .iter()
+                    .find(|info| *info.key == meta.pubkey)
+                    .expect("CPI account missing from account infos")
+                    .clone();
+                info.is_signer = info.is_signer || signers.contains(info.key);
+                info.is_writable = meta.is_writable;
+                info
+            })
+            .collect();
+
+        if instruction.program_id == spl_token::id() {
+            spl_token::processor::Processor::process(
+                &spl_token::id(),
+                &callee_infos,
+                &instruction.data,
+            )
+        } else if instruction.program_id == system_program::id() {
+            system_instruction(&callee_infos, &instruction.data)
+        } else if instruction.program_id == spl_associated_token_account::id() {
+            CALLERS.with(|callers| callers.borrow_mut().push(instruction.program_id));
+            let result = spl_associated_token_account::processor::process_instruction(
+                &instruction.program_id,
+                &callee_infos,
+                &instruction.data,
+            );
+            CALLERS.with(|callers| callers.borrow_mut().pop());
+            result
+        } else if instruction.program_id == nft::TOKEN_METADATA_PROGRAM_ID {
+            metadata_instruction(&callee_infos, &instruction.data)
+        } else if instruction.program_id == PROGRAM_ID {
+            CALLERS.with(|callers| callers.borrow_mut().push(PROGRAM_ID));
+            let result = process_instruction(&PROGRAM_ID, &callee_infos, &instruction.data);
+            CALLERS.with(|callers| callers.borrow_mut().pop());
+            result
+        } else {
+            panic!("no CPI stub for program {}", instruction.program_id);
+        }
+    }
+}
+
+/// Records `CreateMetadataAccountV3` by storing its raw instruction data in the metadata
+/// account, which is enough for tests to read back the name, symbol and URI.
+fn metadata_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (metadata, mint, mint_authority) = (&accounts[0], &accounts[1], &accounts[2]);
+    let expected = Mint::unpack(&mint.data.borrow())?.mint_authority;
+
+    if !mint_authority.is_signer || expected != Some(*mint_authority.key).into() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *metadata.owner != system_program::id() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    *metadata.data.borrow_mut() = Box::leak(data.to_vec().into_boxed_slice());
+    metadata.assign(&nft::TOKEN_METADATA_PROGRAM_ID);
+
+    Ok(())
+}
+
+fn system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let tag = u32::from_le_bytes(data[..4].try_into().unwrap());
+    let lamports = u64::from_le_bytes(data[4..12].try_into().unwrap());
+    let (from, to) = (&accounts[0], &accounts[1]);
+
+    if !from.is_signer || (tag == CREATE_ACCOUNT && !to.is_signer) {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    match tag {
+        CREATE_ACCOUNT => {
+            if to.lamports() > 0 || *to.owner != system_program::id() {
+                // SystemError::AccountAlreadyInUse
+                return Err(ProgramError::Custom(0));
+            }
+            let space = u64::from_le_bytes(data[12..20].try_into().unwrap()) as usize;
+            let owner = Pubkey::new(&data[20..52]);
+            *to.data.borrow_mut() = Box::leak(vec![0; space].into_boxed_slice());
+            to.assign(&owner);
+        }
+        TRANSFER => {}
+        _ => panic!("no stub for system instruction {}", tag),
+    }
+
+    if from.lamports() < lamports {
+        // SystemError::ResultWithNegativeLamports
+        return Err(ProgramError::Custom(1));
+    }
+    **from.lamports.borrow_mut() -= lamports;
+    **to.lamports.borrow_mut() += lamports;
+
+    Ok(())
+}
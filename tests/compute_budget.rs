@@ -0,0 +1,29 @@
+//! `client` compute budget helpers: reading measured usage back from simulation logs.
+
+use solana_program::pubkey::Pubkey;
+use student_intro_sol::client::{
+    compute_unit_limit, consumed_compute_units, MAX_COMPUTE_UNIT_LIMIT,
+};
+
+#[test]
+fn consumed_units_come_from_the_outer_invocation() {
+    let program = Pubkey::new_unique();
+    let token = Pubkey::new_unique();
+    let logs = [
+        format!("Program {program} invoke [1]"),
+        format!("Program {token} consumed 4645 of 170000 compute units"),
+        format!("Program {program} consumed 12000 of 160000 compute units"),
+        format!("Program {program} consumed 38211 of 200000 compute units"),
+        format!("Program {program} success"),
+    ];
+
+    assert_eq!(consumed_compute_units(&logs, &program), Some(38_211));
+    assert_eq!(consumed_compute_units(&logs, &Pubkey::new_unique()), None);
+}
+
+#[test]
+fn limits_add_headroom_and_stay_within_the_cap() {
+    assert_eq!(compute_unit_limit(38_211), 46_000);
+    assert_eq!(compute_unit_limit(50_000), 60_000);
+    assert_eq!(compute_unit_limit(5_000_000), MAX_COMPUTE_UNIT_LIMIT);
+}
@@ -0,0 +1,110 @@
+//! After any mix of successful and rejected reply instructions, each intro's `ReplyCount`
+//! equals the number of reply accounts that exist for it, and those occupy indices
+//! `0..counter` exactly. Pagination and the registry both rely on this. Replies cannot be
+//! deleted yet, so every materialized reply counts.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{borsh::try_from_slice_unchecked, instruction::Instruction, pubkey::Pubkey};
+use student_intro_sol::{pda, state::StudentReplyState};
+
+/// Reply accounts for `intro` found by scanning every account the program owns.
+fn materialized_replies(ledger: &Ledger, intro: &Pubkey) -> Vec<Pubkey> {
+    ledger
+        .keys_owned_by(&PROGRAM_ID)
+        .into_iter()
+        .filter(|key| {
+            try_from_slice_unchecked::<StudentReplyState>(&ledger.data(key)).is_ok_and(|reply| {
+                reply.discriminator == StudentReplyState::DISCRIMINATOR && reply.intro == *intro
+            })
+        })
+        .collect()
+}
+
+fn assert_consistent(ledger: &Ledger, intros: &[Pubkey]) {
+    for intro in intros {
+        let counter = ledger.reply_count(intro);
+        let mut replies = materialized_replies(ledger, intro);
+        let mut expected: Vec<Pubkey> = (0..counter)
+            .map(|index| pda::reply(&PROGRAM_ID, intro, index))
+            .collect();
+        replies.sort();
+        expected.sort();
+        assert_eq!(replies, expected, "counter {counter} for intro {intro}");
+    }
+}
+
+/// Deterministic xorshift so failures reproduce without a seed file.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+#[test]
+fn counter_matches_materialized_replies() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+
+    let mut writers = Vec::new();
+    let mut intros = Vec::new();
+    for _ in 0..3 {
+        let writer = ledger.student();
+        let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+        ledger.process(&ix).unwrap();
+        intros.push(pda::intro(&PROGRAM_ID, &writer));
+        writers.push(writer);
+    }
+    let repliers: Vec<Pubkey> = (0..3).map(|_| ledger.student()).collect();
+    let mut stale: Vec<Instruction> = Vec::new();
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..60 {
+        let intro = intros[rng.next(3) as usize];
+        let replier = repliers[rng.next(3) as usize];
+        let reply = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+
+        let result = match rng.next(5) {
+            // A fresh reply.
+            0 | 1 => ledger.process(&reply),
+            // Replaying an earlier reply instruction against a moved counter.
+            2 if !stale.is_empty() => {
+                let old = &stale[rng.next(stale.len() as u64) as usize];
+                ledger.process(old)
+            }
+            // Two replies in one batch, the second either fresh or stale.
+            3 => {
+                let next = if rng.next(2) == 0 {
+                    substitute(
+                        &reply,
+                        3,
+                        pda::reply(&PROGRAM_ID, &intro, ledger.reply_count(&intro) + 1),
+                    )
+                } else {
+                    reply.clone()
+                };
+                ledger.process(&batch_ix(&[reply.clone(), next]))
+            }
+            // Edits never touch the counter.
+            _ => {
+                let writer = writers[rng.next(3) as usize];
+                ledger.process(&update_intro_ix(&writer, "Ada", "Edited"))
+            }
+        };
+        if result.is_ok() {
+            stale.push(reply);
+        }
+
+        assert_consistent(&ledger, &intros);
+    }
+
+    assert!(intros.iter().all(|intro| ledger.reply_count(intro) > 0));
+}
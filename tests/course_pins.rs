@@ -0,0 +1,117 @@
+//! `PinIntro` and `UnpinIntro`: a teacher keeps a short list of intros at the top of the
+//! course board.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use student_intro_sol::{error::IntroError, pda, state::Course};
+
+const COURSE: &str = "Solana 101";
+
+fn pin_ix(tag: u8, teacher: &Pubkey, course: &Pubkey, intro: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*teacher, true),
+            AccountMeta::new(*course, false),
+            AccountMeta::new_readonly(*intro, false),
+        ],
+        data: vec![tag],
+    }
+}
+
+fn pins(ledger: &Ledger, course: &Pubkey) -> Vec<Pubkey> {
+    try_from_slice_unchecked::<Course>(&ledger.data(course))
+        .unwrap()
+        .pinned_intros
+}
+
+/// Posts an intro, returning its address.
+fn intro(ledger: &mut Ledger) -> Pubkey {
+    let writer = ledger.student();
+    let ix = student_intro_ix(ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    pda::intro(&PROGRAM_ID, &writer)
+}
+
+/// A ledger with a course, returning the teacher and course.
+fn setup() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+
+    (ledger, teacher, course)
+}
+
+#[test]
+fn teachers_pin_and_unpin_intros() {
+    let (mut ledger, teacher, course) = setup();
+    assert!(pins(&ledger, &course).is_empty());
+    let first = intro(&mut ledger);
+    let second = intro(&mut ledger);
+
+    for intro in [first, second, first] {
+        ledger
+            .process(&pin_ix(84, &teacher, &course, &intro))
+            .unwrap();
+    }
+    assert_eq!(pins(&ledger, &course), vec![first, second]);
+
+    ledger
+        .process(&pin_ix(85, &teacher, &course, &first))
+        .unwrap();
+    assert_eq!(pins(&ledger, &course), vec![second]);
+    ledger
+        .process(&pin_ix(85, &teacher, &course, &first))
+        .unwrap();
+    assert_eq!(pins(&ledger, &course), vec![second]);
+}
+
+#[test]
+fn courses_pin_a_bounded_number_of_intros() {
+    let (mut ledger, teacher, course) = setup();
+    for _ in 0..Course::MAX_PINNED_INTROS {
+        let intro = intro(&mut ledger);
+        ledger
+            .process(&pin_ix(84, &teacher, &course, &intro))
+            .unwrap();
+    }
+
+    let extra = intro(&mut ledger);
+    assert_eq!(
+        ledger.process(&pin_ix(84, &teacher, &course, &extra)),
+        Err(IntroError::TooManyPins.into())
+    );
+    assert_eq!(pins(&ledger, &course).len(), Course::MAX_PINNED_INTROS);
+}
+
+#[test]
+fn only_intros_can_be_pinned() {
+    let (mut ledger, teacher, course) = setup();
+    let stray = ledger.wallet();
+
+    assert!(ledger
+        .process(&pin_ix(84, &teacher, &course, &stray))
+        .is_err());
+    assert!(pins(&ledger, &course).is_empty());
+}
+
+#[test]
+fn only_the_teacher_pins() {
+    let (mut ledger, _teacher, course) = setup();
+    let intro = intro(&mut ledger);
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&pin_ix(84, &stranger, &course, &intro)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
@@ -0,0 +1,153 @@
+//! `cpi`: a companion program replying and voting from its own PDA.
+
+#![cfg(all(feature = "no-entrypoint", not(feature = "no-rewards")))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    account_info::AccountInfo, borsh::try_from_slice_unchecked, native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError, pubkey::Pubkey, system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{
+    cpi::{self, ReplyAccounts, VoteReplyAccounts},
+    pda,
+    state::{ReplyScore, StudentReplyState},
+};
+
+const COMPANION: Pubkey = Pubkey::new_from_array([9; 32]);
+
+/// A ledger with an intro and a funded `[b"grader"]` PDA of the companion program, returning
+/// the intro, the grader and its bump.
+fn setup() -> (Ledger, Pubkey, Pubkey, u8) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let (grader, bump) = Pubkey::find_program_address(&[b"grader"], &COMPANION);
+    ledger.set(grader, system_program::id(), LAMPORTS_PER_SOL, vec![]);
+    ledger.token_account(&grader, &pda::token_mint(&PROGRAM_ID));
+
+    (ledger, pda::intro(&PROGRAM_ID, &writer), grader, bump)
+}
+
+fn reply_as_grader(
+    ledger: &mut Ledger,
+    intro: &Pubkey,
+    grader: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(), ProgramError> {
+    let mint = pda::token_mint(&PROGRAM_ID);
+    let keys = [
+        PROGRAM_ID,
+        *grader,
+        *intro,
+        pda::reply_counter(&PROGRAM_ID, intro),
+        pda::reply(&PROGRAM_ID, intro, ledger.reply_count(intro)),
+        mint,
+        pda::mint_auth(&PROGRAM_ID),
+        get_associated_token_address(grader, &mint),
+        system_program::id(),
+        spl_token::id(),
+        pda::config(&PROGRAM_ID),
+        pda::reply_tally(&PROGRAM_ID, intro, grader),
+    ];
+
+    ledger.run_as(COMPANION, &keys, |infos: &[AccountInfo]| {
+        cpi::reply(
+            ReplyAccounts {
+                program: &infos[0],
+                replier: &infos[1],
+                intro: &infos[2],
+                reply_counter: &infos[3],
+                reply: &infos[4],
+                token_mint: &infos[5],
+                mint_auth: &infos[6],
+                replier_ata: &infos[7],
+                system_program: &infos[8],
+                token_program: &infos[9],
+                config: &infos[10],
+                reply_tally: &infos[11],
+            },
+            &[],
+            "Grader",
+            "Graded: A",
+            signer_seeds,
+        )
+    })
+}
+
+#[test]
+fn a_companion_pda_replies() {
+    let (mut ledger, intro, grader, bump) = setup();
+
+    reply_as_grader(&mut ledger, &intro, &grader, &[&[b"grader", &[bump]]]).unwrap();
+
+    assert_eq!(ledger.reply_count(&intro), 1);
+    let reply: StudentReplyState =
+        try_from_slice_unchecked(&ledger.data(&pda::reply(&PROGRAM_ID, &intro, 0))).unwrap();
+    assert_eq!(reply.replier, grader);
+    assert_eq!(reply.message, "Graded: A");
+}
+
+#[test]
+fn replies_without_the_pda_signature_are_rejected() {
+    let (mut ledger, intro, grader, _bump) = setup();
+
+    assert_eq!(
+        reply_as_grader(&mut ledger, &intro, &grader, &[]),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+}
+
+#[test]
+fn a_companion_pda_votes() {
+    let (mut ledger, intro, grader, bump) = setup();
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+    let reply = pda::reply(&PROGRAM_ID, &intro, 0);
+
+    let keys = [
+        PROGRAM_ID,
+        grader,
+        intro,
+        reply,
+        pda::reply_vote(&PROGRAM_ID, &reply, &grader),
+        pda::reply_score(&PROGRAM_ID, &reply),
+        pda::top_replies(&PROGRAM_ID, &intro),
+        system_program::id(),
+        pda::wallet_link(&PROGRAM_ID, &grader),
+        pda::wallet_link(&PROGRAM_ID, &replier),
+        pda::config(&PROGRAM_ID),
+    ];
+    ledger
+        .run_as(COMPANION, &keys, |infos: &[AccountInfo]| {
+            cpi::vote_reply(
+                VoteReplyAccounts {
+                    program: &infos[0],
+                    voter: &infos[1],
+                    intro: &infos[2],
+                    reply: &infos[3],
+                    vote: &infos[4],
+                    score: &infos[5],
+                    top_replies: &infos[6],
+                    system_program: &infos[7],
+                    voter_link: &infos[8],
+                    replier_link: &infos[9],
+                    config: &infos[10],
+                },
+                &[],
+                true,
+                &[&[b"grader", &[bump]]],
+            )
+        })
+        .unwrap();
+
+    let score: ReplyScore =
+        try_from_slice_unchecked(&ledger.data(&pda::reply_score(&PROGRAM_ID, &reply))).unwrap();
+    assert_eq!(score.score, 1);
+}
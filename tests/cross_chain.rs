@@ -0,0 +1,213 @@
+//! `ReceiveCrossChainReply`: replies relayed from the registered foreign emitter through a
+//! posted VAA, held to the same audience checks as local replies.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{ProgramConfig, StudentIntroState},
+    wormhole::{CrossChainReplyPayload, PAYLOAD_VERSION},
+};
+
+const WORMHOLE: Pubkey = Pubkey::new_from_array([9; 32]);
+const EMITTER_CHAIN: u16 = 2;
+const EMITTER: [u8; 32] = [4; 32];
+
+/// A ledger with one intro and a config trusting `EMITTER`, returning the intro.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        wormhole_program: Some(WORMHOLE),
+        foreign_emitter_chain: EMITTER_CHAIN,
+        foreign_emitter_address: EMITTER,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    (ledger, pda::intro(&PROGRAM_ID, &writer))
+}
+
+/// Posts a VAA carrying `payload` as sequence `sequence`, returning its account.
+fn post_vaa(ledger: &mut Ledger, sequence: u64, payload: &CrossChainReplyPayload) -> Pubkey {
+    let mut body = vec![PAYLOAD_VERSION];
+    body.extend(payload.try_to_vec().unwrap());
+    let mut data = b"vaa".to_vec();
+    data.extend(
+        (
+            1u8,
+            1u8,
+            0u32,
+            Pubkey::default(),
+            0u32,
+            0u32,
+            sequence,
+            EMITTER_CHAIN,
+            EMITTER,
+            body,
+        )
+            .try_to_vec()
+            .unwrap(),
+    );
+    let vaa = Pubkey::new_unique();
+    ledger.set(vaa, WORMHOLE, 1_000_000, data);
+    vaa
+}
+
+fn receive_ix(
+    ledger: &Ledger,
+    payer: &Pubkey,
+    intro: &Pubkey,
+    vaa: &Pubkey,
+    sequence: u64,
+) -> Instruction {
+    let received = Pubkey::find_program_address(
+        &[
+            b"received",
+            &EMITTER_CHAIN.to_be_bytes(),
+            &sequence.to_be_bytes(),
+        ],
+        &PROGRAM_ID,
+    )
+    .0;
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*intro, false),
+            AccountMeta::new(pda::reply_counter(&PROGRAM_ID, intro), false),
+            AccountMeta::new(
+                pda::reply(&PROGRAM_ID, intro, ledger.reply_count(intro)),
+                false,
+            ),
+            AccountMeta::new_readonly(*vaa, false),
+            AccountMeta::new(received, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![18],
+    }
+}
+
+fn reply_payload(intro: &Pubkey, message: String) -> CrossChainReplyPayload {
+    CrossChainReplyPayload {
+        intro: *intro,
+        sender: [5; 32],
+        name: "Grace".to_string(),
+        message,
+    }
+}
+
+#[test]
+fn relayed_replies_are_posted_once() {
+    let (mut ledger, intro) = setup();
+    let payer = ledger.student();
+    let vaa = post_vaa(
+        &mut ledger,
+        1,
+        &reply_payload(&intro, "Welcome".to_string()),
+    );
+
+    let ix = receive_ix(&ledger, &payer, &intro, &vaa, 1);
+    ledger.process(&ix).unwrap();
+    assert_eq!(ledger.reply_count(&intro), 1);
+
+    let ix = receive_ix(&ledger, &payer, &intro, &vaa, 1);
+    assert!(ledger.process(&ix).is_err());
+    assert_eq!(ledger.reply_count(&intro), 1);
+}
+
+#[test]
+fn oversized_relayed_replies_are_rejected() {
+    let (mut ledger, intro) = setup();
+    let payer = ledger.student();
+    let message = "a".repeat(CrossChainReplyPayload::MAX_MESSAGE_LEN + 1);
+    let vaa = post_vaa(&mut ledger, 1, &reply_payload(&intro, message));
+
+    let ix = receive_ix(&ledger, &payer, &intro, &vaa, 1);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::InvalidDataLength.into())
+    );
+    assert_eq!(ledger.reply_count(&intro), 0);
+}
+
+#[test]
+fn private_intros_take_no_relayed_replies() {
+    let (mut ledger, intro) = setup();
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.visibility = StudentIntroState::VISIBILITY_PRIVATE;
+    ledger.set_intro(&intro, &intro_data);
+    let payer = ledger.student();
+    let vaa = post_vaa(
+        &mut ledger,
+        1,
+        &reply_payload(&intro, "Welcome".to_string()),
+    );
+
+    let ix = receive_ix(&ledger, &payer, &intro, &vaa, 1);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::RepliesNotAllowed.into())
+    );
+}
+
+#[test]
+fn relayed_replies_to_course_only_intros_need_the_sender_enrolled() {
+    let (mut ledger, intro) = setup();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, "Solana 101");
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.visibility = StudentIntroState::VISIBILITY_COURSE_ONLY;
+    intro_data.course = Some(course);
+    ledger.set_intro(&intro, &intro_data);
+    let payer = ledger.student();
+    let payload = reply_payload(&intro, "Welcome".to_string());
+    let sender = Pubkey::new_from_array(payload.sender);
+    let vaa = post_vaa(&mut ledger, 1, &payload);
+
+    let enrollment = pda::enrollment(&PROGRAM_ID, &course, &sender);
+    let mut ix = receive_ix(&ledger, &payer, &intro, &vaa, 1);
+    ix.accounts.extend([
+        AccountMeta::new_readonly(enrollment, false),
+        AccountMeta::new(pda::course_stats(&PROGRAM_ID, &course), false),
+        AccountMeta::new(pda::student_activity(&PROGRAM_ID, &enrollment), false),
+        AccountMeta::new_readonly(course, false),
+    ]);
+    assert_eq!(ledger.process(&ix), Err(IntroError::NotEnrolled.into()));
+    assert_eq!(ledger.reply_count(&intro), 0);
+}
+
+#[test]
+fn the_receipt_must_be_writable() {
+    let (mut ledger, intro) = setup();
+    let payer = ledger.student();
+    let vaa = post_vaa(
+        &mut ledger,
+        1,
+        &reply_payload(&intro, "Welcome".to_string()),
+    );
+
+    let mut ix = receive_ix(&ledger, &payer, &intro, &vaa, 1);
+    ix.accounts[5].is_writable = false;
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::AccountNotWritable.into())
+    );
+}
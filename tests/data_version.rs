@@ -0,0 +1,206 @@
+//! The upgrade guard: instructions refuse to run while the config's data version is behind the
+//! binary's, until the admin records the migration with `CompleteMigration`.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    events::ProgramEvent,
+    instruction::{config_account, config_account_mut, StudentInstruction},
+    pda,
+    state::{ActionAccount, ProgramConfig},
+};
+
+fn complete_ix(admin: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            config_account_mut(&PROGRAM_ID),
+        ],
+        data: vec![StudentInstruction::COMPLETE_MIGRATION],
+    }
+}
+
+fn config(ledger: &Ledger) -> ProgramConfig {
+    try_from_slice_unchecked(&ledger.data(&pda::config(&PROGRAM_ID))).unwrap()
+}
+
+/// A ledger whose config was written by the binary before the current one, returning the
+/// admin.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        data_version: ProgramConfig::DATA_VERSION - 1,
+        ..ProgramConfig::default()
+    });
+
+    (ledger, admin)
+}
+
+#[test]
+fn unmigrated_configs_block_instructions_until_completed() {
+    let (mut ledger, admin) = setup();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::UpgradeRequiresMigration.into())
+    );
+
+    ledger.process(&complete_ix(&admin)).unwrap();
+    assert_eq!(config(&ledger).data_version, ProgramConfig::DATA_VERSION);
+    assert_eq!(
+        ledger.logged_data(),
+        vec![ProgramEvent::MigrationCompleted {
+            admin,
+            from_version: ProgramConfig::DATA_VERSION - 1,
+            to_version: ProgramConfig::DATA_VERSION,
+        }
+        .try_to_vec()
+        .unwrap()]
+    );
+
+    ledger.process(&ix).unwrap();
+    assert_eq!(ledger.registry_count(), 1);
+}
+
+#[test]
+fn instructions_that_do_not_read_the_config_are_blocked_too() {
+    let (mut ledger, _admin) = setup();
+    let writer = ledger.student();
+
+    assert_eq!(
+        ledger.process(&update_intro_ix(&writer, "Ada", "Hello again")),
+        Err(IntroError::UpgradeRequiresMigration.into())
+    );
+}
+
+#[test]
+fn only_the_admin_completes_migrations() {
+    let (mut ledger, _admin) = setup();
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&complete_ix(&stranger)),
+        Err(IntroError::Unauthorized.into())
+    );
+    assert_eq!(
+        config(&ledger).data_version,
+        ProgramConfig::DATA_VERSION - 1
+    );
+}
+
+#[test]
+fn a_multisig_admin_completes_migrations_through_an_admin_action() {
+    let (mut ledger, _admin) = setup();
+    let (proposer, approver) = (ledger.wallet(), ledger.wallet());
+    let mut config_data = config(&ledger);
+    config_data.admin_signers = vec![proposer, approver];
+    config_data.admin_threshold = 2;
+    ledger.install_config(config_data);
+
+    let action = pda::admin_action(&PROGRAM_ID, 1);
+    let multisig = pda::multisig(&PROGRAM_ID);
+    let wrapped = complete_ix(&multisig);
+    let action_accounts: Vec<ActionAccount> = wrapped
+        .accounts
+        .iter()
+        .map(|meta| ActionAccount {
+            pubkey: meta.pubkey,
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        })
+        .collect();
+
+    let propose = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(proposer, true),
+            AccountMeta::new(action, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(21, &(1u64, action_accounts, wrapped.data)),
+    };
+    ledger.process(&propose).unwrap();
+
+    let approve = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(approver, true),
+            AccountMeta::new(action, false),
+        ],
+        data: vec![22],
+    };
+    ledger.process(&approve).unwrap();
+
+    let execute = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(approver, true),
+            AccountMeta::new(action, false),
+            AccountMeta::new(proposer, false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            AccountMeta::new_readonly(multisig, false),
+            config_account_mut(&PROGRAM_ID),
+            config_account(&PROGRAM_ID),
+        ],
+        data: vec![23],
+    };
+    ledger.process(&execute).unwrap();
+
+    assert_eq!(config(&ledger).data_version, ProgramConfig::DATA_VERSION);
+}
+
+#[test]
+fn admin_actions_wrapping_other_instructions_wait_for_the_migration() {
+    let (mut ledger, _admin) = setup();
+    let proposer = ledger.wallet();
+    let mut config_data = config(&ledger);
+    config_data.admin_signers = vec![proposer];
+    config_data.admin_threshold = 1;
+    ledger.install_config(config_data);
+
+    let propose = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(proposer, true),
+            AccountMeta::new(pda::admin_action(&PROGRAM_ID, 1), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(21, &(1u64, Vec::<ActionAccount>::new(), vec![25u8])),
+    };
+
+    assert_eq!(
+        ledger.process(&propose),
+        Err(IntroError::UpgradeRequiresMigration.into())
+    );
+}
+
+#[test]
+fn migrations_complete_once() {
+    let (mut ledger, admin) = setup();
+    ledger.process(&complete_ix(&admin)).unwrap();
+
+    assert_eq!(
+        ledger.process(&complete_ix(&admin)),
+        Err(ProgramError::InvalidArgument)
+    );
+}
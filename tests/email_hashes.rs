@@ -0,0 +1,124 @@
+//! Email hash registry: one wallet per salted hash across the program, with an admin release
+//! for students who move wallets.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{EmailHashEntry, ProgramConfig},
+};
+
+const HASH: [u8; 32] = [9; 32];
+
+/// A ledger with a config administered by the returned wallet.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    let admin = ledger.wallet();
+
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    (ledger, admin)
+}
+
+fn register_ix(wallet: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*wallet, true),
+            AccountMeta::new(pda::email_hash(&PROGRAM_ID, &HASH), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(48, &HASH),
+    }
+}
+
+fn release_ix(admin: &Pubkey, wallet: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(pda::email_hash(&PROGRAM_ID, &HASH), false),
+            AccountMeta::new(*wallet, false),
+        ],
+        data: vec![49],
+    }
+}
+
+#[test]
+fn hash_belongs_to_one_wallet() {
+    let (mut ledger, _admin) = setup();
+    let first = ledger.wallet();
+    let second = ledger.wallet();
+
+    ledger.process(&register_ix(&first)).unwrap();
+    let entry: EmailHashEntry =
+        try_from_slice_unchecked(&ledger.data(&pda::email_hash(&PROGRAM_ID, &HASH))).unwrap();
+    assert_eq!(entry.wallet, first);
+    assert_eq!(entry.registered_at, UNIX_TIMESTAMP);
+
+    // Its holder may repeat the call; anyone else is turned away.
+    ledger.process(&register_ix(&first)).unwrap();
+    assert_eq!(
+        ledger.process(&register_ix(&second)),
+        Err(IntroError::EmailHashTaken.into())
+    );
+}
+
+#[test]
+fn admin_release_frees_the_hash() {
+    let (mut ledger, admin) = setup();
+    let old_wallet = ledger.wallet();
+    let new_wallet = ledger.wallet();
+    ledger.process(&register_ix(&old_wallet)).unwrap();
+
+    let entry = pda::email_hash(&PROGRAM_ID, &HASH);
+    let balance = ledger.info(&old_wallet).lamports();
+    let rent = ledger.info(&entry).lamports();
+    ledger.process(&release_ix(&admin, &old_wallet)).unwrap();
+    assert!(!ledger.exists(&entry));
+    assert_eq!(ledger.info(&old_wallet).lamports(), balance + rent);
+
+    // The runtime purges the drained entry once the transaction ends.
+    ledger.set(entry, system_program::id(), 0, vec![]);
+    ledger.process(&register_ix(&new_wallet)).unwrap();
+}
+
+#[test]
+fn only_the_admin_releases() {
+    let (mut ledger, _admin) = setup();
+    let wallet = ledger.wallet();
+    ledger.process(&register_ix(&wallet)).unwrap();
+
+    assert_eq!(
+        ledger.process(&release_ix(&wallet, &wallet)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn release_refunds_the_holder() {
+    let (mut ledger, admin) = setup();
+    let wallet = ledger.wallet();
+    ledger.process(&register_ix(&wallet)).unwrap();
+
+    assert_eq!(
+        ledger.process(&release_ix(&admin, &admin)),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
@@ -0,0 +1,91 @@
+//! `ProgramEvent::Error`: rejections with one of the program's errors are logged as events.
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{error::IntroError, events::ProgramEvent, pda};
+
+const COURSE: &str = "Solana 101";
+
+/// `OpenSession` on `course`, signed by `signer`.
+fn open_session_ix(signer: &Pubkey, course: &Pubkey, is_signer: bool) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*signer, is_signer),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new(pda::session(&PROGRAM_ID, course, 1), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(
+            56,
+            &(1u64, UNIX_TIMESTAMP - 60, UNIX_TIMESTAMP + 3_600, 0u64),
+        ),
+    }
+}
+
+fn error_event(code: IntroError, instruction: u8, offender: Pubkey) -> Vec<u8> {
+    ProgramEvent::Error {
+        code: code as u32,
+        instruction,
+        offender,
+    }
+    .try_to_vec()
+    .unwrap()
+}
+
+/// A ledger with a course, returning its course key.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+    (ledger, course)
+}
+
+#[test]
+fn domain_errors_are_logged_with_the_offender() {
+    let (mut ledger, course) = setup();
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&open_session_ix(&stranger, &course, true)),
+        Err(IntroError::Unauthorized.into())
+    );
+    assert_eq!(
+        ledger.logged_data().last(),
+        Some(&error_event(IntroError::Unauthorized, 56, stranger))
+    );
+}
+
+#[test]
+fn runtime_errors_are_not_logged() {
+    let (mut ledger, course) = setup();
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&open_session_ix(&stranger, &course, false)),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert!(ledger.logged_data().is_empty());
+}
+
+#[test]
+fn batch_rejections_are_logged_once_for_the_op() {
+    let (mut ledger, course) = setup();
+    let stranger = ledger.wallet();
+
+    assert!(ledger
+        .process(&batch_ix(&[open_session_ix(&stranger, &course, true)]))
+        .is_err());
+    assert_eq!(
+        ledger.logged_data(),
+        vec![error_event(IntroError::Unauthorized, 56, stranger)]
+    );
+}
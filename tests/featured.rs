@@ -0,0 +1,212 @@
+//! `SelectFeatured`: the featured intro is drawn from a slot hash committed to before it
+//! exists, passing over private and spam-scored intros.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    hash::hashv,
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{Featured, FeaturedDraw, RegistryEntry, StudentIntroState},
+};
+
+const DRAW_SLOT: u64 = SLOT - 10;
+
+fn select_ix(cranker: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*cranker, true),
+            AccountMeta::new(pda::featured(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(pda::registry(&PROGRAM_ID), false),
+            AccountMeta::new(pda::featured_draw(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![16],
+    }
+}
+
+/// `SelectFeatured` drawing through `candidates` registry indexes, paying `writer`.
+fn draw_ix(cranker: &Pubkey, candidates: &[u64], ledger: &Ledger, writer: &Pubkey) -> Instruction {
+    let mint = pda::token_mint(&PROGRAM_ID);
+    let mut ix = select_ix(cranker);
+    for &index in candidates {
+        let entry = pda::registry_entry(&PROGRAM_ID, index);
+        let entry_data: RegistryEntry = try_from_slice_unchecked(&ledger.data(&entry)).unwrap();
+        ix.accounts.push(AccountMeta::new_readonly(entry, false));
+        ix.accounts
+            .push(AccountMeta::new_readonly(entry_data.intro, false));
+    }
+    ix.accounts.extend([
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+        AccountMeta::new(get_associated_token_address(writer, &mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]);
+    ix
+}
+
+/// Sets SlotHashes to `slots`, newest first, each hashed to its own number.
+fn set_slot_hashes(ledger: &mut Ledger, slots: &[u64]) {
+    let mut data = (slots.len() as u64).to_le_bytes().to_vec();
+    for slot in slots {
+        data.extend(slot.to_le_bytes());
+        data.extend(hashv(&[&slot.to_le_bytes()]).to_bytes());
+    }
+    ledger.set(sysvar::slot_hashes::id(), sysvar::id(), 1, data);
+}
+
+/// The registry index candidate `candidate` of a draw from `draw_slot`'s hash lands on.
+fn drawn_index(draw_slot: u64, candidate: u64, count: u64) -> u64 {
+    let draw_hash = hashv(&[&draw_slot.to_le_bytes()]).to_bytes();
+    let seed = hashv(&[&draw_hash, &EPOCH.to_le_bytes(), &candidate.to_le_bytes()]).to_bytes();
+    u64::from_le_bytes(seed[..8].try_into().unwrap()) % count
+}
+
+fn commit_draw(ledger: &mut Ledger, draw_slot: u64) {
+    let draw = FeaturedDraw {
+        discriminator: FeaturedDraw::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        epoch: EPOCH,
+        draw_slot,
+        registry_count: ledger.registry_count(),
+    };
+    ledger.set(
+        pda::featured_draw(&PROGRAM_ID),
+        PROGRAM_ID,
+        1_000_000,
+        draw.try_to_vec().unwrap(),
+    );
+}
+
+fn draw(ledger: &Ledger) -> FeaturedDraw {
+    try_from_slice_unchecked(&ledger.data(&pda::featured_draw(&PROGRAM_ID))).unwrap()
+}
+
+/// A ledger with `count` registered intros, returning their writers in registry order.
+fn setup(count: usize) -> (Ledger, Vec<Pubkey>) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writers = (0..count)
+        .map(|_| {
+            let writer = ledger.student();
+            let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+            ledger.process(&ix).unwrap();
+            writer
+        })
+        .collect();
+    (ledger, writers)
+}
+
+#[test]
+fn the_first_call_commits_the_draw() {
+    let (mut ledger, _writers) = setup(2);
+    set_slot_hashes(&mut ledger, &[SLOT - 1, SLOT - 2]);
+    let cranker = ledger.wallet();
+
+    ledger.process(&select_ix(&cranker)).unwrap();
+    let committed = draw(&ledger);
+    assert_eq!(
+        (
+            committed.epoch,
+            committed.draw_slot,
+            committed.registry_count
+        ),
+        (EPOCH, SLOT + FeaturedDraw::DELAY_SLOTS, 2)
+    );
+
+    assert_eq!(
+        ledger.process(&select_ix(&cranker)),
+        Err(IntroError::FeaturedDrawPending.into())
+    );
+    assert_eq!(draw(&ledger).draw_slot, committed.draw_slot);
+}
+
+#[test]
+fn the_committed_slot_draws_the_intro() {
+    let (mut ledger, writers) = setup(3);
+    commit_draw(&mut ledger, DRAW_SLOT);
+    // The draw slot was skipped, so the next slot's hash decides.
+    set_slot_hashes(&mut ledger, &[SLOT - 1, DRAW_SLOT + 1, DRAW_SLOT - 1]);
+    let index = drawn_index(DRAW_SLOT + 1, 0, 3);
+    let writer = writers[index as usize];
+    let cranker = ledger.wallet();
+
+    ledger
+        .process(&draw_ix(&cranker, &[index], &ledger, &writer))
+        .unwrap();
+
+    let featured: Featured =
+        try_from_slice_unchecked(&ledger.data(&pda::featured(&PROGRAM_ID))).unwrap();
+    assert_eq!(
+        (featured.epoch, featured.intro, featured.writer),
+        (EPOCH, pda::intro(&PROGRAM_ID, &writer), writer)
+    );
+    assert_eq!(
+        ledger.process(&draw_ix(&cranker, &[index], &ledger, &writer)),
+        Err(solana_program::program_error::ProgramError::AccountAlreadyInitialized)
+    );
+    let ata = get_associated_token_address(&writer, &pda::token_mint(&PROGRAM_ID));
+    assert!(ledger.token_balance(&ata) >= 20 * LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn private_intros_are_passed_over() {
+    let (mut ledger, writers) = setup(3);
+    commit_draw(&mut ledger, DRAW_SLOT);
+    set_slot_hashes(&mut ledger, &[SLOT - 1, DRAW_SLOT]);
+    let first = drawn_index(DRAW_SLOT, 0, 3);
+    let hidden = pda::intro(&PROGRAM_ID, &writers[first as usize]);
+    let mut intro_data = ledger.intro(&hidden);
+    intro_data.visibility = StudentIntroState::VISIBILITY_PRIVATE;
+    ledger.set_intro(&hidden, &intro_data);
+
+    let candidates: Vec<u64> = (0..FeaturedDraw::MAX_CANDIDATES)
+        .map(|candidate| drawn_index(DRAW_SLOT, candidate, 3))
+        .collect();
+    let picked = candidates.iter().position(|&index| index != first).unwrap();
+    let writer = writers[candidates[picked] as usize];
+    let cranker = ledger.wallet();
+
+    // The private intro alone does not complete the draw.
+    assert!(ledger
+        .process(&draw_ix(&cranker, &candidates[..picked], &ledger, &writer))
+        .is_err());
+
+    ledger
+        .process(&draw_ix(&cranker, &candidates[..=picked], &ledger, &writer))
+        .unwrap();
+    let featured: Featured =
+        try_from_slice_unchecked(&ledger.data(&pda::featured(&PROGRAM_ID))).unwrap();
+    assert_eq!(featured.writer, writer);
+}
+
+#[test]
+fn draws_whose_slot_left_slot_hashes_are_committed_again() {
+    let (mut ledger, writers) = setup(2);
+    commit_draw(&mut ledger, DRAW_SLOT);
+    set_slot_hashes(&mut ledger, &[SLOT - 1, DRAW_SLOT + 1]);
+    let cranker = ledger.wallet();
+
+    ledger
+        .process(&draw_ix(&cranker, &[0], &ledger, &writers[0]))
+        .unwrap();
+
+    assert_eq!(draw(&ledger).draw_slot, SLOT + FeaturedDraw::DELAY_SLOTS);
+    let featured: Featured =
+        try_from_slice_unchecked(&ledger.data(&pda::featured(&PROGRAM_ID))).unwrap();
+    assert!(!featured.is_initialized);
+}
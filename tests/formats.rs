@@ -0,0 +1,157 @@
+//! Message formats: stored where clients read them, and markdown held to its rules on every
+//! write.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{borsh::try_from_slice_unchecked, instruction::Instruction, pubkey::Pubkey};
+use student_intro_sol::{
+    error::IntroError, format, instruction::QuoteParams, lang, pda, state::StudentReplyState,
+};
+
+fn markdown_intro_ix(ledger: &Ledger, writer: &Pubkey, message: &str) -> Instruction {
+    let mut ix = student_intro_ix(ledger, writer, "Ada", message);
+    ix.data = payload(
+        0,
+        &(
+            "Ada",
+            message,
+            None::<i64>,
+            lang::UNSPECIFIED,
+            format::MARKDOWN,
+        ),
+    );
+    ix
+}
+
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    (ledger, writer)
+}
+
+#[test]
+fn intro_format_sits_beside_the_tag() {
+    let (mut ledger, writer) = setup();
+    let ix = markdown_intro_ix(
+        &ledger,
+        &writer,
+        "I study **Rust**, see [my site](https://a.b)",
+    );
+    ledger.process(&ix).unwrap();
+
+    let intro_data = ledger.intro(&pda::intro(&PROGRAM_ID, &writer));
+    assert_eq!(intro_data.format, format::MARKDOWN);
+    assert_eq!(intro_data.lang, lang::UNSPECIFIED);
+}
+
+#[test]
+fn intros_default_to_plain_text() {
+    let (mut ledger, writer) = setup();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "<b>not markup</b>");
+    ledger.process(&ix).unwrap();
+
+    let intro_data = ledger.intro(&pda::intro(&PROGRAM_ID, &writer));
+    assert_eq!(intro_data.format, format::PLAIN);
+}
+
+#[test]
+fn markdown_rejects_raw_html() {
+    let (mut ledger, writer) = setup();
+
+    for message in [
+        "<script>alert(1)</script>",
+        "hi </div>",
+        "<!-- hidden -->",
+        "<https://a.b>",
+    ] {
+        let ix = markdown_intro_ix(&ledger, &writer, message);
+        assert_eq!(
+            ledger.process(&ix),
+            Err(IntroError::InvalidFormat.into()),
+            "{:?}",
+            message
+        );
+    }
+
+    // A bare comparison is not a tag.
+    let ix = markdown_intro_ix(&ledger, &writer, "1 < 2 and 3 <= 4");
+    ledger.process(&ix).unwrap();
+}
+
+#[test]
+fn markdown_links_are_limited() {
+    let (mut ledger, writer) = setup();
+    let links = "[a](https://a.b) ".repeat(format::MAX_MARKDOWN_LINKS + 1);
+
+    let ix = markdown_intro_ix(&ledger, &writer, &links);
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidFormat.into()));
+}
+
+#[test]
+fn unknown_formats_are_rejected() {
+    let (mut ledger, writer) = setup();
+    let mut ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ix.data = payload(0, &("Ada", "Hello", None::<i64>, lang::UNSPECIFIED, 2u8));
+
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidFormat.into()));
+}
+
+#[test]
+fn edits_keep_markdown_rules() {
+    let (mut ledger, writer) = setup();
+    let ix = markdown_intro_ix(&ledger, &writer, "Hello");
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(
+        ledger.process(&update_intro_ix(&writer, "Ada", "<img src=x>")),
+        Err(IntroError::InvalidFormat.into())
+    );
+    ledger
+        .process(&update_intro_ix(&writer, "Ada", "Hello **again**"))
+        .unwrap();
+    let intro_data = ledger.intro(&pda::intro(&PROGRAM_ID, &writer));
+    assert_eq!(intro_data.format, format::MARKDOWN);
+}
+
+#[test]
+fn replies_carry_their_format() {
+    let (mut ledger, writer) = setup();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+
+    let mut ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "_Welcome_");
+    ix.data = payload(
+        2,
+        &(
+            "Grace",
+            "_Welcome_",
+            None::<QuoteParams>,
+            *b"en",
+            format::MARKDOWN,
+        ),
+    );
+    ledger.process(&ix).unwrap();
+
+    let reply: StudentReplyState =
+        try_from_slice_unchecked(&ledger.data(&pda::reply(&PROGRAM_ID, &intro, 0))).unwrap();
+    assert_eq!(reply.format, format::MARKDOWN);
+
+    ix.data = payload(
+        2,
+        &(
+            "Grace",
+            "<i>Welcome</i>",
+            None::<QuoteParams>,
+            *b"en",
+            format::MARKDOWN,
+        ),
+    );
+    let ix = substitute(&ix, 3, pda::reply(&PROGRAM_ID, &intro, 1));
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidFormat.into()));
+}
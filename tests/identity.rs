@@ -0,0 +1,213 @@
+//! `VerifyIdentity`: the config's identity verifier attests, through a preceding Ed25519
+//! instruction, that an intro's writer holds an off-chain identity.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    ed25519_program,
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    sysvar::{
+        self,
+        instructions::{self, BorrowedInstruction},
+    },
+};
+use student_intro_sol::{
+    error::IntroError, events::ProgramEvent, instruction::IdentityAttestation, pda,
+    state::ProgramConfig,
+};
+
+const IDENTITY_HASH: [u8; 32] = [9; 32];
+
+fn verify_ix(intro: &Pubkey, attestation: &IdentityAttestation) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*intro, false),
+            AccountMeta::new_readonly(instructions::id(), false),
+        ],
+        data: payload(71, attestation),
+    }
+}
+
+/// Ed25519 program data checking one signature by `signer` over `message`. The signature
+/// bytes are left zeroed: the native program, not this one, checks them.
+fn ed25519_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+    let pubkey_offset: u16 = 16;
+    let signature_offset = pubkey_offset + 32;
+    let message_offset = signature_offset + 64;
+    let mut data = vec![1, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,
+        pubkey_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend(field.to_le_bytes());
+    }
+    data.extend(signer.as_ref());
+    data.extend([0; 64]);
+    data.extend(message);
+    data
+}
+
+/// Stores an instructions sysvar in which `verify` runs right after an Ed25519 check of
+/// `signer`'s signature over `message`.
+fn sign(ledger: &mut Ledger, signer: &Pubkey, message: &[u8], verify: &Instruction) {
+    let ed25519_data = ed25519_data(signer, message);
+    let ed25519_id = ed25519_program::id();
+    let mut data = instructions::construct_instructions_data(&[
+        BorrowedInstruction {
+            program_id: &ed25519_id,
+            accounts: vec![],
+            data: &ed25519_data,
+        },
+        BorrowedInstruction {
+            program_id: &verify.program_id,
+            accounts: vec![],
+            data: &verify.data,
+        },
+    ]);
+    instructions::store_current_index(&mut data, 1);
+    ledger.set(instructions::id(), sysvar::id(), LAMPORTS_PER_SOL, data);
+}
+
+/// A ledger with an intro and a config naming an identity verifier, returning the verifier,
+/// writer and intro.
+fn setup() -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let verifier = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        identity_verifier: Some(verifier),
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    (ledger, verifier, writer, pda::intro(&PROGRAM_ID, &writer))
+}
+
+fn attestation(writer: &Pubkey) -> IdentityAttestation {
+    IdentityAttestation {
+        program_id: PROGRAM_ID,
+        writer: *writer,
+        identity_hash: IDENTITY_HASH,
+    }
+}
+
+#[test]
+fn the_verifier_sets_the_checkmark() {
+    let (mut ledger, verifier, writer, intro) = setup();
+    assert!(!ledger.intro(&intro).verified);
+
+    let attestation = attestation(&writer);
+    let ix = verify_ix(&intro, &attestation);
+    sign(
+        &mut ledger,
+        &verifier,
+        &attestation.try_to_vec().unwrap(),
+        &ix,
+    );
+    ledger.process(&ix).unwrap();
+
+    assert!(ledger.intro(&intro).verified);
+    assert_eq!(
+        ledger.logged_data(),
+        vec![ProgramEvent::IdentityVerified {
+            intro,
+            author: writer,
+            verifier,
+            identity_hash: IDENTITY_HASH,
+        }
+        .try_to_vec()
+        .unwrap()]
+    );
+
+    // The checkmark lives in the trailer, so rewriting the intro keeps it.
+    ledger
+        .process(&update_intro_ix(&writer, "Ada", "Hello again"))
+        .unwrap();
+    assert!(ledger.intro(&intro).verified);
+}
+
+#[test]
+fn other_signers_are_rejected() {
+    let (mut ledger, _verifier, writer, intro) = setup();
+
+    let attestation = attestation(&writer);
+    let ix = verify_ix(&intro, &attestation);
+    sign(
+        &mut ledger,
+        &writer,
+        &attestation.try_to_vec().unwrap(),
+        &ix,
+    );
+
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::InvalidAttestation.into())
+    );
+    assert!(!ledger.intro(&intro).verified);
+}
+
+#[test]
+fn attestations_bind_the_writer_and_deployment() {
+    let (mut ledger, verifier, writer, intro) = setup();
+    let other = ledger.wallet();
+
+    for attestation in [
+        attestation(&other),
+        IdentityAttestation {
+            program_id: Pubkey::new_unique(),
+            ..attestation(&writer)
+        },
+    ] {
+        let ix = verify_ix(&intro, &attestation);
+        sign(
+            &mut ledger,
+            &verifier,
+            &attestation.try_to_vec().unwrap(),
+            &ix,
+        );
+
+        assert_eq!(
+            ledger.process(&ix),
+            Err(IntroError::InvalidAttestation.into())
+        );
+    }
+}
+
+#[test]
+fn nothing_verifies_without_a_configured_verifier() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+
+    let attestation = attestation(&writer);
+    let ix = verify_ix(&intro, &attestation);
+    sign(
+        &mut ledger,
+        &Pubkey::default(),
+        &attestation.try_to_vec().unwrap(),
+        &ix,
+    );
+
+    assert_eq!(ledger.process(&ix), Err(IntroError::Unauthorized.into()));
+}
@@ -0,0 +1,152 @@
+//! `IndexExisting`: intros created before the registry are added to it, and to the intro
+//! feed, exactly once.
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{IntroFeedPage, RegistryEntry, StudentIntroState},
+};
+
+fn index_ix(payer: &Pubkey, intro: &Pubkey, entry: u64, target: u8) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*intro, false),
+            AccountMeta::new(pda::registry(&PROGRAM_ID), false),
+            AccountMeta::new(pda::registry_entry(&PROGRAM_ID, entry), false),
+            AccountMeta::new(
+                pda::intro_feed_page(&PROGRAM_ID, IntroFeedPage::page_of(entry)),
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(70, &target),
+    }
+}
+
+/// Writes an intro in the layout used before the registry existed, returning its address.
+fn legacy_intro(ledger: &mut Ledger) -> Pubkey {
+    let writer = ledger.wallet();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let mut data = (
+        StudentIntroState::DISCRIMINATOR.to_string(),
+        true,
+        writer,
+        "Ada".to_string(),
+        "Hello".to_string(),
+    )
+        .try_to_vec()
+        .unwrap();
+    data.resize(StudentIntroState::ACCOUNT_LEN, 0);
+    ledger.set(intro, PROGRAM_ID, LAMPORTS_PER_SOL, data);
+    intro
+}
+
+fn entry(ledger: &Ledger, index: u64) -> RegistryEntry {
+    try_from_slice_unchecked(&ledger.data(&pda::registry_entry(&PROGRAM_ID, index))).unwrap()
+}
+
+#[test]
+fn legacy_intros_are_indexed_once() {
+    let mut ledger = Ledger::new();
+    let payer = ledger.wallet();
+    let intro = legacy_intro(&mut ledger);
+    let ix = index_ix(&payer, &intro, 0, StudentIntroState::INDEX_REGISTRY);
+
+    ledger.process(&ix).unwrap();
+    assert_eq!(ledger.registry_count(), 1);
+    assert_eq!(entry(&ledger, 0).intro, intro);
+    let page: IntroFeedPage =
+        try_from_slice_unchecked(&ledger.data(&pda::intro_feed_page(&PROGRAM_ID, 0))).unwrap();
+    assert_eq!(page.entries[0].intro, intro);
+    assert_eq!(ledger.intro(&intro).indexed, 1);
+
+    let again = index_ix(&payer, &intro, 1, StudentIntroState::INDEX_REGISTRY);
+    assert_eq!(
+        ledger.process(&again),
+        Err(IntroError::AlreadyIndexed.into())
+    );
+    assert_eq!(ledger.registry_count(), 1);
+}
+
+#[test]
+fn intros_registered_before_the_mark_are_only_marked() {
+    let mut ledger = Ledger::new();
+    let payer = ledger.wallet();
+    let intro = legacy_intro(&mut ledger);
+    ledger
+        .process(&index_ix(
+            &payer,
+            &intro,
+            0,
+            StudentIntroState::INDEX_REGISTRY,
+        ))
+        .unwrap();
+
+    // Registered, but without the mark.
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.indexed = 0;
+    ledger.set_intro(&intro, &intro_data);
+
+    ledger
+        .process(&index_ix(
+            &payer,
+            &intro,
+            0,
+            StudentIntroState::INDEX_REGISTRY,
+        ))
+        .unwrap();
+    assert_eq!(ledger.registry_count(), 1);
+    assert_eq!(ledger.intro(&intro).indexed, 1);
+}
+
+#[test]
+fn another_intros_entry_is_rejected() {
+    let mut ledger = Ledger::new();
+    let payer = ledger.wallet();
+    let registered = legacy_intro(&mut ledger);
+    ledger
+        .process(&index_ix(
+            &payer,
+            &registered,
+            0,
+            StudentIntroState::INDEX_REGISTRY,
+        ))
+        .unwrap();
+    let intro = legacy_intro(&mut ledger);
+
+    assert_eq!(
+        ledger.process(&index_ix(
+            &payer,
+            &intro,
+            0,
+            StudentIntroState::INDEX_REGISTRY
+        )),
+        Err(IntroError::InvalidPDA.into())
+    );
+}
+
+#[test]
+fn unknown_indexes_are_rejected() {
+    let mut ledger = Ledger::new();
+    let payer = ledger.wallet();
+    let intro = legacy_intro(&mut ledger);
+
+    assert_eq!(
+        ledger.process(&index_ix(&payer, &intro, 0, 7)),
+        Err(ProgramError::InvalidArgument)
+    );
+}
@@ -0,0 +1,96 @@
+//! The global intro feed: `StudentIntro` appends each intro to the page for its registry index.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked, instruction::AccountMeta, native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{FeedEntry, IntroFeedPage, IntroRegistry},
+};
+
+fn page(ledger: &Ledger, page: u64) -> IntroFeedPage {
+    try_from_slice_unchecked(&ledger.data(&pda::intro_feed_page(&PROGRAM_ID, page))).unwrap()
+}
+
+fn post(ledger: &mut Ledger) -> Pubkey {
+    let writer = ledger.student();
+    let ix = student_intro_ix(ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    pda::intro(&PROGRAM_ID, &writer)
+}
+
+fn setup() -> Ledger {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    ledger
+}
+
+#[test]
+fn intros_are_listed_in_posting_order() {
+    let mut ledger = setup();
+    let first = post(&mut ledger);
+    let second = post(&mut ledger);
+
+    assert_eq!(ledger.intro(&first).indexed, 1);
+
+    let feed = page(&ledger, 0);
+    assert_eq!(feed.page, 0);
+    assert_eq!(feed.len, 2);
+    assert_eq!(
+        feed.entries[..2],
+        [
+            FeedEntry {
+                intro: first,
+                slot: SLOT,
+            },
+            FeedEntry {
+                intro: second,
+                slot: SLOT,
+            },
+        ]
+    );
+}
+
+#[test]
+fn a_full_page_rolls_over_to_the_next() {
+    let mut ledger = setup();
+    post(&mut ledger);
+    let registry = IntroRegistry {
+        discriminator: IntroRegistry::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        count: IntroFeedPage::CAPACITY as u64,
+    };
+    ledger.set(
+        pda::registry(&PROGRAM_ID),
+        PROGRAM_ID,
+        LAMPORTS_PER_SOL,
+        registry.try_to_vec().unwrap(),
+    );
+
+    let intro = post(&mut ledger);
+
+    let feed = page(&ledger, 1);
+    assert_eq!(feed.page, 1);
+    assert_eq!(feed.len, 1);
+    assert_eq!(feed.entries[0].intro, intro);
+    assert_eq!(page(&ledger, 0).len, 1);
+}
+
+#[test]
+fn the_page_must_match_the_registry_index() {
+    let mut ledger = setup();
+    let writer = ledger.student();
+    let mut ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    *ix.accounts.last_mut().unwrap() =
+        AccountMeta::new(pda::intro_feed_page(&PROGRAM_ID, 1), false);
+
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidPDA.into()));
+}
@@ -0,0 +1,112 @@
+//! `ValidateIntroPayload`: a dry run of `StudentIntro` that reports through return data and
+//! writes nothing.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshDeserialize;
+use common::*;
+use solana_program::{
+    instruction::Instruction, native_token::LAMPORTS_PER_SOL, program_error::ProgramError,
+    pubkey::Pubkey, system_program,
+};
+use student_intro_sol::{error::IntroError, pda, return_data::IntroValidationReturn};
+
+/// `StudentIntro`'s accounts and payload under the dry-run variant.
+fn validate_ix(ledger: &Ledger, writer: &Pubkey, name: &str, message: &str) -> Instruction {
+    let mut ix = student_intro_ix(ledger, writer, name, message);
+    ix.data[0] = 69;
+    ix
+}
+
+fn validate(ledger: &mut Ledger, ix: &Instruction) -> IntroValidationReturn {
+    ledger.process(ix).unwrap();
+    IntroValidationReturn::try_from_slice(&ledger.return_data()).unwrap()
+}
+
+fn rejected(error: ProgramError) -> IntroValidationReturn {
+    IntroValidationReturn {
+        error: u64::from(error),
+        reward: 0,
+    }
+}
+
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    (ledger, writer)
+}
+
+#[test]
+fn a_valid_payload_reports_the_reward_and_writes_nothing() {
+    let (mut ledger, writer) = setup();
+    let lamports = ledger.info(&writer).lamports();
+
+    let ix = validate_ix(&ledger, &writer, "Ada", "Hello");
+    assert_eq!(
+        validate(&mut ledger, &ix),
+        IntroValidationReturn {
+            error: 0,
+            reward: 10 * LAMPORTS_PER_SOL,
+        }
+    );
+
+    assert!(!ledger.exists(&pda::intro(&PROGRAM_ID, &writer)));
+    assert_eq!(ledger.info(&writer).lamports(), lamports);
+    assert_eq!(ledger.mint_supply(), 0);
+
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+}
+
+#[test]
+fn payload_errors_are_reported_not_raised() {
+    let (mut ledger, writer) = setup();
+    let ix = validate_ix(&ledger, &writer, "Ada", &"a".repeat(1_000));
+
+    assert_eq!(
+        validate(&mut ledger, &ix),
+        rejected(IntroError::InvalidDataLength.into())
+    );
+}
+
+#[test]
+fn an_existing_intro_is_reported() {
+    let (mut ledger, writer) = setup();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let ix = validate_ix(&ledger, &writer, "Ada", "Hello");
+    assert_eq!(
+        validate(&mut ledger, &ix),
+        rejected(ProgramError::AccountAlreadyInitialized)
+    );
+}
+
+#[test]
+fn a_stale_registry_entry_is_reported() {
+    let (mut ledger, writer) = setup();
+    let ix = validate_ix(&ledger, &writer, "Ada", "Hello");
+    let other = ledger.student();
+    let posted = student_intro_ix(&ledger, &other, "Grace", "Hi");
+    ledger.process(&posted).unwrap();
+
+    assert_eq!(
+        validate(&mut ledger, &ix),
+        rejected(IntroError::InvalidPDA.into())
+    );
+}
+
+#[test]
+fn a_writer_short_of_rent_is_reported() {
+    let (mut ledger, writer) = setup();
+    ledger.set(writer, system_program::id(), 1_000, vec![]);
+
+    let ix = validate_ix(&ledger, &writer, "Ada", "Hello");
+    assert_eq!(
+        validate(&mut ledger, &ix),
+        rejected(ProgramError::InsufficientFunds)
+    );
+}
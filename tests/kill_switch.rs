@@ -0,0 +1,158 @@
+//! `SetInstructionEnabled`: the admin switches single instructions off and back on.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use student_intro_sol::{
+    error::IntroError,
+    instruction::{config_account_mut, StudentInstruction},
+    pda,
+    state::{ProgramConfig, StudentIntroState},
+};
+
+const UPDATE_INTRO: u8 = 1;
+const REPLY_INTRO: u8 = 2;
+
+fn toggle_ix(admin: &Pubkey, variant: u8, enabled: bool) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            config_account_mut(&PROGRAM_ID),
+        ],
+        data: payload(
+            StudentInstruction::SET_INSTRUCTION_ENABLED,
+            &(variant, enabled),
+        ),
+    }
+}
+
+fn config(ledger: &Ledger) -> ProgramConfig {
+    try_from_slice_unchecked(&ledger.data(&pda::config(&PROGRAM_ID))).unwrap()
+}
+
+/// A ledger with a config and one intro, returning the admin and the intro.
+fn setup() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    (ledger, admin, pda::intro(&PROGRAM_ID, &writer))
+}
+
+#[test]
+fn disabling_replies_leaves_intros_working() {
+    let (mut ledger, admin, intro) = setup();
+    ledger
+        .process(&toggle_ix(&admin, REPLY_INTRO, false))
+        .unwrap();
+    assert!(!config(&ledger).is_instruction_enabled(REPLY_INTRO));
+
+    let replier = ledger.student();
+    let reply = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    assert_eq!(
+        ledger.process(&reply),
+        Err(IntroError::InstructionDisabled.into())
+    );
+
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Alan", "Hi");
+    ledger.process(&ix).unwrap();
+
+    ledger
+        .process(&toggle_ix(&admin, REPLY_INTRO, true))
+        .unwrap();
+    ledger.process(&reply).unwrap();
+    assert_eq!(ledger.reply_count(&intro), 1);
+}
+
+#[test]
+fn disabled_instructions_are_blocked_inside_batches() {
+    let (mut ledger, admin, intro) = setup();
+    ledger
+        .process(&toggle_ix(&admin, REPLY_INTRO, false))
+        .unwrap();
+    let replier = ledger.student();
+    let reply = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+
+    assert_eq!(
+        ledger.process(&batch_ix(&[reply])),
+        Err(IntroError::InstructionDisabled.into())
+    );
+}
+
+#[test]
+fn switches_cover_instructions_that_do_not_read_the_config() {
+    let (mut ledger, admin, intro) = setup();
+    ledger
+        .process(&toggle_ix(&admin, UPDATE_INTRO, false))
+        .unwrap();
+    let writer = try_from_slice_unchecked::<StudentIntroState>(&ledger.data(&intro))
+        .unwrap()
+        .writer;
+
+    assert_eq!(
+        ledger.process(&update_intro_ix(&writer, "Ada", "Hello again")),
+        Err(IntroError::InstructionDisabled.into())
+    );
+}
+
+#[test]
+fn only_the_admin_flips_switches() {
+    let (mut ledger, _admin, _intro) = setup();
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&toggle_ix(&stranger, REPLY_INTRO, false)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn the_switch_cannot_disable_itself() {
+    let (mut ledger, admin, _intro) = setup();
+
+    assert_eq!(
+        ledger.process(&toggle_ix(
+            &admin,
+            StudentInstruction::SET_INSTRUCTION_ENABLED,
+            false
+        )),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn the_admin_path_cannot_be_disabled() {
+    let (mut ledger, admin, _intro) = setup();
+
+    for variant in StudentInstruction::ALWAYS_ENABLED {
+        assert_eq!(
+            ledger.process(&toggle_ix(&admin, variant, false)),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+    let config_data = config(&ledger);
+    assert!(StudentInstruction::ALWAYS_ENABLED
+        .iter()
+        .all(|variant| config_data.is_instruction_enabled(*variant)));
+}
@@ -0,0 +1,81 @@
+//! Language tags: validated on intros and replies, and stored where clients can filter on them.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{borsh::try_from_slice_unchecked, instruction::Instruction, pubkey::Pubkey};
+use student_intro_sol::{
+    error::IntroError, instruction::QuoteParams, lang, pda, state::StudentReplyState,
+};
+
+fn tagged_intro_ix(ledger: &Ledger, writer: &Pubkey, lang: [u8; 2]) -> Instruction {
+    let mut ix = student_intro_ix(ledger, writer, "Ada", "Merhaba");
+    ix.data = payload(0, &("Ada", "Merhaba", None::<i64>, lang));
+    ix
+}
+
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    (ledger, writer)
+}
+
+#[test]
+fn intro_tag_survives_edits() {
+    let (mut ledger, writer) = setup();
+    let ix = tagged_intro_ix(&ledger, &writer, *b"tr");
+    ledger.process(&ix).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    assert_eq!(ledger.intro(&intro).lang, *b"tr");
+
+    ledger
+        .process(&update_intro_ix(&writer, "Ada", "Selam"))
+        .unwrap();
+    assert_eq!(ledger.intro(&intro).lang, *b"tr");
+}
+
+#[test]
+fn untagged_intros_read_as_unspecified() {
+    let (mut ledger, writer) = setup();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let intro_data = ledger.intro(&pda::intro(&PROGRAM_ID, &writer));
+    assert_eq!(intro_data.lang, lang::UNSPECIFIED);
+}
+
+#[test]
+fn unknown_intro_tags_are_rejected() {
+    let (mut ledger, writer) = setup();
+
+    for tag in [*b"EN", *b"xx", *b"e\0"] {
+        let ix = tagged_intro_ix(&ledger, &writer, tag);
+        assert_eq!(ledger.process(&ix), Err(IntroError::InvalidLanguage.into()));
+    }
+    assert!(!ledger.exists(&pda::intro(&PROGRAM_ID, &writer)));
+}
+
+#[test]
+fn replies_carry_their_tag() {
+    let (mut ledger, writer) = setup();
+    let ix = tagged_intro_ix(&ledger, &writer, *b"tr");
+    ledger.process(&ix).unwrap();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+
+    let mut ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ix.data = payload(2, &("Grace", "Welcome", None::<QuoteParams>, *b"en"));
+    ledger.process(&ix).unwrap();
+
+    let reply: StudentReplyState =
+        try_from_slice_unchecked(&ledger.data(&pda::reply(&PROGRAM_ID, &intro, 0))).unwrap();
+    assert_eq!(reply.lang, *b"en");
+
+    ix.data = payload(2, &("Grace", "Welcome", None::<QuoteParams>, *b"zz"));
+    let ix = substitute(&ix, 3, pda::reply(&PROGRAM_ID, &intro, 1));
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidLanguage.into()));
+}
@@ -0,0 +1,217 @@
+//! Economic invariants: reward tokens only come into existence alongside the content account
+//! they pay for, so total supply always equals the rewards for the intros and replies on chain.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshDeserialize;
+use common::*;
+use solana_program::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{ProgramConfig, StudentReplyState},
+};
+
+const INTRO_REWARD: u64 = 10 * LAMPORTS_PER_SOL;
+const REPLY_REWARD: u64 = 5 * LAMPORTS_PER_SOL;
+
+/// Checks that supply matches the content on chain and that every counted reply exists.
+fn assert_backed(ledger: &Ledger, intros: &[Pubkey]) {
+    let mut replies = 0;
+    for intro in intros {
+        assert!(ledger.exists(intro));
+        let count = ledger.reply_count(intro);
+        for index in 0..count {
+            assert!(ledger.exists(&pda::reply(&PROGRAM_ID, intro, index)));
+        }
+        assert!(!ledger.exists(&pda::reply(&PROGRAM_ID, intro, count)));
+        replies += count;
+    }
+    assert_eq!(
+        ledger.mint_supply(),
+        INTRO_REWARD * intros.len() as u64 + REPLY_REWARD * replies
+    );
+}
+
+fn post_intro(ledger: &mut Ledger) -> Pubkey {
+    let writer = ledger.student();
+    let ix = student_intro_ix(ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    pda::intro(&PROGRAM_ID, &writer)
+}
+
+#[test]
+fn rewards_track_content_across_many_posts() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let intros = [post_intro(&mut ledger), post_intro(&mut ledger)];
+
+    for round in 0..3 {
+        for intro in &intros[..=round % 2] {
+            let replier = ledger.student();
+            let ix = reply_intro_ix(&ledger, &replier, intro, "Grace", "Welcome");
+            ledger.process(&ix).unwrap();
+        }
+        assert_backed(&ledger, &intros);
+    }
+}
+
+#[test]
+fn replayed_reply_against_stale_counter_mints_nothing() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let intro = post_intro(&mut ledger);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(
+            ledger.process(&ix),
+            Err(IntroError::InvalidPDA.into()),
+            "a replayed reply must not reuse its index"
+        );
+    }
+    assert_eq!(ledger.reply_count(&intro), 1);
+    assert_backed(&ledger, &[intro]);
+}
+
+#[test]
+fn replayed_intro_mints_nothing() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let replay = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    assert!(ledger.process(&replay).is_err());
+    assert_backed(&ledger, &[pda::intro(&PROGRAM_ID, &writer)]);
+}
+
+#[test]
+fn create_intro_if_missing_rewards_once() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+
+    for _ in 0..3 {
+        let mut ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+        ix.data = payload(32, &("Ada", "Hello", None::<i64>));
+        ledger.process(&ix).unwrap();
+    }
+    assert_backed(&ledger, &[pda::intro(&PROGRAM_ID, &writer)]);
+}
+
+#[test]
+fn updates_mint_nothing() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    for message in ["Edited", "Edited again", "Hello"] {
+        ledger
+            .process(&update_intro_ix(&writer, "Ada", message))
+            .unwrap();
+    }
+    assert_backed(&ledger, &[pda::intro(&PROGRAM_ID, &writer)]);
+}
+
+#[test]
+fn reply_to_missing_intro_mints_nothing() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let intro = post_intro(&mut ledger);
+    let ghost = pda::intro(&PROGRAM_ID, &Pubkey::new_unique());
+    let replier = ledger.student();
+
+    let ix = substitute(
+        &reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome"),
+        1,
+        ghost,
+    );
+    assert!(ledger.process(&ix).is_err());
+    assert_backed(&ledger, &[intro]);
+}
+
+#[test]
+fn batch_with_stale_reply_rolls_back_every_mint() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let intro = post_intro(&mut ledger);
+    let replier = ledger.student();
+
+    // Both ops are built against the same counter value, so the second one is stale.
+    let reply = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    let ix = batch_ix(&[reply.clone(), reply]);
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidPDA.into()));
+    assert_eq!(ledger.reply_count(&intro), 0);
+    assert_backed(&ledger, &[intro]);
+}
+
+#[test]
+fn batch_of_fresh_replies_mints_per_reply() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let intro = post_intro(&mut ledger);
+    let (alice, bob) = (ledger.student(), ledger.student());
+
+    let first = reply_intro_ix(&ledger, &alice, &intro, "Grace", "Welcome");
+    let second = substitute(
+        &reply_intro_ix(&ledger, &bob, &intro, "Linus", "Hi"),
+        3,
+        pda::reply(&PROGRAM_ID, &intro, 1),
+    );
+    ledger.process(&batch_ix(&[first, second])).unwrap();
+    assert_eq!(ledger.reply_count(&intro), 2);
+    assert_backed(&ledger, &[intro]);
+}
+
+#[test]
+fn repeat_replies_from_one_wallet_decay_to_nothing() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let intro = post_intro(&mut ledger);
+    let replier = ledger.student();
+
+    for _ in 0..4 {
+        let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+        ledger.process(&ix).unwrap();
+    }
+
+    // Full, half, then nothing, however many more replies follow.
+    assert_eq!(ledger.reply_count(&intro), 4);
+    assert_eq!(
+        ledger.mint_supply(),
+        INTRO_REWARD + REPLY_REWARD + REPLY_REWARD / 2
+    );
+}
+
+#[test]
+fn welcome_bot_replies_mint_nothing() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let intro = post_intro(&mut ledger);
+    let bot = ledger.student();
+
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        welcome_bots: vec![bot],
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    let ix = reply_intro_ix(&ledger, &bot, &intro, "Welcome bot", "Welcome aboard!");
+    ledger.process(&ix).unwrap();
+
+    let reply =
+        StudentReplyState::try_from_slice(&ledger.data(&pda::reply(&PROGRAM_ID, &intro, 0)))
+            .unwrap();
+    assert!(reply.is_bot);
+    assert_eq!(ledger.mint_supply(), INTRO_REWARD);
+}
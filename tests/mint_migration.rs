@@ -0,0 +1,181 @@
+//! `MigrateMint` and `SwapOldTokens`: after a migration every reward path pays in the new mint,
+//! and old tokens convert 1:1 by burning them.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Mint;
+use student_intro_sol::{
+    error::IntroError, instruction::config_account_mut, pda, state::ProgramConfig,
+};
+
+const INTRO_REWARD: u64 = 10 * LAMPORTS_PER_SOL;
+
+/// A ledger with the original mint and a config administered by the returned wallet.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    (ledger, admin)
+}
+
+fn migrate_mint_ix(admin: &Pubkey, version: u8) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(pda::token_mint_version(&PROGRAM_ID, version), false),
+            AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            config_account_mut(&PROGRAM_ID),
+        ],
+        data: payload(39, &false),
+    }
+}
+
+fn swap_old_tokens_ix(
+    owner: &Pubkey,
+    from_version: u8,
+    to_version: u8,
+    amount: u64,
+) -> Instruction {
+    let old_mint = pda::token_mint_version(&PROGRAM_ID, from_version);
+    let new_mint = pda::token_mint_version(&PROGRAM_ID, to_version);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(old_mint, false),
+            AccountMeta::new(get_associated_token_address(owner, &old_mint), false),
+            AccountMeta::new(new_mint, false),
+            AccountMeta::new(get_associated_token_address(owner, &new_mint), false),
+            AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: payload(40, &(from_version, amount)),
+    }
+}
+
+fn supply(ledger: &Ledger, mint: &Pubkey) -> u64 {
+    Mint::unpack(&ledger.data(mint)).unwrap().supply
+}
+
+#[test]
+fn rewards_move_to_the_new_mint() {
+    let (mut ledger, admin) = setup();
+    ledger.process(&migrate_mint_ix(&admin, 1)).unwrap();
+    let new_mint = pda::token_mint_version(&PROGRAM_ID, 1);
+
+    let writer = ledger.student();
+    let new_ata = ledger.token_account(&writer, &new_mint);
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+
+    // The original mint no longer pays out.
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+
+    let ix = substitute(&substitute(&ix, 3, new_mint), 5, new_ata);
+    ledger.process(&ix).unwrap();
+    assert_eq!(ledger.token_balance(&new_ata), INTRO_REWARD);
+    assert_eq!(supply(&ledger, &new_mint), INTRO_REWARD);
+    assert_eq!(ledger.mint_supply(), 0);
+}
+
+#[test]
+fn old_tokens_swap_one_to_one() {
+    let (mut ledger, admin) = setup();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    ledger.process(&migrate_mint_ix(&admin, 1)).unwrap();
+    let new_mint = pda::token_mint_version(&PROGRAM_ID, 1);
+    let new_ata = ledger.token_account(&writer, &new_mint);
+
+    let amount = INTRO_REWARD / 4;
+    ledger
+        .process(&swap_old_tokens_ix(&writer, 0, 1, amount))
+        .unwrap();
+
+    let old_ata = get_associated_token_address(&writer, &pda::token_mint(&PROGRAM_ID));
+    assert_eq!(ledger.token_balance(&old_ata), INTRO_REWARD - amount);
+    assert_eq!(ledger.mint_supply(), INTRO_REWARD - amount);
+    assert_eq!(ledger.token_balance(&new_ata), amount);
+    assert_eq!(supply(&ledger, &new_mint), amount);
+}
+
+#[test]
+fn swapping_more_than_held_mints_nothing() {
+    let (mut ledger, admin) = setup();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    ledger.process(&migrate_mint_ix(&admin, 1)).unwrap();
+    let new_mint = pda::token_mint_version(&PROGRAM_ID, 1);
+    ledger.token_account(&writer, &new_mint);
+
+    let ix = swap_old_tokens_ix(&writer, 0, 1, INTRO_REWARD + 1);
+    assert!(ledger.process(&ix).is_err());
+    assert_eq!(supply(&ledger, &new_mint), 0);
+}
+
+#[test]
+fn only_older_versions_can_be_swapped() {
+    let (mut ledger, admin) = setup();
+    ledger.process(&migrate_mint_ix(&admin, 1)).unwrap();
+    let writer = ledger.student();
+    ledger.token_account(&writer, &pda::token_mint_version(&PROGRAM_ID, 1));
+
+    let ix = swap_old_tokens_ix(&writer, 1, 1, 1);
+    assert_eq!(ledger.process(&ix), Err(ProgramError::InvalidArgument));
+}
+
+#[test]
+fn only_the_admin_migrates() {
+    let (mut ledger, _admin) = setup();
+    let intruder = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&migrate_mint_ix(&intruder, 1)),
+        Err(IntroError::Unauthorized.into())
+    );
+    assert!(!ledger.exists(&pda::token_mint_version(&PROGRAM_ID, 1)));
+}
+
+#[test]
+fn versions_chain() {
+    let (mut ledger, admin) = setup();
+    ledger.process(&migrate_mint_ix(&admin, 1)).unwrap();
+
+    // Re-running the same migration cannot recreate version 1.
+    assert_eq!(
+        ledger.process(&migrate_mint_ix(&admin, 1)),
+        Err(IntroError::InvalidPDA.into())
+    );
+    ledger.process(&migrate_mint_ix(&admin, 2)).unwrap();
+    assert!(ledger.exists(&pda::token_mint_version(&PROGRAM_ID, 2)));
+}
@@ -0,0 +1,177 @@
+//! `JoinQueue` / `LeaveQueue` / `PopQueue`: a per-course office-hours queue that enrolled
+//! students join and the teacher serves in order.
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{OfficeHoursQueue, QUEUE_CAPACITY},
+};
+
+const COURSE: &str = "Solana 101";
+
+/// A ledger with a course, returning the teacher and course.
+fn setup() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+
+    (ledger, teacher, course)
+}
+
+/// A wallet the teacher has enrolled in the course.
+fn enrolled(ledger: &mut Ledger, teacher: &Pubkey, course: &Pubkey) -> Pubkey {
+    let student = ledger.wallet();
+    ledger.enroll(teacher, course, &student);
+    student
+}
+
+fn join_ix(student: &Pubkey, course: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*student, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new_readonly(pda::enrollment(&PROGRAM_ID, course, student), false),
+            AccountMeta::new(pda::office_hours_queue(&PROGRAM_ID, course), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![53],
+    }
+}
+
+fn leave_ix(student: &Pubkey, course: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*student, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new(pda::office_hours_queue(&PROGRAM_ID, course), false),
+        ],
+        data: vec![54],
+    }
+}
+
+fn pop_ix(teacher: &Pubkey, course: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*teacher, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new(pda::office_hours_queue(&PROGRAM_ID, course), false),
+        ],
+        data: vec![55],
+    }
+}
+
+fn queued(ledger: &Ledger, course: &Pubkey) -> Vec<Pubkey> {
+    let queue: OfficeHoursQueue =
+        try_from_slice_unchecked(&ledger.data(&pda::office_hours_queue(&PROGRAM_ID, course)))
+            .unwrap();
+    queue.students().copied().collect()
+}
+
+#[test]
+fn teacher_pops_students_in_join_order() {
+    let (mut ledger, teacher, course) = setup();
+    let students: Vec<Pubkey> = (0..3)
+        .map(|_| enrolled(&mut ledger, &teacher, &course))
+        .collect();
+    for student in &students {
+        ledger.process(&join_ix(student, &course)).unwrap();
+    }
+    assert_eq!(queued(&ledger, &course), students);
+
+    ledger.process(&pop_ix(&teacher, &course)).unwrap();
+    assert_eq!(queued(&ledger, &course), students[1..]);
+}
+
+#[test]
+fn leaving_keeps_everyone_else_in_order() {
+    let (mut ledger, teacher, course) = setup();
+    let students: Vec<Pubkey> = (0..3)
+        .map(|_| enrolled(&mut ledger, &teacher, &course))
+        .collect();
+    for student in &students {
+        ledger.process(&join_ix(student, &course)).unwrap();
+    }
+
+    ledger.process(&leave_ix(&students[1], &course)).unwrap();
+    assert_eq!(queued(&ledger, &course), [students[0], students[2]]);
+
+    assert_eq!(
+        ledger.process(&leave_ix(&students[1], &course)),
+        Err(IntroError::NotQueued.into())
+    );
+}
+
+#[test]
+fn full_queues_reject_joins_until_served() {
+    let (mut ledger, teacher, course) = setup();
+    let students: Vec<Pubkey> = (0..QUEUE_CAPACITY + 1)
+        .map(|_| enrolled(&mut ledger, &teacher, &course))
+        .collect();
+    for student in &students[..QUEUE_CAPACITY] {
+        ledger.process(&join_ix(student, &course)).unwrap();
+    }
+
+    let late = students[QUEUE_CAPACITY];
+    assert_eq!(
+        ledger.process(&join_ix(&late, &course)),
+        Err(IntroError::QueueFull.into())
+    );
+
+    // Serving the front frees a slot, and the newcomer wraps around to the back.
+    ledger.process(&pop_ix(&teacher, &course)).unwrap();
+    ledger.process(&join_ix(&late, &course)).unwrap();
+    assert_eq!(queued(&ledger, &course), students[1..]);
+}
+
+#[test]
+fn students_queue_once() {
+    let (mut ledger, teacher, course) = setup();
+    let student = enrolled(&mut ledger, &teacher, &course);
+    ledger.process(&join_ix(&student, &course)).unwrap();
+
+    assert_eq!(
+        ledger.process(&join_ix(&student, &course)),
+        Err(IntroError::AlreadyQueued.into())
+    );
+}
+
+#[test]
+fn joining_needs_an_enrollment() {
+    let (mut ledger, _teacher, course) = setup();
+    let outsider = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&join_ix(&outsider, &course)),
+        Err(IntroError::NotEnrolled.into())
+    );
+}
+
+#[test]
+fn only_the_teacher_pops() {
+    let (mut ledger, teacher, course) = setup();
+    let student = enrolled(&mut ledger, &teacher, &course);
+    ledger.process(&join_ix(&student, &course)).unwrap();
+
+    assert_eq!(
+        ledger.process(&pop_ix(&student, &course)),
+        Err(IntroError::Unauthorized.into())
+    );
+
+    ledger.process(&pop_ix(&teacher, &course)).unwrap();
+    assert_eq!(
+        ledger.process(&pop_ix(&teacher, &course)),
+        Err(IntroError::QueueEmpty.into())
+    );
+}
@@ -0,0 +1,40 @@
+//! `scale_reward`: oracle-priced rewards stay within `MAX_PRICE_SCALE` of the base reward, and
+//! price exponents too large to compute with are rejected rather than overflowing.
+
+use student_intro_sol::{
+    error::IntroError,
+    oracle::{scale_reward, OraclePrice, MAX_PRICE_SCALE},
+};
+
+#[test]
+fn rewards_scale_against_the_reference_price() {
+    // $2.00 against a $1.00 reference halves the reward.
+    let oracle = OraclePrice {
+        price: 200_000_000,
+        expo: -8,
+    };
+
+    assert_eq!(scale_reward(1_000, 100, &oracle).unwrap(), 500);
+}
+
+#[test]
+fn scaled_rewards_stay_within_the_price_scale() {
+    let oracle = OraclePrice { price: 1, expo: -8 };
+
+    assert_eq!(
+        scale_reward(1_000, 100, &oracle).unwrap(),
+        1_000 * MAX_PRICE_SCALE
+    );
+}
+
+#[test]
+fn out_of_range_exponents_are_rejected() {
+    for expo in [40, -40, i32::MAX, i32::MIN] {
+        let oracle = OraclePrice { price: 1, expo };
+
+        assert!(matches!(
+            scale_reward(1_000, 100, &oracle),
+            Err(IntroError::InvalidOracleAccount)
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+//! `GcOrphans`: anyone closes the reply counter of an intro that is gone, paying the rent to
+//! the config's destination.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+};
+use student_intro_sol::{
+    error::IntroError,
+    events::ProgramEvent,
+    pda,
+    state::{ProgramConfig, ReplyCount, CLOSED_ACCOUNT_DISCRIMINATOR},
+};
+
+fn gc_ix(intro: &Pubkey, destination: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*intro, false),
+            AccountMeta::new(pda::reply_counter(&PROGRAM_ID, intro), false),
+            AccountMeta::new(*destination, false),
+        ],
+        data: vec![79],
+    }
+}
+
+/// A ledger whose config names `destination`, if any, returning the admin.
+fn setup(destination: Option<Pubkey>) -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        data_version: ProgramConfig::DATA_VERSION,
+        orphan_rent_destination: destination,
+        ..ProgramConfig::default()
+    });
+
+    (ledger, admin)
+}
+
+/// Posts an intro and then tombstones it alone, as if its counter had been left behind.
+fn orphan_by_closing(ledger: &mut Ledger) -> Pubkey {
+    let writer = ledger.student();
+    let ix = student_intro_ix(ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let mut data = ledger.data(&intro);
+    data.fill(0);
+    CLOSED_ACCOUNT_DISCRIMINATOR
+        .serialize(&mut &mut data[..])
+        .unwrap();
+    ledger.set(intro, PROGRAM_ID, 0, data);
+    intro
+}
+
+#[test]
+fn counters_of_closed_intros_are_collected() {
+    let (mut ledger, admin) = setup(None);
+    let intro = orphan_by_closing(&mut ledger);
+    let counter = pda::reply_counter(&PROGRAM_ID, &intro);
+    let rent = ledger.info(&counter).lamports();
+    let before = ledger.info(&admin).lamports();
+
+    ledger.process(&gc_ix(&intro, &admin)).unwrap();
+
+    assert_eq!(ledger.info(&counter).lamports(), 0);
+    assert_eq!(ledger.info(&admin).lamports(), before + rent);
+    assert_eq!(
+        ledger.logged_data(),
+        vec![ProgramEvent::OrphanCollected {
+            account: counter,
+            intro,
+            destination: admin,
+            lamports: rent,
+        }
+        .try_to_vec()
+        .unwrap()]
+    );
+
+    assert_eq!(
+        ledger.process(&gc_ix(&intro, &admin)),
+        Err(IntroError::AccountClosed.into())
+    );
+}
+
+#[test]
+fn counters_of_intros_never_created_are_collected() {
+    let destination = Pubkey::new_unique();
+    let (mut ledger, _admin) = setup(Some(destination));
+    let intro = Pubkey::new_unique();
+    let (counter, _bump) = Pubkey::find_program_address(&[intro.as_ref(), b"counter"], &PROGRAM_ID);
+    let counter_data = ReplyCount {
+        discriminator: ReplyCount::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        counter: 0,
+    };
+    ledger.set(
+        counter,
+        PROGRAM_ID,
+        LAMPORTS_PER_SOL,
+        counter_data.try_to_vec().unwrap(),
+    );
+
+    ledger.process(&gc_ix(&intro, &destination)).unwrap();
+
+    assert_eq!(ledger.info(&destination).lamports(), LAMPORTS_PER_SOL);
+}
+
+#[test]
+fn counters_of_open_intros_stay() {
+    let (mut ledger, admin) = setup(None);
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(
+        ledger.process(&gc_ix(&pda::intro(&PROGRAM_ID, &writer), &admin)),
+        Err(IntroError::NotOrphaned.into())
+    );
+}
+
+#[test]
+fn rent_goes_only_to_the_configured_destination() {
+    let destination = Pubkey::new_unique();
+    let (mut ledger, admin) = setup(Some(destination));
+    let intro = orphan_by_closing(&mut ledger);
+
+    assert_eq!(
+        ledger.process(&gc_ix(&intro, &admin)),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
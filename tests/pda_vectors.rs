@@ -0,0 +1,43 @@
+//! `pda::test_vectors` stays in step with the derivations the program checks.
+
+use solana_program::pubkey::Pubkey;
+use student_intro_sol::pda::{self, test_vectors::*};
+
+#[test]
+fn vectors_use_the_test_program_id() {
+    assert_eq!(PROGRAM_ID, Pubkey::new_from_array([7; 32]));
+}
+
+#[test]
+fn mint_vectors_match_the_derivations() {
+    assert_eq!(pda::token_mint(&PROGRAM_ID), TOKEN_MINT);
+    assert_eq!(pda::mint_auth(&PROGRAM_ID), MINT_AUTH);
+    assert_eq!(
+        Pubkey::find_program_address(&[b"token_mint"], &PROGRAM_ID),
+        (TOKEN_MINT, TOKEN_MINT_BUMP)
+    );
+    assert_eq!(
+        Pubkey::find_program_address(&[b"token_auth"], &PROGRAM_ID),
+        (MINT_AUTH, MINT_AUTH_BUMP)
+    );
+}
+
+#[test]
+fn intro_vectors_match_the_derivations() {
+    for (seed, vector) in (1..).zip(&INTROS) {
+        assert_eq!(vector.writer, Pubkey::new_from_array([seed; 32]));
+        assert_eq!(pda::intro(&PROGRAM_ID, &vector.writer), vector.intro);
+        assert_eq!(
+            pda::reply_counter(&PROGRAM_ID, &vector.intro),
+            vector.counter
+        );
+        assert_eq!(
+            Pubkey::find_program_address(&[vector.writer.as_ref(), b"intro"], &PROGRAM_ID),
+            (vector.intro, vector.intro_bump)
+        );
+        assert_eq!(
+            Pubkey::find_program_address(&[vector.intro.as_ref(), b"counter"], &PROGRAM_ID),
+            (vector.counter, vector.counter_bump)
+        );
+    }
+}
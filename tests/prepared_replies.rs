@@ -0,0 +1,170 @@
+//! `PrepareReply` and `FinalizeReply`: a replier records a draft by hash and posts it later,
+//! keeping the place it prepared in for the first-reply bonus.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{
+    error::IntroError,
+    events::ProgramEvent,
+    pda,
+    state::{ProgramConfig, ReplyPreparation},
+};
+
+const BONUS: u64 = LAMPORTS_PER_SOL;
+const REPLY_REWARD: u64 = 5 * LAMPORTS_PER_SOL;
+
+fn prepare_ix(replier: &Pubkey, intro: &Pubkey, name: &str, message: &str) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*replier, true),
+            AccountMeta::new(*intro, false),
+            AccountMeta::new_readonly(pda::reply_counter(&PROGRAM_ID, intro), false),
+            AccountMeta::new(pda::reply_preparation(&PROGRAM_ID, intro, replier), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(82, &ReplyPreparation::hash_reply(name, message)),
+    }
+}
+
+fn finalize_ix(
+    ledger: &Ledger,
+    replier: &Pubkey,
+    intro: &Pubkey,
+    name: &str,
+    message: &str,
+) -> Instruction {
+    let mut ix = reply_intro_ix(ledger, replier, intro, name, message);
+    ix.data = payload(83, &(name, message));
+    ix.accounts.push(AccountMeta::new(
+        pda::reply_preparation(&PROGRAM_ID, intro, replier),
+        false,
+    ));
+    ix
+}
+
+fn reward_balance(ledger: &Ledger, owner: &Pubkey) -> u64 {
+    ledger.token_balance(&get_associated_token_address(
+        owner,
+        &pda::token_mint(&PROGRAM_ID),
+    ))
+}
+
+/// A ledger paying a first-reply bonus, with an intro, returning the intro.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        first_reply_bonus: BONUS,
+        first_reply_window_secs: 3_600,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    (ledger, pda::intro(&PROGRAM_ID, &writer))
+}
+
+#[test]
+fn prepared_replies_keep_the_first_reply_bonus() {
+    let (mut ledger, intro) = setup();
+    let early = ledger.student();
+    ledger
+        .process(&prepare_ix(&early, &intro, "Grace", "Welcome"))
+        .unwrap();
+    assert!(ledger.intro(&intro).first_reply_claimed);
+
+    // Lands first, but after the preparation.
+    let late = ledger.student();
+    let ix = reply_intro_ix(&ledger, &late, &intro, "Alan", "Hi");
+    ledger.process(&ix).unwrap();
+    assert_eq!(reward_balance(&ledger, &late), REPLY_REWARD);
+
+    let preparation = pda::reply_preparation(&PROGRAM_ID, &intro, &early);
+    let prepared_slot = try_from_slice_unchecked::<ReplyPreparation>(&ledger.data(&preparation))
+        .unwrap()
+        .prepared_slot;
+    let ix = finalize_ix(&ledger, &early, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(reward_balance(&ledger, &early), REPLY_REWARD + BONUS);
+    assert_eq!(ledger.reply_count(&intro), 2);
+    assert_eq!(ledger.info(&preparation).lamports(), 0);
+    assert!(ledger.logged_data().contains(
+        &ProgramEvent::ReplyFinalized {
+            intro,
+            reply: pda::reply(&PROGRAM_ID, &intro, 1),
+            prepared_slot,
+        }
+        .try_to_vec()
+        .unwrap()
+    ));
+}
+
+#[test]
+fn preparations_after_the_first_reply_earn_no_bonus() {
+    let (mut ledger, intro) = setup();
+    let first = ledger.student();
+    let ix = reply_intro_ix(&ledger, &first, &intro, "Alan", "Hi");
+    ledger.process(&ix).unwrap();
+    assert_eq!(reward_balance(&ledger, &first), REPLY_REWARD + BONUS);
+
+    let replier = ledger.student();
+    ledger
+        .process(&prepare_ix(&replier, &intro, "Grace", "Welcome"))
+        .unwrap();
+    let ix = finalize_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(reward_balance(&ledger, &replier), REPLY_REWARD);
+}
+
+#[test]
+fn finalized_text_must_match_the_preparation() {
+    let (mut ledger, intro) = setup();
+    let replier = ledger.student();
+    ledger
+        .process(&prepare_ix(&replier, &intro, "Grace", "Welcome"))
+        .unwrap();
+
+    let ix = finalize_ix(&ledger, &replier, &intro, "Grace", "Welcome!");
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::CommitmentMismatch.into())
+    );
+    assert_eq!(ledger.reply_count(&intro), 0);
+}
+
+#[test]
+fn replies_finalize_only_their_own_preparation() {
+    let (mut ledger, intro) = setup();
+    let replier = ledger.student();
+    ledger
+        .process(&prepare_ix(&replier, &intro, "Grace", "Welcome"))
+        .unwrap();
+    let other = ledger.student();
+
+    let mut ix = finalize_ix(&ledger, &other, &intro, "Grace", "Welcome");
+    *ix.accounts.last_mut().unwrap() =
+        AccountMeta::new(pda::reply_preparation(&PROGRAM_ID, &intro, &replier), false);
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidPDA.into()));
+}
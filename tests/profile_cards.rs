@@ -0,0 +1,160 @@
+//! `SyncProfileCard`: anyone rebuilds a student's fixed-size card from their intro, reputation
+//! and badge accounts.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{ProfileCard, Reputation, StudentIntroState},
+};
+
+fn sync_ix(payer: &Pubkey, writer: &Pubkey) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    let (badge, _bump) = Pubkey::find_program_address(&[intro.as_ref(), b"badge"], &PROGRAM_ID);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(intro, false),
+            AccountMeta::new(pda::profile_card(&PROGRAM_ID, writer), false),
+            AccountMeta::new_readonly(pda::reputation(&PROGRAM_ID, writer), false),
+            AccountMeta::new_readonly(badge, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![74],
+    }
+}
+
+fn set_slug_ix(writer: &Pubkey, slug: &str) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*writer, true),
+            AccountMeta::new(pda::intro(&PROGRAM_ID, writer), false),
+            AccountMeta::new(pda::slug(&PROGRAM_ID, slug), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(43, &slug),
+    }
+}
+
+fn card(ledger: &Ledger, writer: &Pubkey) -> ProfileCard {
+    ProfileCard::try_from_slice(&ledger.data(&pda::profile_card(&PROGRAM_ID, writer))).unwrap()
+}
+
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    (ledger, writer)
+}
+
+#[test]
+fn anyone_syncs_a_card_from_the_intro() {
+    let (mut ledger, writer) = setup();
+    let payer = ledger.wallet();
+
+    ledger.process(&sync_ix(&payer, &writer)).unwrap();
+
+    let card = card(&ledger, &writer);
+    assert_eq!(card.student, writer);
+    assert_eq!(card.intro, pda::intro(&PROGRAM_ID, &writer));
+    assert_eq!(&card.name[..4], b"Ada\0");
+    assert_eq!(card.handle, [0; 32]);
+    assert_eq!((card.reputation, card.badges), (0, 0));
+    assert_eq!(card.synced_slot, SLOT);
+    assert_eq!(
+        ledger.data(&pda::profile_card(&PROGRAM_ID, &writer)).len(),
+        ProfileCard::SIZE
+    );
+}
+
+#[test]
+fn syncing_again_picks_up_changes() {
+    let (mut ledger, writer) = setup();
+    ledger.process(&sync_ix(&writer, &writer)).unwrap();
+
+    ledger.process(&set_slug_ix(&writer, "ada-l")).unwrap();
+    let reputation = Reputation {
+        discriminator: Reputation::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        user: writer,
+        points: 30,
+        claimable: 0,
+    };
+    ledger.set(
+        pda::reputation(&PROGRAM_ID, &writer),
+        PROGRAM_ID,
+        LAMPORTS_PER_SOL,
+        reputation.try_to_vec().unwrap(),
+    );
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.verified = true;
+    intro_data.status = StudentIntroState::STATUS_GRADUATED;
+    ledger.set_intro(&intro, &intro_data);
+
+    ledger.process(&sync_ix(&writer, &writer)).unwrap();
+
+    let card = card(&ledger, &writer);
+    assert_eq!(&card.handle[..6], b"ada-l\0");
+    assert_eq!(card.reputation, 30);
+    assert_eq!(
+        card.badges,
+        ProfileCard::BADGE_VERIFIED | ProfileCard::BADGE_GRADUATED
+    );
+}
+
+#[test]
+fn fields_sit_at_their_offsets() {
+    let (mut ledger, writer) = setup();
+    ledger.process(&sync_ix(&writer, &writer)).unwrap();
+
+    let data = ledger.data(&pda::profile_card(&PROGRAM_ID, &writer));
+    let at = |offset: usize| &data[offset..offset + 32];
+    assert_eq!(at(ProfileCard::STUDENT_OFFSET), writer.as_ref());
+    assert_eq!(
+        at(ProfileCard::INTRO_OFFSET),
+        pda::intro(&PROGRAM_ID, &writer).as_ref()
+    );
+    assert_eq!(&at(ProfileCard::NAME_OFFSET)[..3], b"Ada");
+}
+
+#[test]
+fn names_are_cut_at_a_character_boundary() {
+    // 31 ASCII bytes leave one byte, too few for the two-byte 'é'.
+    let name = format!("{}é", "a".repeat(31));
+    let field: [u8; ProfileCard::NAME_LEN] = ProfileCard::fixed_text(&name);
+
+    assert_eq!(&field[..31], "a".repeat(31).as_bytes());
+    assert_eq!(field[31], 0);
+}
+
+#[test]
+fn cards_are_only_at_the_writers_address() {
+    let (mut ledger, writer) = setup();
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&substitute(
+            &sync_ix(&writer, &writer),
+            2,
+            pda::profile_card(&PROGRAM_ID, &stranger)
+        )),
+        Err(IntroError::InvalidPDA.into())
+    );
+}
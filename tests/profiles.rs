@@ -0,0 +1,136 @@
+//! `SetProfile`: the writer's pronouns, time zone and country, each stored in full whether or
+//! not it is public.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{error::IntroError, instruction::ProfileParams, pda, state::IntroProfile};
+
+fn set_profile_ix(writer: &Pubkey, params: &ProfileParams) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*writer, true),
+            AccountMeta::new_readonly(intro, false),
+            AccountMeta::new(pda::profile(&PROGRAM_ID, &intro), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(47, params),
+    }
+}
+
+fn profile(ledger: &Ledger, writer: &Pubkey) -> IntroProfile {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    try_from_slice_unchecked(&ledger.data(&pda::profile(&PROGRAM_ID, &intro))).unwrap()
+}
+
+fn params() -> ProfileParams {
+    ProfileParams {
+        pronouns: "she/her".to_string(),
+        timezone: "Europe/Istanbul".to_string(),
+        country: *b"TR",
+        public_fields: IntroProfile::PRONOUNS_PUBLIC,
+    }
+}
+
+/// A ledger with one posted intro, returning its writer.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    (ledger, writer)
+}
+
+#[test]
+fn private_fields_are_still_stored() {
+    let (mut ledger, writer) = setup();
+
+    ledger.process(&set_profile_ix(&writer, &params())).unwrap();
+
+    let stored = profile(&ledger, &writer);
+    assert_eq!(stored.intro, pda::intro(&PROGRAM_ID, &writer));
+    assert_eq!(stored.pronouns, "she/her");
+    assert_eq!(stored.timezone, "Europe/Istanbul");
+    assert_eq!(stored.country, *b"TR");
+    assert_eq!(stored.public_fields, IntroProfile::PRONOUNS_PUBLIC);
+}
+
+#[test]
+fn profiles_can_be_rewritten() {
+    let (mut ledger, writer) = setup();
+    ledger.process(&set_profile_ix(&writer, &params())).unwrap();
+
+    let cleared = ProfileParams {
+        pronouns: "they/them".to_string(),
+        ..ProfileParams::default()
+    };
+    ledger.process(&set_profile_ix(&writer, &cleared)).unwrap();
+
+    let stored = profile(&ledger, &writer);
+    assert_eq!(stored.pronouns, "they/them");
+    assert_eq!(stored.timezone, "");
+    assert_eq!(stored.country, IntroProfile::UNSET_COUNTRY);
+    assert_eq!(stored.public_fields, 0);
+}
+
+#[test]
+fn malformed_profiles_are_rejected() {
+    let (mut ledger, writer) = setup();
+
+    let cases = [
+        ProfileParams {
+            pronouns: "x".repeat(IntroProfile::MAX_PRONOUNS_LEN + 1),
+            ..params()
+        },
+        ProfileParams {
+            timezone: "Europe/Istanbul; DROP".to_string(),
+            ..params()
+        },
+        ProfileParams {
+            country: *b"tr",
+            ..params()
+        },
+        ProfileParams {
+            public_fields: 1 << 3,
+            ..params()
+        },
+    ];
+    for case in &cases {
+        assert_eq!(
+            ledger.process(&set_profile_ix(&writer, case)),
+            Err(IntroError::InvalidProfile.into())
+        );
+    }
+}
+
+#[test]
+fn only_the_writer_sets_the_profile() {
+    let (mut ledger, writer) = setup();
+    let intruder = ledger.wallet();
+
+    let ix = substitute(&set_profile_ix(&writer, &params()), 0, intruder);
+    assert_eq!(ledger.process(&ix), Err(IntroError::Unauthorized.into()));
+}
+
+#[test]
+fn events_carry_only_public_fields() {
+    let (mut ledger, writer) = setup();
+    ledger.process(&set_profile_ix(&writer, &params())).unwrap();
+
+    let redacted = profile(&ledger, &writer).redacted();
+    assert_eq!(redacted.pronouns, "she/her");
+    assert_eq!(redacted.timezone, "");
+    assert_eq!(redacted.country, IntroProfile::UNSET_COUNTRY);
+}
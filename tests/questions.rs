@@ -0,0 +1,206 @@
+//! Question intros: listed in the `["questions"]` feed, their writer picks a best answer with
+//! `SelectBestAnswer`, and they stop taking replies a grace period later.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use student_intro_sol::{
+    error::IntroError,
+    events::ProgramEvent,
+    format, lang, pda,
+    state::{QuestionFeed, StudentIntroState, StudentReplyState},
+};
+
+/// `StudentIntro` of `kind`, with the program id standing in for the wallet link ahead of the
+/// questions feed.
+fn question_ix(ledger: &Ledger, writer: &Pubkey, kind: u8) -> Instruction {
+    let mut ix = student_intro_ix(ledger, writer, "Ada", "How do PDAs work?");
+    ix.data = payload(
+        0,
+        &(
+            "Ada",
+            "How do PDAs work?",
+            None::<i64>,
+            lang::UNSPECIFIED,
+            format::PLAIN,
+            kind,
+        ),
+    );
+    ix.accounts.extend([
+        AccountMeta::new_readonly(PROGRAM_ID, false),
+        AccountMeta::new(pda::questions(&PROGRAM_ID), false),
+    ]);
+    ix
+}
+
+fn select_ix(writer: &Pubkey, index: u64) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*writer, true),
+            AccountMeta::new(intro, false),
+            AccountMeta::new_readonly(pda::reply(&PROGRAM_ID, &intro, index), false),
+        ],
+        data: payload(81, &index),
+    }
+}
+
+/// A ledger with a question and one reply to it, returning the writer and the question.
+fn setup() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = question_ix(&ledger, &writer, StudentIntroState::KIND_QUESTION);
+    ledger.process(&ix).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Seeds and a bump");
+    ledger.process(&ix).unwrap();
+
+    (ledger, writer, intro)
+}
+
+fn reply(ledger: &mut Ledger, intro: &Pubkey) -> Result<(), ProgramError> {
+    let replier = ledger.student();
+    let ix = reply_intro_ix(ledger, &replier, intro, "Alan", "Also this");
+    ledger.process(&ix)
+}
+
+#[test]
+fn questions_are_listed_in_their_feed() {
+    let (mut ledger, _writer, intro) = setup();
+    let other = ledger.student();
+    let ix = student_intro_ix(&ledger, &other, "Alan", "Hi");
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(ledger.intro(&intro).kind, StudentIntroState::KIND_QUESTION);
+    assert_eq!(
+        ledger.intro(&pda::intro(&PROGRAM_ID, &other)).kind,
+        StudentIntroState::KIND_INTRODUCTION
+    );
+    let feed: QuestionFeed =
+        try_from_slice_unchecked(&ledger.data(&pda::questions(&PROGRAM_ID))).unwrap();
+    assert_eq!(
+        feed.recent().map(|entry| entry.intro).collect::<Vec<_>>(),
+        vec![intro]
+    );
+}
+
+#[test]
+fn questions_need_the_feed_last() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let mut ix = question_ix(&ledger, &writer, StudentIntroState::KIND_QUESTION);
+    ix.accounts.pop();
+
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidPDA.into()));
+}
+
+#[test]
+fn questions_need_a_question_feed() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let other = ledger.student();
+    ledger
+        .process(&student_intro_ix(&ledger, &other, "Alan", "Hi"))
+        .unwrap();
+    let intro = ledger.data(&pda::intro(&PROGRAM_ID, &other));
+    ledger.set(pda::questions(&PROGRAM_ID), PROGRAM_ID, 1, intro);
+    let ix = question_ix(&ledger, &writer, StudentIntroState::KIND_QUESTION);
+
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
+
+#[test]
+fn unknown_kinds_are_rejected() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = question_ix(&ledger, &writer, 2);
+
+    assert_eq!(ledger.process(&ix), Err(ProgramError::InvalidArgument));
+}
+
+#[test]
+fn answered_questions_close_after_the_grace_period() {
+    let (mut ledger, writer, intro) = setup();
+
+    let best = pda::reply(&PROGRAM_ID, &intro, 0);
+    let replier = try_from_slice_unchecked::<StudentReplyState>(&ledger.data(&best))
+        .unwrap()
+        .replier;
+
+    ledger.process(&select_ix(&writer, 0)).unwrap();
+
+    let intro_data = ledger.intro(&intro);
+    assert_eq!(intro_data.answered_at, UNIX_TIMESTAMP);
+    assert_eq!(intro_data.best_answer, 0);
+    assert_eq!(
+        ledger.logged_data(),
+        vec![ProgramEvent::BestAnswerSelected {
+            intro,
+            reply: best,
+            replier,
+        }
+        .try_to_vec()
+        .unwrap()]
+    );
+    assert_eq!(
+        ledger.process(&select_ix(&writer, 0)),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    // Still open within the grace period.
+    reply(&mut ledger, &intro).unwrap();
+
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.answered_at = UNIX_TIMESTAMP - StudentIntroState::ANSWER_GRACE_SECS;
+    ledger.set_intro(&intro, &intro_data);
+
+    assert_eq!(
+        reply(&mut ledger, &intro),
+        Err(IntroError::QuestionClosed.into())
+    );
+}
+
+#[test]
+fn introductions_have_no_best_answer() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    reply(&mut ledger, &intro).unwrap();
+
+    assert_eq!(
+        ledger.process(&select_ix(&writer, 0)),
+        Err(IntroError::NotAQuestion.into())
+    );
+}
+
+#[test]
+fn only_the_writer_selects_the_best_answer() {
+    let (mut ledger, writer, _intro) = setup();
+    let stranger = ledger.wallet();
+    let mut ix = select_ix(&writer, 0);
+    ix.accounts[0] = AccountMeta::new_readonly(stranger, true);
+
+    assert_eq!(ledger.process(&ix), Err(IntroError::Unauthorized.into()));
+}
@@ -0,0 +1,276 @@
+//! `CreateQuiz` / `SubmitQuizAnswer` / `RevealAnswer`: students commit hashed answers while a
+//! quiz is open and the teacher's reveal grades them.
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+#[cfg(not(feature = "no-rewards"))]
+use spl_associated_token_account::get_associated_token_address;
+#[cfg(not(feature = "no-rewards"))]
+use student_intro_sol::state::ProgramConfig;
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{Quiz, QuizSubmission},
+};
+
+const COURSE: &str = "Solana 101";
+const ANSWER: &str = "b";
+const SALT: [u8; 32] = [9; 32];
+
+/// A ledger with a course, two enrolled students and quiz 1 open for an hour, returning the
+/// teacher, course and students.
+fn setup(reward: u64) -> (Ledger, Pubkey, Pubkey, [Pubkey; 2]) {
+    let mut ledger = Ledger::new();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+
+    let students = [ledger.student(), ledger.student()];
+    for student in students {
+        ledger.enroll(&teacher, &course, &student);
+    }
+
+    ledger
+        .process(&create_ix(
+            &teacher,
+            &course,
+            UNIX_TIMESTAMP + 3_600,
+            Quiz::commit_answer(ANSWER, &SALT),
+            reward,
+        ))
+        .unwrap();
+
+    (ledger, teacher, course, students)
+}
+
+fn create_ix(
+    teacher: &Pubkey,
+    course: &Pubkey,
+    closes_at: i64,
+    answer_hash: [u8; 32],
+    reward: u64,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*teacher, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new(pda::quiz(&PROGRAM_ID, course, 1), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(58, &(1u64, closes_at, answer_hash, reward)),
+    }
+}
+
+fn submit_ix(student: &Pubkey, course: &Pubkey, answer: &str) -> Instruction {
+    let quiz = pda::quiz(&PROGRAM_ID, course, 1);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*student, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new_readonly(pda::enrollment(&PROGRAM_ID, course, student), false),
+            AccountMeta::new(quiz, false),
+            AccountMeta::new(pda::quiz_submission(&PROGRAM_ID, &quiz, student), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(
+            59,
+            &(1u64, QuizSubmission::hash_answer(&quiz, student, answer)),
+        ),
+    }
+}
+
+/// `RevealAnswer` grading `students`' submissions, without reward accounts.
+fn reveal_ix(teacher: &Pubkey, course: &Pubkey, answer: &str, students: &[Pubkey]) -> Instruction {
+    let quiz = pda::quiz(&PROGRAM_ID, course, 1);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*teacher, true),
+        AccountMeta::new_readonly(*course, false),
+        AccountMeta::new(quiz, false),
+    ];
+    accounts.extend(
+        students.iter().map(|student| {
+            AccountMeta::new(pda::quiz_submission(&PROGRAM_ID, &quiz, student), false)
+        }),
+    );
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: payload(60, &(answer, SALT)),
+    }
+}
+
+/// Moves the quiz's close time to now, as if its window had passed.
+fn close_quiz(ledger: &mut Ledger, course: &Pubkey) {
+    let quiz = pda::quiz(&PROGRAM_ID, course, 1);
+    let mut data = ledger.data(&quiz);
+    let mut state = try_from_slice_unchecked::<Quiz>(&data).unwrap();
+    state.closes_at = UNIX_TIMESTAMP;
+    state.serialize(&mut &mut data[..]).unwrap();
+    let lamports = ledger.info(&quiz).lamports();
+    ledger.set(quiz, PROGRAM_ID, lamports, data);
+}
+
+fn submission(ledger: &Ledger, course: &Pubkey, student: &Pubkey) -> QuizSubmission {
+    let quiz = pda::quiz(&PROGRAM_ID, course, 1);
+    try_from_slice_unchecked(&ledger.data(&pda::quiz_submission(&PROGRAM_ID, &quiz, student)))
+        .unwrap()
+}
+
+#[test]
+fn reveal_grades_each_submission() {
+    let (mut ledger, teacher, course, [right, wrong]) = setup(0);
+    ledger.process(&submit_ix(&right, &course, ANSWER)).unwrap();
+    ledger.process(&submit_ix(&wrong, &course, "c")).unwrap();
+    close_quiz(&mut ledger, &course);
+
+    ledger
+        .process(&reveal_ix(&teacher, &course, ANSWER, &[right, wrong]))
+        .unwrap();
+
+    let graded = submission(&ledger, &course, &right);
+    assert!(graded.graded && graded.correct);
+    let graded = submission(&ledger, &course, &wrong);
+    assert!(graded.graded && !graded.correct);
+    let quiz: Quiz =
+        try_from_slice_unchecked(&ledger.data(&pda::quiz(&PROGRAM_ID, &course, 1))).unwrap();
+    assert!(quiz.revealed);
+    assert_eq!((quiz.submissions, quiz.correct), (2, 1));
+
+    // Grading again skips what is already graded.
+    ledger
+        .process(&reveal_ix(&teacher, &course, ANSWER, &[right]))
+        .unwrap();
+    let quiz: Quiz =
+        try_from_slice_unchecked(&ledger.data(&pda::quiz(&PROGRAM_ID, &course, 1))).unwrap();
+    assert_eq!(quiz.correct, 1);
+}
+
+#[test]
+fn copied_hashes_do_not_match() {
+    let (mut ledger, teacher, course, [right, copier]) = setup(0);
+    ledger.process(&submit_ix(&right, &course, ANSWER)).unwrap();
+    let copied = submission(&ledger, &course, &right).answer_hash;
+    let mut ix = submit_ix(&copier, &course, "");
+    ix.data = payload(59, &(1u64, copied));
+    ledger.process(&ix).unwrap();
+    close_quiz(&mut ledger, &course);
+
+    ledger
+        .process(&reveal_ix(&teacher, &course, ANSWER, &[copier]))
+        .unwrap();
+
+    assert!(!submission(&ledger, &course, &copier).correct);
+}
+
+#[test]
+fn submissions_close_with_the_quiz() {
+    let (mut ledger, _teacher, course, [student, _]) = setup(0);
+    close_quiz(&mut ledger, &course);
+
+    assert_eq!(
+        ledger.process(&submit_ix(&student, &course, ANSWER)),
+        Err(IntroError::QuizClosed.into())
+    );
+}
+
+#[test]
+fn answers_are_revealed_only_after_the_quiz_closes() {
+    let (mut ledger, teacher, course, [student, _]) = setup(0);
+    ledger
+        .process(&submit_ix(&student, &course, ANSWER))
+        .unwrap();
+
+    assert_eq!(
+        ledger.process(&reveal_ix(&teacher, &course, ANSWER, &[student])),
+        Err(IntroError::QuizStillOpen.into())
+    );
+}
+
+#[test]
+fn reveals_must_match_the_commitment() {
+    let (mut ledger, teacher, course, [student, _]) = setup(0);
+    ledger.process(&submit_ix(&student, &course, "c")).unwrap();
+    close_quiz(&mut ledger, &course);
+
+    assert_eq!(
+        ledger.process(&reveal_ix(&teacher, &course, "c", &[student])),
+        Err(IntroError::AnswerMismatch.into())
+    );
+}
+
+#[test]
+fn students_submit_once_and_only_the_teacher_reveals() {
+    let (mut ledger, _teacher, course, [student, _]) = setup(0);
+    ledger
+        .process(&submit_ix(&student, &course, ANSWER))
+        .unwrap();
+    assert!(ledger.process(&submit_ix(&student, &course, "c")).is_err());
+    close_quiz(&mut ledger, &course);
+
+    assert_eq!(
+        ledger.process(&reveal_ix(&student, &course, ANSWER, &[student])),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[cfg(not(feature = "no-rewards"))]
+#[test]
+fn correct_submitters_earn_the_quiz_reward() {
+    let reward = Quiz::MAX_REWARD;
+    let (mut ledger, teacher, course, [right, wrong]) = setup(reward);
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        course_reward_cap: reward,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+    ledger.process(&submit_ix(&right, &course, ANSWER)).unwrap();
+    ledger.process(&submit_ix(&wrong, &course, "c")).unwrap();
+    close_quiz(&mut ledger, &course);
+
+    let mint = pda::token_mint(&PROGRAM_ID);
+    let quiz = pda::quiz(&PROGRAM_ID, &course, 1);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(teacher, true),
+        AccountMeta::new(course, false),
+        AccountMeta::new(quiz, false),
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    for student in [right, wrong] {
+        accounts.push(AccountMeta::new(
+            pda::quiz_submission(&PROGRAM_ID, &quiz, &student),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(&student, &mint),
+            false,
+        ));
+    }
+    ledger
+        .process(&Instruction {
+            program_id: PROGRAM_ID,
+            accounts,
+            data: payload(60, &(ANSWER, SALT)),
+        })
+        .unwrap();
+
+    let balance =
+        |student: &Pubkey| ledger.token_balance(&get_associated_token_address(student, &mint));
+    assert_eq!(balance(&right), reward);
+    assert_eq!(balance(&wrong), 0);
+}
@@ -0,0 +1,117 @@
+//! `MarkReplyRead`: the intro writer flags replies they have read.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use student_intro_sol::{error::IntroError, pda, state::StudentReplyState};
+
+fn mark_read_ix(writer: &Pubkey, intro: &Pubkey, reply: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*writer, true),
+            AccountMeta::new_readonly(*intro, false),
+            AccountMeta::new(*reply, false),
+        ],
+        data: vec![65],
+    }
+}
+
+fn is_read(ledger: &Ledger, reply: &Pubkey) -> bool {
+    try_from_slice_unchecked::<StudentReplyState>(&ledger.data(reply))
+        .unwrap()
+        .read
+}
+
+/// A ledger with an intro and one reply to it, returning the writer, intro and reply.
+fn setup() -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    (ledger, writer, intro, pda::reply(&PROGRAM_ID, &intro, 0))
+}
+
+#[test]
+fn replies_start_unread_and_the_writer_marks_them() {
+    let (mut ledger, writer, intro, reply) = setup();
+    assert!(!is_read(&ledger, &reply));
+
+    ledger
+        .process(&mark_read_ix(&writer, &intro, &reply))
+        .unwrap();
+    assert!(is_read(&ledger, &reply));
+
+    // Marking again changes nothing.
+    ledger
+        .process(&mark_read_ix(&writer, &intro, &reply))
+        .unwrap();
+    assert!(is_read(&ledger, &reply));
+}
+
+#[test]
+fn only_the_intro_writer_marks_replies() {
+    let (mut ledger, _writer, intro, reply) = setup();
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&mark_read_ix(&stranger, &intro, &reply)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn replies_must_be_on_the_intro() {
+    let (mut ledger, _writer, _intro, reply) = setup();
+    let other = ledger.student();
+    let ix = student_intro_ix(&ledger, &other, "Alan", "Hi");
+    ledger.process(&ix).unwrap();
+    let other_intro = pda::intro(&PROGRAM_ID, &other);
+
+    assert_eq!(
+        ledger.process(&mark_read_ix(&other, &other_intro, &reply)),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
+
+#[test]
+fn replies_from_before_spam_scores_are_marked() {
+    let (mut ledger, writer, intro, reply) = setup();
+    let mut data = ledger.data(&reply);
+    data.pop();
+    let lamports = ledger.info(&reply).lamports();
+    ledger.set(reply, PROGRAM_ID, lamports, data);
+
+    ledger
+        .process(&mark_read_ix(&writer, &intro, &reply))
+        .unwrap();
+    assert!(is_read(&ledger, &reply));
+}
+
+#[test]
+fn replies_from_before_receipts_are_rejected() {
+    let (mut ledger, writer, intro, reply) = setup();
+    let mut data = ledger.data(&reply);
+    data.truncate(data.len() - 2);
+    let lamports = ledger.info(&reply).lamports();
+    ledger.set(reply, PROGRAM_ID, lamports, data);
+
+    assert_eq!(
+        ledger.process(&mark_read_ix(&writer, &intro, &reply)),
+        Err(IntroError::ReadReceiptUnsupported.into())
+    );
+}
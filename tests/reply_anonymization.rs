@@ -0,0 +1,223 @@
+//! `SetCourseRetention` and `AnonymizeReplies`: after a course that opted in has ended, anyone
+//! strips the replier and name from replies to its intros, keeping the message.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    events::ProgramEvent,
+    pda,
+    state::{StudentIntroState, StudentReplyState, ANONYMOUS_REPLIER},
+};
+
+const COURSE: &str = "Solana 101";
+/// Bytes of the fields after `message` in a reply without a quote or links.
+const TRAILING_FIELDS_LEN: usize = 1 + 1 + 2 + 1 + 4 + 1 + 1;
+
+/// A ledger with a course and a public intro scoped to it, returning the teacher, course and
+/// intro.
+fn setup() -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let enrollment = pda::enrollment(&PROGRAM_ID, &course, &writer);
+    ledger.enroll(&teacher, &course, &writer);
+    for visibility in [
+        StudentIntroState::VISIBILITY_COURSE_ONLY,
+        StudentIntroState::VISIBILITY_PUBLIC,
+    ] {
+        ledger
+            .process(&Instruction {
+                program_id: PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(writer, true),
+                    AccountMeta::new(pda::intro(&PROGRAM_ID, &writer), false),
+                    AccountMeta::new_readonly(enrollment, false),
+                    AccountMeta::new(pda::course_stats(&PROGRAM_ID, &course), false),
+                    AccountMeta::new(pda::student_activity(&PROGRAM_ID, &enrollment), false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new_readonly(course, false),
+                ],
+                data: payload(14, &visibility),
+            })
+            .unwrap();
+    }
+
+    (ledger, teacher, course, pda::intro(&PROGRAM_ID, &writer))
+}
+
+fn retention_ix(teacher: &Pubkey, course: &Pubkey, ends_at: i64, anonymize: bool) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*teacher, true),
+            AccountMeta::new(*course, false),
+        ],
+        data: payload(76, &(ends_at, anonymize)),
+    }
+}
+
+fn anonymize_ix(course: &Pubkey, intro: &Pubkey, replies: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*course, false),
+        AccountMeta::new_readonly(*intro, false),
+    ];
+    accounts.extend(replies.iter().map(|reply| AccountMeta::new(*reply, false)));
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: vec![77],
+    }
+}
+
+/// Posts a reply to `intro`, returning its address.
+fn reply(ledger: &mut Ledger, intro: &Pubkey) -> Pubkey {
+    let index = ledger.reply_count(intro);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(ledger, &replier, intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+    pda::reply(&PROGRAM_ID, intro, index)
+}
+
+fn reply_data(ledger: &Ledger, reply: &Pubkey) -> StudentReplyState {
+    try_from_slice_unchecked(&ledger.data(reply)).unwrap()
+}
+
+#[test]
+fn ended_courses_anonymize_replies() {
+    let (mut ledger, teacher, course, intro) = setup();
+    let replies = [reply(&mut ledger, &intro), reply(&mut ledger, &intro)];
+    let len = ledger.data(&replies[0]).len();
+    ledger
+        .process(&retention_ix(&teacher, &course, UNIX_TIMESTAMP - 1, true))
+        .unwrap();
+
+    ledger
+        .process(&anonymize_ix(&course, &intro, &replies))
+        .unwrap();
+
+    for reply in &replies {
+        let reply_data = reply_data(&ledger, reply);
+        assert_eq!(reply_data.replier, ANONYMOUS_REPLIER);
+        assert_eq!(reply_data.name, "");
+        assert_eq!(reply_data.message, "Welcome");
+        assert_eq!(reply_data.intro, intro);
+
+        let data = ledger.data(reply);
+        assert_eq!(data.len(), len);
+        assert_eq!(data[len - "Grace".len()..], [0; 5]);
+    }
+    assert_eq!(
+        ledger.logged_data(),
+        vec![ProgramEvent::RepliesAnonymized {
+            course,
+            intro,
+            count: 2,
+        }
+        .try_to_vec()
+        .unwrap()]
+    );
+
+    // Running the crank again leaves the replies as they are.
+    let before = ledger.data(&replies[0]);
+    ledger
+        .process(&anonymize_ix(&course, &intro, &replies))
+        .unwrap();
+    assert_eq!(ledger.data(&replies[0]), before);
+}
+
+#[test]
+fn replies_from_before_later_fields_keep_their_defaults() {
+    let (mut ledger, teacher, course, intro) = setup();
+    let reply = reply(&mut ledger, &intro);
+    let mut data = ledger.data(&reply);
+    data.truncate(data.len() - TRAILING_FIELDS_LEN);
+    let lamports = ledger.info(&reply).lamports();
+    ledger.set(reply, PROGRAM_ID, lamports, data);
+    ledger
+        .process(&retention_ix(&teacher, &course, UNIX_TIMESTAMP - 1, true))
+        .unwrap();
+
+    ledger
+        .process(&anonymize_ix(&course, &intro, &[reply]))
+        .unwrap();
+
+    let reply_data = reply_data(&ledger, &reply);
+    assert_eq!(reply_data.replier, ANONYMOUS_REPLIER);
+    assert_eq!(reply_data.message, "Welcome");
+    assert!(reply_data.links.is_empty());
+    assert!(!reply_data.read);
+}
+
+#[test]
+fn replies_stay_until_the_course_ends() {
+    let (mut ledger, teacher, course, intro) = setup();
+    let reply = reply(&mut ledger, &intro);
+    ledger
+        .process(&retention_ix(&teacher, &course, UNIX_TIMESTAMP, true))
+        .unwrap();
+
+    assert_eq!(
+        ledger.process(&anonymize_ix(&course, &intro, &[reply])),
+        Err(IntroError::AnonymizationNotDue.into())
+    );
+    assert_eq!(reply_data(&ledger, &reply).name, "Grace");
+}
+
+#[test]
+fn courses_opt_in_to_anonymization() {
+    let (mut ledger, teacher, course, intro) = setup();
+    let reply = reply(&mut ledger, &intro);
+    ledger
+        .process(&retention_ix(&teacher, &course, UNIX_TIMESTAMP - 1, false))
+        .unwrap();
+
+    assert_eq!(
+        ledger.process(&anonymize_ix(&course, &intro, &[reply])),
+        Err(IntroError::AnonymizationNotDue.into())
+    );
+}
+
+#[test]
+fn only_the_teacher_sets_retention() {
+    let (mut ledger, _teacher, course, _intro) = setup();
+    let stranger = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&retention_ix(&stranger, &course, UNIX_TIMESTAMP - 1, true)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn only_replies_to_the_intro_are_anonymized() {
+    let (mut ledger, teacher, course, intro) = setup();
+    ledger
+        .process(&retention_ix(&teacher, &course, UNIX_TIMESTAMP - 1, true))
+        .unwrap();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Alan", "Hi");
+    ledger.process(&ix).unwrap();
+    let other = reply(&mut ledger, &pda::intro(&PROGRAM_ID, &writer));
+
+    assert_eq!(
+        ledger.process(&anonymize_ix(&course, &intro, &[other])),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
@@ -0,0 +1,74 @@
+//! Links extracted from replies when they are posted: https only, bounded, and stored on the
+//! reply at its exact size.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{borsh::try_from_slice_unchecked, pubkey::Pubkey};
+use student_intro_sol::{pda, state::StudentReplyState};
+
+/// Posts `message` as the first reply to a fresh intro and returns the reply's key.
+fn post_reply(message: &str) -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", message);
+    ledger.process(&ix).unwrap();
+
+    (ledger, pda::reply(&PROGRAM_ID, &intro, 0))
+}
+
+fn links(message: &str) -> Vec<String> {
+    let (ledger, reply) = post_reply(message);
+    try_from_slice_unchecked::<StudentReplyState>(&ledger.data(&reply))
+        .unwrap()
+        .links
+}
+
+#[test]
+fn links_are_extracted_in_order() {
+    assert_eq!(
+        links("See https://solana.com/docs, and [the book](https://doc.rust-lang.org/book)."),
+        ["https://solana.com/docs", "https://doc.rust-lang.org/book"]
+    );
+}
+
+#[test]
+fn only_https_links_are_kept() {
+    assert_eq!(
+        links("http://plain.example ftp://files.example javascript:alert(1) https://ok.example"),
+        ["https://ok.example"]
+    );
+    assert!(links("just https:// on its own").is_empty());
+}
+
+#[test]
+fn links_are_bounded() {
+    let message = (0..StudentReplyState::MAX_LINKS + 2)
+        .map(|i| format!("https://{}.example", i))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let links = links(&message);
+    assert_eq!(links.len(), StudentReplyState::MAX_LINKS);
+    assert_eq!(links[0], "https://0.example");
+}
+
+#[test]
+fn reply_is_sized_for_its_links() {
+    let message = "Try https://solana.com";
+    let (ledger, reply) = post_reply(message);
+
+    assert_eq!(
+        ledger.data(&reply).len(),
+        StudentReplyState::get_account_size("Grace", message)
+            + StudentReplyState::links_size(&["https://solana.com".to_string()])
+    );
+}
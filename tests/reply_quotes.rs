@@ -0,0 +1,141 @@
+//! Quote-replies: the quoted reply must be on the same intro and contain the excerpt, and the
+//! stored quote lets clients cut the excerpt back out of it.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked, hash::hash, instruction::AccountMeta,
+    instruction::Instruction, pubkey::Pubkey,
+};
+use student_intro_sol::{
+    error::IntroError,
+    instruction::QuoteParams,
+    pda,
+    state::{ReplyQuote, StudentReplyState},
+};
+
+/// `ReplyIntro` quoting `excerpt` from `quoted`. No wallet link is passed, so the program id
+/// holds its place ahead of the quoted reply.
+fn quote_reply_ix(
+    ledger: &Ledger,
+    replier: &Pubkey,
+    intro: &Pubkey,
+    quoted: &Pubkey,
+    excerpt: &str,
+) -> Instruction {
+    let mut ix = reply_intro_ix(ledger, replier, intro, "Grace", "Agreed");
+    ix.accounts.extend([
+        AccountMeta::new_readonly(PROGRAM_ID, false),
+        AccountMeta::new_readonly(*quoted, false),
+    ]);
+    let quote = QuoteParams {
+        reply: *quoted,
+        excerpt: excerpt.to_string(),
+    };
+    ix.data = payload(2, &("Grace", "Agreed", Some(quote)));
+    ix
+}
+
+fn reply(ledger: &Ledger, key: &Pubkey) -> StudentReplyState {
+    try_from_slice_unchecked(&ledger.data(key)).unwrap()
+}
+
+/// A ledger with an intro and one reply to it, returning the intro and that reply.
+fn setup() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome to the course");
+    ledger.process(&ix).unwrap();
+
+    (ledger, intro, pda::reply(&PROGRAM_ID, &intro, 0))
+}
+
+#[test]
+fn quote_locates_the_excerpt() {
+    let (mut ledger, intro, quoted) = setup();
+    let replier = ledger.student();
+
+    let ix = quote_reply_ix(&ledger, &replier, &intro, &quoted, "the course");
+    ledger.process(&ix).unwrap();
+
+    let quote = reply(&ledger, &pda::reply(&PROGRAM_ID, &intro, 1))
+        .quote
+        .unwrap();
+    assert_eq!(
+        quote,
+        ReplyQuote {
+            reply: quoted,
+            excerpt_start: 11,
+            excerpt_len: 10,
+            excerpt_hash: hash(b"the course").to_bytes(),
+        }
+    );
+
+    let message = reply(&ledger, &quoted).message;
+    let start = quote.excerpt_start as usize;
+    let excerpt = &message[start..start + quote.excerpt_len as usize];
+    assert_eq!(hash(excerpt.as_bytes()).to_bytes(), quote.excerpt_hash);
+}
+
+#[test]
+fn plain_replies_carry_no_quote() {
+    let (ledger, _intro, quoted) = setup();
+    assert_eq!(reply(&ledger, &quoted).quote, None);
+}
+
+#[test]
+fn doctored_excerpts_are_rejected() {
+    let (mut ledger, intro, quoted) = setup();
+    let replier = ledger.student();
+
+    let ix = quote_reply_ix(&ledger, &replier, &intro, &quoted, "the curse");
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidQuote.into()));
+    assert_eq!(ledger.reply_count(&intro), 1);
+}
+
+#[test]
+fn quoted_reply_must_be_on_the_same_intro() {
+    let (mut ledger, _intro, quoted) = setup();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Alan", "Hi");
+    ledger.process(&ix).unwrap();
+    let other_intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+
+    let ix = quote_reply_ix(&ledger, &replier, &other_intro, &quoted, "Welcome");
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidQuote.into()));
+}
+
+#[test]
+fn quoted_account_must_be_the_quoted_reply() {
+    let (mut ledger, intro, quoted) = setup();
+    let replier = ledger.student();
+
+    // The quote names one reply but the account passed is the intro.
+    let ix = quote_reply_ix(&ledger, &replier, &intro, &quoted, "Welcome");
+    let last = ix.accounts.len() - 1;
+    let ix = substitute(&ix, last, intro);
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidQuote.into()));
+}
+
+#[test]
+fn excerpts_are_bounded() {
+    let (mut ledger, intro, quoted) = setup();
+    let replier = ledger.student();
+    let excerpt = "a".repeat(ReplyQuote::MAX_EXCERPT_LEN + 1);
+
+    let ix = quote_reply_ix(&ledger, &replier, &intro, &quoted, &excerpt);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::InvalidDataLength.into())
+    );
+}
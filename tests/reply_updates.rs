@@ -0,0 +1,45 @@
+//! `client::reply_updates`: typed reply creations from a raw program subscription.
+
+use borsh::BorshSerialize;
+use solana_program::pubkey::Pubkey;
+use student_intro_sol::{client::reply_updates, format, lang, state::StudentReplyState};
+
+fn reply_data(message: &str) -> Vec<u8> {
+    StudentReplyState {
+        discriminator: StudentReplyState::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        intro: Pubkey::new_unique(),
+        replier: Pubkey::new_unique(),
+        name: "Grace".to_string(),
+        message: message.to_string(),
+        is_bot: false,
+        quote: None,
+        lang: lang::UNSPECIFIED,
+        format: format::PLAIN,
+        links: vec![],
+        read: false,
+        spam_score: 0,
+    }
+    .try_to_vec()
+    .unwrap()
+}
+
+#[test]
+fn each_reply_is_yielded_once() {
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let notifications = vec![
+        (first, reply_data("Welcome")),
+        (Pubkey::new_unique(), b"not a reply".to_vec()),
+        (first, reply_data("Welcome, edited")),
+        (second, reply_data("Hi")),
+    ];
+
+    let created: Vec<_> = reply_updates(notifications)
+        .map(|update| (update.reply, update.state.message))
+        .collect();
+
+    assert_eq!(
+        created,
+        vec![(first, "Welcome".to_string()), (second, "Hi".to_string())]
+    );
+}
@@ -0,0 +1,259 @@
+//! Reply vote milestones credited to the replier's reputation, and `ClaimReputationRewards`.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{ReplyScore, Reputation, StudentReplyState, ANONYMOUS_REPLIER},
+};
+
+fn vote_ix(
+    voter: &Pubkey,
+    intro: &Pubkey,
+    reply: &Pubkey,
+    replier: &Pubkey,
+    upvote: bool,
+) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new_readonly(*intro, false),
+            AccountMeta::new_readonly(*reply, false),
+            AccountMeta::new(pda::reply_vote(&PROGRAM_ID, reply, voter), false),
+            AccountMeta::new(pda::reply_score(&PROGRAM_ID, reply), false),
+            AccountMeta::new(pda::top_replies(&PROGRAM_ID, intro), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, voter), false),
+            AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, replier), false),
+        ],
+        data: payload(11, &upvote),
+    }
+}
+
+/// `VoteReply` passing the replier's reputation.
+fn credited_vote_ix(
+    voter: &Pubkey,
+    intro: &Pubkey,
+    reply: &Pubkey,
+    replier: &Pubkey,
+    upvote: bool,
+) -> Instruction {
+    let mut ix = vote_ix(voter, intro, reply, replier, upvote);
+    ix.accounts.push(AccountMeta::new(
+        pda::reputation(&PROGRAM_ID, replier),
+        false,
+    ));
+    ix
+}
+
+fn claim_ix(user: &Pubkey) -> Instruction {
+    let mint = pda::token_mint(&PROGRAM_ID);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*user, true),
+            AccountMeta::new(pda::reputation(&PROGRAM_ID, user), false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(pda::mint_auth(&PROGRAM_ID), false),
+            AccountMeta::new(get_associated_token_address(user, &mint), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: vec![66],
+    }
+}
+
+fn reputation(ledger: &Ledger, user: &Pubkey) -> Reputation {
+    try_from_slice_unchecked(&ledger.data(&pda::reputation(&PROGRAM_ID, user))).unwrap()
+}
+
+fn score(ledger: &Ledger, reply: &Pubkey) -> ReplyScore {
+    try_from_slice_unchecked(&ledger.data(&pda::reply_score(&PROGRAM_ID, reply))).unwrap()
+}
+
+/// A ledger with an intro and one reply to it, returning the intro, reply and replier.
+fn setup() -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    (ledger, intro, pda::reply(&PROGRAM_ID, &intro, 0), replier)
+}
+
+/// Casts `count` votes from fresh wallets, passing the reputation when `credit` is set.
+fn vote(
+    ledger: &mut Ledger,
+    (intro, reply, replier): (&Pubkey, &Pubkey, &Pubkey),
+    count: usize,
+    upvote: bool,
+    credit: bool,
+) {
+    for _ in 0..count {
+        let voter = ledger.wallet();
+        let ix = if credit {
+            credited_vote_ix(&voter, intro, reply, replier, upvote)
+        } else {
+            vote_ix(&voter, intro, reply, replier, upvote)
+        };
+        ledger.process(&ix).unwrap();
+    }
+}
+
+#[test]
+fn reaching_a_milestone_credits_the_replier() {
+    let (mut ledger, intro, reply, replier) = setup();
+    let first = ReplyScore::MILESTONES[0] as usize;
+
+    vote(
+        &mut ledger,
+        (&intro, &reply, &replier),
+        first - 1,
+        true,
+        true,
+    );
+    assert!(!ledger.exists(&pda::reputation(&PROGRAM_ID, &replier)));
+
+    vote(&mut ledger, (&intro, &reply, &replier), 2, true, true);
+    let credited = reputation(&ledger, &replier);
+    assert_eq!(credited.user, replier);
+    assert_eq!(credited.points, Reputation::POINTS_PER_MILESTONE);
+    assert_eq!(credited.claimable, Reputation::MILESTONE_REWARD);
+    assert_eq!(score(&ledger, &reply).milestones, 1);
+}
+
+#[test]
+fn milestones_are_credited_once_even_if_the_score_dips() {
+    let (mut ledger, intro, reply, replier) = setup();
+    let first = ReplyScore::MILESTONES[0] as usize;
+    let accounts = (&intro, &reply, &replier);
+
+    vote(&mut ledger, accounts, first, true, true);
+    vote(&mut ledger, accounts, 1, false, true);
+    vote(&mut ledger, accounts, 1, true, true);
+
+    assert_eq!(score(&ledger, &reply).score, ReplyScore::MILESTONES[0]);
+    assert_eq!(
+        reputation(&ledger, &replier).points,
+        Reputation::POINTS_PER_MILESTONE
+    );
+}
+
+#[test]
+fn milestones_missed_without_the_reputation_are_credited_later() {
+    let (mut ledger, intro, reply, replier) = setup();
+    let second = ReplyScore::MILESTONES[1] as usize;
+
+    vote(&mut ledger, (&intro, &reply, &replier), second, true, false);
+    assert_eq!(score(&ledger, &reply).milestones, 0);
+
+    vote(&mut ledger, (&intro, &reply, &replier), 1, true, true);
+    assert_eq!(
+        reputation(&ledger, &replier).points,
+        2 * Reputation::POINTS_PER_MILESTONE
+    );
+    assert_eq!(score(&ledger, &reply).milestones, 2);
+}
+
+#[test]
+fn claiming_mints_accrued_rewards_once() {
+    let (mut ledger, intro, reply, replier) = setup();
+    let first = ReplyScore::MILESTONES[0] as usize;
+    vote(&mut ledger, (&intro, &reply, &replier), first, true, true);
+    let ata = get_associated_token_address(&replier, &pda::token_mint(&PROGRAM_ID));
+    let before = ledger.token_balance(&ata);
+
+    ledger.process(&claim_ix(&replier)).unwrap();
+    assert_eq!(
+        ledger.token_balance(&ata),
+        before + Reputation::MILESTONE_REWARD
+    );
+    assert_eq!(reputation(&ledger, &replier).claimable, 0);
+
+    ledger.process(&claim_ix(&replier)).unwrap();
+    assert_eq!(
+        ledger.token_balance(&ata),
+        before + Reputation::MILESTONE_REWARD
+    );
+}
+
+#[test]
+fn reputation_must_be_the_repliers() {
+    let (mut ledger, intro, reply, replier) = setup();
+    let first = ReplyScore::MILESTONES[0] as usize;
+    vote(
+        &mut ledger,
+        (&intro, &reply, &replier),
+        first - 1,
+        true,
+        true,
+    );
+    let voter = ledger.wallet();
+
+    let mut ix = vote_ix(&voter, &intro, &reply, &replier, true);
+    ix.accounts.push(AccountMeta::new(
+        pda::reputation(&PROGRAM_ID, &voter),
+        false,
+    ));
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidPDA.into()));
+}
+
+#[test]
+fn scores_from_before_reputation_keep_counting_without_credit() {
+    let (mut ledger, intro, reply, replier) = setup();
+    vote(&mut ledger, (&intro, &reply, &replier), 1, true, true);
+    let score_key = pda::reply_score(&PROGRAM_ID, &reply);
+    let mut legacy = score(&ledger, &reply).try_to_vec().unwrap();
+    legacy.pop();
+    let lamports = ledger.info(&score_key).lamports();
+    ledger.set(score_key, PROGRAM_ID, lamports, legacy);
+
+    let first = ReplyScore::MILESTONES[0] as usize;
+    vote(&mut ledger, (&intro, &reply, &replier), first, true, true);
+
+    assert_eq!(ledger.data(&score_key).len(), ReplyScore::SIZE - 1);
+    assert_eq!(score(&ledger, &reply).score, first as i64 + 1);
+    assert!(!ledger.exists(&pda::reputation(&PROGRAM_ID, &replier)));
+}
+
+#[test]
+fn anonymous_replies_earn_no_reputation() {
+    let (mut ledger, intro, reply, _replier) = setup();
+    let mut reply_data: StudentReplyState = try_from_slice_unchecked(&ledger.data(&reply)).unwrap();
+    reply_data.replier = ANONYMOUS_REPLIER;
+    let lamports = ledger.info(&reply).lamports();
+    let mut data = ledger.data(&reply);
+    let encoded = reply_data.try_to_vec().unwrap();
+    data[..encoded.len()].copy_from_slice(&encoded);
+    ledger.set(reply, PROGRAM_ID, lamports, data);
+
+    let first = ReplyScore::MILESTONES[0] as usize;
+    vote(
+        &mut ledger,
+        (&intro, &reply, &ANONYMOUS_REPLIER),
+        first,
+        true,
+        true,
+    );
+
+    assert_eq!(score(&ledger, &reply).score, first as i64);
+    assert!(!ledger.exists(&pda::reputation(&PROGRAM_ID, &ANONYMOUS_REPLIER)));
+}
@@ -0,0 +1,146 @@
+//! `ReplyIntro` reward splits: shares of a reply's reward routed to co-recipients' reward
+//! token accounts.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{error::IntroError, format, instruction::QuoteParams, lang, pda};
+
+/// `ReplyIntro` splitting `shares_bps` with `recipients`. No wallet link is passed, so the
+/// program id holds its place ahead of them.
+fn split_reply_ix(
+    ledger: &Ledger,
+    replier: &Pubkey,
+    intro: &Pubkey,
+    shares_bps: &[u16],
+    recipients: &[Pubkey],
+) -> Instruction {
+    let mut ix = reply_intro_ix(ledger, replier, intro, "Grace", "Welcome");
+    ix.accounts
+        .push(AccountMeta::new_readonly(PROGRAM_ID, false));
+    ix.accounts.extend(
+        recipients
+            .iter()
+            .map(|recipient| AccountMeta::new(*recipient, false)),
+    );
+    ix.data = payload(
+        2,
+        &(
+            "Grace",
+            "Welcome",
+            None::<QuoteParams>,
+            lang::UNSPECIFIED,
+            format::PLAIN,
+            shares_bps.to_vec(),
+        ),
+    );
+    ix
+}
+
+/// A ledger with an intro, returning the intro and a replier.
+fn setup() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    let replier = ledger.student();
+    (ledger, pda::intro(&PROGRAM_ID, &writer), replier)
+}
+
+fn reward_ata(owner: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, &pda::token_mint(&PROGRAM_ID))
+}
+
+#[test]
+fn shares_go_to_co_recipients_and_the_rest_to_the_replier() {
+    let (mut ledger, intro, replier) = setup();
+    let co_author = ledger.student();
+    let team = ledger.student();
+    let supply = ledger.mint_supply();
+
+    let ix = split_reply_ix(
+        &ledger,
+        &replier,
+        &intro,
+        &[2_500, 1_000],
+        &[reward_ata(&co_author), reward_ata(&team)],
+    );
+    ledger.process(&ix).unwrap();
+
+    let reward = ledger.mint_supply() - supply;
+    assert!(reward > 0);
+    assert_eq!(ledger.token_balance(&reward_ata(&co_author)), reward / 4);
+    assert_eq!(ledger.token_balance(&reward_ata(&team)), reward / 10);
+    assert_eq!(
+        ledger.token_balance(&reward_ata(&replier)),
+        reward - reward / 4 - reward / 10
+    );
+}
+
+#[test]
+fn shares_over_the_whole_reward_are_rejected() {
+    let (mut ledger, intro, replier) = setup();
+    let first = ledger.student();
+    let second = ledger.student();
+
+    let ix = split_reply_ix(
+        &ledger,
+        &replier,
+        &intro,
+        &[6_000, 5_000],
+        &[reward_ata(&first), reward_ata(&second)],
+    );
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::InvalidRewardSplit.into())
+    );
+}
+
+#[test]
+fn recipients_must_be_associated_token_accounts() {
+    let (mut ledger, intro, replier) = setup();
+    let owner = ledger.wallet();
+    let stray = Pubkey::new_unique();
+    ledger.raw_token_account(stray, &owner, &pda::token_mint(&PROGRAM_ID));
+
+    let ix = split_reply_ix(&ledger, &replier, &intro, &[5_000], &[stray]);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
+
+#[test]
+fn the_replier_cannot_split_with_themselves() {
+    let (mut ledger, intro, replier) = setup();
+
+    let ix = split_reply_ix(&ledger, &replier, &intro, &[5_000], &[reward_ata(&replier)]);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::InvalidRewardSplit.into())
+    );
+}
+
+#[test]
+fn every_share_needs_its_account() {
+    let (mut ledger, intro, replier) = setup();
+    let co_author = ledger.student();
+
+    let ix = split_reply_ix(
+        &ledger,
+        &replier,
+        &intro,
+        &[2_500, 2_500],
+        &[reward_ata(&co_author)],
+    );
+    assert_eq!(ledger.process(&ix), Err(ProgramError::NotEnoughAccountKeys));
+}
@@ -0,0 +1,57 @@
+//! `schema`: the exported Borsh schemas track the program's actual layouts.
+
+use borsh::{
+    schema::{BorshSchemaContainer, Definition, Fields},
+    BorshDeserialize, BorshSchema,
+};
+use student_intro_sol::{
+    instruction::StudentInstruction,
+    schema,
+    state::{ProgramConfig, ReplyScore},
+};
+
+fn fields<T: BorshSchema>() -> Vec<String> {
+    let container = T::schema_container();
+    match &container.definitions[&container.declaration] {
+        Definition::Struct {
+            fields: Fields::NamedFields(fields),
+        } => fields.iter().map(|(name, _)| name.clone()).collect(),
+        definition => panic!("{} is not a struct: {definition:?}", container.declaration),
+    }
+}
+
+#[test]
+fn serialized_schemas_decode_back_to_the_containers() {
+    let decoded = Vec::<BorshSchemaContainer>::try_from_slice(&schema::serialized()).unwrap();
+
+    assert_eq!(decoded, schema::containers());
+    assert!(decoded
+        .iter()
+        .any(|container| container.declaration == "ProgramConfig"));
+}
+
+#[test]
+fn instruction_variants_are_in_wire_order() {
+    let container = StudentInstruction::schema_container();
+    let Definition::Enum { variants } = &container.definitions["StudentInstruction"] else {
+        panic!("StudentInstruction is not an enum");
+    };
+    let name = |index: u8| variants[index as usize].0.as_str();
+
+    assert_eq!(name(0), "StudentIntro");
+    assert_eq!(name(2), "ReplyIntro");
+    assert_eq!(
+        name(StudentInstruction::SET_INSTRUCTION_ENABLED),
+        "SetInstructionEnabled"
+    );
+    assert_eq!(variants[69].0, "ValidateIntroPayload");
+}
+
+#[test]
+fn account_schemas_include_trailing_fields() {
+    assert_eq!(fields::<ReplyScore>().last().unwrap(), "milestones");
+    assert_eq!(
+        fields::<ProgramConfig>().last().unwrap(),
+        "course_reward_cap"
+    );
+}
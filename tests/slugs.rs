@@ -0,0 +1,276 @@
+//! `SetSlug`: unique intro handles, redirects left behind by renames, and slugs freed again
+//! once their redirect lapses or their intro is closed.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{IntroSlug, StudentIntroState, CLOSED_ACCOUNT_DISCRIMINATOR},
+};
+
+fn set_slug_ix(writer: &Pubkey, slug: &str, previous: Option<&str>) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*writer, true),
+        AccountMeta::new(pda::intro(&PROGRAM_ID, writer), false),
+        AccountMeta::new(pda::slug(&PROGRAM_ID, slug), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(previous) = previous {
+        accounts.push(AccountMeta::new(pda::slug(&PROGRAM_ID, previous), false));
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: payload(43, &slug),
+    }
+}
+
+/// `SetSlug` for a slug whose entry names `holder`'s intro.
+fn claim_slug_ix(writer: &Pubkey, slug: &str, holder: &Pubkey) -> Instruction {
+    let mut ix = set_slug_ix(writer, slug, None);
+    ix.accounts.push(AccountMeta::new_readonly(
+        pda::intro(&PROGRAM_ID, holder),
+        false,
+    ));
+    ix
+}
+
+fn slug_entry(ledger: &Ledger, slug: &str) -> IntroSlug {
+    try_from_slice_unchecked(&ledger.data(&pda::slug(&PROGRAM_ID, slug))).unwrap()
+}
+
+fn intro(ledger: &Ledger, writer: &Pubkey) -> StudentIntroState {
+    try_from_slice_unchecked(&ledger.data(&pda::intro(&PROGRAM_ID, writer))).unwrap()
+}
+
+/// A ledger with one posted intro per returned writer.
+fn setup(writers: usize) -> (Ledger, Vec<Pubkey>) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writers = (0..writers)
+        .map(|_| {
+            let writer = ledger.student();
+            let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+            ledger.process(&ix).unwrap();
+            writer
+        })
+        .collect();
+    (ledger, writers)
+}
+
+#[test]
+fn slug_points_at_the_intro() {
+    let (mut ledger, writers) = setup(1);
+    let writer = writers[0];
+
+    ledger
+        .process(&set_slug_ix(&writer, "ada-2024", None))
+        .unwrap();
+
+    let entry = slug_entry(&ledger, "ada-2024");
+    assert_eq!(entry.intro, pda::intro(&PROGRAM_ID, &writer));
+    assert_eq!(entry.redirect_to, None);
+    assert_eq!(intro(&ledger, &writer).slug, "ada-2024");
+}
+
+#[test]
+fn renaming_leaves_a_redirect() {
+    let (mut ledger, writers) = setup(2);
+    let (writer, other) = (writers[0], writers[1]);
+    ledger.process(&set_slug_ix(&writer, "ada", None)).unwrap();
+
+    ledger
+        .process(&set_slug_ix(&writer, "ada-lovelace", Some("ada")))
+        .unwrap();
+
+    let old = slug_entry(&ledger, "ada");
+    assert_eq!(
+        old.redirect_to,
+        Some(pda::slug(&PROGRAM_ID, "ada-lovelace"))
+    );
+    assert_eq!(old.redirect_until_epoch, EPOCH + IntroSlug::REDIRECT_EPOCHS);
+    assert_eq!(intro(&ledger, &writer).slug, "ada-lovelace");
+
+    // The redirect still holds the slug.
+    assert_eq!(
+        ledger.process(&claim_slug_ix(&other, "ada", &writer)),
+        Err(IntroError::SlugTaken.into())
+    );
+}
+
+#[test]
+fn lapsed_redirect_can_be_claimed() {
+    let (mut ledger, writers) = setup(2);
+    let (writer, other) = (writers[0], writers[1]);
+    ledger.process(&set_slug_ix(&writer, "ada", None)).unwrap();
+    ledger
+        .process(&set_slug_ix(&writer, "ada-lovelace", Some("ada")))
+        .unwrap();
+
+    let key = pda::slug(&PROGRAM_ID, "ada");
+    let mut entry = slug_entry(&ledger, "ada");
+    entry.redirect_until_epoch = EPOCH - 1;
+    let lamports = ledger.info(&key).lamports();
+    ledger.set(key, PROGRAM_ID, lamports, entry.try_to_vec().unwrap());
+
+    ledger
+        .process(&claim_slug_ix(&other, "ada", &writer))
+        .unwrap();
+    let entry = slug_entry(&ledger, "ada");
+    assert_eq!(entry.intro, pda::intro(&PROGRAM_ID, &other));
+    assert_eq!(entry.redirect_to, None);
+}
+
+#[test]
+fn writer_can_take_back_an_old_slug() {
+    let (mut ledger, writers) = setup(1);
+    let writer = writers[0];
+    ledger.process(&set_slug_ix(&writer, "ada", None)).unwrap();
+    ledger
+        .process(&set_slug_ix(&writer, "ada-lovelace", Some("ada")))
+        .unwrap();
+
+    ledger
+        .process(&set_slug_ix(&writer, "ada", Some("ada-lovelace")))
+        .unwrap();
+
+    assert_eq!(slug_entry(&ledger, "ada").redirect_to, None);
+    assert_eq!(
+        slug_entry(&ledger, "ada-lovelace").redirect_to,
+        Some(pda::slug(&PROGRAM_ID, "ada"))
+    );
+    assert_eq!(intro(&ledger, &writer).slug, "ada");
+}
+
+#[test]
+fn current_slugs_are_unique() {
+    let (mut ledger, writers) = setup(2);
+    ledger
+        .process(&set_slug_ix(&writers[0], "ada", None))
+        .unwrap();
+
+    assert_eq!(
+        ledger.process(&set_slug_ix(&writers[1], "ada", None)),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        ledger.process(&claim_slug_ix(&writers[1], "ada", &writers[0])),
+        Err(IntroError::SlugTaken.into())
+    );
+    assert_eq!(intro(&ledger, &writers[1]).slug, "");
+}
+
+#[test]
+fn redirects_of_closed_intros_are_free() {
+    let (mut ledger, writers) = setup(2);
+    let (writer, other) = (writers[0], writers[1]);
+    ledger.process(&set_slug_ix(&writer, "ada", None)).unwrap();
+    ledger
+        .process(&set_slug_ix(&writer, "ada-lovelace", Some("ada")))
+        .unwrap();
+
+    let intro_key = pda::intro(&PROGRAM_ID, &writer);
+    let tombstone = CLOSED_ACCOUNT_DISCRIMINATOR.try_to_vec().unwrap();
+    ledger.set(intro_key, PROGRAM_ID, 0, tombstone);
+
+    ledger
+        .process(&claim_slug_ix(&other, "ada", &writer))
+        .unwrap();
+    assert_eq!(
+        slug_entry(&ledger, "ada").intro,
+        pda::intro(&PROGRAM_ID, &other)
+    );
+}
+
+#[test]
+fn renames_only_redirect_the_intros_own_slug() {
+    let (mut ledger, writers) = setup(2);
+    let (writer, other) = (writers[0], writers[1]);
+    ledger.process(&set_slug_ix(&writer, "ada", None)).unwrap();
+
+    let key = pda::slug(&PROGRAM_ID, "ada");
+    let mut entry = slug_entry(&ledger, "ada");
+    entry.intro = pda::intro(&PROGRAM_ID, &other);
+    let lamports = ledger.info(&key).lamports();
+    ledger.set(key, PROGRAM_ID, lamports, entry.try_to_vec().unwrap());
+
+    assert_eq!(
+        ledger.process(&set_slug_ix(&writer, "ada-lovelace", Some("ada"))),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+    assert_eq!(slug_entry(&ledger, "ada").redirect_to, None);
+}
+
+#[test]
+fn malformed_slugs_are_rejected() {
+    let (mut ledger, writers) = setup(1);
+
+    for slug in ["ad", "Ada", "ada lovelace", "-ada", "ada-", "ada_l"] {
+        assert_eq!(
+            ledger.process(&set_slug_ix(&writers[0], slug, None)),
+            Err(IntroError::InvalidSlug.into()),
+            "{:?}",
+            slug
+        );
+    }
+}
+
+#[test]
+fn only_the_writer_sets_a_slug() {
+    let (mut ledger, writers) = setup(1);
+    let intruder = ledger.wallet();
+
+    let ix = substitute(&set_slug_ix(&writers[0], "ada", None), 0, intruder);
+    assert_eq!(ledger.process(&ix), Err(IntroError::Unauthorized.into()));
+}
+
+#[test]
+fn closing_the_intro_frees_its_slug() {
+    let (mut ledger, writers) = setup(1);
+    let writer = writers[0];
+    ledger.process(&set_slug_ix(&writer, "ada", None)).unwrap();
+
+    let intro_key = pda::intro(&PROGRAM_ID, &writer);
+    let mut state = intro(&ledger, &writer);
+    state.expires_at = Some(UNIX_TIMESTAMP);
+    let mut data = ledger.data(&intro_key);
+    state.serialize(&mut &mut data[..]).unwrap();
+    let lamports = ledger.info(&intro_key).lamports();
+    ledger.set(intro_key, PROGRAM_ID, lamports, data);
+
+    let close = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(writer, false),
+            AccountMeta::new(intro_key, false),
+            AccountMeta::new(pda::reply_counter(&PROGRAM_ID, &intro_key), false),
+        ],
+        data: vec![15],
+    };
+
+    // The slug entry must come along.
+    assert_eq!(
+        ledger.process(&close),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+
+    let mut close = close;
+    close
+        .accounts
+        .push(AccountMeta::new(pda::slug(&PROGRAM_ID, "ada"), false));
+    ledger.process(&close).unwrap();
+    assert!(!ledger.exists(&pda::slug(&PROGRAM_ID, "ada")));
+}
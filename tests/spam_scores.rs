@@ -0,0 +1,243 @@
+//! `SetSpamScore` and `ReleaseRewardHold`: the configured classifier scores intros and replies,
+//! and high scores hold the author's rewards until an admin reviews them.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{ProgramConfig, ReplyScore, Reputation, RewardHold, StudentReplyState},
+};
+
+const THRESHOLD: u8 = 100;
+
+fn score_ix(classifier: &Pubkey, target: &Pubkey, author: &Pubkey, score: u8) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*classifier, true),
+            AccountMeta::new(*target, false),
+            AccountMeta::new(pda::reward_hold(&PROGRAM_ID, author), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(72, &(*target, score)),
+    }
+}
+
+fn release_ix(admin: &Pubkey, author: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(pda::reward_hold(&PROGRAM_ID, author), false),
+        ],
+        data: vec![73],
+    }
+}
+
+/// Appends the author's reward hold, behind the wallet link placeholder, as a configured
+/// classifier requires.
+fn with_hold(mut ix: Instruction, author: &Pubkey) -> Instruction {
+    ix.accounts
+        .push(AccountMeta::new_readonly(PROGRAM_ID, false));
+    ix.accounts.push(AccountMeta::new_readonly(
+        pda::reward_hold(&PROGRAM_ID, author),
+        false,
+    ));
+    ix
+}
+
+fn post_intro(ledger: &mut Ledger) -> (Pubkey, Pubkey) {
+    let writer = ledger.student();
+    let ix = with_hold(student_intro_ix(ledger, &writer, "Ada", "Hello"), &writer);
+    ledger.process(&ix).unwrap();
+    (writer, pda::intro(&PROGRAM_ID, &writer))
+}
+
+/// Replies to `intro` as `replier`, returning the reward it earned.
+fn reply(ledger: &mut Ledger, replier: &Pubkey, intro: &Pubkey) -> u64 {
+    let ata = get_associated_token_address(replier, &pda::token_mint(&PROGRAM_ID));
+    let before = ledger.token_balance(&ata);
+    let ix = with_hold(
+        reply_intro_ix(ledger, replier, intro, "Grace", "Welcome"),
+        replier,
+    );
+    ledger.process(&ix).unwrap();
+    ledger.token_balance(&ata) - before
+}
+
+fn hold(ledger: &Ledger, author: &Pubkey) -> RewardHold {
+    try_from_slice_unchecked(&ledger.data(&pda::reward_hold(&PROGRAM_ID, author))).unwrap()
+}
+
+/// A ledger whose config names a spam classifier, returning the admin and classifier.
+fn setup() -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+
+    let admin = ledger.wallet();
+    let classifier = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        spam_classifier: Some(classifier),
+        spam_threshold: THRESHOLD,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    (ledger, admin, classifier)
+}
+
+#[test]
+fn high_reply_scores_hold_rewards_until_review() {
+    let (mut ledger, admin, classifier) = setup();
+    let (_first_writer, first_intro) = post_intro(&mut ledger);
+    let (_second_writer, second_intro) = post_intro(&mut ledger);
+    let (_third_writer, third_intro) = post_intro(&mut ledger);
+    let replier = ledger.student();
+
+    assert!(reply(&mut ledger, &replier, &first_intro) > 0);
+    let flagged = pda::reply(&PROGRAM_ID, &first_intro, 0);
+    ledger
+        .process(&score_ix(&classifier, &flagged, &replier, 200))
+        .unwrap();
+
+    let reply_data: StudentReplyState = try_from_slice_unchecked(&ledger.data(&flagged)).unwrap();
+    assert_eq!(reply_data.spam_score, 200);
+    let held = hold(&ledger, &replier);
+    assert!(held.held);
+    assert_eq!(
+        (held.author, held.target, held.score),
+        (replier, flagged, 200)
+    );
+
+    assert_eq!(reply(&mut ledger, &replier, &second_intro), 0);
+
+    ledger.process(&release_ix(&admin, &replier)).unwrap();
+    assert!(!hold(&ledger, &replier).held);
+    assert!(reply(&mut ledger, &replier, &third_intro) > 0);
+}
+
+#[test]
+fn scores_at_or_below_the_threshold_only_record() {
+    let (mut ledger, _admin, classifier) = setup();
+    let (writer, intro) = post_intro(&mut ledger);
+
+    ledger
+        .process(&score_ix(&classifier, &intro, &writer, THRESHOLD))
+        .unwrap();
+
+    assert_eq!(ledger.intro(&intro).spam_score, THRESHOLD);
+    assert!(!ledger.exists(&pda::reward_hold(&PROGRAM_ID, &writer)));
+}
+
+#[test]
+fn high_intro_scores_hold_the_writer() {
+    let (mut ledger, _admin, classifier) = setup();
+    let (writer, intro) = post_intro(&mut ledger);
+    let (_other, other_intro) = post_intro(&mut ledger);
+
+    ledger
+        .process(&score_ix(&classifier, &intro, &writer, 255))
+        .unwrap();
+
+    assert_eq!(ledger.intro(&intro).spam_score, 255);
+    assert!(hold(&ledger, &writer).held);
+    assert_eq!(reply(&mut ledger, &writer, &other_intro), 0);
+}
+
+#[test]
+fn only_the_classifier_scores() {
+    let (mut ledger, admin, _classifier) = setup();
+    let (writer, intro) = post_intro(&mut ledger);
+
+    assert_eq!(
+        ledger.process(&score_ix(&admin, &intro, &writer, 200)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn only_the_admin_releases_holds() {
+    let (mut ledger, _admin, classifier) = setup();
+    let (writer, intro) = post_intro(&mut ledger);
+    ledger
+        .process(&score_ix(&classifier, &intro, &writer, 200))
+        .unwrap();
+
+    assert_eq!(
+        ledger.process(&release_ix(&classifier, &writer)),
+        Err(IntroError::Unauthorized.into())
+    );
+    assert!(hold(&ledger, &writer).held);
+}
+
+#[test]
+fn replies_from_before_spam_scores_are_rejected() {
+    let (mut ledger, _admin, classifier) = setup();
+    let (_writer, intro) = post_intro(&mut ledger);
+    let replier = ledger.student();
+    reply(&mut ledger, &replier, &intro);
+
+    let flagged = pda::reply(&PROGRAM_ID, &intro, 0);
+    let mut data = ledger.data(&flagged);
+    data.pop();
+    let lamports = ledger.info(&flagged).lamports();
+    ledger.set(flagged, PROGRAM_ID, lamports, data);
+
+    assert_eq!(
+        ledger.process(&score_ix(&classifier, &flagged, &replier, 200)),
+        Err(IntroError::SpamScoreUnsupported.into())
+    );
+}
+
+#[test]
+fn held_repliers_earn_points_but_accrue_nothing_from_votes() {
+    let (mut ledger, _admin, classifier) = setup();
+    let (_writer, intro) = post_intro(&mut ledger);
+    let replier = ledger.student();
+    reply(&mut ledger, &replier, &intro);
+    let flagged = pda::reply(&PROGRAM_ID, &intro, 0);
+    ledger
+        .process(&score_ix(&classifier, &flagged, &replier, 200))
+        .unwrap();
+
+    for _ in 0..ReplyScore::MILESTONES[0] {
+        let voter = ledger.wallet();
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(voter, true),
+                AccountMeta::new_readonly(intro, false),
+                AccountMeta::new_readonly(flagged, false),
+                AccountMeta::new(pda::reply_vote(&PROGRAM_ID, &flagged, &voter), false),
+                AccountMeta::new(pda::reply_score(&PROGRAM_ID, &flagged), false),
+                AccountMeta::new(pda::top_replies(&PROGRAM_ID, &intro), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, &voter), false),
+                AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, &replier), false),
+                AccountMeta::new(pda::reputation(&PROGRAM_ID, &replier), false),
+                AccountMeta::new_readonly(pda::reward_hold(&PROGRAM_ID, &replier), false),
+            ],
+            data: payload(11, &true),
+        };
+        ledger.process(&ix).unwrap();
+    }
+
+    let reputation: Reputation =
+        try_from_slice_unchecked(&ledger.data(&pda::reputation(&PROGRAM_ID, &replier))).unwrap();
+    assert_eq!(reputation.points, Reputation::POINTS_PER_MILESTONE);
+    assert_eq!(reputation.claimable, 0);
+}
@@ -0,0 +1,148 @@
+//! Student ID hashes: courses that require them let each hash scope intros for one wallet only.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{CertificateRequirements, StudentIdEntry, StudentIntroState},
+};
+
+const COURSE: &str = "Solana 101";
+const HASH: [u8; 32] = [7; 32];
+
+/// A ledger with a course created by the returned teacher.
+fn setup(requires_student_id: bool) -> (Ledger, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let teacher = ledger.wallet();
+    let certificate = CertificateRequirements::default();
+    let ix = create_course_ix(&teacher, COURSE, requires_student_id, &certificate);
+    ledger.process(&ix).unwrap();
+    let course = pda::course(&PROGRAM_ID, &teacher, COURSE);
+
+    (ledger, teacher, course)
+}
+
+/// An enrolled student with a posted intro.
+fn student(ledger: &mut Ledger, teacher: &Pubkey, course: &Pubkey) -> Pubkey {
+    let student = ledger.student();
+    let ix = student_intro_ix(ledger, &student, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    ledger.enroll(teacher, course, &student);
+
+    student
+}
+
+/// `SetVisibility` scoping `student`'s intro to `course`.
+fn scope_ix(student: &Pubkey, course: &Pubkey, hash: Option<[u8; 32]>) -> Instruction {
+    let enrollment = pda::enrollment(&PROGRAM_ID, course, student);
+    let mut accounts = vec![
+        AccountMeta::new(*student, true),
+        AccountMeta::new(pda::intro(&PROGRAM_ID, student), false),
+        AccountMeta::new_readonly(enrollment, false),
+        AccountMeta::new(pda::course_stats(&PROGRAM_ID, course), false),
+        AccountMeta::new(pda::student_activity(&PROGRAM_ID, &enrollment), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*course, false),
+    ];
+    if let Some(hash) = hash {
+        accounts.push(AccountMeta::new(
+            pda::student_id(&PROGRAM_ID, course, &hash),
+            false,
+        ));
+    }
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: payload(14, &(StudentIntroState::VISIBILITY_COURSE_ONLY, hash)),
+    }
+}
+
+fn intro_course(ledger: &Ledger, student: &Pubkey) -> Option<Pubkey> {
+    try_from_slice_unchecked::<StudentIntroState>(&ledger.data(&pda::intro(&PROGRAM_ID, student)))
+        .unwrap()
+        .course
+}
+
+#[test]
+fn student_id_is_claimed_on_scoping() {
+    let (mut ledger, teacher, course) = setup(true);
+    let student = student(&mut ledger, &teacher, &course);
+
+    ledger
+        .process(&scope_ix(&student, &course, Some(HASH)))
+        .unwrap();
+
+    assert_eq!(intro_course(&ledger, &student), Some(course));
+    let entry: StudentIdEntry =
+        try_from_slice_unchecked(&ledger.data(&pda::student_id(&PROGRAM_ID, &course, &HASH)))
+            .unwrap();
+    assert_eq!(entry.course, course);
+    assert_eq!(entry.student, student);
+
+    // Scoping again with the same hash is fine for its owner.
+    ledger
+        .process(&scope_ix(&student, &course, Some(HASH)))
+        .unwrap();
+}
+
+#[test]
+fn student_id_is_unique_per_course() {
+    let (mut ledger, teacher, course) = setup(true);
+    let first = student(&mut ledger, &teacher, &course);
+    let second = student(&mut ledger, &teacher, &course);
+    ledger
+        .process(&scope_ix(&first, &course, Some(HASH)))
+        .unwrap();
+
+    assert_eq!(
+        ledger.process(&scope_ix(&second, &course, Some(HASH))),
+        Err(IntroError::StudentIdTaken.into())
+    );
+    assert_eq!(intro_course(&ledger, &second), None);
+}
+
+#[test]
+fn student_id_is_required_when_the_course_asks() {
+    let (mut ledger, teacher, course) = setup(true);
+    let student = student(&mut ledger, &teacher, &course);
+
+    assert_eq!(
+        ledger.process(&scope_ix(&student, &course, None)),
+        Err(IntroError::StudentIdRequired.into())
+    );
+}
+
+#[test]
+fn other_courses_need_no_student_id() {
+    let (mut ledger, teacher, course) = setup(false);
+    let student = student(&mut ledger, &teacher, &course);
+
+    ledger.process(&scope_ix(&student, &course, None)).unwrap();
+    assert_eq!(intro_course(&ledger, &student), Some(course));
+}
+
+#[test]
+fn course_account_must_match_the_enrollment() {
+    let (mut ledger, teacher, course) = setup(true);
+    let student = student(&mut ledger, &teacher, &course);
+    let stranger = ledger.wallet();
+
+    let ix = substitute(&scope_ix(&student, &course, Some(HASH)), 6, stranger);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
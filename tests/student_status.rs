@@ -0,0 +1,183 @@
+//! `SetStudentStatus`: the course teacher marks intros graduated or withdrawn, which stops
+//! replies to them from earning while leaving them readable.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{error::IntroError, pda, state::StudentIntroState};
+
+const COURSE: &str = "Solana 101";
+
+/// A ledger with a course and an intro scoped to it, returning the teacher, course and writer.
+fn setup() -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let teacher = ledger.wallet();
+    let course = ledger.create_course(&teacher, COURSE);
+
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let enrollment = pda::enrollment(&PROGRAM_ID, &course, &writer);
+    ledger.enroll(&teacher, &course, &writer);
+    ledger
+        .process(&Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(writer, true),
+                AccountMeta::new(pda::intro(&PROGRAM_ID, &writer), false),
+                AccountMeta::new_readonly(enrollment, false),
+                AccountMeta::new(pda::course_stats(&PROGRAM_ID, &course), false),
+                AccountMeta::new(pda::student_activity(&PROGRAM_ID, &enrollment), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(course, false),
+            ],
+            data: payload(14, &StudentIntroState::VISIBILITY_COURSE_ONLY),
+        })
+        .unwrap();
+
+    (ledger, teacher, course, writer)
+}
+
+fn set_status_ix(teacher: &Pubkey, course: &Pubkey, writer: &Pubkey, status: u8) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*teacher, true),
+            AccountMeta::new_readonly(*course, false),
+            AccountMeta::new(pda::intro(&PROGRAM_ID, writer), false),
+        ],
+        data: payload(50, &status),
+    }
+}
+
+fn status(ledger: &Ledger, writer: &Pubkey) -> u8 {
+    ledger.intro(&pda::intro(&PROGRAM_ID, writer)).status
+}
+
+/// Opens the intro to everyone again so a plain `ReplyIntro` reaches it.
+fn make_public(ledger: &mut Ledger, writer: &Pubkey) {
+    ledger
+        .process(&Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*writer, true),
+                AccountMeta::new(pda::intro(&PROGRAM_ID, writer), false),
+            ],
+            data: payload(14, &StudentIntroState::VISIBILITY_PUBLIC),
+        })
+        .unwrap();
+}
+
+#[test]
+fn intros_start_active() {
+    let (ledger, _teacher, _course, writer) = setup();
+    assert_eq!(status(&ledger, &writer), StudentIntroState::STATUS_ACTIVE);
+}
+
+#[test]
+fn graduated_intros_stay_readable_but_stop_earning() {
+    let (mut ledger, teacher, course, writer) = setup();
+
+    ledger
+        .process(&set_status_ix(
+            &teacher,
+            &course,
+            &writer,
+            StudentIntroState::STATUS_GRADUATED,
+        ))
+        .unwrap();
+    assert_eq!(
+        status(&ledger, &writer),
+        StudentIntroState::STATUS_GRADUATED
+    );
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let state: StudentIntroState = try_from_slice_unchecked(&ledger.data(&intro)).unwrap();
+    assert_eq!(state.message, "Hello");
+
+    make_public(&mut ledger, &writer);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Congratulations");
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(ledger.reply_count(&intro), 1);
+    let ata = get_associated_token_address(&replier, &pda::token_mint(&PROGRAM_ID));
+    assert_eq!(ledger.token_balance(&ata), 0);
+}
+
+#[test]
+fn reactivated_intros_earn_again() {
+    let (mut ledger, teacher, course, writer) = setup();
+    for status in [
+        StudentIntroState::STATUS_WITHDRAWN,
+        StudentIntroState::STATUS_ACTIVE,
+    ] {
+        ledger
+            .process(&set_status_ix(&teacher, &course, &writer, status))
+            .unwrap();
+    }
+
+    make_public(&mut ledger, &writer);
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome back");
+    ledger.process(&ix).unwrap();
+
+    let ata = get_associated_token_address(&replier, &pda::token_mint(&PROGRAM_ID));
+    assert!(ledger.token_balance(&ata) > 0);
+}
+
+#[test]
+fn only_the_course_teacher_sets_status() {
+    let (mut ledger, _teacher, course, writer) = setup();
+    let intruder = ledger.wallet();
+
+    assert_eq!(
+        ledger.process(&set_status_ix(
+            &intruder,
+            &course,
+            &writer,
+            StudentIntroState::STATUS_GRADUATED
+        )),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn status_needs_an_intro_in_the_course() {
+    let (mut ledger, teacher, course, _writer) = setup();
+    let outsider = ledger.student();
+    let ix = student_intro_ix(&ledger, &outsider, "Alan", "Hi");
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(
+        ledger.process(&set_status_ix(
+            &teacher,
+            &course,
+            &outsider,
+            StudentIntroState::STATUS_GRADUATED
+        )),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
+
+#[test]
+fn unknown_statuses_are_rejected() {
+    let (mut ledger, teacher, course, writer) = setup();
+
+    assert_eq!(
+        ledger.process(&set_status_ix(&teacher, &course, &writer, 3)),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
@@ -0,0 +1,156 @@
+//! `CreateTeam` / `JoinTeam` / `LeaveTeam`: project teams of students with intros, each student
+//! on at most one.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{TeamMembership, TeamState},
+};
+
+const TEAM: &str = "Validators";
+
+/// A wallet with a posted intro.
+fn with_intro(ledger: &mut Ledger) -> Pubkey {
+    let student = ledger.student();
+    let ix = student_intro_ix(ledger, &student, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    student
+}
+
+fn create_ix(creator: &Pubkey, name: &str) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*creator, true),
+            AccountMeta::new_readonly(pda::intro(&PROGRAM_ID, creator), false),
+            AccountMeta::new(pda::team(&PROGRAM_ID, name), false),
+            AccountMeta::new(pda::team_membership(&PROGRAM_ID, creator), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(61, &name),
+    }
+}
+
+fn join_ix(student: &Pubkey, name: &str) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*student, true),
+            AccountMeta::new_readonly(pda::intro(&PROGRAM_ID, student), false),
+            AccountMeta::new(pda::team(&PROGRAM_ID, name), false),
+            AccountMeta::new(pda::team_membership(&PROGRAM_ID, student), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: vec![62],
+    }
+}
+
+fn leave_ix(student: &Pubkey, name: &str) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*student, true),
+            AccountMeta::new(pda::team(&PROGRAM_ID, name), false),
+            AccountMeta::new(pda::team_membership(&PROGRAM_ID, student), false),
+        ],
+        data: vec![63],
+    }
+}
+
+fn members(ledger: &Ledger, name: &str) -> Vec<Pubkey> {
+    try_from_slice_unchecked::<TeamState>(&ledger.data(&pda::team(&PROGRAM_ID, name)))
+        .unwrap()
+        .members
+}
+
+/// A ledger with the reward mint, a creator and a team they created.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let creator = with_intro(&mut ledger);
+    ledger.process(&create_ix(&creator, TEAM)).unwrap();
+    (ledger, creator)
+}
+
+#[test]
+fn creators_and_joiners_are_members() {
+    let (mut ledger, creator) = setup();
+    let student = with_intro(&mut ledger);
+
+    ledger.process(&join_ix(&student, TEAM)).unwrap();
+
+    assert_eq!(members(&ledger, TEAM), [creator, student]);
+    let membership: TeamMembership =
+        try_from_slice_unchecked(&ledger.data(&pda::team_membership(&PROGRAM_ID, &student)))
+            .unwrap();
+    assert_eq!(membership.team, pda::team(&PROGRAM_ID, TEAM));
+}
+
+#[test]
+fn members_need_an_intro() {
+    let (mut ledger, _creator) = setup();
+    let stranger = ledger.wallet();
+
+    assert!(ledger.process(&join_ix(&stranger, TEAM)).is_err());
+    assert_eq!(members(&ledger, TEAM).len(), 1);
+}
+
+#[test]
+fn students_are_on_one_team() {
+    let (mut ledger, creator) = setup();
+
+    assert_eq!(
+        ledger.process(&create_ix(&creator, "Leaders")),
+        Err(IntroError::AlreadyOnTeam.into())
+    );
+}
+
+#[test]
+fn teams_are_capped() {
+    let (mut ledger, _creator) = setup();
+    for _ in 1..TeamState::MAX_MEMBERS {
+        let student = with_intro(&mut ledger);
+        ledger.process(&join_ix(&student, TEAM)).unwrap();
+    }
+    let late = with_intro(&mut ledger);
+
+    assert_eq!(
+        ledger.process(&join_ix(&late, TEAM)),
+        Err(IntroError::TeamFull.into())
+    );
+}
+
+#[test]
+fn leaving_frees_the_student_for_another_team() {
+    let (mut ledger, creator) = setup();
+    let student = with_intro(&mut ledger);
+    ledger.process(&join_ix(&student, TEAM)).unwrap();
+
+    ledger.process(&leave_ix(&student, TEAM)).unwrap();
+    assert_eq!(members(&ledger, TEAM), [creator]);
+
+    // What the runtime does with the drained membership once the transaction ends.
+    let membership = pda::team_membership(&PROGRAM_ID, &student);
+    ledger.set(membership, system_program::id(), 0, vec![]);
+    ledger.process(&create_ix(&student, "Leaders")).unwrap();
+    assert_eq!(members(&ledger, "Leaders"), [student]);
+}
+
+#[test]
+fn team_names_are_unique() {
+    let (mut ledger, _creator) = setup();
+    let student = with_intro(&mut ledger);
+
+    assert!(ledger.process(&create_ix(&student, TEAM)).is_err());
+}
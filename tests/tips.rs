@@ -0,0 +1,106 @@
+//! `TipReply`: reward tokens sent to a reply's author, with the config's protocol fee routed to
+//! the treasury.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{error::IntroError, pda, state::ProgramConfig};
+
+const TIP: u64 = 1_000_000;
+
+fn ata(owner: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, &pda::token_mint(&PROGRAM_ID))
+}
+
+fn tip_ix(tipper: &Pubkey, reply: &Pubkey, replier: &Pubkey, amount: u64) -> Instruction {
+    let treasury = pda::treasury(&PROGRAM_ID);
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*tipper, true),
+            AccountMeta::new(ata(tipper), false),
+            AccountMeta::new_readonly(*reply, false),
+            AccountMeta::new(ata(replier), false),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(ata(&treasury), false),
+            AccountMeta::new_readonly(pda::token_mint(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: payload(86, &amount),
+    }
+}
+
+/// A ledger charging `protocol_fee_bps` on tips, with an intro and one reply to it, returning
+/// the writer, who holds the intro reward, the reply and its replier.
+fn setup(protocol_fee_bps: u16) -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let (mut ledger, writer, intro) = ledger_with_intro();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        protocol_fee_bps,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+    ledger.token_account(&pda::treasury(&PROGRAM_ID), &pda::token_mint(&PROGRAM_ID));
+
+    let replier = ledger.student();
+    let ix = reply_intro_ix(&ledger, &replier, &intro, "Grace", "Welcome");
+    ledger.process(&ix).unwrap();
+
+    (ledger, writer, pda::reply(&PROGRAM_ID, &intro, 0), replier)
+}
+
+#[test]
+fn tips_pay_the_replier_less_the_treasury_fee() {
+    let (mut ledger, writer, reply, replier) = setup(250);
+    let treasury = ata(&pda::treasury(&PROGRAM_ID));
+    let before = [writer, replier].map(|owner| ledger.token_balance(&ata(&owner)));
+
+    ledger
+        .process(&tip_ix(&writer, &reply, &replier, TIP))
+        .unwrap();
+
+    let fee = TIP / 40;
+    assert_eq!(ledger.token_balance(&ata(&writer)), before[0] - TIP);
+    assert_eq!(ledger.token_balance(&ata(&replier)), before[1] + TIP - fee);
+    assert_eq!(ledger.token_balance(&treasury), fee);
+}
+
+#[test]
+fn tips_without_a_fee_go_entirely_to_the_replier() {
+    let (mut ledger, writer, reply, replier) = setup(0);
+    let before = ledger.token_balance(&ata(&replier));
+
+    ledger
+        .process(&tip_ix(&writer, &reply, &replier, TIP))
+        .unwrap();
+
+    assert_eq!(ledger.token_balance(&ata(&replier)), before + TIP);
+    assert_eq!(ledger.token_balance(&ata(&pda::treasury(&PROGRAM_ID))), 0);
+}
+
+#[test]
+fn tips_cannot_be_redirected() {
+    let (mut ledger, writer, reply, replier) = setup(250);
+    let thief = ledger.student();
+
+    let ix = tip_ix(&writer, &reply, &thief, TIP);
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+    let ix = substitute(&tip_ix(&writer, &reply, &replier, TIP), 5, ata(&thief));
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
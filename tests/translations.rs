@@ -0,0 +1,119 @@
+//! `AddTranslation`: per-language copies of an intro's message, written only by its writer.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use student_intro_sol::{error::IntroError, pda, state::IntroTranslation};
+
+fn add_translation_ix(writer: &Pubkey, lang: [u8; 2], message: &str) -> Instruction {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*writer, true),
+            AccountMeta::new_readonly(intro, false),
+            AccountMeta::new(pda::translation(&PROGRAM_ID, &intro, &lang), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(46, &(lang, message)),
+    }
+}
+
+fn translation(ledger: &Ledger, writer: &Pubkey, lang: [u8; 2]) -> IntroTranslation {
+    let intro = pda::intro(&PROGRAM_ID, writer);
+    try_from_slice_unchecked(&ledger.data(&pda::translation(&PROGRAM_ID, &intro, &lang))).unwrap()
+}
+
+/// A ledger with one posted intro, returning its writer.
+fn setup() -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+    (ledger, writer)
+}
+
+#[test]
+fn translations_are_kept_per_language() {
+    let (mut ledger, writer) = setup();
+
+    ledger
+        .process(&add_translation_ix(&writer, *b"tr", "Merhaba"))
+        .unwrap();
+    ledger
+        .process(&add_translation_ix(&writer, *b"de", "Hallo"))
+        .unwrap();
+
+    let turkish = translation(&ledger, &writer, *b"tr");
+    assert_eq!(turkish.intro, pda::intro(&PROGRAM_ID, &writer));
+    assert_eq!(turkish.lang, *b"tr");
+    assert_eq!(turkish.message, "Merhaba");
+    assert_eq!(translation(&ledger, &writer, *b"de").message, "Hallo");
+}
+
+#[test]
+fn translations_can_be_rewritten_and_removed() {
+    let (mut ledger, writer) = setup();
+    ledger
+        .process(&add_translation_ix(&writer, *b"tr", "Merhaba, ben Ada"))
+        .unwrap();
+
+    ledger
+        .process(&add_translation_ix(&writer, *b"tr", "Selam"))
+        .unwrap();
+    assert_eq!(translation(&ledger, &writer, *b"tr").message, "Selam");
+
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let key = pda::translation(&PROGRAM_ID, &intro, b"tr");
+    let balance = ledger.info(&writer).lamports();
+    let rent = ledger.info(&key).lamports();
+    ledger
+        .process(&add_translation_ix(&writer, *b"tr", ""))
+        .unwrap();
+    assert!(!ledger.exists(&key));
+    assert_eq!(ledger.info(&writer).lamports(), balance + rent);
+}
+
+#[test]
+fn only_the_writer_translates() {
+    let (mut ledger, writer) = setup();
+    let intruder = ledger.wallet();
+
+    let ix = substitute(&add_translation_ix(&writer, *b"tr", "Merhaba"), 0, intruder);
+    assert_eq!(ledger.process(&ix), Err(IntroError::Unauthorized.into()));
+}
+
+#[test]
+fn translations_need_a_known_language() {
+    let (mut ledger, writer) = setup();
+
+    for lang in [[0, 0], *b"xx"] {
+        assert_eq!(
+            ledger.process(&add_translation_ix(&writer, lang, "Merhaba")),
+            Err(IntroError::InvalidLanguage.into())
+        );
+    }
+}
+
+#[test]
+fn translation_address_is_checked() {
+    let (mut ledger, writer) = setup();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+
+    let ix = substitute(
+        &add_translation_ix(&writer, *b"tr", "Merhaba"),
+        2,
+        pda::translation(&PROGRAM_ID, &intro, b"de"),
+    );
+    assert_eq!(ledger.process(&ix), Err(IntroError::InvalidPDA.into()));
+}
@@ -0,0 +1,275 @@
+//! The treasury: its token account is created with the config, and tokens only leave it through
+//! a withdrawal that has waited out the config timelock.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account as TokenAccount;
+use student_intro_sol::{
+    error::IntroError,
+    instruction::{config_account_mut, ConfigParams},
+    pda,
+    state::ProgramConfig,
+};
+
+fn config_params(config_timelock_slots: u64) -> ConfigParams {
+    ConfigParams {
+        gatekeeper_network: None,
+        reveal_delay_slots: 0,
+        credential_collection: None,
+        credential_multiplier_bps: 10_000,
+        price_feed: None,
+        reference_price_cents: 0,
+        max_price_staleness_slots: 0,
+        max_price_confidence_bps: 0,
+        wormhole_program: None,
+        foreign_emitter_chain: 0,
+        foreign_emitter_address: [0; 32],
+        governance_program: None,
+        governance: Pubkey::default(),
+        config_timelock_slots,
+        admin_signers: vec![],
+        admin_threshold: 0,
+        vesting_threshold: 0,
+        vesting_duration_secs: 0,
+        badge_reply_threshold: 0,
+        reply_reward_tiers: vec![],
+        first_reply_bonus: 0,
+        first_reply_window_secs: 0,
+        welcome_bots: vec![],
+        view_indexer: None,
+        identity_verifier: None,
+        spam_classifier: None,
+        spam_threshold: 0,
+        orphan_rent_destination: None,
+        protocol_fee_bps: 0,
+        course_reward_cap: 0,
+    }
+}
+
+fn treasury_ata() -> Pubkey {
+    get_associated_token_address(&pda::treasury(&PROGRAM_ID), &pda::token_mint(&PROGRAM_ID))
+}
+
+fn initialize_config_ix(admin: &Pubkey, config_timelock_slots: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(pda::treasury(&PROGRAM_ID), false),
+            AccountMeta::new(treasury_ata(), false),
+            AccountMeta::new_readonly(pda::token_mint(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            config_account_mut(&PROGRAM_ID),
+        ],
+        data: payload(4, &config_params(config_timelock_slots)),
+    }
+}
+
+fn propose_withdrawal_ix(admin: &Pubkey, destination: &Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(pda::pending_withdrawal(&PROGRAM_ID), false),
+            AccountMeta::new_readonly(*destination, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: payload(41, &amount),
+    }
+}
+
+fn execute_withdrawal_ix(proposer: &Pubkey, destination: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new(pda::pending_withdrawal(&PROGRAM_ID), false),
+            AccountMeta::new(*proposer, false),
+            AccountMeta::new_readonly(pda::treasury(&PROGRAM_ID), false),
+            AccountMeta::new(treasury_ata(), false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: vec![42],
+    }
+}
+
+/// Overwrites the treasury token account's balance.
+fn fund_treasury(ledger: &mut Ledger, amount: u64) {
+    let key = treasury_ata();
+    let mut account = TokenAccount::unpack(&ledger.data(&key)).unwrap();
+    account.amount = amount;
+    let mut data = vec![0; TokenAccount::LEN];
+    account.pack_into_slice(&mut data);
+    let lamports = ledger.info(&key).lamports();
+    ledger.set(key, spl_token::id(), lamports, data);
+}
+
+/// A ledger with the mint, an initialized config with the given timelock, and its admin.
+fn setup(config_timelock_slots: u64) -> (Ledger, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    ledger
+        .process(&initialize_config_ix(&admin, config_timelock_slots))
+        .unwrap();
+    (ledger, admin)
+}
+
+#[test]
+fn config_init_creates_the_treasury_account() {
+    let (ledger, _admin) = setup(0);
+
+    let account = TokenAccount::unpack(&ledger.data(&treasury_ata())).unwrap();
+    assert_eq!(account.owner, pda::treasury(&PROGRAM_ID));
+    assert_eq!(account.mint, pda::token_mint(&PROGRAM_ID));
+}
+
+#[test]
+fn existing_treasury_account_does_not_block_config_init() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    ledger.token_account(&pda::treasury(&PROGRAM_ID), &pda::token_mint(&PROGRAM_ID));
+    let admin = ledger.wallet();
+
+    ledger.process(&initialize_config_ix(&admin, 0)).unwrap();
+    assert!(ledger.exists(&pda::config(&PROGRAM_ID)));
+}
+
+#[test]
+fn withdrawal_pays_out_once_and_refunds_rent() {
+    let (mut ledger, admin) = setup(0);
+    fund_treasury(&mut ledger, 100);
+    let recipient = ledger.wallet();
+    let destination = ledger.token_account(&recipient, &pda::token_mint(&PROGRAM_ID));
+
+    ledger
+        .process(&propose_withdrawal_ix(&admin, &destination, 60))
+        .unwrap();
+    let balance = ledger.info(&admin).lamports();
+    let rent = ledger
+        .info(&pda::pending_withdrawal(&PROGRAM_ID))
+        .lamports();
+
+    ledger
+        .process(&execute_withdrawal_ix(&admin, &destination))
+        .unwrap();
+    assert_eq!(ledger.token_balance(&destination), 60);
+    assert_eq!(ledger.token_balance(&treasury_ata()), 40);
+    assert_eq!(ledger.info(&admin).lamports(), balance + rent);
+
+    // The pending withdrawal is consumed; replaying it fails.
+    assert_eq!(
+        ledger.process(&execute_withdrawal_ix(&admin, &destination)),
+        Err(IntroError::UninitializedAccount.into())
+    );
+}
+
+#[test]
+fn withdrawal_waits_for_the_timelock() {
+    let (mut ledger, admin) = setup(10);
+    fund_treasury(&mut ledger, 100);
+    let recipient = ledger.wallet();
+    let destination = ledger.token_account(&recipient, &pda::token_mint(&PROGRAM_ID));
+
+    ledger
+        .process(&propose_withdrawal_ix(&admin, &destination, 60))
+        .unwrap();
+    assert_eq!(
+        ledger.process(&execute_withdrawal_ix(&admin, &destination)),
+        Err(IntroError::TimelockNotElapsed.into())
+    );
+    assert_eq!(ledger.token_balance(&treasury_ata()), 100);
+}
+
+#[test]
+fn withdrawal_goes_only_to_the_proposed_destination() {
+    let (mut ledger, admin) = setup(0);
+    fund_treasury(&mut ledger, 100);
+    let recipient = ledger.wallet();
+    let destination = ledger.token_account(&recipient, &pda::token_mint(&PROGRAM_ID));
+    let thief = ledger.wallet();
+    let other = ledger.token_account(&thief, &pda::token_mint(&PROGRAM_ID));
+
+    ledger
+        .process(&propose_withdrawal_ix(&admin, &destination, 60))
+        .unwrap();
+    assert_eq!(
+        ledger.process(&execute_withdrawal_ix(&admin, &other)),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
+
+#[test]
+fn only_the_admin_proposes_withdrawals() {
+    let (mut ledger, _admin) = setup(0);
+    let intruder = ledger.wallet();
+    let destination = ledger.token_account(&intruder, &pda::token_mint(&PROGRAM_ID));
+
+    assert_eq!(
+        ledger.process(&propose_withdrawal_ix(&intruder, &destination, 1)),
+        Err(IntroError::Unauthorized.into())
+    );
+    assert!(!ledger.exists(&pda::pending_withdrawal(&PROGRAM_ID)));
+}
+
+#[test]
+fn zero_withdrawals_are_rejected() {
+    let (mut ledger, admin) = setup(0);
+    let destination = ledger.token_account(&admin, &pda::token_mint(&PROGRAM_ID));
+
+    assert_eq!(
+        ledger.process(&propose_withdrawal_ix(&admin, &destination, 0)),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn protocol_fee_cannot_exceed_the_whole_tip() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    let mut ix = initialize_config_ix(&admin, 0);
+    ix.data = payload(
+        4,
+        &ConfigParams {
+            protocol_fee_bps: 10_001,
+            ..config_params(0)
+        },
+    );
+
+    assert_eq!(ledger.process(&ix), Err(ProgramError::InvalidArgument));
+}
+
+#[test]
+fn credential_multiplier_must_be_bounded() {
+    for credential_multiplier_bps in [0, ProgramConfig::MAX_CREDENTIAL_MULTIPLIER_BPS + 1] {
+        let mut ledger = Ledger::new();
+        ledger.initialize_mint();
+        let admin = ledger.wallet();
+        let mut ix = initialize_config_ix(&admin, 0);
+        ix.data = payload(
+            4,
+            &ConfigParams {
+                credential_collection: Some(Pubkey::new_unique()),
+                credential_multiplier_bps,
+                ..config_params(0)
+            },
+        );
+
+        assert_eq!(ledger.process(&ix), Err(ProgramError::InvalidArgument));
+    }
+}
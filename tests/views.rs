@@ -0,0 +1,105 @@
+//! `RecordViews`: the configured indexer batches off-chain view counts into intros, once per
+//! intro per epoch.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use common::*;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use student_intro_sol::{error::IntroError, pda, state::ProgramConfig};
+
+fn record_ix(indexer: &Pubkey, intro: &Pubkey, count: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*indexer, true),
+            AccountMeta::new(*intro, false),
+        ],
+        data: payload(67, &count),
+    }
+}
+
+fn views(ledger: &Ledger, intro: &Pubkey) -> (u64, u64) {
+    let intro_data = ledger.intro(intro);
+    (intro_data.views, intro_data.views_epoch)
+}
+
+/// A ledger with an intro and a config naming a view indexer, returning the indexer, writer
+/// and intro.
+fn setup() -> (Ledger, Pubkey, Pubkey, Pubkey) {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    let ix = student_intro_ix(&ledger, &writer, "Ada", "Hello");
+    ledger.process(&ix).unwrap();
+
+    let indexer = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        view_indexer: Some(indexer),
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+
+    (ledger, indexer, writer, pda::intro(&PROGRAM_ID, &writer))
+}
+
+#[test]
+fn the_indexer_records_one_batch_per_epoch() {
+    let (mut ledger, indexer, _writer, intro) = setup();
+    assert_eq!(views(&ledger, &intro), (0, 0));
+
+    ledger.process(&record_ix(&indexer, &intro, 10)).unwrap();
+    assert_eq!(views(&ledger, &intro), (10, EPOCH));
+
+    assert_eq!(
+        ledger.process(&record_ix(&indexer, &intro, 10)),
+        Err(IntroError::ViewsAlreadyRecorded.into())
+    );
+
+    // Back-date the last batch to the previous epoch.
+    let mut intro_data = ledger.intro(&intro);
+    intro_data.views_epoch = EPOCH - 1;
+    ledger.set_intro(&intro, &intro_data);
+
+    ledger.process(&record_ix(&indexer, &intro, 5)).unwrap();
+    assert_eq!(views(&ledger, &intro), (15, EPOCH));
+}
+
+#[test]
+fn only_the_view_indexer_records() {
+    let (mut ledger, _indexer, writer, intro) = setup();
+
+    assert_eq!(
+        ledger.process(&record_ix(&writer, &intro, 10)),
+        Err(IntroError::Unauthorized.into())
+    );
+}
+
+#[test]
+fn empty_batches_are_rejected() {
+    let (mut ledger, indexer, _writer, intro) = setup();
+
+    assert_eq!(
+        ledger.process(&record_ix(&indexer, &intro, 0)),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn views_survive_intro_edits() {
+    let (mut ledger, indexer, writer, intro) = setup();
+    ledger.process(&record_ix(&indexer, &intro, 10)).unwrap();
+
+    ledger
+        .process(&update_intro_ix(&writer, "Ada", "Hello again"))
+        .unwrap();
+
+    assert_eq!(views(&ledger, &intro), (10, EPOCH));
+}
@@ -0,0 +1,311 @@
+//! `LinkWallet` and `UnlinkWallet`, and how a linked wallet shares its primary's reputation and
+//! reply tallies.
+
+#![cfg(not(feature = "no-rewards"))]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::*;
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{AccountMeta, Instruction},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use student_intro_sol::{
+    error::IntroError,
+    pda,
+    state::{
+        ProgramConfig, ReplyScore, Reputation, WalletLink, WalletLinkCount,
+        CLOSED_ACCOUNT_DISCRIMINATOR,
+    },
+};
+
+fn link_ix(primary: &Pubkey, linked: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*primary, true),
+            AccountMeta::new_readonly(*linked, true),
+            AccountMeta::new(pda::wallet_link(&PROGRAM_ID, linked), false),
+            AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, primary), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(pda::wallet_link_count(&PROGRAM_ID, primary), false),
+            AccountMeta::new_readonly(pda::wallet_link_count(&PROGRAM_ID, linked), false),
+        ],
+        data: vec![6],
+    }
+}
+
+fn unlink_ix(primary: &Pubkey, linked: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*primary, true),
+            AccountMeta::new_readonly(*linked, true),
+            AccountMeta::new(pda::wallet_link(&PROGRAM_ID, linked), false),
+            AccountMeta::new(pda::wallet_link_count(&PROGRAM_ID, primary), false),
+        ],
+        data: vec![7],
+    }
+}
+
+fn links(ledger: &Ledger, primary: &Pubkey) -> u64 {
+    try_from_slice_unchecked::<WalletLinkCount>(
+        &ledger.data(&pda::wallet_link_count(&PROGRAM_ID, primary)),
+    )
+    .unwrap()
+    .links
+}
+
+#[test]
+fn linking_records_the_link_and_counts_it() {
+    let mut ledger = Ledger::new();
+    let primary = ledger.wallet();
+    let linked = ledger.wallet();
+
+    ledger.process(&link_ix(&primary, &linked)).unwrap();
+
+    let link: WalletLink =
+        try_from_slice_unchecked(&ledger.data(&pda::wallet_link(&PROGRAM_ID, &linked))).unwrap();
+    assert_eq!(link.primary, primary);
+    assert_eq!(link.linked, linked);
+    assert_eq!(links(&ledger, &primary), 1);
+
+    ledger.process(&unlink_ix(&primary, &linked)).unwrap();
+    assert_eq!(links(&ledger, &primary), 0);
+    let tombstone = CLOSED_ACCOUNT_DISCRIMINATOR.try_to_vec().unwrap();
+    assert!(ledger
+        .data(&pda::wallet_link(&PROGRAM_ID, &linked))
+        .starts_with(&tombstone));
+}
+
+#[test]
+fn a_primary_cannot_be_linked_elsewhere() {
+    let mut ledger = Ledger::new();
+    let primary = ledger.wallet();
+    let linked = ledger.wallet();
+    let other = ledger.wallet();
+    ledger.process(&link_ix(&primary, &linked)).unwrap();
+
+    assert_eq!(
+        ledger.process(&link_ix(&other, &primary)),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
+
+#[test]
+fn a_linked_wallet_cannot_be_linked_again() {
+    let mut ledger = Ledger::new();
+    let primary = ledger.wallet();
+    let linked = ledger.wallet();
+    let other = ledger.wallet();
+    ledger.process(&link_ix(&primary, &linked)).unwrap();
+
+    assert!(ledger.process(&link_ix(&other, &linked)).is_err());
+    let link: WalletLink =
+        try_from_slice_unchecked(&ledger.data(&pda::wallet_link(&PROGRAM_ID, &linked))).unwrap();
+    assert_eq!(link.primary, primary);
+}
+
+#[test]
+fn a_linked_wallet_cannot_become_a_primary() {
+    let mut ledger = Ledger::new();
+    let primary = ledger.wallet();
+    let linked = ledger.wallet();
+    let other = ledger.wallet();
+    ledger.process(&link_ix(&primary, &linked)).unwrap();
+
+    assert_eq!(
+        ledger.process(&link_ix(&linked, &other)),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+}
+
+#[test]
+fn linked_wallets_share_the_reply_tally() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    ledger
+        .process(&student_intro_ix(&ledger, &writer, "Ada", "Hello"))
+        .unwrap();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+
+    let primary = ledger.student();
+    let linked = ledger.student();
+    ledger.process(&link_ix(&primary, &linked)).unwrap();
+
+    let supply = ledger.mint_supply();
+    ledger
+        .process(&reply_intro_ix(
+            &ledger, &primary, &intro, "Grace", "Welcome",
+        ))
+        .unwrap();
+    let first = ledger.mint_supply() - supply;
+
+    // The reward, like the tally, is the primary's.
+    let mint = pda::token_mint(&PROGRAM_ID);
+    let mut ix = reply_intro_ix(&ledger, &linked, &intro, "Grace", "Welcome");
+    ix = substitute(&ix, 6, get_associated_token_address(&primary, &mint));
+    let tally = ix.accounts.len() - 1;
+    ix = substitute(&ix, tally, pda::reply_tally(&PROGRAM_ID, &intro, &primary));
+    ix.accounts.push(AccountMeta::new_readonly(
+        pda::wallet_link(&PROGRAM_ID, &linked),
+        false,
+    ));
+    let supply = ledger.mint_supply();
+    ledger.process(&ix).unwrap();
+
+    assert_eq!(ledger.mint_supply() - supply, first / 2);
+    assert!(!ledger.exists(&pda::reply_tally(&PROGRAM_ID, &intro, &linked)));
+}
+
+#[test]
+fn a_linked_repliers_milestones_credit_the_primary() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    ledger
+        .process(&student_intro_ix(&ledger, &writer, "Ada", "Hello"))
+        .unwrap();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+
+    let primary = ledger.wallet();
+    let linked = ledger.student();
+    ledger.process(&link_ix(&primary, &linked)).unwrap();
+    ledger
+        .process(&reply_intro_ix(
+            &ledger, &linked, &intro, "Grace", "Welcome",
+        ))
+        .unwrap();
+    let reply = pda::reply(&PROGRAM_ID, &intro, 0);
+
+    for _ in 0..ReplyScore::MILESTONES[0] {
+        let voter = ledger.wallet();
+        let ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(voter, true),
+                AccountMeta::new_readonly(intro, false),
+                AccountMeta::new_readonly(reply, false),
+                AccountMeta::new(pda::reply_vote(&PROGRAM_ID, &reply, &voter), false),
+                AccountMeta::new(pda::reply_score(&PROGRAM_ID, &reply), false),
+                AccountMeta::new(pda::top_replies(&PROGRAM_ID, &intro), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, &voter), false),
+                AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, &linked), false),
+                AccountMeta::new(pda::reputation(&PROGRAM_ID, &primary), false),
+            ],
+            data: payload(11, &true),
+        };
+        ledger.process(&ix).unwrap();
+    }
+
+    let reputation: Reputation =
+        try_from_slice_unchecked(&ledger.data(&pda::reputation(&PROGRAM_ID, &primary))).unwrap();
+    assert_eq!(reputation.user, primary);
+    assert_eq!(reputation.points, Reputation::POINTS_PER_MILESTONE);
+    assert!(!ledger.exists(&pda::reputation(&PROGRAM_ID, &linked)));
+}
+
+#[test]
+fn a_linked_wallet_cannot_vote_on_its_primarys_reply() {
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let writer = ledger.student();
+    ledger
+        .process(&student_intro_ix(&ledger, &writer, "Ada", "Hello"))
+        .unwrap();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+
+    let primary = ledger.student();
+    let linked = ledger.wallet();
+    ledger.process(&link_ix(&primary, &linked)).unwrap();
+    ledger
+        .process(&reply_intro_ix(
+            &ledger, &primary, &intro, "Grace", "Welcome",
+        ))
+        .unwrap();
+    let reply = pda::reply(&PROGRAM_ID, &intro, 0);
+
+    let ix = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(linked, true),
+            AccountMeta::new_readonly(intro, false),
+            AccountMeta::new_readonly(reply, false),
+            AccountMeta::new(pda::reply_vote(&PROGRAM_ID, &reply, &linked), false),
+            AccountMeta::new(pda::reply_score(&PROGRAM_ID, &reply), false),
+            AccountMeta::new(pda::top_replies(&PROGRAM_ID, &intro), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, &linked), false),
+            AccountMeta::new_readonly(pda::wallet_link(&PROGRAM_ID, &primary), false),
+        ],
+        data: payload(11, &true),
+    };
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::IncorrectAccountError.into())
+    );
+    assert!(!ledger.exists(&pda::reply_vote(&PROGRAM_ID, &reply, &linked)));
+}
+
+#[test]
+fn unlinking_needs_the_link_writable() {
+    let mut ledger = Ledger::new();
+    let primary = ledger.wallet();
+    let linked = ledger.wallet();
+    ledger.process(&link_ix(&primary, &linked)).unwrap();
+
+    let mut ix = unlink_ix(&primary, &linked);
+    ix.accounts[2].is_writable = false;
+    assert_eq!(
+        ledger.process(&ix),
+        Err(IntroError::AccountNotWritable.into())
+    );
+    assert_eq!(links(&ledger, &primary), 1);
+}
+
+#[test]
+fn a_writers_linked_wallet_earns_no_first_reply_bonus() {
+    const BONUS: u64 = 1_000 * LAMPORTS_PER_SOL;
+    let mut ledger = Ledger::new();
+    ledger.initialize_mint();
+    let admin = ledger.wallet();
+    ledger.install_config(ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+        is_initialized: true,
+        admin,
+        first_reply_bonus: BONUS,
+        first_reply_window_secs: 3_600,
+        data_version: ProgramConfig::DATA_VERSION,
+        ..ProgramConfig::default()
+    });
+    let writer = ledger.student();
+    ledger
+        .process(&student_intro_ix(&ledger, &writer, "Ada", "Hello"))
+        .unwrap();
+    let intro = pda::intro(&PROGRAM_ID, &writer);
+    let linked = ledger.student();
+    ledger.process(&link_ix(&writer, &linked)).unwrap();
+
+    let mint = pda::token_mint(&PROGRAM_ID);
+    let writer_ata = get_associated_token_address(&writer, &mint);
+    let mut ix = reply_intro_ix(&ledger, &linked, &intro, "Grace", "Welcome");
+    ix = substitute(&ix, 6, writer_ata);
+    let tally = ix.accounts.len() - 1;
+    ix = substitute(&ix, tally, pda::reply_tally(&PROGRAM_ID, &intro, &writer));
+    ix.accounts.push(AccountMeta::new_readonly(
+        pda::wallet_link(&PROGRAM_ID, &linked),
+        false,
+    ));
+    let before = ledger.token_balance(&writer_ata);
+    ledger.process(&ix).unwrap();
+
+    let earned = ledger.token_balance(&writer_ata) - before;
+    assert!(earned > 0 && earned < BONUS);
+}